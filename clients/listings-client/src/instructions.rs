@@ -0,0 +1,1672 @@
+use anchor_lang::{
+    prelude::{Pubkey, System},
+    solana_program::{instruction::Instruction, stake, sysvar},
+    Id, InstructionData, ToAccountMetas,
+};
+use anchor_spl::{
+    associated_token::{
+        get_associated_token_address, get_associated_token_address_with_program_id, AssociatedToken,
+    },
+    token::Token,
+    token_2022::Token2022,
+};
+use bridgesplit_program_utils::{mpl_bubblegum, MplTokenMetadata};
+use listings::{
+    accounts, instruction,
+    instructions::{
+        AmmPoolData, CompressedFillOrderData, CompressedOrderData, EditBuyOrderData,
+        EditSellOrderData, InitOrderData, TrailingPriceData,
+    },
+    state::{AmmPool, ListingMode, Order, PROTOCOL_TREASURY},
+};
+
+use crate::{
+    pda::*,
+    remaining_accounts::{RemainingAccounts, RemainingAccountsLayout},
+};
+
+/// spl noop program used as bubblegum log wrapper
+pub const SPL_NOOP_ID: Pubkey =
+    anchor_lang::solana_program::pubkey!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+/// spl account compression program
+pub const SPL_ACCOUNT_COMPRESSION_ID: Pubkey =
+    anchor_lang::solana_program::pubkey!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+
+pub fn treasury() -> Pubkey {
+    PROTOCOL_TREASURY.parse().unwrap()
+}
+
+fn build(
+    accounts: impl ToAccountMetas,
+    data: impl InstructionData,
+    remaining_accounts: Vec<anchor_lang::prelude::AccountMeta>,
+) -> Instruction {
+    let mut metas = accounts.to_account_metas(None);
+    metas.extend(remaining_accounts);
+    Instruction {
+        program_id: listings::ID,
+        accounts: metas,
+        data: data.data(),
+    }
+}
+
+/// nft accounts shared by the instructions moving a token account nft
+#[derive(Clone, Copy, Debug)]
+pub struct NftAccounts {
+    pub mint: Pubkey,
+    pub metadata: Pubkey,
+    pub edition: Pubkey,
+}
+
+impl NftAccounts {
+    pub fn new(mint: Pubkey) -> Self {
+        Self {
+            mint,
+            metadata: find_metadata(&mint).0,
+            edition: find_edition(&mint).0,
+        }
+    }
+}
+
+/// bubblegum accounts of the tree holding a compressed nft
+#[derive(Clone, Debug)]
+pub struct CompressedNftAccounts {
+    pub merkle_tree: Pubkey,
+    /// proof path of the leaf, appended as remaining accounts
+    pub proof: Vec<Pubkey>,
+}
+
+impl CompressedNftAccounts {
+    fn proof_metas(&self) -> Vec<anchor_lang::prelude::AccountMeta> {
+        self.proof
+            .iter()
+            .map(|node| anchor_lang::prelude::AccountMeta::new_readonly(*node, false))
+            .collect()
+    }
+}
+
+/// stake pool accounts of the lst config
+#[derive(Clone, Copy, Debug)]
+pub struct StakePoolAccounts {
+    pub program: Pubkey,
+    pub stake_pool: Pubkey,
+    pub withdraw_authority: Pubkey,
+    pub reserve_stake: Pubkey,
+    pub manager_fee_account: Pubkey,
+    pub pool_mint: Pubkey,
+}
+
+impl StakePoolAccounts {
+    pub fn new(
+        program: Pubkey,
+        stake_pool: Pubkey,
+        reserve_stake: Pubkey,
+        manager_fee_account: Pubkey,
+        pool_mint: Pubkey,
+    ) -> Self {
+        Self {
+            program,
+            stake_pool,
+            withdraw_authority: find_stake_pool_withdraw_authority(&program, &stake_pool).0,
+            reserve_stake,
+            manager_fee_account,
+            pool_mint,
+        }
+    }
+}
+
+pub fn init_market(initializer: Pubkey, pool_mint: Pubkey) -> Instruction {
+    build(
+        accounts::InitMarket {
+            initializer,
+            pool_mint,
+            market: find_market(&pool_mint).0,
+            system_program: System::id(),
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::InitMarket {},
+        Vec::new(),
+    )
+}
+
+/// instructions are built for markets without an order book, pass the order book of the market
+/// the order book is the first optional account of every instruction keeping it in sync
+pub fn with_order_book(mut instruction: Instruction, market: &Pubkey) -> Instruction {
+    let order_book = instruction
+        .accounts
+        .iter_mut()
+        .find(|meta| meta.pubkey == listings::ID)
+        .expect("instruction without an order book account");
+    *order_book = anchor_lang::prelude::AccountMeta::new(find_order_book(market).0, false);
+    instruction
+}
+
+/// initializer must be the initializer of the market
+pub fn edit_market(
+    initializer: Pubkey,
+    pool_mint: Pubkey,
+    listing_mode: ListingMode,
+) -> Instruction {
+    build(
+        accounts::EditMarket {
+            initializer,
+            market: find_market(&pool_mint).0,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::EditMarket {
+            listing_mode: listing_mode.into(),
+        },
+        Vec::new(),
+    )
+}
+
+pub fn set_min_withdrawal_delay(initializer: Pubkey, pool_mint: Pubkey, delay: i64) -> Instruction {
+    build(
+        accounts::EditMarket {
+            initializer,
+            market: find_market(&pool_mint).0,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::SetMinWithdrawalDelay { delay },
+        Vec::new(),
+    )
+}
+
+/// orders passed as read only remaining accounts
+fn order_metas(orders: &[Pubkey]) -> Vec<anchor_lang::prelude::AccountMeta> {
+    orders
+        .iter()
+        .map(|order| anchor_lang::prelude::AccountMeta::new_readonly(*order, false))
+        .collect()
+}
+
+/// initializer must be the initializer of the market
+pub fn init_order_book(initializer: Pubkey, pool_mint: Pubkey) -> Instruction {
+    let market = find_market(&pool_mint).0;
+    build(
+        accounts::InitOrderBook {
+            initializer,
+            market,
+            order_book: find_order_book(&market).0,
+            system_program: System::id(),
+        },
+        instruction::InitOrderBook {},
+        Vec::new(),
+    )
+}
+
+/// orders and their wallets are passed as remaining accounts, all of the market of the order book
+pub fn sync_order_book(market: Pubkey, orders: &[(Pubkey, Pubkey)]) -> Instruction {
+    build(
+        accounts::SyncOrderBook {
+            order_book: find_order_book(&market).0,
+        },
+        instruction::SyncOrderBook {},
+        orders
+            .iter()
+            .flat_map(|(order, wallet)| order_metas(&[*order, *wallet]))
+            .collect(),
+    )
+}
+
+/// (order, wallet) of the candidate bids of the market in increasing order of address
+/// with the order book added by `with_order_book`, of bids of the book from the best,
+/// bids left out are skipped
+pub fn get_best_bid(market: Pubkey, bids: &[(Pubkey, Pubkey)]) -> Instruction {
+    build(
+        accounts::GetQuote {
+            market,
+            order_book: None,
+        },
+        instruction::GetBestBid {},
+        bids.iter()
+            .flat_map(|(bid, wallet)| order_metas(&[*bid, *wallet]))
+            .collect(),
+    )
+}
+
+/// candidate orders of the market in increasing order of address,
+/// or none with the order book added by `with_order_book`
+pub fn get_floor(market: Pubkey, orders: &[Pubkey]) -> Instruction {
+    build(
+        accounts::GetQuote {
+            market,
+            order_book: None,
+        },
+        instruction::GetFloor {},
+        order_metas(orders),
+    )
+}
+
+/// upgrade an order, market or wallet account to the current layout version
+pub fn migrate_account(initializer: Pubkey, account: Pubkey) -> Instruction {
+    build(
+        accounts::MigrateAccount {
+            initializer,
+            account,
+        },
+        instruction::MigrateAccount {},
+        Vec::new(),
+    )
+}
+
+/// upgrade an order of the borsh layout, counting it in the open orders of its migrated wallet
+pub fn migrate_legacy_order(initializer: Pubkey, order: Pubkey, wallet: Pubkey) -> Instruction {
+    build(
+        accounts::MigrateAccount {
+            initializer,
+            account: order,
+        },
+        instruction::MigrateAccount {},
+        vec![anchor_lang::prelude::AccountMeta::new(wallet, false)],
+    )
+}
+
+/// owner confirms every borsh layout order of its migrated wallet has been migrated
+pub fn complete_wallet_migration(initializer: Pubkey) -> Instruction {
+    build(
+        accounts::CompleteWalletMigration {
+            initializer,
+            wallet: find_wallet(&initializer).0,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::CompleteWalletMigration {},
+        Vec::new(),
+    )
+}
+
+pub fn init_wallet(initializer: Pubkey, amount: u64) -> Instruction {
+    build(
+        accounts::InitBiddingWallet {
+            initializer,
+            wallet: find_wallet(&initializer).0,
+            system_program: System::id(),
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::InitWallet { amount },
+        Vec::new(),
+    )
+}
+
+pub fn edit_wallet(initializer: Pubkey, amount_change: u64, increase: bool) -> Instruction {
+    build(
+        accounts::EditBiddingWallet {
+            initializer,
+            wallet: find_wallet(&initializer).0,
+            system_program: System::id(),
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::EditWallet {
+            amount_change,
+            increase,
+        },
+        Vec::new(),
+    )
+}
+
+pub fn close_wallet(initializer: Pubkey) -> Instruction {
+    build(
+        accounts::CloseBiddingWallet {
+            initializer,
+            wallet: find_wallet(&initializer).0,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::CloseWallet {},
+        Vec::new(),
+    )
+}
+
+pub fn sync_wallet(initializer: Pubkey) -> Instruction {
+    build(
+        accounts::SyncBiddingWallet {
+            initializer,
+            wallet: find_wallet(&initializer).0,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::SyncWallet {},
+        Vec::new(),
+    )
+}
+
+pub fn set_withdrawal_delay(initializer: Pubkey, delay: i64) -> Instruction {
+    build(
+        accounts::SetWithdrawalDelay {
+            initializer,
+            wallet: find_wallet(&initializer).0,
+            clock: sysvar::clock::ID,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::SetWithdrawalDelay { delay },
+        Vec::new(),
+    )
+}
+
+/// an amount of 0 cancels the pending withdrawal
+pub fn request_withdrawal(initializer: Pubkey, amount: u64) -> Instruction {
+    build(
+        accounts::RequestWithdrawal {
+            initializer,
+            wallet: find_wallet(&initializer).0,
+            clock: sysvar::clock::ID,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::RequestWithdrawal { amount },
+        Vec::new(),
+    )
+}
+
+pub fn complete_withdrawal(initializer: Pubkey) -> Instruction {
+    build(
+        accounts::CompleteWithdrawal {
+            initializer,
+            wallet: find_wallet(&initializer).0,
+            clock: sysvar::clock::ID,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::CompleteWithdrawal {},
+        Vec::new(),
+    )
+}
+
+pub fn set_wallet_delegate(
+    initializer: Pubkey,
+    delegate: Pubkey,
+    spending_cap: u64,
+) -> Instruction {
+    build(
+        accounts::SetWalletDelegate {
+            initializer,
+            wallet: find_wallet(&initializer).0,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::SetWalletDelegate {
+            delegate,
+            spending_cap,
+        },
+        Vec::new(),
+    )
+}
+
+/// nft_mint is `Pubkey::default()` for a collection bid
+/// initializer is the owner of the wallet or its delegate
+pub fn init_buy_order(
+    initializer: Pubkey,
+    owner: Pubkey,
+    pool_mint: Pubkey,
+    nft_mint: Pubkey,
+    data: InitOrderData,
+    remaining_accounts: &RemainingAccounts,
+) -> Instruction {
+    let market = find_market(&pool_mint).0;
+    build(
+        accounts::InitBuyOrder {
+            initializer,
+            wallet: find_wallet(&owner).0,
+            market,
+            order_book: None,
+            order: find_order(&data.nonce, &market, &owner).0,
+            nft_mint,
+            system_program: System::id(),
+            clock: sysvar::clock::ID,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::InitBuyOrder { data },
+        remaining_accounts.to_account_metas(RemainingAccountsLayout::InitBuy),
+    )
+}
+
+pub fn init_sell_order(
+    initializer: Pubkey,
+    pool_mint: Pubkey,
+    nft: NftAccounts,
+    data: InitOrderData,
+    remaining_accounts: &RemainingAccounts,
+) -> Instruction {
+    let market = find_market(&pool_mint).0;
+    build(
+        accounts::InitSellOrder {
+            initializer,
+            wallet: find_wallet(&initializer).0,
+            market,
+            order_book: None,
+            order: find_order(&data.nonce, &market, &initializer).0,
+            appraisal: find_appraisal(&pool_mint, &nft.mint).0,
+            nft_mint: nft.mint,
+            nft_metadata: Some(nft.metadata),
+            nft_edition: Some(nft.edition),
+            nft_ta: get_associated_token_address(&initializer, &nft.mint),
+            escrow_nft_ta: get_associated_token_address(&find_wallet(&initializer).0, &nft.mint),
+            sysvar_instructions: sysvar::instructions::ID,
+            system_program: System::id(),
+            token_program: Token::id(),
+            associated_token_program: AssociatedToken::id(),
+            token_metadata_program: MplTokenMetadata::id(),
+            clock: sysvar::clock::ID,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::InitSellOrder { data },
+        remaining_accounts.to_account_metas(RemainingAccountsLayout::InitSell),
+    )
+}
+
+pub fn edit_buy_order(
+    initializer: Pubkey,
+    order_address: Pubkey,
+    order: &Order,
+    pool_mint: Pubkey,
+    data: EditBuyOrderData,
+) -> Instruction {
+    build(
+        accounts::EditBuyOrder {
+            initializer,
+            market: find_market(&pool_mint).0,
+            order_book: None,
+            order: order_address,
+            wallet: find_wallet(&order.owner).0,
+            system_program: System::id(),
+            clock: sysvar::clock::ID,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::EditBuyOrder { data },
+        Vec::new(),
+    )
+}
+
+/// a max price of 0 fixes the price of the bid again
+pub fn set_trailing_price(
+    initializer: Pubkey,
+    order_address: Pubkey,
+    order: &Order,
+    data: TrailingPriceData,
+) -> Instruction {
+    build(
+        accounts::SetTrailingPrice {
+            initializer,
+            market: order.market,
+            order_book: None,
+            order: order_address,
+            wallet: find_wallet(&order.owner).0,
+            clock: sysvar::clock::ID,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::SetTrailingPrice { data },
+        Vec::new(),
+    )
+}
+
+/// reference_address is an active listing of the market of the bid
+/// a trigger price of 0 activates the listing again
+pub fn set_sell_trigger(
+    initializer: Pubkey,
+    order_address: Pubkey,
+    order: &Order,
+    trigger_price: u64,
+) -> Instruction {
+    build(
+        accounts::SetSellTrigger {
+            initializer,
+            market: order.market,
+            order_book: None,
+            order: order_address,
+            clock: sysvar::clock::ID,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::SetSellTrigger { trigger_price },
+        Vec::new(),
+    )
+}
+
+/// bids are the best buy orders of the market, all below the trigger price of the listing
+/// `bids` are the (order, wallet) of the order book bids from the best, up to the first funded one
+pub fn activate_order(
+    initializer: Pubkey,
+    order_address: Pubkey,
+    order: &Order,
+    bids: &[(Pubkey, Pubkey)],
+) -> Instruction {
+    build(
+        accounts::ActivateOrder {
+            initializer,
+            market: order.market,
+            order_book: find_order_book(&order.market).0,
+            order: order_address,
+            clock: sysvar::clock::ID,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::ActivateOrder {},
+        bids.iter()
+            .flat_map(|(bid, wallet)| order_metas(&[*bid, *wallet]))
+            .collect(),
+    )
+}
+
+pub fn edit_sell_order(
+    initializer: Pubkey,
+    order_address: Pubkey,
+    pool_mint: Pubkey,
+    data: EditSellOrderData,
+) -> Instruction {
+    let market = find_market(&pool_mint).0;
+    build(
+        accounts::EditSellOrder {
+            initializer,
+            order: order_address,
+            market,
+            order_book: None,
+            clock: sysvar::clock::ID,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::EditSellOrder { data },
+        Vec::new(),
+    )
+}
+
+pub fn close_buy_order(initializer: Pubkey, order_address: Pubkey, order: &Order) -> Instruction {
+    build(
+        accounts::CloseBuyOrder {
+            initializer,
+            owner: order.owner,
+            wallet: find_wallet(&order.owner).0,
+            order: order_address,
+            market: order.market,
+            order_book: None,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::CloseBuyOrder {},
+        Vec::new(),
+    )
+}
+
+pub fn close_sell_order(
+    initializer: Pubkey,
+    order_address: Pubkey,
+    order: &Order,
+    nft: NftAccounts,
+    remaining_accounts: &RemainingAccounts,
+) -> Instruction {
+    build(
+        accounts::CloseSellOrder {
+            initializer,
+            order: order_address,
+            market: order.market,
+            order_book: None,
+            wallet: find_wallet(&order.owner).0,
+            nft_mint: nft.mint,
+            nft_metadata: Some(nft.metadata),
+            nft_edition: Some(nft.edition),
+            nft_ta: get_associated_token_address(&initializer, &nft.mint),
+            escrow_nft_ta: get_associated_token_address(&find_wallet(&order.owner).0, &nft.mint),
+            system_program: System::id(),
+            token_program: Token::id(),
+            sysvar_instructions: sysvar::instructions::ID,
+            associated_token_program: AssociatedToken::id(),
+            token_metadata_program: MplTokenMetadata::id(),
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::CloseSellOrder {},
+        remaining_accounts.to_account_metas(RemainingAccountsLayout::CloseSell),
+    )
+}
+
+/// initializer is the seller filling the bid
+pub fn fill_buy_order(
+    initializer: Pubkey,
+    order_address: Pubkey,
+    order: &Order,
+    nft: NftAccounts,
+    remaining_accounts: &RemainingAccounts,
+) -> Instruction {
+    fill_buy_order_with_stake_pool(
+        initializer,
+        order_address,
+        order,
+        nft,
+        remaining_accounts,
+        None,
+        None,
+    )
+}
+
+/// initializer is the seller filling the bid of a bid pool, the nft must be appraised in the market
+pub fn fill_bid_pool_order(
+    initializer: Pubkey,
+    order_address: Pubkey,
+    order: &Order,
+    pool_mint: Pubkey,
+    nft: NftAccounts,
+    remaining_accounts: &RemainingAccounts,
+) -> Instruction {
+    fill_buy_order_with_stake_pool(
+        initializer,
+        order_address,
+        order,
+        nft,
+        remaining_accounts,
+        None,
+        Some(find_appraisal(&pool_mint, &nft.mint).0),
+    )
+}
+
+/// initializer is the seller filling the bid
+/// the stake pool accounts let the fill unstake the part of the bid not covered by the balance
+pub fn fill_staked_buy_order(
+    initializer: Pubkey,
+    order_address: Pubkey,
+    order: &Order,
+    nft: NftAccounts,
+    remaining_accounts: &RemainingAccounts,
+    stake_pool: &StakePoolAccounts,
+) -> Instruction {
+    fill_buy_order_with_stake_pool(
+        initializer,
+        order_address,
+        order,
+        nft,
+        remaining_accounts,
+        Some(stake_pool),
+        None,
+    )
+}
+
+fn fill_buy_order_with_stake_pool(
+    initializer: Pubkey,
+    order_address: Pubkey,
+    order: &Order,
+    nft: NftAccounts,
+    remaining_accounts: &RemainingAccounts,
+    stake_pool: Option<&StakePoolAccounts>,
+    appraisal: Option<Pubkey>,
+) -> Instruction {
+    build(
+        accounts::FillBuyOrder {
+            initializer,
+            buyer: order.owner,
+            wallet: find_wallet(&order.owner).0,
+            market: order.market,
+            order_book: None,
+            order: order_address,
+            nft_mint: nft.mint,
+            nft_metadata: Some(nft.metadata),
+            nft_edition: Some(nft.edition),
+            appraisal,
+            seller_nft_ta: get_associated_token_address(&initializer, &nft.mint),
+            buyer_nft_ta: get_associated_token_address(&order.owner, &nft.mint),
+            treasury: treasury(),
+            system_program: System::id(),
+            token_program: Token::id(),
+            sysvar_instructions: sysvar::instructions::ID,
+            associated_token_program: AssociatedToken::id(),
+            token_metadata_program: MplTokenMetadata::id(),
+            clock: sysvar::clock::ID,
+            lst_config: stake_pool.map(|_| find_lst_config().0),
+            stake_pool_program: stake_pool.map(|stake_pool| stake_pool.program),
+            stake_pool: stake_pool.map(|stake_pool| stake_pool.stake_pool),
+            stake_pool_withdraw_authority: stake_pool
+                .map(|stake_pool| stake_pool.withdraw_authority),
+            reserve_stake: stake_pool.map(|stake_pool| stake_pool.reserve_stake),
+            manager_fee_account: stake_pool.map(|stake_pool| stake_pool.manager_fee_account),
+            lst_mint: stake_pool.map(|stake_pool| stake_pool.pool_mint),
+            wallet_lst_ta: stake_pool.map(|stake_pool| {
+                get_associated_token_address(&find_wallet(&order.owner).0, &stake_pool.pool_mint)
+            }),
+            stake_history: stake_pool.map(|_| sysvar::stake_history::ID),
+            stake_program: stake_pool.map(|_| stake::program::ID),
+            lst_token_program: stake_pool.map(|_| Token::id()),
+            pool_nft: Some(find_pool_nft(&order.owner, &nft.mint).0),
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::FillBuyOrder {},
+        remaining_accounts.to_account_metas(RemainingAccountsLayout::Fill),
+    )
+}
+
+/// initializer is the buyer filling the listing
+pub fn fill_sell_order(
+    initializer: Pubkey,
+    order_address: Pubkey,
+    order: &Order,
+    nft: NftAccounts,
+    remaining_accounts: &RemainingAccounts,
+) -> Instruction {
+    build(
+        accounts::FillSellOrder {
+            initializer,
+            seller: order.owner,
+            wallet: find_wallet(&order.owner).0,
+            market: order.market,
+            order_book: None,
+            order: order_address,
+            nft_mint: nft.mint,
+            nft_metadata: Some(nft.metadata),
+            nft_edition: Some(nft.edition),
+            seller_nft_ta: get_associated_token_address(&order.get_nft_holder(), &nft.mint),
+            buyer_nft_ta: get_associated_token_address(&initializer, &nft.mint),
+            treasury: treasury(),
+            system_program: System::id(),
+            token_program: Token::id(),
+            sysvar_instructions: sysvar::instructions::ID,
+            associated_token_program: AssociatedToken::id(),
+            token_metadata_program: MplTokenMetadata::id(),
+            clock: sysvar::clock::ID,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::FillSellOrder {},
+        remaining_accounts.to_account_metas(RemainingAccountsLayout::Fill),
+    )
+}
+
+/// listed nfts are appraised, the appraisal is passed for bids of bid pools
+#[allow(clippy::too_many_arguments)]
+pub fn match_orders(
+    initializer: Pubkey,
+    sell_order_address: Pubkey,
+    sell_order: &Order,
+    buy_order_address: Pubkey,
+    buy_order: &Order,
+    pool_mint: Pubkey,
+    nft: NftAccounts,
+    remaining_accounts: &RemainingAccounts,
+) -> Instruction {
+    match_orders_with_stake_pool(
+        initializer,
+        sell_order_address,
+        sell_order,
+        buy_order_address,
+        buy_order,
+        pool_mint,
+        nft,
+        remaining_accounts,
+        None,
+    )
+}
+
+/// the stake pool accounts let the match unstake the part of the bid not covered by the balance
+/// of the buyer wallet
+#[allow(clippy::too_many_arguments)]
+pub fn match_staked_orders(
+    initializer: Pubkey,
+    sell_order_address: Pubkey,
+    sell_order: &Order,
+    buy_order_address: Pubkey,
+    buy_order: &Order,
+    pool_mint: Pubkey,
+    nft: NftAccounts,
+    remaining_accounts: &RemainingAccounts,
+    stake_pool: &StakePoolAccounts,
+) -> Instruction {
+    match_orders_with_stake_pool(
+        initializer,
+        sell_order_address,
+        sell_order,
+        buy_order_address,
+        buy_order,
+        pool_mint,
+        nft,
+        remaining_accounts,
+        Some(stake_pool),
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn match_orders_with_stake_pool(
+    initializer: Pubkey,
+    sell_order_address: Pubkey,
+    sell_order: &Order,
+    buy_order_address: Pubkey,
+    buy_order: &Order,
+    pool_mint: Pubkey,
+    nft: NftAccounts,
+    remaining_accounts: &RemainingAccounts,
+    stake_pool: Option<&StakePoolAccounts>,
+) -> Instruction {
+    build(
+        accounts::MatchOrders {
+            initializer,
+            seller: sell_order.owner,
+            seller_wallet: find_wallet(&sell_order.owner).0,
+            buyer: buy_order.owner,
+            buyer_wallet: find_wallet(&buy_order.owner).0,
+            market: sell_order.market,
+            order_book: None,
+            sell_order: sell_order_address,
+            buy_order: buy_order_address,
+            nft_mint: nft.mint,
+            nft_metadata: nft.metadata,
+            nft_edition: nft.edition,
+            appraisal: Some(find_appraisal(&pool_mint, &nft.mint).0),
+            seller_nft_ta: get_associated_token_address(&sell_order.get_nft_holder(), &nft.mint),
+            buyer_nft_ta: get_associated_token_address(&buy_order.owner, &nft.mint),
+            treasury: treasury(),
+            system_program: System::id(),
+            token_program: Token::id(),
+            sysvar_instructions: sysvar::instructions::ID,
+            associated_token_program: AssociatedToken::id(),
+            token_metadata_program: MplTokenMetadata::id(),
+            clock: sysvar::clock::ID,
+            pool_nft: Some(find_pool_nft(&buy_order.owner, &nft.mint).0),
+            lst_config: stake_pool.map(|_| find_lst_config().0),
+            stake_pool_program: stake_pool.map(|stake_pool| stake_pool.program),
+            stake_pool: stake_pool.map(|stake_pool| stake_pool.stake_pool),
+            stake_pool_withdraw_authority: stake_pool
+                .map(|stake_pool| stake_pool.withdraw_authority),
+            reserve_stake: stake_pool.map(|stake_pool| stake_pool.reserve_stake),
+            manager_fee_account: stake_pool.map(|stake_pool| stake_pool.manager_fee_account),
+            lst_mint: stake_pool.map(|stake_pool| stake_pool.pool_mint),
+            wallet_lst_ta: stake_pool.map(|stake_pool| {
+                get_associated_token_address(
+                    &find_wallet(&buy_order.owner).0,
+                    &stake_pool.pool_mint,
+                )
+            }),
+            stake_history: stake_pool.map(|_| sysvar::stake_history::ID),
+            stake_program: stake_pool.map(|_| stake::program::ID),
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::MatchOrders {},
+        remaining_accounts.to_account_metas(RemainingAccountsLayout::Fill),
+    )
+}
+
+pub fn compressed_init_sell_order(
+    initializer: Pubkey,
+    pool_mint: Pubkey,
+    tree: &CompressedNftAccounts,
+    data: CompressedOrderData,
+) -> Instruction {
+    let market = find_market(&pool_mint).0;
+    build(
+        accounts::CompressedInitSellOrder {
+            initializer,
+            wallet: find_wallet(&initializer).0,
+            market,
+            order_book: None,
+            order: find_order(&data.order_nonce, &market, &initializer).0,
+            appraisal: find_appraisal(&pool_mint, &data.mint_id).0,
+            tree_authority: find_tree_authority(&tree.merkle_tree).0,
+            merkle_tree: tree.merkle_tree,
+            log_wrapper: SPL_NOOP_ID,
+            compression_program: SPL_ACCOUNT_COMPRESSION_ID,
+            mpl_bubblegum: mpl_bubblegum::ID,
+            system_program: System::id(),
+            clock: sysvar::clock::ID,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::CompressedInitSellOrder { data },
+        tree.proof_metas(),
+    )
+}
+
+/// remaining accounts are the ovol accounts and the creators of the nft, followed by the proof
+pub fn compressed_fill_sell_order(
+    initializer: Pubkey,
+    order_address: Pubkey,
+    order: &Order,
+    tree: &CompressedNftAccounts,
+    data: CompressedFillOrderData,
+    remaining_accounts: &RemainingAccounts,
+) -> Instruction {
+    let mut metas = remaining_accounts.to_account_metas(RemainingAccountsLayout::Core);
+    metas.extend(tree.proof_metas());
+    build(
+        accounts::CompressedFillSellOrder {
+            initializer,
+            seller: order.owner,
+            wallet: find_wallet(&order.owner).0,
+            market: order.market,
+            order_book: None,
+            order: order_address,
+            treasury: treasury(),
+            tree_authority: find_tree_authority(&tree.merkle_tree).0,
+            merkle_tree: tree.merkle_tree,
+            log_wrapper: SPL_NOOP_ID,
+            compression_program: SPL_ACCOUNT_COMPRESSION_ID,
+            mpl_bubblegum: mpl_bubblegum::ID,
+            system_program: System::id(),
+            clock: sysvar::clock::ID,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::CompressedFillSellOrder { data },
+        metas,
+    )
+}
+
+pub fn compressed_close_sell_order(
+    initializer: Pubkey,
+    order_address: Pubkey,
+    order: &Order,
+    tree: &CompressedNftAccounts,
+    data: CompressedOrderData,
+) -> Instruction {
+    build(
+        accounts::CompressedCloseSellOrder {
+            initializer,
+            order: order_address,
+            market: order.market,
+            order_book: None,
+            wallet: find_wallet(&order.owner).0,
+            tree_authority: find_tree_authority(&tree.merkle_tree).0,
+            merkle_tree: tree.merkle_tree,
+            log_wrapper: SPL_NOOP_ID,
+            compression_program: SPL_ACCOUNT_COMPRESSION_ID,
+            mpl_bubblegum: mpl_bubblegum::ID,
+            system_program: System::id(),
+            clock: sysvar::clock::ID,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::CompressedCloseSellOrder { data },
+        tree.proof_metas(),
+    )
+}
+
+/// remaining accounts are the ovol accounts and the creators of the nft, followed by the proof
+pub fn compressed_fill_buy_order(
+    initializer: Pubkey,
+    order_address: Pubkey,
+    order: &Order,
+    tree: &CompressedNftAccounts,
+    data: CompressedFillOrderData,
+    remaining_accounts: &RemainingAccounts,
+) -> Instruction {
+    let mut metas = remaining_accounts.to_account_metas(RemainingAccountsLayout::Core);
+    metas.extend(tree.proof_metas());
+    build(
+        accounts::CompressedFillBuyOrder {
+            initializer,
+            buyer: order.owner,
+            wallet: find_wallet(&order.owner).0,
+            market: order.market,
+            order_book: None,
+            order: order_address,
+            treasury: treasury(),
+            tree_authority: find_tree_authority(&tree.merkle_tree).0,
+            merkle_tree: tree.merkle_tree,
+            log_wrapper: SPL_NOOP_ID,
+            compression_program: SPL_ACCOUNT_COMPRESSION_ID,
+            mpl_bubblegum: mpl_bubblegum::ID,
+            system_program: System::id(),
+            clock: sysvar::clock::ID,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::CompressedFillBuyOrder { data },
+        metas,
+    )
+}
+
+/// signed by the protocol treasury
+pub fn set_lst_config(
+    initializer: Pubkey,
+    stake_pool: &StakePoolAccounts,
+    enabled: bool,
+) -> Instruction {
+    build(
+        accounts::SetLstConfig {
+            initializer,
+            lst_config: find_lst_config().0,
+            stake_pool_program: stake_pool.program,
+            stake_pool: stake_pool.stake_pool,
+            pool_mint: stake_pool.pool_mint,
+            system_program: System::id(),
+        },
+        instruction::SetLstConfig { enabled },
+        Vec::new(),
+    )
+}
+
+pub fn stake_wallet(
+    initializer: Pubkey,
+    stake_pool: &StakePoolAccounts,
+    amount: u64,
+) -> Instruction {
+    let wallet = find_wallet(&initializer).0;
+    build(
+        accounts::StakeWallet {
+            initializer,
+            wallet,
+            lst_config: find_lst_config().0,
+            stake_pool_program: stake_pool.program,
+            stake_pool: stake_pool.stake_pool,
+            stake_pool_withdraw_authority: stake_pool.withdraw_authority,
+            reserve_stake: stake_pool.reserve_stake,
+            manager_fee_account: stake_pool.manager_fee_account,
+            pool_mint: stake_pool.pool_mint,
+            wallet_pool_ta: get_associated_token_address(&wallet, &stake_pool.pool_mint),
+            system_program: System::id(),
+            token_program: Token::id(),
+            associated_token_program: AssociatedToken::id(),
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::StakeWallet { amount },
+        Vec::new(),
+    )
+}
+
+pub fn unstake_wallet(
+    initializer: Pubkey,
+    stake_pool: &StakePoolAccounts,
+    pool_tokens: u64,
+) -> Instruction {
+    let wallet = find_wallet(&initializer).0;
+    build(
+        accounts::UnstakeWallet {
+            initializer,
+            wallet,
+            lst_config: find_lst_config().0,
+            stake_pool_program: stake_pool.program,
+            stake_pool: stake_pool.stake_pool,
+            stake_pool_withdraw_authority: stake_pool.withdraw_authority,
+            reserve_stake: stake_pool.reserve_stake,
+            manager_fee_account: stake_pool.manager_fee_account,
+            pool_mint: stake_pool.pool_mint,
+            wallet_pool_ta: get_associated_token_address(&wallet, &stake_pool.pool_mint),
+            stake_history: sysvar::stake_history::ID,
+            stake_program: stake::program::ID,
+            token_program: Token::id(),
+            clock: sysvar::clock::ID,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::UnstakeWallet { pool_tokens },
+        Vec::new(),
+    )
+}
+
+/// send ahead of a fill when the liquid balance of the buyer does not cover the bid
+pub fn unwind_wallet(
+    initializer: Pubkey,
+    order_address: Pubkey,
+    order: &Order,
+    stake_pool: &StakePoolAccounts,
+) -> Instruction {
+    build(
+        accounts::UnwindWallet {
+            initializer,
+            wallet: order.wallet,
+            order: order_address,
+            lst_config: find_lst_config().0,
+            stake_pool_program: stake_pool.program,
+            stake_pool: stake_pool.stake_pool,
+            stake_pool_withdraw_authority: stake_pool.withdraw_authority,
+            reserve_stake: stake_pool.reserve_stake,
+            manager_fee_account: stake_pool.manager_fee_account,
+            pool_mint: stake_pool.pool_mint,
+            wallet_pool_ta: get_associated_token_address(&order.wallet, &stake_pool.pool_mint),
+            stake_history: sysvar::stake_history::ID,
+            stake_program: stake::program::ID,
+            token_program: Token::id(),
+            clock: sysvar::clock::ID,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::UnwindWallet {},
+        Vec::new(),
+    )
+}
+
+/// initializer is the manager of the pool
+pub fn init_bid_pool(initializer: Pubkey, nonce: Pubkey) -> Instruction {
+    let bid_pool = find_bid_pool(&initializer, &nonce).0;
+    build(
+        accounts::InitBidPool {
+            initializer,
+            bid_pool,
+            wallet: find_wallet(&bid_pool).0,
+            system_program: System::id(),
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::InitBidPool { nonce },
+        Vec::new(),
+    )
+}
+
+pub fn deposit_bid_pool(initializer: Pubkey, bid_pool: Pubkey, amount: u64) -> Instruction {
+    build(
+        accounts::DepositBidPool {
+            initializer,
+            bid_pool,
+            wallet: find_wallet(&bid_pool).0,
+            pool_share: find_pool_share(&bid_pool, &initializer).0,
+            system_program: System::id(),
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::DepositBidPool { amount },
+        Vec::new(),
+    )
+}
+
+pub fn redeem_bid_pool_sol(initializer: Pubkey, bid_pool: Pubkey, shares: u64) -> Instruction {
+    build(
+        accounts::RedeemBidPoolSol {
+            initializer,
+            bid_pool,
+            wallet: find_wallet(&bid_pool).0,
+            pool_share: find_pool_share(&bid_pool, &initializer).0,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::RedeemBidPoolSol { shares },
+        Vec::new(),
+    )
+}
+
+/// the counterparty token record of the remaining accounts is the token record of the initializer
+pub fn redeem_bid_pool_nft(
+    initializer: Pubkey,
+    bid_pool: Pubkey,
+    nft: NftAccounts,
+    remaining_accounts: &RemainingAccounts,
+) -> Instruction {
+    build(
+        accounts::RedeemBidPoolNft {
+            initializer,
+            bid_pool,
+            wallet: find_wallet(&bid_pool).0,
+            pool_share: find_pool_share(&bid_pool, &initializer).0,
+            nft_mint: nft.mint,
+            pool_nft: find_pool_nft(&bid_pool, &nft.mint).0,
+            nft_metadata: nft.metadata,
+            nft_edition: nft.edition,
+            bid_pool_nft_ta: get_associated_token_address(&bid_pool, &nft.mint),
+            initializer_nft_ta: get_associated_token_address(&initializer, &nft.mint),
+            system_program: System::id(),
+            token_program: Token::id(),
+            sysvar_instructions: sysvar::instructions::ID,
+            associated_token_program: AssociatedToken::id(),
+            token_metadata_program: MplTokenMetadata::id(),
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::RedeemBidPoolNft {},
+        remaining_accounts.to_account_metas(RemainingAccountsLayout::Fill),
+    )
+}
+
+/// initializer owns the pool, its bidding wallet funds the buy side
+pub fn init_amm_pool(
+    initializer: Pubkey,
+    pool_mint: Pubkey,
+    nonce: Pubkey,
+    data: AmmPoolData,
+) -> Instruction {
+    let market = find_market(&pool_mint).0;
+    build(
+        accounts::InitAmmPool {
+            initializer,
+            wallet: find_wallet(&initializer).0,
+            market,
+            amm_pool: find_amm_pool(&market, &initializer, &nonce).0,
+            system_program: System::id(),
+            clock: sysvar::clock::ID,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::InitAmmPool { nonce, data },
+        Vec::new(),
+    )
+}
+
+pub fn edit_amm_pool(initializer: Pubkey, amm_pool: Pubkey, data: AmmPoolData) -> Instruction {
+    build(
+        accounts::EditAmmPool {
+            initializer,
+            amm_pool,
+            clock: sysvar::clock::ID,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::EditAmmPool { data },
+        Vec::new(),
+    )
+}
+
+pub fn close_amm_pool(initializer: Pubkey, amm_pool: Pubkey) -> Instruction {
+    build(
+        accounts::CloseAmmPool {
+            initializer,
+            wallet: find_wallet(&initializer).0,
+            amm_pool,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::CloseAmmPool {},
+        Vec::new(),
+    )
+}
+
+/// the counterparty token record of the remaining accounts is the token record of the pool
+pub fn deposit_amm_nft(
+    initializer: Pubkey,
+    pool_mint: Pubkey,
+    amm_pool: Pubkey,
+    nft: NftAccounts,
+    remaining_accounts: &RemainingAccounts,
+) -> Instruction {
+    build(
+        accounts::DepositAmmNft {
+            initializer,
+            market: find_market(&pool_mint).0,
+            amm_pool,
+            appraisal: find_appraisal(&pool_mint, &nft.mint).0,
+            nft_mint: nft.mint,
+            nft_metadata: nft.metadata,
+            nft_edition: nft.edition,
+            initializer_nft_ta: get_associated_token_address(&initializer, &nft.mint),
+            amm_pool_nft_ta: get_associated_token_address(&amm_pool, &nft.mint),
+            system_program: System::id(),
+            token_program: Token::id(),
+            sysvar_instructions: sysvar::instructions::ID,
+            associated_token_program: AssociatedToken::id(),
+            token_metadata_program: MplTokenMetadata::id(),
+            clock: sysvar::clock::ID,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::DepositAmmNft {},
+        remaining_accounts.to_account_metas(RemainingAccountsLayout::Fill),
+    )
+}
+
+/// the counterparty token record of the remaining accounts is the token record of the initializer
+pub fn withdraw_amm_nft(
+    initializer: Pubkey,
+    amm_pool: Pubkey,
+    nft: NftAccounts,
+    remaining_accounts: &RemainingAccounts,
+) -> Instruction {
+    build(
+        accounts::WithdrawAmmNft {
+            initializer,
+            amm_pool,
+            nft_mint: nft.mint,
+            nft_metadata: nft.metadata,
+            nft_edition: nft.edition,
+            amm_pool_nft_ta: get_associated_token_address(&amm_pool, &nft.mint),
+            initializer_nft_ta: get_associated_token_address(&initializer, &nft.mint),
+            system_program: System::id(),
+            token_program: Token::id(),
+            sysvar_instructions: sysvar::instructions::ID,
+            associated_token_program: AssociatedToken::id(),
+            token_metadata_program: MplTokenMetadata::id(),
+            clock: sysvar::clock::ID,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::WithdrawAmmNft {},
+        remaining_accounts.to_account_metas(RemainingAccountsLayout::Fill),
+    )
+}
+
+/// initializer buys an nft held by the pool at its ask, fails above max_price
+pub fn amm_buy_nft(
+    initializer: Pubkey,
+    amm_pool_address: Pubkey,
+    amm_pool: &AmmPool,
+    nft: NftAccounts,
+    max_price: u64,
+    remaining_accounts: &RemainingAccounts,
+) -> Instruction {
+    build(
+        accounts::AmmBuyNft {
+            initializer,
+            wallet: find_wallet(&amm_pool.owner).0,
+            market: amm_pool.market,
+            amm_pool: amm_pool_address,
+            nft_mint: nft.mint,
+            nft_metadata: nft.metadata,
+            nft_edition: nft.edition,
+            amm_pool_nft_ta: get_associated_token_address(&amm_pool_address, &nft.mint),
+            buyer_nft_ta: get_associated_token_address(&initializer, &nft.mint),
+            treasury: treasury(),
+            system_program: System::id(),
+            token_program: Token::id(),
+            sysvar_instructions: sysvar::instructions::ID,
+            associated_token_program: AssociatedToken::id(),
+            token_metadata_program: MplTokenMetadata::id(),
+            clock: sysvar::clock::ID,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::AmmBuyNft { max_price },
+        remaining_accounts.to_account_metas(RemainingAccountsLayout::Fill),
+    )
+}
+
+/// initializer sells an appraised nft to the pool at its bid, fails below min_price
+pub fn amm_sell_nft(
+    initializer: Pubkey,
+    pool_mint: Pubkey,
+    amm_pool_address: Pubkey,
+    amm_pool: &AmmPool,
+    nft: NftAccounts,
+    min_price: u64,
+    remaining_accounts: &RemainingAccounts,
+) -> Instruction {
+    build(
+        accounts::AmmSellNft {
+            initializer,
+            wallet: find_wallet(&amm_pool.owner).0,
+            market: amm_pool.market,
+            amm_pool: amm_pool_address,
+            appraisal: find_appraisal(&pool_mint, &nft.mint).0,
+            nft_mint: nft.mint,
+            nft_metadata: nft.metadata,
+            nft_edition: nft.edition,
+            seller_nft_ta: get_associated_token_address(&initializer, &nft.mint),
+            amm_pool_nft_ta: get_associated_token_address(&amm_pool_address, &nft.mint),
+            treasury: treasury(),
+            system_program: System::id(),
+            token_program: Token::id(),
+            sysvar_instructions: sysvar::instructions::ID,
+            associated_token_program: AssociatedToken::id(),
+            token_metadata_program: MplTokenMetadata::id(),
+            clock: sysvar::clock::ID,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::AmmSellNft { min_price },
+        remaining_accounts.to_account_metas(RemainingAccountsLayout::Fill),
+    )
+}
+
+pub fn core_init_sell_order(
+    initializer: Pubkey,
+    pool_mint: Pubkey,
+    asset: Pubkey,
+    collection: Option<Pubkey>,
+    data: InitOrderData,
+    remaining_accounts: &RemainingAccounts,
+) -> Instruction {
+    let market = find_market(&pool_mint).0;
+    build(
+        accounts::CoreInitSellOrder {
+            initializer,
+            wallet: find_wallet(&initializer).0,
+            market,
+            order_book: None,
+            order: find_order(&data.nonce, &market, &initializer).0,
+            appraisal: find_appraisal(&pool_mint, &asset).0,
+            asset,
+            collection,
+            mpl_core_program: mpl_core::ID,
+            system_program: System::id(),
+            clock: sysvar::clock::ID,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::CoreInitSellOrder { data },
+        remaining_accounts.to_account_metas(RemainingAccountsLayout::Core),
+    )
+}
+
+pub fn core_fill_sell_order(
+    initializer: Pubkey,
+    order_address: Pubkey,
+    order: &Order,
+    collection: Option<Pubkey>,
+    remaining_accounts: &RemainingAccounts,
+) -> Instruction {
+    build(
+        accounts::CoreFillSellOrder {
+            initializer,
+            seller: order.owner,
+            wallet: find_wallet(&order.owner).0,
+            market: order.market,
+            order_book: None,
+            order: order_address,
+            asset: order.nft_mint,
+            collection,
+            treasury: treasury(),
+            mpl_core_program: mpl_core::ID,
+            system_program: System::id(),
+            clock: sysvar::clock::ID,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::CoreFillSellOrder {},
+        remaining_accounts.to_account_metas(RemainingAccountsLayout::Core),
+    )
+}
+
+pub fn core_close_sell_order(
+    initializer: Pubkey,
+    order_address: Pubkey,
+    order: &Order,
+    collection: Option<Pubkey>,
+) -> Instruction {
+    build(
+        accounts::CoreCloseSellOrder {
+            initializer,
+            order: order_address,
+            market: order.market,
+            order_book: None,
+            wallet: find_wallet(&order.owner).0,
+            asset: order.nft_mint,
+            collection,
+            mpl_core_program: mpl_core::ID,
+            system_program: System::id(),
+            clock: sysvar::clock::ID,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::CoreCloseSellOrder {},
+        Vec::new(),
+    )
+}
+
+pub fn core_fill_buy_order(
+    initializer: Pubkey,
+    order_address: Pubkey,
+    order: &Order,
+    pool_mint: Pubkey,
+    asset: Pubkey,
+    collection: Option<Pubkey>,
+    remaining_accounts: &RemainingAccounts,
+) -> Instruction {
+    build(
+        accounts::CoreFillBuyOrder {
+            initializer,
+            buyer: order.owner,
+            wallet: find_wallet(&order.owner).0,
+            market: order.market,
+            order_book: None,
+            order: order_address,
+            appraisal: find_appraisal(&pool_mint, &asset).0,
+            asset,
+            collection,
+            treasury: treasury(),
+            mpl_core_program: mpl_core::ID,
+            system_program: System::id(),
+            clock: sysvar::clock::ID,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::CoreFillBuyOrder {},
+        remaining_accounts.to_account_metas(RemainingAccountsLayout::Core),
+    )
+}
+
+fn token22_ata(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(owner, mint, &Token2022::id())
+}
+
+pub fn token22_init_sell_order(
+    initializer: Pubkey,
+    pool_mint: Pubkey,
+    nft_mint: Pubkey,
+    data: InitOrderData,
+    remaining_accounts: &RemainingAccounts,
+) -> Instruction {
+    let market = find_market(&pool_mint).0;
+    let wallet = find_wallet(&initializer).0;
+    build(
+        accounts::InitSellOrder {
+            initializer,
+            wallet,
+            market,
+            order_book: None,
+            order: find_order(&data.nonce, &market, &initializer).0,
+            appraisal: find_appraisal(&pool_mint, &nft_mint).0,
+            nft_mint,
+            nft_metadata: None,
+            nft_edition: None,
+            nft_ta: token22_ata(&initializer, &nft_mint),
+            escrow_nft_ta: token22_ata(&wallet, &nft_mint),
+            sysvar_instructions: sysvar::instructions::ID,
+            system_program: System::id(),
+            token_program: Token2022::id(),
+            associated_token_program: AssociatedToken::id(),
+            token_metadata_program: MplTokenMetadata::id(),
+            clock: sysvar::clock::ID,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::InitSellOrder { data },
+        remaining_accounts.to_account_metas(RemainingAccountsLayout::Token22),
+    )
+}
+
+pub fn token22_fill_sell_order(
+    initializer: Pubkey,
+    order_address: Pubkey,
+    order: &Order,
+    remaining_accounts: &RemainingAccounts,
+) -> Instruction {
+    let wallet = find_wallet(&order.owner).0;
+    build(
+        accounts::FillSellOrder {
+            initializer,
+            seller: order.owner,
+            wallet,
+            market: order.market,
+            order_book: None,
+            order: order_address,
+            nft_mint: order.nft_mint,
+            nft_metadata: None,
+            nft_edition: None,
+            seller_nft_ta: token22_ata(&wallet, &order.nft_mint),
+            buyer_nft_ta: token22_ata(&initializer, &order.nft_mint),
+            treasury: treasury(),
+            system_program: System::id(),
+            token_program: Token2022::id(),
+            sysvar_instructions: sysvar::instructions::ID,
+            associated_token_program: AssociatedToken::id(),
+            token_metadata_program: MplTokenMetadata::id(),
+            clock: sysvar::clock::ID,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::FillSellOrder {},
+        remaining_accounts.to_account_metas(RemainingAccountsLayout::Token22),
+    )
+}
+
+pub fn token22_close_sell_order(
+    initializer: Pubkey,
+    order_address: Pubkey,
+    order: &Order,
+    remaining_accounts: &RemainingAccounts,
+) -> Instruction {
+    let wallet = find_wallet(&order.owner).0;
+    build(
+        accounts::CloseSellOrder {
+            initializer,
+            order: order_address,
+            market: order.market,
+            order_book: None,
+            wallet,
+            nft_mint: order.nft_mint,
+            nft_metadata: None,
+            nft_edition: None,
+            nft_ta: token22_ata(&initializer, &order.nft_mint),
+            escrow_nft_ta: token22_ata(&wallet, &order.nft_mint),
+            system_program: System::id(),
+            token_program: Token2022::id(),
+            sysvar_instructions: sysvar::instructions::ID,
+            associated_token_program: AssociatedToken::id(),
+            token_metadata_program: MplTokenMetadata::id(),
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::CloseSellOrder {},
+        remaining_accounts.to_account_metas(RemainingAccountsLayout::Token22),
+    )
+}
+
+pub fn token22_fill_buy_order(
+    initializer: Pubkey,
+    order_address: Pubkey,
+    order: &Order,
+    pool_mint: Pubkey,
+    nft_mint: Pubkey,
+    remaining_accounts: &RemainingAccounts,
+) -> Instruction {
+    build(
+        accounts::FillBuyOrder {
+            initializer,
+            buyer: order.owner,
+            wallet: find_wallet(&order.owner).0,
+            market: order.market,
+            order_book: None,
+            order: order_address,
+            nft_mint,
+            nft_metadata: None,
+            nft_edition: None,
+            appraisal: Some(find_appraisal(&pool_mint, &nft_mint).0),
+            seller_nft_ta: token22_ata(&initializer, &nft_mint),
+            buyer_nft_ta: token22_ata(&order.owner, &nft_mint),
+            treasury: treasury(),
+            system_program: System::id(),
+            token_program: Token2022::id(),
+            sysvar_instructions: sysvar::instructions::ID,
+            associated_token_program: AssociatedToken::id(),
+            token_metadata_program: MplTokenMetadata::id(),
+            clock: sysvar::clock::ID,
+            pool_nft: None,
+            lst_config: None,
+            stake_pool_program: None,
+            stake_pool: None,
+            stake_pool_withdraw_authority: None,
+            reserve_stake: None,
+            manager_fee_account: None,
+            lst_mint: None,
+            wallet_lst_ta: None,
+            stake_history: None,
+            stake_program: None,
+            lst_token_program: None,
+            event_authority: find_event_authority().0,
+            program: listings::ID,
+        },
+        instruction::FillBuyOrder {},
+        remaining_accounts.to_account_metas(RemainingAccountsLayout::Token22),
+    )
+}
@@ -0,0 +1,18 @@
+//! off-chain client for the listings program
+//! derives the program addresses and builds the instructions along with their remaining accounts
+
+pub mod instructions;
+pub mod pda;
+pub mod remaining_accounts;
+
+pub use instructions::*;
+pub use pda::*;
+pub use remaining_accounts::*;
+
+pub use listings::{
+    instructions::{
+        AmmPoolData, CompressedCreator, CompressedFillOrderData, CompressedOrderData,
+        EditBuyOrderData, EditSellOrderData, InitOrderData, Quote, TrailingPriceData,
+    },
+    state,
+};
@@ -0,0 +1,118 @@
+use anchor_lang::prelude::Pubkey;
+use listings::state::{
+    AMM_POOL_SEED, BID_POOL_SEED, LST_CONFIG_SEED, MARKET_SEED, ORDER_BOOK_SEED, ORDER_SEED,
+    POOL_NFT_SEED, POOL_SHARE_SEED, WALLET_SEED,
+};
+use vault::state::APPRAISAL_SEED;
+
+/// seed of the event authority used by `emit_cpi!`
+pub const EVENT_AUTHORITY_SEED: &[u8] = b"__event_authority";
+
+pub fn find_market(pool_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MARKET_SEED.as_ref(), pool_mint.as_ref()], &listings::ID)
+}
+
+/// orders are keyed by the owner, not by whoever signs the transaction
+pub fn find_order(nonce: &Pubkey, market: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            ORDER_SEED.as_ref(),
+            nonce.as_ref(),
+            market.as_ref(),
+            owner.as_ref(),
+        ],
+        &listings::ID,
+    )
+}
+
+/// order book of a market, the order handlers only update it once it is initialized
+pub fn find_order_book(market: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ORDER_BOOK_SEED.as_ref(), market.as_ref()], &listings::ID)
+}
+
+pub fn find_amm_pool(market: &Pubkey, owner: &Pubkey, nonce: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            AMM_POOL_SEED.as_ref(),
+            market.as_ref(),
+            owner.as_ref(),
+            nonce.as_ref(),
+        ],
+        &listings::ID,
+    )
+}
+
+pub fn find_wallet(owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[WALLET_SEED.as_ref(), owner.as_ref()], &listings::ID)
+}
+
+/// the wallet of a bid pool is `find_wallet(&bid_pool)`
+pub fn find_bid_pool(manager: &Pubkey, nonce: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[BID_POOL_SEED.as_ref(), manager.as_ref(), nonce.as_ref()],
+        &listings::ID,
+    )
+}
+
+pub fn find_pool_share(bid_pool: &Pubkey, depositor: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            POOL_SHARE_SEED.as_ref(),
+            bid_pool.as_ref(),
+            depositor.as_ref(),
+        ],
+        &listings::ID,
+    )
+}
+
+/// cost record of a mint held by a bid pool, passed to every buy fill
+pub fn find_pool_nft(bid_pool: &Pubkey, nft_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[POOL_NFT_SEED.as_ref(), bid_pool.as_ref(), nft_mint.as_ref()],
+        &listings::ID,
+    )
+}
+
+pub fn find_lst_config() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[LST_CONFIG_SEED.as_ref()], &listings::ID)
+}
+
+/// withdraw authority of a stake pool, mint authority of its pool tokens
+pub fn find_stake_pool_withdraw_authority(
+    stake_pool_program: &Pubkey,
+    stake_pool: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[stake_pool.as_ref(), b"withdraw"], stake_pool_program)
+}
+
+pub fn find_event_authority() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[EVENT_AUTHORITY_SEED], &listings::ID)
+}
+
+/// appraisal of an nft in the vault program, required to list it
+pub fn find_appraisal(pool_mint: &Pubkey, nft_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[APPRAISAL_SEED, pool_mint.as_ref(), nft_mint.as_ref()],
+        &vault::ID,
+    )
+}
+
+pub fn find_metadata(nft_mint: &Pubkey) -> (Pubkey, u8) {
+    token_metadata::pda::find_metadata_account(nft_mint)
+}
+
+pub fn find_edition(nft_mint: &Pubkey) -> (Pubkey, u8) {
+    token_metadata::pda::find_master_edition_account(nft_mint)
+}
+
+pub fn find_token_record(nft_mint: &Pubkey, token_account: &Pubkey) -> (Pubkey, u8) {
+    token_metadata::pda::find_token_record_account(nft_mint, token_account)
+}
+
+/// bubblegum tree authority of a merkle tree
+pub fn find_tree_authority(merkle_tree: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[merkle_tree.as_ref()],
+        &bridgesplit_program_utils::mpl_bubblegum::ID,
+    )
+}
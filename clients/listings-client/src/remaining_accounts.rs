@@ -0,0 +1,152 @@
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+
+/// token record and auth rules of a pnft
+#[derive(Clone, Copy, Debug)]
+pub struct PnftAccounts {
+    pub token_record: Pubkey,
+    pub authorization_rules: Pubkey,
+    pub authorization_rules_program: Pubkey,
+}
+
+/// ovol nft proving the signer is exempt from protocol fees
+#[derive(Clone, Copy, Debug)]
+pub struct OvolAccounts {
+    pub nft_ta: Pubkey,
+    pub nft_metadata: Pubkey,
+}
+
+/// delegate which has to be removed before the nft can be listed
+#[derive(Clone, Copy, Debug)]
+pub struct ExistingDelegateAccounts {
+    pub existing_delegate: Pubkey,
+    pub existing_delegate_record: Pubkey,
+}
+
+/// accounts passed after the named accounts of an instruction
+/// mirrors what `listings::utils::parse_remaining_accounts` expects
+#[derive(Clone, Debug, Default)]
+pub struct RemainingAccounts {
+    pub pnft: Option<PnftAccounts>,
+    /// sale delegate record, or the token record of the receiving ata when a listing is
+    /// escrowed or returned from escrow, unused by fills
+    pub delegate_record: Option<Pubkey>,
+    /// token record of the counterparty in fills
+    pub counterparty_token_record: Option<Pubkey>,
+    pub existing_delegate: Option<ExistingDelegateAccounts>,
+    pub ovol: Option<OvolAccounts>,
+    /// creators in the order of the metadata, required to pay pnft royalties
+    pub creators: Vec<Pubkey>,
+    /// transfer hook program, its validation account and extra accounts of a token-2022 mint
+    pub transfer_hook: Vec<AccountMeta>,
+}
+
+/// shape of the remaining accounts for a given instruction
+#[derive(Clone, Copy, Debug)]
+pub enum RemainingAccountsLayout {
+    /// init buy order: pnft accounts, an unused delegate record slot and ovol accounts
+    InitBuy,
+    /// init sell order: pnft accounts, delegate record, existing delegate and ovol accounts
+    InitSell,
+    /// close sell order: pnft accounts and delegate record
+    CloseSell,
+    /// fill buy/sell order: pnft accounts, an unused slot, counterparty token record,
+    /// ovol accounts and creators
+    Fill,
+    /// token-2022 instructions: ovol accounts and transfer hook accounts
+    Token22,
+    /// metaplex core instructions and compressed fills: ovol accounts and royalty creators
+    Core,
+}
+
+fn meta(key: Option<Pubkey>, is_writable: bool) -> AccountMeta {
+    match key {
+        Some(key) if is_writable => AccountMeta::new(key, false),
+        Some(key) => AccountMeta::new_readonly(key, false),
+        None => AccountMeta::new_readonly(Pubkey::default(), false),
+    }
+}
+
+impl RemainingAccounts {
+    /// ovol accounts, or default pubkeys when the signer holds none
+    fn ovol_metas(&self) -> Vec<AccountMeta> {
+        vec![
+            meta(self.ovol.map(|ovol| ovol.nft_ta), false),
+            meta(self.ovol.map(|ovol| ovol.nft_metadata), false),
+        ]
+    }
+
+    pub fn to_account_metas(&self, layout: RemainingAccountsLayout) -> Vec<AccountMeta> {
+        match layout {
+            RemainingAccountsLayout::Token22 => {
+                let mut metas = self.ovol_metas();
+                metas.extend(self.transfer_hook.iter().cloned());
+                return metas;
+            }
+            RemainingAccountsLayout::Core => {
+                let mut metas = self.ovol_metas();
+                metas.extend(
+                    self.creators
+                        .iter()
+                        .map(|creator| AccountMeta::new(*creator, false)),
+                );
+                return metas;
+            }
+            _ => {}
+        }
+
+        let mut metas = vec![
+            meta(self.pnft.map(|pnft| pnft.token_record), true),
+            meta(self.pnft.map(|pnft| pnft.authorization_rules), false),
+            meta(
+                self.pnft.map(|pnft| pnft.authorization_rules_program),
+                false,
+            ),
+        ];
+
+        match layout {
+            RemainingAccountsLayout::InitBuy => {
+                metas.push(meta(None, false));
+            }
+            RemainingAccountsLayout::InitSell => {
+                metas.push(meta(self.delegate_record, true));
+                metas.push(meta(
+                    self.existing_delegate
+                        .map(|delegate| delegate.existing_delegate),
+                    false,
+                ));
+                metas.push(meta(
+                    self.existing_delegate
+                        .map(|delegate| delegate.existing_delegate_record),
+                    true,
+                ));
+            }
+            RemainingAccountsLayout::CloseSell => {
+                metas.push(meta(self.delegate_record, true));
+                // nothing is read after the delegate record
+                return metas;
+            }
+            RemainingAccountsLayout::Fill => {
+                // fills skip one account after the pnft accounts, the parsed delegate record
+                // is the counterparty token record read by the fill
+                metas.push(meta(None, false));
+                metas.push(meta(self.counterparty_token_record, true));
+            }
+            RemainingAccountsLayout::Token22 | RemainingAccountsLayout::Core => {
+                unreachable!("layouts without pnft accounts return early")
+            }
+        }
+
+        // the program checks for an ovol holder whenever accounts remain,
+        // so default accounts are passed if creators follow without an ovol nft
+        if self.ovol.is_some() || !self.creators.is_empty() {
+            metas.extend(self.ovol_metas());
+        }
+
+        metas.extend(
+            self.creators
+                .iter()
+                .map(|creator| AccountMeta::new(*creator, false)),
+        );
+        metas
+    }
+}
@@ -0,0 +1,210 @@
+//! the remaining accounts built by the client are parsed back with the program parser
+
+use anchor_lang::prelude::{AccountInfo, AccountMeta, Pubkey};
+use listings::utils::{parse_remaining_accounts, ParsedRemainingAccounts};
+use listings_client::{
+    ExistingDelegateAccounts, OvolAccounts, PnftAccounts, RemainingAccounts,
+    RemainingAccountsLayout,
+};
+
+/// backing storage of the account infos handed to the parser
+struct Accounts {
+    keys: Vec<Pubkey>,
+    lamports: Vec<u64>,
+    data: Vec<Vec<u8>>,
+    owner: Pubkey,
+}
+
+impl Accounts {
+    fn new(metas: &[AccountMeta]) -> Self {
+        Self {
+            keys: metas.iter().map(|meta| meta.pubkey).collect(),
+            lamports: vec![0; metas.len()],
+            data: vec![Vec::new(); metas.len()],
+            owner: Pubkey::default(),
+        }
+    }
+
+    fn infos(&mut self) -> Vec<AccountInfo> {
+        let owner = &self.owner;
+        self.keys
+            .iter()
+            .zip(self.lamports.iter_mut())
+            .zip(self.data.iter_mut())
+            .map(|((key, lamports), data)| {
+                AccountInfo::new(key, false, false, lamports, data, owner, false, 0)
+            })
+            .collect()
+    }
+}
+
+fn key() -> Pubkey {
+    Pubkey::new_unique()
+}
+
+fn pnft() -> PnftAccounts {
+    PnftAccounts {
+        token_record: key(),
+        authorization_rules: key(),
+        authorization_rules_program: key(),
+    }
+}
+
+fn ovol() -> OvolAccounts {
+    OvolAccounts {
+        nft_ta: key(),
+        nft_metadata: key(),
+    }
+}
+
+fn keys(accounts: &[AccountInfo]) -> Vec<Pubkey> {
+    accounts.iter().map(|account| *account.key).collect()
+}
+
+fn assert_pnft(parsed: &ParsedRemainingAccounts, pnft: PnftAccounts) {
+    let params = &parsed.pnft_params;
+    assert_eq!(
+        params.token_record.as_ref().map(|account| *account.key),
+        Some(pnft.token_record)
+    );
+    assert_eq!(
+        params
+            .authorization_rules
+            .as_ref()
+            .map(|account| *account.key),
+        Some(pnft.authorization_rules)
+    );
+    assert_eq!(
+        params
+            .authorization_rules_program
+            .as_ref()
+            .map(|account| *account.key),
+        Some(pnft.authorization_rules_program)
+    );
+}
+
+#[test]
+fn fill_layout_round_trip() {
+    let pnft = pnft();
+    let counterparty_token_record = key();
+    let creators = vec![key(), key(), key()];
+    let remaining_accounts = RemainingAccounts {
+        pnft: Some(pnft),
+        counterparty_token_record: Some(counterparty_token_record),
+        ovol: Some(ovol()),
+        creators: creators.clone(),
+        ..RemainingAccounts::default()
+    };
+    let metas = remaining_accounts.to_account_metas(RemainingAccountsLayout::Fill);
+    let mut accounts = Accounts::new(&metas);
+    let infos = accounts.infos();
+
+    // fills skip the account after the pnft accounts
+    let parsed = parse_remaining_accounts(infos.clone(), key(), true, false, Some(1));
+    assert_pnft(&parsed, pnft);
+    // fill handlers read the counterparty token record at index 4
+    assert_eq!(*infos[4].key, counterparty_token_record);
+    assert_eq!(
+        parsed.delegate_record.map(|account| *account.key),
+        Some(counterparty_token_record)
+    );
+    assert!(parsed.existing_delegate_params.is_none());
+    // the ovol nft does not deserialize, fees stay on
+    assert!(parsed.fees_on);
+    assert_eq!(keys(&parsed.creator_accounts), creators);
+}
+
+#[test]
+fn fill_layout_without_ovol_round_trip() {
+    let creators = vec![key()];
+    let remaining_accounts = RemainingAccounts {
+        creators: creators.clone(),
+        ..RemainingAccounts::default()
+    };
+    let metas = remaining_accounts.to_account_metas(RemainingAccountsLayout::Fill);
+    let mut accounts = Accounts::new(&metas);
+
+    let parsed = parse_remaining_accounts(accounts.infos(), key(), false, false, Some(1));
+    assert!(parsed.pnft_params.token_record.is_none());
+    assert!(parsed.delegate_record.is_none());
+    assert_eq!(keys(&parsed.creator_accounts), creators);
+}
+
+#[test]
+fn init_buy_layout_round_trip() {
+    let remaining_accounts = RemainingAccounts::default();
+    let metas = remaining_accounts.to_account_metas(RemainingAccountsLayout::InitBuy);
+    let mut accounts = Accounts::new(&metas);
+
+    // without ovol accounts the fees of the order apply
+    let parsed = parse_remaining_accounts(accounts.infos(), key(), false, false, None);
+    assert!(parsed.delegate_record.is_none());
+    assert!(!parsed.fees_on);
+    assert!(parsed.creator_accounts.is_empty());
+
+    let remaining_accounts = RemainingAccounts {
+        ovol: Some(ovol()),
+        ..RemainingAccounts::default()
+    };
+    let metas = remaining_accounts.to_account_metas(RemainingAccountsLayout::InitBuy);
+    let mut accounts = Accounts::new(&metas);
+
+    // ovol accounts are checked, the fake nft keeps fees on
+    let parsed = parse_remaining_accounts(accounts.infos(), key(), false, false, None);
+    assert!(parsed.fees_on);
+    assert!(parsed.creator_accounts.is_empty());
+}
+
+#[test]
+fn init_sell_layout_round_trip() {
+    let pnft = pnft();
+    let delegate_record = key();
+    let existing_delegate = ExistingDelegateAccounts {
+        existing_delegate: key(),
+        existing_delegate_record: key(),
+    };
+    let remaining_accounts = RemainingAccounts {
+        pnft: Some(pnft),
+        delegate_record: Some(delegate_record),
+        existing_delegate: Some(existing_delegate),
+        ..RemainingAccounts::default()
+    };
+    let metas = remaining_accounts.to_account_metas(RemainingAccountsLayout::InitSell);
+    let mut accounts = Accounts::new(&metas);
+
+    let parsed = parse_remaining_accounts(accounts.infos(), key(), false, true, None);
+    assert_pnft(&parsed, pnft);
+    assert_eq!(
+        parsed.delegate_record.map(|account| *account.key),
+        Some(delegate_record)
+    );
+    let params = parsed.existing_delegate_params.unwrap();
+    assert_eq!(
+        *params.existing_delegate.key,
+        existing_delegate.existing_delegate
+    );
+    assert_eq!(
+        *params.existing_delegate_record.key,
+        existing_delegate.existing_delegate_record
+    );
+    assert!(!parsed.fees_on);
+}
+
+#[test]
+fn close_sell_layout_round_trip() {
+    let delegate_record = key();
+    let remaining_accounts = RemainingAccounts {
+        delegate_record: Some(delegate_record),
+        ..RemainingAccounts::default()
+    };
+    let metas = remaining_accounts.to_account_metas(RemainingAccountsLayout::CloseSell);
+    let mut accounts = Accounts::new(&metas);
+
+    let parsed = parse_remaining_accounts(accounts.infos(), key(), false, false, None);
+    assert!(parsed.pnft_params.token_record.is_none());
+    assert_eq!(
+        parsed.delegate_record.map(|account| *account.key),
+        Some(delegate_record)
+    );
+    assert!(parsed.creator_accounts.is_empty());
+}
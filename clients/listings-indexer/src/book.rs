@@ -0,0 +1,284 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anchor_lang::prelude::Pubkey;
+use listings::state::{
+    AmmPoolEditEvent, AmmPoolEditType, BidPoolEditEvent, MarketEditEventV2, OrderEditEventV2,
+    OrderEditType, OrderFillEvent, OrderSide, OrderState, WalletEditEventV2, WalletEditType,
+};
+
+use crate::events::ListingsEvent;
+
+/// live order as last seen in an event
+#[derive(Clone, Debug, PartialEq)]
+pub struct BookOrder {
+    pub address: Pubkey,
+    pub owner: Pubkey,
+    pub wallet: Pubkey,
+    pub side: u8,
+    pub price: u64,
+    pub size: u64,
+    pub state: u8,
+    pub nft_mint: Pubkey,
+    pub last_edit_time: i64,
+    /// cap of a trailing bid, 0 if the price is fixed
+    pub max_price: u64,
+    /// escrowed listings hold their nft in the escrow ata of the wallet
+    pub listing_mode: u8,
+    pub original_size: u64,
+    pub filled_size: u64,
+    /// sum of the prices of all fills
+    pub filled_value: u64,
+}
+
+impl BookOrder {
+    pub fn is_buy(&self) -> bool {
+        self.side == u8::from(OrderSide::Buy)
+    }
+
+    /// average price of the filled units
+    pub fn average_fill_price(&self) -> Option<u64> {
+        self.filled_value.checked_div(self.filled_size)
+    }
+
+    /// inactive listings wait for their trigger and are not part of the book
+    pub fn is_fillable(&self) -> bool {
+        self.size > 0 && self.state != u8::from(OrderState::Inactive)
+    }
+}
+
+/// amm pool as last seen in an event
+#[derive(Clone, Debug, PartialEq)]
+pub struct BookAmmPool {
+    pub address: Pubkey,
+    pub owner: Pubkey,
+    pub wallet: Pubkey,
+    pub curve: u8,
+    /// current bid of the pool
+    pub spot_price: u64,
+    pub delta: u64,
+    pub spread_bps: u64,
+    pub buy_size: u64,
+    pub nft_count: u64,
+    pub last_edit_time: i64,
+}
+
+/// order book of a single market
+#[derive(Clone, Debug, Default)]
+pub struct MarketBook {
+    pub pool_mint: Pubkey,
+    pub initializer: Pubkey,
+    pub state: u8,
+    /// listing mode of new sell orders
+    pub listing_mode: u8,
+    /// minimum withdrawal delay of the wallets placing bids
+    pub min_withdrawal_delay: i64,
+    pub orders: HashMap<Pubkey, BookOrder>,
+    pub amm_pools: HashMap<Pubkey, BookAmmPool>,
+    /// price of the latest fill
+    pub last_fill_price: Option<u64>,
+    /// sum of the prices of all fills
+    pub volume: u64,
+    pub fill_count: u64,
+}
+
+impl MarketBook {
+    fn side(&self, buy: bool) -> impl Iterator<Item = &BookOrder> {
+        self.orders
+            .values()
+            .filter(move |order| order.is_buy() == buy && order.is_fillable())
+    }
+
+    /// highest bid
+    pub fn best_bid(&self) -> Option<&BookOrder> {
+        self.side(true).max_by_key(|order| order.price)
+    }
+
+    /// lowest listing
+    pub fn best_ask(&self) -> Option<&BookOrder> {
+        self.side(false).min_by_key(|order| order.price)
+    }
+
+    /// number of nfts bid for at each price
+    pub fn bid_depth(&self) -> BTreeMap<u64, u64> {
+        Self::depth(self.side(true))
+    }
+
+    /// number of nfts listed at each price
+    pub fn ask_depth(&self) -> BTreeMap<u64, u64> {
+        Self::depth(self.side(false))
+    }
+
+    fn depth<'a>(orders: impl Iterator<Item = &'a BookOrder>) -> BTreeMap<u64, u64> {
+        let mut depth = BTreeMap::new();
+        for order in orders {
+            *depth.entry(order.price).or_insert(0) += order.size;
+        }
+        depth
+    }
+}
+
+/// bidding wallet as last seen in an event
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WalletBalance {
+    pub owner: Pubkey,
+    pub balance: u64,
+    /// default if the wallet has no delegate
+    pub delegate: Pubkey,
+    pub delegate_spending_cap: u64,
+    pub open_buy_orders: u32,
+    pub open_sell_orders: u32,
+    /// amount requested for withdrawal, still part of the balance
+    pub pending_withdrawal: u64,
+    pub withdrawal_unlock_time: i64,
+    /// liquid staking tokens and the lamports they were deposited for
+    pub staked_balance: u64,
+    pub staked_lamports: u64,
+}
+
+/// bid pool as last seen in an event
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BidPoolState {
+    pub manager: Pubkey,
+    pub total_shares: u64,
+    /// nfts held by the pool and the price paid for them
+    pub nft_count: u64,
+    pub nft_cost: u64,
+    /// shares of each depositor
+    pub shares: HashMap<Pubkey, u64>,
+}
+
+/// in-memory state rebuilt from the events of the listings program
+#[derive(Clone, Debug, Default)]
+pub struct OrderBook {
+    pub markets: HashMap<Pubkey, MarketBook>,
+    pub wallets: HashMap<Pubkey, WalletBalance>,
+    pub bid_pools: HashMap<Pubkey, BidPoolState>,
+}
+
+impl OrderBook {
+    pub fn market(&self, market: &Pubkey) -> Option<&MarketBook> {
+        self.markets.get(market)
+    }
+
+    pub fn apply(&mut self, event: &ListingsEvent) {
+        match event {
+            ListingsEvent::OrderEdit(event) => self.apply_order_edit(event),
+            ListingsEvent::OrderFill(event) => self.apply_order_fill(event),
+            ListingsEvent::WalletEdit(event) => self.apply_wallet_edit(event),
+            ListingsEvent::MarketEdit(event) => self.apply_market_edit(event),
+            ListingsEvent::BidPoolEdit(event) => self.apply_bid_pool_edit(event),
+            ListingsEvent::AmmPoolEdit(event) => self.apply_amm_pool_edit(event),
+        }
+    }
+
+    fn apply_market_edit(&mut self, event: &MarketEditEventV2) {
+        let market = self.markets.entry(event.address).or_default();
+        market.pool_mint = event.pool_mint;
+        market.initializer = event.initializer;
+        market.state = event.state;
+        market.listing_mode = event.listing_mode;
+        market.min_withdrawal_delay = event.min_withdrawal_delay;
+    }
+
+    fn apply_order_edit(&mut self, event: &OrderEditEventV2) {
+        // orders may be seen before their market when replaying a partial history
+        let market = self.markets.entry(event.market).or_default();
+        market.pool_mint = event.pool_mint;
+
+        let is_closed = event.edit_type == u8::from(OrderEditType::Close)
+            || event.edit_type == u8::from(OrderEditType::FillAndClose);
+        if is_closed {
+            market.orders.remove(&event.address);
+            return;
+        }
+
+        market.orders.insert(
+            event.address,
+            BookOrder {
+                address: event.address,
+                owner: event.owner,
+                wallet: event.wallet,
+                side: event.side,
+                price: event.price,
+                size: event.size,
+                state: event.state,
+                nft_mint: event.nft_mint,
+                last_edit_time: event.last_edit_time,
+                max_price: event.max_price,
+                listing_mode: event.listing_mode,
+                original_size: event.original_size,
+                filled_size: event.filled_size,
+                filled_value: event.filled_value,
+            },
+        );
+    }
+
+    fn apply_order_fill(&mut self, event: &OrderFillEvent) {
+        let market = self.markets.entry(event.market).or_default();
+        market.last_fill_price = Some(event.price);
+        market.volume = market.volume.saturating_add(event.price);
+        market.fill_count += 1;
+    }
+
+    fn apply_wallet_edit(&mut self, event: &WalletEditEventV2) {
+        if event.edit_type == u8::from(WalletEditType::Close) {
+            self.wallets.remove(&event.address);
+            return;
+        }
+        self.wallets.insert(
+            event.address,
+            WalletBalance {
+                owner: event.owner,
+                balance: event.balance,
+                delegate: event.delegate,
+                delegate_spending_cap: event.delegate_spending_cap,
+                open_buy_orders: event.open_buy_orders,
+                open_sell_orders: event.open_sell_orders,
+                pending_withdrawal: event.pending_withdrawal,
+                withdrawal_unlock_time: event.withdrawal_unlock_time,
+                staked_balance: event.staked_balance,
+                staked_lamports: event.staked_lamports,
+            },
+        );
+    }
+
+    fn apply_bid_pool_edit(&mut self, event: &BidPoolEditEvent) {
+        let bid_pool = self.bid_pools.entry(event.address).or_default();
+        bid_pool.manager = event.manager;
+        bid_pool.total_shares = event.total_shares;
+        bid_pool.nft_count = event.nft_count;
+        bid_pool.nft_cost = event.nft_cost;
+        if event.depositor != Pubkey::default() {
+            if event.depositor_shares == 0 {
+                bid_pool.shares.remove(&event.depositor);
+            } else {
+                bid_pool
+                    .shares
+                    .insert(event.depositor, event.depositor_shares);
+            }
+        }
+    }
+
+    fn apply_amm_pool_edit(&mut self, event: &AmmPoolEditEvent) {
+        let market = self.markets.entry(event.market).or_default();
+        if event.edit_type == u8::from(AmmPoolEditType::Close) {
+            market.amm_pools.remove(&event.address);
+            return;
+        }
+        market.amm_pools.insert(
+            event.address,
+            BookAmmPool {
+                address: event.address,
+                owner: event.owner,
+                wallet: event.wallet,
+                curve: event.curve,
+                spot_price: event.spot_price,
+                delta: event.delta,
+                spread_bps: event.spread_bps,
+                buy_size: event.buy_size,
+                nft_count: event.nft_count,
+                last_edit_time: event.last_edit_time,
+            },
+        );
+    }
+}
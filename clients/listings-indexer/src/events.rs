@@ -0,0 +1,214 @@
+use std::str::FromStr;
+
+use anchor_lang::{event::EVENT_IX_TAG_LE, prelude::Pubkey, AnchorDeserialize, Discriminator};
+use listings::state::{
+    legacy, AmmPoolEditEvent, BidPoolEditEvent, MarketEditEventV2, OrderEditEventV2,
+    OrderFillEvent, WalletEditEventV2, EVENT_VERSION,
+};
+
+use crate::IndexerError;
+
+/// event decoded from an `emit_cpi!` inner instruction
+pub enum ListingsEvent {
+    OrderEdit(OrderEditEventV2),
+    OrderFill(OrderFillEvent),
+    WalletEdit(WalletEditEventV2),
+    MarketEdit(MarketEditEventV2),
+    BidPoolEdit(BidPoolEditEvent),
+    AmmPoolEdit(AmmPoolEditEvent),
+}
+
+fn parse_pubkey(value: &str) -> Result<Pubkey, IndexerError> {
+    Pubkey::from_str(value).map_err(|_| IndexerError::InvalidPubkey(value.to_string()))
+}
+
+fn deserialize<T: AnchorDeserialize>(mut data: &[u8]) -> Result<T, IndexerError> {
+    T::deserialize(&mut data).map_err(|_| IndexerError::InvalidEventData)
+}
+
+/// the `Pubkey` events start with their event version
+fn event_version(data: &[u8]) -> Result<u8, IndexerError> {
+    data.first().copied().ok_or(IndexerError::InvalidEventData)
+}
+
+/// events whose layout only exists at the current event version
+fn deserialize_current<T: AnchorDeserialize>(data: &[u8]) -> Result<T, IndexerError> {
+    match event_version(data)? {
+        EVENT_VERSION => deserialize(data),
+        version => Err(IndexerError::UnsupportedEventVersion(version)),
+    }
+}
+
+/// decode the data of an inner instruction to the listings program
+/// returns `None` if the instruction is not an event
+/// events at an older event version are mapped onto the current layout
+pub fn decode_event(data: &[u8]) -> Result<Option<ListingsEvent>, IndexerError> {
+    if data.len() < 16 || data[..8] != EVENT_IX_TAG_LE {
+        return Ok(None);
+    }
+    let (discriminator, data) = data[8..].split_at(8);
+
+    let event = if discriminator == OrderEditEventV2::discriminator() {
+        ListingsEvent::OrderEdit(match event_version(data)? {
+            1 => upgrade_order_event_v1(deserialize(data)?),
+            _ => deserialize_current(data)?,
+        })
+    } else if discriminator == OrderFillEvent::discriminator() {
+        // the fill layout is the same at every event version
+        match event_version(data)? {
+            1..=EVENT_VERSION => ListingsEvent::OrderFill(deserialize(data)?),
+            version => return Err(IndexerError::UnsupportedEventVersion(version)),
+        }
+    } else if discriminator == WalletEditEventV2::discriminator() {
+        ListingsEvent::WalletEdit(match event_version(data)? {
+            1 => upgrade_wallet_event_v1(deserialize(data)?),
+            _ => deserialize_current(data)?,
+        })
+    } else if discriminator == MarketEditEventV2::discriminator() {
+        ListingsEvent::MarketEdit(match event_version(data)? {
+            1 => upgrade_market_event_v1(deserialize(data)?),
+            _ => deserialize_current(data)?,
+        })
+    } else if discriminator == BidPoolEditEvent::discriminator() {
+        ListingsEvent::BidPoolEdit(deserialize_current(data)?)
+    } else if discriminator == AmmPoolEditEvent::discriminator() {
+        ListingsEvent::AmmPoolEdit(deserialize_current(data)?)
+    } else if discriminator == legacy::OrderEditEvent::discriminator() {
+        ListingsEvent::OrderEdit(upgrade_order_event(deserialize(data)?)?)
+    } else if discriminator == legacy::WalletEditEvent::discriminator() {
+        ListingsEvent::WalletEdit(upgrade_wallet_event(deserialize(data)?)?)
+    } else if discriminator == legacy::MarketEditEvent::discriminator() {
+        ListingsEvent::MarketEdit(upgrade_market_event(deserialize(data)?)?)
+    } else {
+        return Err(IndexerError::UnknownEvent(discriminator.to_vec()));
+    };
+    Ok(Some(event))
+}
+
+/// legacy events are mapped onto the current layout with event version 0
+fn upgrade_order_event(event: legacy::OrderEditEvent) -> Result<OrderEditEventV2, IndexerError> {
+    Ok(OrderEditEventV2 {
+        event_version: 0,
+        edit_type: event.edit_type,
+        address: parse_pubkey(&event.address)?,
+        version: event.version,
+        nonce: parse_pubkey(&event.nonce)?,
+        market: parse_pubkey(&event.market)?,
+        owner: parse_pubkey(&event.owner)?,
+        wallet: parse_pubkey(&event.wallet)?,
+        side: event.side,
+        size: event.size,
+        price: event.price,
+        state: event.state,
+        init_time: event.init_time,
+        last_edit_time: event.last_edit_time,
+        nft_mint: parse_pubkey(&event.nft_mint)?,
+        pool_mint: parse_pubkey(&event.pool_mint)?,
+        price_mode: 0,
+        trailing_bps: 0,
+        max_price: 0,
+        trigger_price: 0,
+        listing_mode: 0,
+        original_size: event.size,
+        filled_size: 0,
+        filled_value: 0,
+    })
+}
+
+fn upgrade_wallet_event(event: legacy::WalletEditEvent) -> Result<WalletEditEventV2, IndexerError> {
+    Ok(WalletEditEventV2 {
+        event_version: 0,
+        edit_type: event.edit_type,
+        address: parse_pubkey(&event.address)?,
+        version: event.version,
+        owner: parse_pubkey(&event.owner)?,
+        balance: event.balance,
+        delegate: Pubkey::default(),
+        delegate_spending_cap: 0,
+        open_buy_orders: 0,
+        open_sell_orders: 0,
+        withdrawal_delay: 0,
+        pending_withdrawal: 0,
+        withdrawal_unlock_time: 0,
+        staked_balance: 0,
+        staked_lamports: 0,
+    })
+}
+
+fn upgrade_market_event(event: legacy::MarketEditEvent) -> Result<MarketEditEventV2, IndexerError> {
+    Ok(MarketEditEventV2 {
+        event_version: 0,
+        edit_type: event.edit_type,
+        address: parse_pubkey(&event.address)?,
+        version: event.version,
+        pool_mint: parse_pubkey(&event.pool_mint)?,
+        initializer: parse_pubkey(&event.initializer)?,
+        state: event.state,
+        listing_mode: 0,
+        min_withdrawal_delay: 0,
+    })
+}
+
+/// version 1 events keep their event version, the fields added since are zero
+fn upgrade_order_event_v1(event: legacy::OrderEditEventV1) -> OrderEditEventV2 {
+    OrderEditEventV2 {
+        event_version: event.event_version,
+        edit_type: event.edit_type,
+        address: event.address,
+        version: event.version,
+        nonce: event.nonce,
+        market: event.market,
+        owner: event.owner,
+        wallet: event.wallet,
+        side: event.side,
+        size: event.size,
+        price: event.price,
+        state: event.state,
+        init_time: event.init_time,
+        last_edit_time: event.last_edit_time,
+        nft_mint: event.nft_mint,
+        pool_mint: event.pool_mint,
+        price_mode: 0,
+        trailing_bps: 0,
+        max_price: 0,
+        trigger_price: 0,
+        listing_mode: 0,
+        original_size: event.size,
+        filled_size: 0,
+        filled_value: 0,
+    }
+}
+
+fn upgrade_wallet_event_v1(event: legacy::WalletEditEventV1) -> WalletEditEventV2 {
+    WalletEditEventV2 {
+        event_version: event.event_version,
+        edit_type: event.edit_type,
+        address: event.address,
+        version: event.version,
+        owner: event.owner,
+        balance: event.balance,
+        delegate: Pubkey::default(),
+        delegate_spending_cap: 0,
+        open_buy_orders: 0,
+        open_sell_orders: 0,
+        withdrawal_delay: 0,
+        pending_withdrawal: 0,
+        withdrawal_unlock_time: 0,
+        staked_balance: 0,
+        staked_lamports: 0,
+    }
+}
+
+fn upgrade_market_event_v1(event: legacy::MarketEditEventV1) -> MarketEditEventV2 {
+    MarketEditEventV2 {
+        event_version: event.event_version,
+        edit_type: event.edit_type,
+        address: event.address,
+        version: event.version,
+        pool_mint: event.pool_mint,
+        initializer: event.initializer,
+        state: event.state,
+        listing_mode: 0,
+        min_withdrawal_delay: 0,
+    }
+}
@@ -0,0 +1,28 @@
+//! off-chain indexer for the listings program
+//! decodes the `emit_cpi!` events from transaction inner instructions and rebuilds the order book
+
+pub mod book;
+pub mod events;
+pub mod replay;
+
+pub use book::*;
+pub use events::*;
+pub use replay::*;
+
+#[derive(Debug, thiserror::Error)]
+pub enum IndexerError {
+    #[error("invalid pubkey {0}")]
+    InvalidPubkey(String),
+    #[error("event data could not be deserialized")]
+    InvalidEventData,
+    #[error("unknown event discriminator {0:?}")]
+    UnknownEvent(Vec<u8>),
+    #[error("unsupported event version {0}")]
+    UnsupportedEventVersion(u8),
+    #[error("transaction must be fetched with base64 encoding")]
+    UndecodableTransaction,
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
@@ -0,0 +1,94 @@
+use std::io::BufRead;
+
+use anchor_lang::prelude::Pubkey;
+use solana_sdk::transaction::VersionedTransaction;
+use solana_transaction_status::{
+    option_serializer::OptionSerializer, EncodedConfirmedTransactionWithStatusMeta, UiInstruction,
+};
+
+use crate::{
+    book::OrderBook,
+    events::{decode_event, ListingsEvent},
+    IndexerError,
+};
+
+/// all account keys of a transaction, including the ones loaded from lookup tables
+fn account_keys(
+    transaction: &VersionedTransaction,
+    loaded_writable: Vec<String>,
+    loaded_readonly: Vec<String>,
+) -> Result<Vec<Pubkey>, IndexerError> {
+    let mut keys = transaction.message.static_account_keys().to_vec();
+    for key in loaded_writable.iter().chain(loaded_readonly.iter()) {
+        keys.push(
+            key.parse()
+                .map_err(|_| IndexerError::InvalidPubkey(key.clone()))?,
+        );
+    }
+    Ok(keys)
+}
+
+/// events emitted by the listings program in a transaction, in execution order
+/// failed transactions have no events
+pub fn transaction_events(
+    transaction: &EncodedConfirmedTransactionWithStatusMeta,
+) -> Result<Vec<ListingsEvent>, IndexerError> {
+    let meta = match &transaction.transaction.meta {
+        Some(meta) if meta.err.is_none() => meta,
+        _ => return Ok(Vec::new()),
+    };
+    let decoded = transaction
+        .transaction
+        .transaction
+        .decode()
+        .ok_or(IndexerError::UndecodableTransaction)?;
+
+    let (loaded_writable, loaded_readonly) = match &meta.loaded_addresses {
+        OptionSerializer::Some(loaded) => (loaded.writable.clone(), loaded.readonly.clone()),
+        _ => (Vec::new(), Vec::new()),
+    };
+    let keys = account_keys(&decoded, loaded_writable, loaded_readonly)?;
+
+    let inner_instructions = match &meta.inner_instructions {
+        OptionSerializer::Some(inner_instructions) => inner_instructions.clone(),
+        _ => Vec::new(),
+    };
+
+    let mut events = Vec::new();
+    for inner in inner_instructions {
+        for instruction in inner.instructions {
+            let compiled = match instruction {
+                UiInstruction::Compiled(compiled) => compiled,
+                UiInstruction::Parsed(_) => return Err(IndexerError::UndecodableTransaction),
+            };
+            if keys.get(compiled.program_id_index as usize) != Some(&listings::ID) {
+                continue;
+            }
+            let data = bs58::decode(&compiled.data)
+                .into_vec()
+                .map_err(|_| IndexerError::InvalidEventData)?;
+            if let Some(event) = decode_event(&data)? {
+                events.push(event);
+            }
+        }
+    }
+    Ok(events)
+}
+
+/// replay a file with one `getTransaction` response per line, fetched with base64 encoding,
+/// applying the events to the order book in file order
+pub fn replay<R: BufRead>(reader: R, book: &mut OrderBook) -> Result<usize, IndexerError> {
+    let mut transactions = 0;
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let transaction: EncodedConfirmedTransactionWithStatusMeta = serde_json::from_str(&line)?;
+        for event in transaction_events(&transaction)? {
+            book.apply(&event);
+        }
+        transactions += 1;
+    }
+    Ok(transactions)
+}
@@ -0,0 +1,349 @@
+use anchor_lang::{event::EVENT_IX_TAG_LE, prelude::Pubkey, AnchorSerialize, Discriminator, Event};
+use listings::state::{
+    legacy, OrderEditEventV2, OrderEditType, OrderFillEvent, OrderSide, OrderState,
+    WalletEditEventV2, WalletEditType, EVENT_VERSION,
+};
+use listings_indexer::{decode_event, OrderBook};
+
+fn emitted<T: Event>(event: &T) -> Vec<u8> {
+    let mut data = EVENT_IX_TAG_LE.to_vec();
+    data.extend(event.data());
+    data
+}
+
+fn order_event(
+    market: Pubkey,
+    address: Pubkey,
+    side: OrderSide,
+    edit_type: OrderEditType,
+    price: u64,
+    size: u64,
+) -> OrderEditEventV2 {
+    OrderEditEventV2 {
+        event_version: EVENT_VERSION,
+        edit_type: edit_type.into(),
+        address,
+        version: 1,
+        nonce: Pubkey::new_unique(),
+        market,
+        owner: Pubkey::new_unique(),
+        wallet: Pubkey::new_unique(),
+        side: side.into(),
+        size,
+        price,
+        state: OrderState::Ready.into(),
+        init_time: 0,
+        last_edit_time: 0,
+        nft_mint: Pubkey::default(),
+        pool_mint: Pubkey::default(),
+        price_mode: 0,
+        trailing_bps: 0,
+        max_price: 0,
+        trigger_price: 0,
+        listing_mode: 0,
+        original_size: size,
+        filled_size: 0,
+        filled_value: 0,
+    }
+}
+
+fn apply(book: &mut OrderBook, data: Vec<u8>) {
+    let event = decode_event(&data).unwrap().unwrap();
+    book.apply(&event);
+}
+
+#[test]
+fn best_bid_ask_and_depth() {
+    let mut book = OrderBook::default();
+    let market = Pubkey::new_unique();
+    let bids = [
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    ];
+    let asks = [Pubkey::new_unique(), Pubkey::new_unique()];
+
+    apply(
+        &mut book,
+        emitted(&order_event(
+            market,
+            bids[0],
+            OrderSide::Buy,
+            OrderEditType::Init,
+            10,
+            2,
+        )),
+    );
+    apply(
+        &mut book,
+        emitted(&order_event(
+            market,
+            bids[1],
+            OrderSide::Buy,
+            OrderEditType::Init,
+            12,
+            1,
+        )),
+    );
+    apply(
+        &mut book,
+        emitted(&order_event(
+            market,
+            bids[2],
+            OrderSide::Buy,
+            OrderEditType::Init,
+            10,
+            3,
+        )),
+    );
+    apply(
+        &mut book,
+        emitted(&order_event(
+            market,
+            asks[0],
+            OrderSide::Sell,
+            OrderEditType::Init,
+            20,
+            1,
+        )),
+    );
+    apply(
+        &mut book,
+        emitted(&order_event(
+            market,
+            asks[1],
+            OrderSide::Sell,
+            OrderEditType::Init,
+            15,
+            1,
+        )),
+    );
+
+    let market_book = book.market(&market).unwrap();
+    assert_eq!(market_book.best_bid().unwrap().address, bids[1]);
+    assert_eq!(market_book.best_ask().unwrap().address, asks[1]);
+    assert_eq!(market_book.bid_depth().get(&10), Some(&5));
+    assert_eq!(market_book.ask_depth().len(), 2);
+
+    // best bid is filled and closed, best ask is repriced above the other listing
+    apply(
+        &mut book,
+        emitted(&order_event(
+            market,
+            bids[1],
+            OrderSide::Buy,
+            OrderEditType::FillAndClose,
+            12,
+            0,
+        )),
+    );
+    apply(
+        &mut book,
+        emitted(&order_event(
+            market,
+            asks[1],
+            OrderSide::Sell,
+            OrderEditType::Edit,
+            25,
+            1,
+        )),
+    );
+
+    let market_book = book.market(&market).unwrap();
+    assert_eq!(market_book.best_bid().unwrap().price, 10);
+    assert_eq!(market_book.best_ask().unwrap().address, asks[0]);
+}
+
+#[test]
+fn fills_and_wallets() {
+    let mut book = OrderBook::default();
+    let market = Pubkey::new_unique();
+    let wallet = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+
+    apply(
+        &mut book,
+        emitted(&WalletEditEventV2 {
+            event_version: EVENT_VERSION,
+            edit_type: WalletEditType::Init.into(),
+            address: wallet,
+            version: 1,
+            owner,
+            balance: 100,
+            delegate: Pubkey::default(),
+            delegate_spending_cap: 0,
+            open_buy_orders: 0,
+            open_sell_orders: 0,
+            withdrawal_delay: 0,
+            pending_withdrawal: 0,
+            withdrawal_unlock_time: 0,
+            staked_balance: 0,
+            staked_lamports: 0,
+        }),
+    );
+    apply(
+        &mut book,
+        emitted(&OrderFillEvent {
+            event_version: EVENT_VERSION,
+            address: Pubkey::new_unique(),
+            market,
+            pool_mint: Pubkey::default(),
+            side: OrderSide::Buy.into(),
+            buyer: owner,
+            seller: Pubkey::new_unique(),
+            nft_mint: Pubkey::new_unique(),
+            price: 40,
+            seller_amount: 40,
+            protocol_fee: 0,
+            royalty_total: 0,
+            royalties: Vec::new(),
+            ovol_exempt: false,
+            fill_time: 0,
+        }),
+    );
+
+    assert_eq!(book.wallets.get(&wallet).unwrap().balance, 100);
+    let market_book = book.market(&market).unwrap();
+    assert_eq!(market_book.last_fill_price, Some(40));
+    assert_eq!(market_book.volume, 40);
+
+    apply(
+        &mut book,
+        emitted(&WalletEditEventV2 {
+            event_version: EVENT_VERSION,
+            edit_type: WalletEditType::Close.into(),
+            address: wallet,
+            version: 1,
+            owner,
+            balance: 100,
+            delegate: Pubkey::default(),
+            delegate_spending_cap: 0,
+            open_buy_orders: 0,
+            open_sell_orders: 0,
+            withdrawal_delay: 0,
+            pending_withdrawal: 0,
+            withdrawal_unlock_time: 0,
+            staked_balance: 0,
+            staked_lamports: 0,
+        }),
+    );
+    assert!(book.wallets.get(&wallet).is_none());
+}
+
+#[test]
+fn fill_progress_of_partial_bids() {
+    let mut book = OrderBook::default();
+    let market = Pubkey::new_unique();
+    let address = Pubkey::new_unique();
+
+    let mut event = order_event(market, address, OrderSide::Buy, OrderEditType::Fill, 10, 1);
+    event.state = OrderState::Partial.into();
+    event.original_size = 3;
+    event.filled_size = 2;
+    event.filled_value = 18;
+    apply(&mut book, emitted(&event));
+
+    let order = book.market(&market).unwrap().best_bid().unwrap();
+    assert_eq!(order.original_size, 3);
+    assert_eq!(order.average_fill_price(), Some(9));
+
+    event.filled_size = 0;
+    event.filled_value = 0;
+    apply(&mut book, emitted(&event));
+    let order = book.market(&market).unwrap().best_bid().unwrap();
+    assert_eq!(order.average_fill_price(), None);
+}
+
+#[test]
+fn inactive_listings_are_not_in_the_book() {
+    let mut book = OrderBook::default();
+    let market = Pubkey::new_unique();
+    let address = Pubkey::new_unique();
+    let mut event = order_event(market, address, OrderSide::Sell, OrderEditType::Edit, 15, 1);
+    event.state = OrderState::Inactive.into();
+    event.trigger_price = 10;
+    apply(&mut book, emitted(&event));
+    assert!(book.market(&market).unwrap().best_ask().is_none());
+
+    event.state = OrderState::Ready.into();
+    apply(&mut book, emitted(&event));
+    assert_eq!(
+        book.market(&market).unwrap().best_ask().unwrap().address,
+        address
+    );
+}
+
+#[test]
+fn legacy_events_are_decoded() {
+    let mut book = OrderBook::default();
+    let market = Pubkey::new_unique();
+    let address = Pubkey::new_unique();
+    let event = legacy::OrderEditEvent {
+        edit_type: OrderEditType::Init.into(),
+        address: address.to_string(),
+        version: 1,
+        nonce: Pubkey::new_unique().to_string(),
+        market: market.to_string(),
+        owner: Pubkey::new_unique().to_string(),
+        wallet: Pubkey::new_unique().to_string(),
+        side: OrderSide::Sell.into(),
+        size: 1,
+        price: 7,
+        state: OrderState::Ready.into(),
+        init_time: 0,
+        last_edit_time: 0,
+        nft_mint: Pubkey::new_unique().to_string(),
+        pool_mint: Pubkey::new_unique().to_string(),
+    };
+    apply(&mut book, emitted(&event));
+    assert_eq!(
+        book.market(&market).unwrap().best_ask().unwrap().address,
+        address
+    );
+}
+
+#[test]
+fn events_are_decoded_by_event_version() {
+    let mut book = OrderBook::default();
+    let market = Pubkey::new_unique();
+    let address = Pubkey::new_unique();
+    let event = legacy::OrderEditEventV1 {
+        event_version: 1,
+        edit_type: OrderEditType::Init.into(),
+        address,
+        version: 1,
+        nonce: Pubkey::new_unique(),
+        market,
+        owner: Pubkey::new_unique(),
+        wallet: Pubkey::new_unique(),
+        side: OrderSide::Buy.into(),
+        size: 2,
+        price: 7,
+        state: OrderState::Ready.into(),
+        init_time: 0,
+        last_edit_time: 0,
+        nft_mint: Pubkey::default(),
+        pool_mint: Pubkey::default(),
+    };
+    let mut data = EVENT_IX_TAG_LE.to_vec();
+    data.extend(OrderEditEventV2::discriminator());
+    data.extend(event.try_to_vec().unwrap());
+    apply(&mut book, data);
+    let order = book.market(&market).unwrap().best_bid().unwrap();
+    assert_eq!(order.address, address);
+    assert_eq!(order.original_size, 2);
+
+    let event = order_event(market, address, OrderSide::Buy, OrderEditType::Edit, 9, 1);
+    apply(&mut book, emitted(&event));
+    assert_eq!(book.market(&market).unwrap().best_bid().unwrap().price, 9);
+
+    // versions this indexer doesn't know are rejected
+    let mut data = emitted(&event);
+    data[16] = EVENT_VERSION + 1;
+    assert!(decode_event(&data).is_err());
+}
+
+#[test]
+fn non_event_instructions_are_skipped() {
+    assert!(decode_event(&[0; 24]).unwrap().is_none());
+}
@@ -0,0 +1,538 @@
+//! compute units consumed per instruction across asset standards and creator counts
+//! build the program with `cargo build-sbf` first, then run `cargo bench -p listings --bench compute_units`
+//! exits with an error when a case goes over its budget in [`BUDGETS`]
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_spl::associated_token::get_associated_token_address;
+use bridgesplit_program_utils::anchor_lang;
+use common::*;
+use listings::state::Order;
+use listings_client::{
+    find_token_record, CompressedOrderData, EditBuyOrderData, InitOrderData, NftAccounts,
+    PnftAccounts, RemainingAccounts,
+};
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction, pubkey::Pubkey, signature::Keypair, signer::Signer,
+};
+
+const PRICE: u64 = LAMPORTS_PER_SOL;
+/// limit requested by every measured transaction so that no case is cut off
+const COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+/// royalty creators of the pnft and core variants
+const CREATOR_COUNTS: &[usize] = &[0, 1, 3, 5];
+
+/// maximum compute units of each case, including the compute budget instruction
+/// lower a budget when an optimization lands, raise it only with the change that needs it
+const BUDGETS: &[(&str, u64)] = &[
+    ("init_market", 15_000),
+    ("init_wallet", 20_000),
+    ("edit_wallet", 10_000),
+    ("init_buy_order", 35_000),
+    ("edit_buy_order", 20_000),
+    ("close_buy_order", 20_000),
+    ("nft init_sell_order", 70_000),
+    ("nft close_sell_order", 60_000),
+    ("nft fill_sell_order", 100_000),
+    ("nft fill_buy_order", 110_000),
+    ("pnft/0 init_sell_order", 150_000),
+    ("pnft/0 fill_sell_order", 240_000),
+    ("pnft/0 fill_buy_order", 250_000),
+    ("pnft/1 init_sell_order", 150_000),
+    ("pnft/1 fill_sell_order", 250_000),
+    ("pnft/1 fill_buy_order", 260_000),
+    ("pnft/3 init_sell_order", 155_000),
+    ("pnft/3 fill_sell_order", 270_000),
+    ("pnft/3 fill_buy_order", 280_000),
+    ("pnft/5 init_sell_order", 160_000),
+    ("pnft/5 fill_sell_order", 290_000),
+    ("pnft/5 fill_buy_order", 300_000),
+    ("cnft init_sell_order", 90_000),
+    ("cnft fill_sell_order", 110_000),
+    ("cnft fill_buy_order", 120_000),
+    ("core/0 init_sell_order", 50_000),
+    ("core/0 fill_sell_order", 70_000),
+    ("core/0 fill_buy_order", 80_000),
+    ("core/1 init_sell_order", 50_000),
+    ("core/1 fill_sell_order", 80_000),
+    ("core/1 fill_buy_order", 90_000),
+    ("core/3 init_sell_order", 55_000),
+    ("core/3 fill_sell_order", 95_000),
+    ("core/3 fill_buy_order", 105_000),
+    ("core/5 init_sell_order", 60_000),
+    ("core/5 fill_sell_order", 110_000),
+    ("core/5 fill_buy_order", 120_000),
+    ("token22 init_sell_order", 50_000),
+    ("token22 fill_sell_order", 70_000),
+    ("token22 fill_buy_order", 80_000),
+];
+
+struct Bench {
+    context: TestContext,
+    pool_mint: Pubkey,
+    market: Pubkey,
+    results: Vec<(String, u64)>,
+}
+
+impl Bench {
+    async fn new() -> Self {
+        let mut context = TestContext::new().await;
+        let (pool_mint, market) = context.init_market().await;
+        Self {
+            context,
+            pool_mint,
+            market,
+            results: Vec::new(),
+        }
+    }
+
+    /// funded user with a wallet holding `deposit`
+    async fn user(&mut self, deposit: u64) -> Keypair {
+        let user = self.context.create_user(10 * LAMPORTS_PER_SOL).await;
+        self.context.init_wallet(&user, deposit).await;
+        user
+    }
+
+    async fn process(&mut self, instruction: Instruction, signers: &[&Keypair]) {
+        self.context.process(&[instruction], signers).await.unwrap();
+    }
+
+    async fn measure(&mut self, case: &str, instruction: Instruction, signers: &[&Keypair]) {
+        let units = self
+            .context
+            .compute_units(
+                &[
+                    ComputeBudgetInstruction::set_compute_unit_limit(COMPUTE_UNIT_LIMIT),
+                    instruction,
+                ],
+                signers,
+            )
+            .await;
+        self.results.push((case.to_string(), units));
+    }
+
+    /// new order data and the address of the order for its owner
+    fn order_data(&self, owner: &Keypair) -> (InitOrderData, Pubkey) {
+        let nonce = Keypair::new().pubkey();
+        let data = InitOrderData {
+            nonce,
+            price: PRICE,
+            size: 1,
+        };
+        (data, order_address(nonce, self.market, owner.pubkey()))
+    }
+
+    /// bid of a new buyer for any nft of the market
+    async fn bid(&mut self) -> (Pubkey, Order) {
+        let buyer = self.user(PRICE).await;
+        let (data, address) = self.order_data(&buyer);
+        let init = listings_client::init_buy_order(
+            buyer.pubkey(),
+            buyer.pubkey(),
+            self.pool_mint,
+            Pubkey::default(),
+            data,
+            &RemainingAccounts::default(),
+        );
+        self.process(init, &[&buyer]).await;
+        (address, self.context.get_zero_copy(address).await)
+    }
+
+    /// prints every case against its budget, returns false if any case is over budget
+    fn report(&self) -> bool {
+        let mut within_budget = true;
+        println!("{:<28} {:>10} {:>10}", "case", "units", "budget");
+        for (case, units) in &self.results {
+            let budget = BUDGETS
+                .iter()
+                .find(|(name, _)| name == case)
+                .map(|(_, budget)| *budget);
+            let status = match budget {
+                Some(budget) if *units <= budget => "",
+                Some(_) => "over budget",
+                None => "no budget",
+            };
+            if !status.is_empty() {
+                within_budget = false;
+            }
+            println!(
+                "{:<28} {:>10} {:>10} {}",
+                case,
+                units,
+                budget.map(|budget| budget.to_string()).unwrap_or_default(),
+                status
+            );
+        }
+        within_budget
+    }
+}
+
+fn pnft_accounts(nft: &NftAccounts, owner: &Pubkey) -> PnftAccounts {
+    let token = get_associated_token_address(owner, &nft.mint);
+    PnftAccounts {
+        token_record: find_token_record(&nft.mint, &token).0,
+        authorization_rules: Pubkey::default(),
+        authorization_rules_program: mpl_token_auth_rules::ID,
+    }
+}
+
+fn creators(count: usize) -> Vec<Pubkey> {
+    (0..count).map(|_| Keypair::new().pubkey()).collect()
+}
+
+async fn bench_wallet_and_buy_order(bench: &mut Bench) {
+    let pool_mint = Keypair::new().pubkey();
+    let initializer = bench.context.create_user(LAMPORTS_PER_SOL).await;
+    bench
+        .measure(
+            "init_market",
+            listings_client::init_market(initializer.pubkey(), pool_mint),
+            &[&initializer],
+        )
+        .await;
+
+    let buyer = bench.context.create_user(10 * LAMPORTS_PER_SOL).await;
+    bench
+        .measure(
+            "init_wallet",
+            listings_client::init_wallet(buyer.pubkey(), PRICE),
+            &[&buyer],
+        )
+        .await;
+    bench
+        .measure(
+            "edit_wallet",
+            listings_client::edit_wallet(buyer.pubkey(), PRICE, true),
+            &[&buyer],
+        )
+        .await;
+
+    let (data, address) = bench.order_data(&buyer);
+    let init = listings_client::init_buy_order(
+        buyer.pubkey(),
+        buyer.pubkey(),
+        bench.pool_mint,
+        Pubkey::default(),
+        data,
+        &RemainingAccounts::default(),
+    );
+    bench.measure("init_buy_order", init, &[&buyer]).await;
+
+    let order: Order = bench.context.get_zero_copy(address).await;
+    let edit = listings_client::edit_buy_order(
+        buyer.pubkey(),
+        address,
+        &order,
+        bench.pool_mint,
+        EditBuyOrderData {
+            new_size: 2,
+            new_price: PRICE / 2,
+        },
+    );
+    bench.measure("edit_buy_order", edit, &[&buyer]).await;
+
+    let order: Order = bench.context.get_zero_copy(address).await;
+    let close = listings_client::close_buy_order(buyer.pubkey(), address, &order);
+    bench.measure("close_buy_order", close, &[&buyer]).await;
+}
+
+async fn bench_nft(bench: &mut Bench) {
+    let seller = bench.user(0).await;
+    let nft = bench.context.mint_nft(&seller).await;
+    bench.context.add_appraisal(bench.pool_mint, nft.mint).await;
+    let (data, address) = bench.order_data(&seller);
+    let init = listings_client::init_sell_order(
+        seller.pubkey(),
+        bench.pool_mint,
+        nft,
+        data,
+        &RemainingAccounts::default(),
+    );
+    bench.measure("nft init_sell_order", init, &[&seller]).await;
+
+    let order: Order = bench.context.get_zero_copy(address).await;
+    let close = listings_client::close_sell_order(
+        seller.pubkey(),
+        address,
+        &order,
+        nft,
+        &RemainingAccounts::default(),
+    );
+    bench
+        .measure("nft close_sell_order", close, &[&seller])
+        .await;
+
+    let (data, address) = bench.order_data(&seller);
+    let init = listings_client::init_sell_order(
+        seller.pubkey(),
+        bench.pool_mint,
+        nft,
+        data,
+        &RemainingAccounts::default(),
+    );
+    bench.process(init, &[&seller]).await;
+    let order: Order = bench.context.get_zero_copy(address).await;
+    let buyer = bench.context.create_user(10 * LAMPORTS_PER_SOL).await;
+    let fill = listings_client::fill_sell_order(
+        buyer.pubkey(),
+        address,
+        &order,
+        nft,
+        &RemainingAccounts::default(),
+    );
+    bench.measure("nft fill_sell_order", fill, &[&buyer]).await;
+
+    let (bid, order) = bench.bid().await;
+    let nft = bench.context.mint_nft(&seller).await;
+    let fill = listings_client::fill_buy_order(
+        seller.pubkey(),
+        bid,
+        &order,
+        nft,
+        &RemainingAccounts::default(),
+    );
+    bench.measure("nft fill_buy_order", fill, &[&seller]).await;
+}
+
+async fn bench_pnft(bench: &mut Bench, creator_count: usize) {
+    let case = |name: &str| format!("pnft/{} {}", creator_count, name);
+    let creators = creators(creator_count);
+    let seller = bench.user(0).await;
+
+    let nft = bench.context.mint_pnft(&seller, creators.clone()).await;
+    bench.context.add_appraisal(bench.pool_mint, nft.mint).await;
+    let (data, address) = bench.order_data(&seller);
+    let init = listings_client::init_sell_order(
+        seller.pubkey(),
+        bench.pool_mint,
+        nft,
+        data,
+        &RemainingAccounts {
+            pnft: Some(pnft_accounts(&nft, &seller.pubkey())),
+            ..Default::default()
+        },
+    );
+    bench
+        .measure(&case("init_sell_order"), init, &[&seller])
+        .await;
+
+    let order: Order = bench.context.get_zero_copy(address).await;
+    let buyer = bench.context.create_user(10 * LAMPORTS_PER_SOL).await;
+    let buyer_token = get_associated_token_address(&buyer.pubkey(), &nft.mint);
+    let fill = listings_client::fill_sell_order(
+        buyer.pubkey(),
+        address,
+        &order,
+        nft,
+        &RemainingAccounts {
+            pnft: Some(pnft_accounts(&nft, &seller.pubkey())),
+            counterparty_token_record: Some(find_token_record(&nft.mint, &buyer_token).0),
+            creators: creators.clone(),
+            ..Default::default()
+        },
+    );
+    bench
+        .measure(&case("fill_sell_order"), fill, &[&buyer])
+        .await;
+
+    let (bid, order) = bench.bid().await;
+    let nft = bench.context.mint_pnft(&seller, creators.clone()).await;
+    let buyer_token = get_associated_token_address(&order.owner, &nft.mint);
+    let fill = listings_client::fill_buy_order(
+        seller.pubkey(),
+        bid,
+        &order,
+        nft,
+        &RemainingAccounts {
+            pnft: Some(pnft_accounts(&nft, &seller.pubkey())),
+            counterparty_token_record: Some(find_token_record(&nft.mint, &buyer_token).0),
+            creators,
+            ..Default::default()
+        },
+    );
+    bench
+        .measure(&case("fill_buy_order"), fill, &[&seller])
+        .await;
+}
+
+async fn bench_compressed(bench: &mut Bench) {
+    let seller = bench.user(0).await;
+    let wallet = listings_client::find_wallet(&seller.pubkey()).0;
+    let mut tree = CompressedTree::new(&mut bench.context).await;
+
+    let mut nft = tree.mint(&mut bench.context, seller.pubkey()).await;
+    bench
+        .context
+        .add_appraisal(bench.pool_mint, nft.asset_id)
+        .await;
+    let nonce = Keypair::new().pubkey();
+    let address = order_address(nonce, bench.market, seller.pubkey());
+    let init = listings_client::compressed_init_sell_order(
+        seller.pubkey(),
+        bench.pool_mint,
+        &tree.accounts(&nft),
+        CompressedOrderData {
+            order_nonce: nonce,
+            mint_id: nft.asset_id,
+            price: PRICE,
+            root: tree.root(),
+            data_hash: nft.data_hash,
+            creator_hash: nft.creator_hash,
+            index: nft.index,
+        },
+    );
+    bench
+        .measure("cnft init_sell_order", init, &[&seller])
+        .await;
+    tree.set_owner(&mut nft, wallet);
+
+    let order: Order = bench.context.get_zero_copy(address).await;
+    let buyer = bench.context.create_user(10 * LAMPORTS_PER_SOL).await;
+    let fill = listings_client::compressed_fill_sell_order(
+        buyer.pubkey(),
+        address,
+        &order,
+        &tree.accounts(&nft),
+        tree.fill_data(&nft),
+        &nft.remaining_accounts(),
+    );
+    bench.measure("cnft fill_sell_order", fill, &[&buyer]).await;
+    tree.set_owner(&mut nft, buyer.pubkey());
+
+    let (bid, order) = bench.bid().await;
+    let nft = tree.mint(&mut bench.context, seller.pubkey()).await;
+    let fill = listings_client::compressed_fill_buy_order(
+        seller.pubkey(),
+        bid,
+        &order,
+        &tree.accounts(&nft),
+        tree.fill_data(&nft),
+        &nft.remaining_accounts(),
+    );
+    bench.measure("cnft fill_buy_order", fill, &[&seller]).await;
+}
+
+async fn bench_core(bench: &mut Bench, creator_count: usize) {
+    let case = |name: &str| format!("core/{} {}", creator_count, name);
+    let creators = creators(creator_count);
+    let seller = bench.user(0).await;
+
+    let asset = bench
+        .context
+        .create_core_asset(&seller, creators.clone())
+        .await;
+    bench.context.add_appraisal(bench.pool_mint, asset).await;
+    let (data, address) = bench.order_data(&seller);
+    let init = listings_client::core_init_sell_order(
+        seller.pubkey(),
+        bench.pool_mint,
+        asset,
+        None,
+        data,
+        &RemainingAccounts::default(),
+    );
+    bench
+        .measure(&case("init_sell_order"), init, &[&seller])
+        .await;
+
+    let order: Order = bench.context.get_zero_copy(address).await;
+    let buyer = bench.context.create_user(10 * LAMPORTS_PER_SOL).await;
+    let fill = listings_client::core_fill_sell_order(
+        buyer.pubkey(),
+        address,
+        &order,
+        None,
+        &RemainingAccounts {
+            creators: creators.clone(),
+            ..Default::default()
+        },
+    );
+    bench
+        .measure(&case("fill_sell_order"), fill, &[&buyer])
+        .await;
+
+    let (bid, order) = bench.bid().await;
+    let asset = bench
+        .context
+        .create_core_asset(&seller, creators.clone())
+        .await;
+    let fill = listings_client::core_fill_buy_order(
+        seller.pubkey(),
+        bid,
+        &order,
+        bench.pool_mint,
+        asset,
+        None,
+        &RemainingAccounts {
+            creators,
+            ..Default::default()
+        },
+    );
+    bench
+        .measure(&case("fill_buy_order"), fill, &[&seller])
+        .await;
+}
+
+async fn bench_token22(bench: &mut Bench) {
+    let seller = bench.user(0).await;
+
+    let nft_mint = bench.context.mint_token22_nft(&seller, false).await;
+    bench.context.add_appraisal(bench.pool_mint, nft_mint).await;
+    let (data, address) = bench.order_data(&seller);
+    let init = listings_client::token22_init_sell_order(
+        seller.pubkey(),
+        bench.pool_mint,
+        nft_mint,
+        data,
+        &RemainingAccounts::default(),
+    );
+    bench
+        .measure("token22 init_sell_order", init, &[&seller])
+        .await;
+
+    let order: Order = bench.context.get_zero_copy(address).await;
+    let buyer = bench.context.create_user(10 * LAMPORTS_PER_SOL).await;
+    let fill = listings_client::token22_fill_sell_order(
+        buyer.pubkey(),
+        address,
+        &order,
+        &RemainingAccounts::default(),
+    );
+    bench
+        .measure("token22 fill_sell_order", fill, &[&buyer])
+        .await;
+
+    let (bid, order) = bench.bid().await;
+    let nft_mint = bench.context.mint_token22_nft(&seller, false).await;
+    let fill = listings_client::token22_fill_buy_order(
+        seller.pubkey(),
+        bid,
+        &order,
+        bench.pool_mint,
+        nft_mint,
+        &RemainingAccounts::default(),
+    );
+    bench
+        .measure("token22 fill_buy_order", fill, &[&seller])
+        .await;
+}
+
+#[tokio::main]
+async fn main() {
+    let mut bench = Bench::new().await;
+    bench_wallet_and_buy_order(&mut bench).await;
+    bench_nft(&mut bench).await;
+    for &creator_count in CREATOR_COUNTS {
+        bench_pnft(&mut bench, creator_count).await;
+    }
+    bench_compressed(&mut bench).await;
+    for &creator_count in CREATOR_COUNTS {
+        bench_core(&mut bench, creator_count).await;
+    }
+    bench_token22(&mut bench).await;
+
+    if !bench.report() {
+        std::process::exit(1);
+    }
+}
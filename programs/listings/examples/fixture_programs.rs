@@ -0,0 +1,9 @@
+//! prints the name and program id of every fixture, one per line, read by `tests/fixtures/dump.sh`
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+fn main() {
+    for (name, id) in common::fixture_programs() {
+        println!("{} {}", name, id);
+    }
+}
@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+use bridgesplit_program_utils::anchor_lang;
+
+#[error_code]
+pub enum ListingsError {
+    #[msg("Wallet balance overflow or underflow")]
+    BalanceOverflow,
+    #[msg("Wallet balance is not backed by its lamports")]
+    WalletUnderfunded,
+    #[msg("Withdrawals from this wallet are time locked")]
+    WithdrawalLocked,
+    #[msg("Wallet balance already covers the order")]
+    NothingToUnwind,
+    #[msg("Stake pool account data is invalid")]
+    InvalidStakePool,
+    #[msg("Amount is too small for a single share")]
+    ZeroShares,
+    #[msg("Bid pool does not hold enough sol, redeem nfts instead")]
+    InsufficientPoolLiquidity,
+    #[msg("Bid pool holds no nfts")]
+    NoPoolNfts,
+    #[msg("Not enough shares to redeem")]
+    InsufficientShares,
+    #[msg("Invalid amm curve parameters")]
+    InvalidCurve,
+    #[msg("Amm price out of range")]
+    PriceOutOfRange,
+    #[msg("Reference order is not an active order of the market")]
+    InvalidReferenceOrder,
+    #[msg("Order trigger condition is not met")]
+    TriggerNotMet,
+    #[msg("Bid does not cover the listing price, fees and royalties")]
+    OrdersDoNotCross,
+    #[msg("Mint is not a token-2022 nft")]
+    InvalidToken22Nft,
+    #[msg("Non transferable nfts cannot be traded")]
+    NonTransferableNft,
+    #[msg("Creator of the royalties plugin is missing from the remaining accounts")]
+    MissingRoyaltyCreator,
+    #[msg("Invalid listing mode")]
+    InvalidListingMode,
+    #[msg("Only semi-fungible assets can be listed with a size above 1")]
+    InvalidOrderSize,
+    #[msg("Account version is not supported by this program")]
+    UnsupportedAccountVersion,
+    #[msg("Account is already at the current version")]
+    NothingToMigrate,
+    #[msg("Order does not belong to the market")]
+    InvalidOrderMarket,
+    #[msg("Royalties do not match the data and creator hash of the compressed nft")]
+    InvalidCompressedRoyalties,
+    #[msg("Open bids of the delegate would exceed its spending cap")]
+    DelegateCapExceeded,
+    #[msg("Open order counters of the wallet do not match its orders")]
+    OpenOrderCountMismatch,
+    #[msg("Wallet of the order is missing from the remaining accounts")]
+    MissingOrderWallet,
+    #[msg("Withdrawal delay is above the maximum")]
+    InvalidWithdrawalDelay,
+    #[msg("Withdrawal unlock time overflows")]
+    UnlockTimeOverflow,
+    #[msg("Withdrawal delay of the wallet is below the minimum of the market")]
+    WithdrawalDelayTooShort,
+    #[msg("Stake pool can't change while wallets hold its tokens")]
+    StakePoolInUse,
+    #[msg("Stake pool accounts are required to unwind the wallet")]
+    MissingStakePoolAccounts,
+    #[msg("Cost record of the nft bought by the bid pool is missing or invalid")]
+    InvalidPoolNft,
+    #[msg("Amm price moved past the limit of the trade")]
+    AmmSlippageExceeded,
+    #[msg("Amm pool nft count or buy size overflow or underflow")]
+    AmmPoolCountOverflow,
+    #[msg("Token metadata accounts are required for token program nfts")]
+    MissingNftMetadata,
+    #[msg("Token-2022 nfts with a permanent delegate or a mint close authority cannot be traded")]
+    UnsupportedToken22Extension,
+    #[msg("Order has no units left to fill")]
+    OrderFillOverflow,
+    #[msg("The order book of the market must be passed")]
+    MissingOrderBook,
+    #[msg("Trailing bids need a listing in the order book of the market")]
+    MissingFloorListing,
+    #[msg(
+        "Orders of the migrated wallet may not be counted until its owner completes the migration"
+    )]
+    WalletMigrationIncomplete,
+    #[msg("Nfts sold to a bid pool need an appraisal of the market")]
+    MissingAppraisal,
+    #[msg("Candidate orders must be passed in increasing order of address")]
+    UnsortedCandidateOrders,
+}
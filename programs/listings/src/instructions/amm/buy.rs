@@ -0,0 +1,230 @@
+use anchor_lang::{prelude::*, solana_program::sysvar};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+use bridgesplit_program_utils::{anchor_lang, pnft::utils::get_is_pnft};
+use bridgesplit_program_utils::{state::Metadata, ExtraTransferParams, MplTokenMetadata};
+use vault::utils::get_bump_in_seed_form;
+
+use crate::{
+    errors::ListingsError,
+    state::*,
+    utils::{get_fee_amount, parse_remaining_accounts, pay_royalties, transfer_nft, transfer_sol},
+};
+
+#[derive(Accounts)]
+#[instruction()]
+#[event_cpi]
+pub struct AmmBuyNft<'info> {
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+    #[account(
+        mut,
+        constraint = wallet.load()?.version == WALLET_VERSION @ ListingsError::UnsupportedAccountVersion,
+        seeds = [WALLET_SEED.as_ref(),
+        amm_pool.owner.as_ref()],
+        bump,
+    )]
+    pub wallet: AccountLoader<'info, Wallet>,
+    #[account(
+        constraint = Market::is_active(market.state),
+        seeds = [MARKET_SEED.as_ref(),
+        market.pool_mint.as_ref()],
+        bump,
+    )]
+    pub market: Box<Account<'info, Market>>,
+    #[account(
+        mut,
+        constraint = amm_pool.market == market.key(),
+        constraint = amm_pool.nft_count > 0,
+        seeds = [AMM_POOL_SEED.as_ref(),
+        amm_pool.market.as_ref(),
+        amm_pool.owner.as_ref(),
+        amm_pool.nonce.as_ref()],
+        bump,
+    )]
+    pub amm_pool: Box<Account<'info, AmmPool>>,
+    #[account(mut)]
+    pub nft_mint: Box<Account<'info, Mint>>,
+    #[account(mut)]
+    pub nft_metadata: Box<Account<'info, Metadata>>,
+    /// CHECK: constraint check in multiple CPI calls
+    pub nft_edition: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        associated_token::mint = nft_mint,
+        associated_token::authority = amm_pool,
+    )]
+    pub amm_pool_nft_ta: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = initializer,
+        associated_token::mint = nft_mint,
+        associated_token::authority = initializer,
+    )]
+    pub buyer_nft_ta: Box<Account<'info, TokenAccount>>,
+    /// CHECK: constraint
+    #[account(
+        mut,
+        constraint = treasury.key().to_string() == PROTOCOL_TREASURY
+    )]
+    pub treasury: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    /// CHECK: checked by constraint and in cpi
+    #[account(address = sysvar::instructions::id())]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_metadata_program: Program<'info, MplTokenMetadata>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+//remaining accounts
+// 0 amm pool token_record or default,
+// 1 authorization_rules or default,
+// 2 authorization_rules_program or default,
+//
+// 4 buyer token record or default,
+// 5 ovol nft ta or default
+// 6 ovol nft metadata or default
+// 7-12 optional creator accounts in order of metadata. Will error if is pnft and correct creator accounts are not present
+
+/// buyer is initializer and buys an nft held by the pool at the ask price
+/// the price is credited to the bidding wallet of the pool owner
+/// max_price bounds the ask, the spot price can move between signing and execution
+#[inline(always)]
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, AmmBuyNft<'info>>,
+    max_price: u64,
+) -> Result<()> {
+    let parsed_accounts = parse_remaining_accounts(
+        ctx.remaining_accounts.to_vec(),
+        ctx.accounts.initializer.key(),
+        true,
+        false,
+        Some(1),
+    );
+    let pnft_params = parsed_accounts.pnft_params;
+
+    let price = ctx.accounts.amm_pool.get_ask_price()?;
+    if price > max_price {
+        return Err(ListingsError::AmmSlippageExceeded.into());
+    }
+    let fee_amount = if parsed_accounts.fees_on {
+        get_fee_amount(price)
+    } else {
+        0
+    };
+
+    msg!("Edit wallet balance: {}", ctx.accounts.wallet.key());
+    Wallet::edit_balance(&mut ctx.accounts.wallet.load_mut()?, true, price)?;
+    transfer_sol(
+        ctx.accounts.initializer.to_account_info(),
+        ctx.accounts.wallet.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        None,
+        price,
+    )?;
+    if fee_amount > 0 {
+        transfer_sol(
+            ctx.accounts.initializer.to_account_info(),
+            ctx.accounts.treasury.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            None,
+            fee_amount,
+        )?;
+    }
+
+    let dest_token_record =
+        if ctx.remaining_accounts.get(4).cloned().unwrap().key() == Pubkey::default() {
+            None
+        } else {
+            ctx.remaining_accounts.get(4).cloned()
+        };
+
+    let bump = &get_bump_in_seed_form(ctx.bumps.get("amm_pool").unwrap());
+    let signer_seeds = &[&[
+        AMM_POOL_SEED.as_ref(),
+        ctx.accounts.amm_pool.market.as_ref(),
+        ctx.accounts.amm_pool.owner.as_ref(),
+        ctx.accounts.amm_pool.nonce.as_ref(),
+        bump,
+    ][..]];
+
+    transfer_nft(
+        ctx.accounts.amm_pool.to_account_info(),
+        ctx.accounts.amm_pool.to_account_info(),
+        ctx.accounts.initializer.to_account_info(),
+        ctx.accounts.initializer.to_account_info(),
+        ctx.accounts.nft_mint.to_account_info(),
+        ctx.accounts.nft_metadata.to_account_info(),
+        ctx.accounts.nft_edition.to_account_info(),
+        ctx.accounts.amm_pool_nft_ta.to_account_info(),
+        ctx.accounts.buyer_nft_ta.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        ctx.accounts.sysvar_instructions.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        ctx.accounts.associated_token_program.to_account_info(),
+        ctx.accounts.token_metadata_program.to_account_info(),
+        ExtraTransferParams {
+            owner_token_record: pnft_params.token_record,
+            dest_token_record,
+            authorization_rules: pnft_params.authorization_rules,
+            authorization_rules_program: pnft_params.authorization_rules_program.clone(),
+            authorization_data: None,
+        },
+        signer_seeds,
+    )?;
+
+    // buyer pays fees and royalties on top of the price
+    let royalties = if get_is_pnft(&ctx.accounts.nft_metadata) {
+        pay_royalties(
+            price,
+            ctx.accounts.nft_metadata.clone(),
+            ctx.accounts.initializer.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            parsed_accounts.creator_accounts,
+            false,
+            None,
+        )?
+    } else {
+        Vec::new()
+    };
+
+    AmmPool::record_sell(
+        &mut ctx.accounts.amm_pool,
+        ctx.accounts.clock.unix_timestamp,
+    )?;
+
+    emit_cpi!(AmmPool::get_fill_event(
+        &ctx.accounts.amm_pool,
+        ctx.accounts.amm_pool.key(),
+        ctx.accounts.market.pool_mint,
+        OrderSide::Sell,
+        ctx.accounts.initializer.key(),
+        ctx.accounts.amm_pool.owner,
+        ctx.accounts.nft_mint.key(),
+        price,
+        fee_amount,
+        royalties,
+        !parsed_accounts.fees_on,
+        ctx.accounts.clock.unix_timestamp,
+    ));
+    emit_cpi!(AmmPool::get_edit_event(
+        &ctx.accounts.amm_pool,
+        ctx.accounts.amm_pool.key(),
+        AmmPoolEditType::Sell,
+    ));
+    emit_cpi!(Wallet::get_edit_event(
+        &ctx.accounts.wallet.load()?,
+        ctx.accounts.wallet.key(),
+        WalletEditType::Edit,
+    ));
+
+    Wallet::check_balance(
+        &ctx.accounts.wallet.load()?,
+        &ctx.accounts.wallet.to_account_info(),
+    )?;
+    Ok(())
+}
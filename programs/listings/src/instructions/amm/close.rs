@@ -0,0 +1,50 @@
+use anchor_lang::{prelude::*, solana_program::entrypoint::ProgramResult};
+use bridgesplit_program_utils::anchor_lang;
+
+use crate::{errors::ListingsError, state::*};
+
+#[derive(Accounts)]
+#[instruction()]
+#[event_cpi]
+pub struct CloseAmmPool<'info> {
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+    #[account(
+        mut,
+        constraint = wallet.load()?.version == WALLET_VERSION @ ListingsError::UnsupportedAccountVersion,
+        seeds = [WALLET_SEED.as_ref(),
+        initializer.key().as_ref()],
+        bump,
+    )]
+    pub wallet: AccountLoader<'info, Wallet>,
+    #[account(
+        mut,
+        // nfts have to be withdrawn first
+        constraint = amm_pool.nft_count == 0,
+        seeds = [AMM_POOL_SEED.as_ref(),
+        amm_pool.market.as_ref(),
+        initializer.key().as_ref(),
+        amm_pool.nonce.as_ref()],
+        bump,
+        close = initializer,
+    )]
+    pub amm_pool: Box<Account<'info, AmmPool>>,
+}
+
+#[inline(always)]
+pub fn handler(ctx: Context<CloseAmmPool>) -> ProgramResult {
+    msg!("Close amm pool: {}", ctx.accounts.amm_pool.key());
+
+    Wallet::remove_open_order(&mut ctx.accounts.wallet.load_mut()?, true)?;
+
+    emit_cpi!(AmmPool::get_edit_event(
+        &ctx.accounts.amm_pool,
+        ctx.accounts.amm_pool.key(),
+        AmmPoolEditType::Close,
+    ));
+    Wallet::check_balance(
+        &ctx.accounts.wallet.load()?,
+        &ctx.accounts.wallet.to_account_info(),
+    )?;
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use anchor_lang::{prelude::*, solana_program::entrypoint::ProgramResult};
+use bridgesplit_program_utils::anchor_lang;
+
+use crate::state::*;
+
+use super::AmmPoolData;
+
+#[derive(Accounts)]
+#[instruction()]
+#[event_cpi]
+pub struct EditAmmPool<'info> {
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [AMM_POOL_SEED.as_ref(),
+        amm_pool.market.as_ref(),
+        initializer.key().as_ref(),
+        amm_pool.nonce.as_ref()],
+        bump,
+    )]
+    pub amm_pool: Box<Account<'info, AmmPool>>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+#[inline(always)]
+pub fn handler(ctx: Context<EditAmmPool>, data: AmmPoolData) -> ProgramResult {
+    msg!("Edit amm pool: {}", ctx.accounts.amm_pool.key());
+
+    AmmPool::edit(
+        &mut ctx.accounts.amm_pool,
+        data.curve,
+        data.spot_price,
+        data.delta,
+        data.spread_bps,
+        data.buy_size,
+        ctx.accounts.clock.unix_timestamp,
+    )?;
+
+    emit_cpi!(AmmPool::get_edit_event(
+        &ctx.accounts.amm_pool,
+        ctx.accounts.amm_pool.key(),
+        AmmPoolEditType::Edit,
+    ));
+    Ok(())
+}
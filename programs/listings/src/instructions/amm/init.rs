@@ -0,0 +1,81 @@
+use anchor_lang::{prelude::*, solana_program::entrypoint::ProgramResult};
+use bridgesplit_program_utils::anchor_lang;
+
+use crate::{errors::ListingsError, state::*};
+
+use super::AmmPoolData;
+
+#[derive(Accounts)]
+#[instruction(nonce: Pubkey)]
+#[event_cpi]
+pub struct InitAmmPool<'info> {
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+    #[account(
+        mut,
+        constraint = wallet.load()?.version == WALLET_VERSION @ ListingsError::UnsupportedAccountVersion,
+        seeds = [WALLET_SEED.as_ref(),
+        initializer.key().as_ref()],
+        bump,
+    )]
+    pub wallet: AccountLoader<'info, Wallet>,
+    #[account(
+        constraint = Market::is_active(market.state),
+        // bids of the market are backed by wallets that cannot withdraw instantly
+        constraint = wallet.load()?.withdrawal_delay >= market.min_withdrawal_delay @ ListingsError::WithdrawalDelayTooShort,
+        seeds = [MARKET_SEED.as_ref(),
+        market.pool_mint.as_ref()],
+        bump,
+    )]
+    pub market: Box<Account<'info, Market>>,
+    #[account(
+        init,
+        seeds = [AMM_POOL_SEED.as_ref(),
+        market.key().as_ref(),
+        initializer.key().as_ref(),
+        nonce.as_ref()],
+        bump,
+        payer = initializer,
+        space = 8 + std::mem::size_of::<AmmPool>()
+    )]
+    pub amm_pool: Box<Account<'info, AmmPool>>,
+    pub system_program: Program<'info, System>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+/// the buy side of the pool is funded by the bidding wallet of the initializer
+#[inline(always)]
+pub fn handler(ctx: Context<InitAmmPool>, nonce: Pubkey, data: AmmPoolData) -> ProgramResult {
+    msg!("Initialize a new amm pool: {}", ctx.accounts.amm_pool.key());
+
+    AmmPool::init(
+        &mut ctx.accounts.amm_pool,
+        nonce,
+        ctx.accounts.market.key(),
+        ctx.accounts.initializer.key(),
+        ctx.accounts.wallet.key(),
+        ctx.accounts.clock.unix_timestamp,
+    );
+    AmmPool::edit(
+        &mut ctx.accounts.amm_pool,
+        data.curve,
+        data.spot_price,
+        data.delta,
+        data.spread_bps,
+        data.buy_size,
+        ctx.accounts.clock.unix_timestamp,
+    )?;
+    // the pool bids from the wallet until it is closed
+    Wallet::add_open_order(&mut ctx.accounts.wallet.load_mut()?, true)?;
+
+    emit_cpi!(AmmPool::get_edit_event(
+        &ctx.accounts.amm_pool,
+        ctx.accounts.amm_pool.key(),
+        AmmPoolEditType::Init,
+    ));
+    Wallet::check_balance(
+        &ctx.accounts.wallet.load()?,
+        &ctx.accounts.wallet.to_account_info(),
+    )?;
+    Ok(())
+}
@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+use bridgesplit_program_utils::anchor_lang;
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone)]
+pub struct AmmPoolData {
+    /// `AmmCurve`
+    pub curve: u8,
+    pub spot_price: u64,
+    pub delta: u64,
+    pub spread_bps: u64,
+    pub buy_size: u64,
+}
+
+pub mod buy;
+pub mod close;
+pub mod deposit_nft;
+pub mod edit;
+pub mod init;
+pub mod sell;
+pub mod withdraw_nft;
+
+pub use buy::*;
+pub use close::*;
+pub use deposit_nft::*;
+pub use edit::*;
+pub use init::*;
+pub use sell::*;
+pub use withdraw_nft::*;
@@ -0,0 +1,229 @@
+use anchor_lang::{prelude::*, solana_program::sysvar};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+use bridgesplit_program_utils::{anchor_lang, pnft::utils::get_is_pnft};
+use bridgesplit_program_utils::{state::Metadata, ExtraTransferParams, MplTokenMetadata};
+use vault::{
+    state::{Appraisal, APPRAISAL_SEED},
+    utils::lamport_transfer,
+};
+
+use crate::{
+    errors::ListingsError,
+    state::*,
+    utils::{get_fee_amount, parse_remaining_accounts, pay_royalties, transfer_nft},
+};
+
+#[derive(Accounts)]
+#[instruction()]
+#[event_cpi]
+pub struct AmmSellNft<'info> {
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+    #[account(
+        mut,
+        constraint = wallet.load()?.version == WALLET_VERSION @ ListingsError::UnsupportedAccountVersion,
+        seeds = [WALLET_SEED.as_ref(),
+        amm_pool.owner.as_ref()],
+        bump,
+    )]
+    pub wallet: AccountLoader<'info, Wallet>,
+    #[account(
+        constraint = Market::is_active(market.state),
+        seeds = [MARKET_SEED.as_ref(),
+        market.pool_mint.as_ref()],
+        bump,
+    )]
+    pub market: Box<Account<'info, Market>>,
+    #[account(
+        mut,
+        constraint = amm_pool.market == market.key(),
+        constraint = amm_pool.buy_size > 0,
+        seeds = [AMM_POOL_SEED.as_ref(),
+        amm_pool.market.as_ref(),
+        amm_pool.owner.as_ref(),
+        amm_pool.nonce.as_ref()],
+        bump,
+    )]
+    pub amm_pool: Box<Account<'info, AmmPool>>,
+    #[account(
+        seeds = [APPRAISAL_SEED, market.pool_mint.as_ref(), nft_mint.key().as_ref()],
+        bump,
+        seeds::program = vault::ID,
+    )]
+    pub appraisal: Box<Account<'info, Appraisal>>,
+    #[account(mut)]
+    pub nft_mint: Box<Account<'info, Mint>>,
+    #[account(mut)]
+    pub nft_metadata: Box<Account<'info, Metadata>>,
+    /// CHECK: constraint check in multiple CPI calls
+    pub nft_edition: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        associated_token::mint = nft_mint,
+        associated_token::authority = initializer,
+    )]
+    pub seller_nft_ta: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = initializer,
+        associated_token::mint = nft_mint,
+        associated_token::authority = amm_pool,
+    )]
+    pub amm_pool_nft_ta: Box<Account<'info, TokenAccount>>,
+    /// CHECK: constraint
+    #[account(
+        mut,
+        constraint = treasury.key().to_string() == PROTOCOL_TREASURY
+    )]
+    pub treasury: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    /// CHECK: checked by constraint and in cpi
+    #[account(address = sysvar::instructions::id())]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_metadata_program: Program<'info, MplTokenMetadata>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+//remaining accounts
+// 0 token_record or default,
+// 1 authorization_rules or default,
+// 2 authorization_rules_program or default,
+//
+// 4 amm pool token record or default,
+// 5 ovol nft ta or default
+// 6 ovol nft metadata default
+// 7-12 optional creator accounts in order of metadata. Will error if is pnft and correct creator accounts are not present
+
+/// seller is initializer and sells an appraised nft to the pool at the bid price
+/// the price is paid by the bidding wallet of the pool owner
+/// min_price bounds the bid, the spot price can move between signing and execution
+#[inline(always)]
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, AmmSellNft<'info>>,
+    min_price: u64,
+) -> Result<()> {
+    let parsed_accounts = parse_remaining_accounts(
+        ctx.remaining_accounts.to_vec(),
+        ctx.accounts.initializer.key(),
+        true,
+        false,
+        Some(1),
+    );
+    let pnft_params = parsed_accounts.pnft_params;
+
+    let price = ctx.accounts.amm_pool.get_bid_price();
+    if price == 0 {
+        return Err(ListingsError::PriceOutOfRange.into());
+    }
+    if price < min_price {
+        return Err(ListingsError::AmmSlippageExceeded.into());
+    }
+    msg!("Edit wallet balance: {}", ctx.accounts.wallet.key());
+    Wallet::edit_balance(&mut ctx.accounts.wallet.load_mut()?, false, price)?;
+
+    let amm_pool_token_record =
+        if ctx.remaining_accounts.get(4).cloned().unwrap().key() == Pubkey::default() {
+            None
+        } else {
+            ctx.remaining_accounts.get(4).cloned()
+        };
+
+    transfer_nft(
+        ctx.accounts.initializer.to_account_info(),
+        ctx.accounts.initializer.to_account_info(),
+        ctx.accounts.initializer.to_account_info(),
+        ctx.accounts.amm_pool.to_account_info(),
+        ctx.accounts.nft_mint.to_account_info(),
+        ctx.accounts.nft_metadata.to_account_info(),
+        ctx.accounts.nft_edition.to_account_info(),
+        ctx.accounts.seller_nft_ta.to_account_info(),
+        ctx.accounts.amm_pool_nft_ta.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        ctx.accounts.sysvar_instructions.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        ctx.accounts.associated_token_program.to_account_info(),
+        ctx.accounts.token_metadata_program.to_account_info(),
+        ExtraTransferParams {
+            owner_token_record: pnft_params.token_record,
+            dest_token_record: amm_pool_token_record,
+            authorization_rules: pnft_params.authorization_rules,
+            authorization_rules_program: pnft_params.authorization_rules_program.clone(),
+            authorization_data: None,
+        },
+        &[],
+    )?;
+
+    let fee_amount = if parsed_accounts.fees_on {
+        get_fee_amount(price)
+    } else {
+        0
+    };
+    lamport_transfer(
+        ctx.accounts.wallet.to_account_info(),
+        ctx.accounts.initializer.to_account_info(),
+        price - fee_amount,
+    )?;
+    if fee_amount > 0 {
+        lamport_transfer(
+            ctx.accounts.wallet.to_account_info(),
+            ctx.accounts.treasury.to_account_info(),
+            fee_amount,
+        )?;
+    }
+
+    // seller pays the royalties out of the proceeds
+    let royalties = if get_is_pnft(&ctx.accounts.nft_metadata) {
+        pay_royalties(
+            price,
+            ctx.accounts.nft_metadata.clone(),
+            ctx.accounts.initializer.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            parsed_accounts.creator_accounts,
+            false,
+            None,
+        )?
+    } else {
+        Vec::new()
+    };
+
+    AmmPool::record_buy(
+        &mut ctx.accounts.amm_pool,
+        ctx.accounts.clock.unix_timestamp,
+    )?;
+
+    emit_cpi!(AmmPool::get_fill_event(
+        &ctx.accounts.amm_pool,
+        ctx.accounts.amm_pool.key(),
+        ctx.accounts.market.pool_mint,
+        OrderSide::Buy,
+        ctx.accounts.amm_pool.owner,
+        ctx.accounts.initializer.key(),
+        ctx.accounts.nft_mint.key(),
+        price,
+        fee_amount,
+        royalties,
+        !parsed_accounts.fees_on,
+        ctx.accounts.clock.unix_timestamp,
+    ));
+    emit_cpi!(AmmPool::get_edit_event(
+        &ctx.accounts.amm_pool,
+        ctx.accounts.amm_pool.key(),
+        AmmPoolEditType::Buy,
+    ));
+    emit_cpi!(Wallet::get_edit_event(
+        &ctx.accounts.wallet.load()?,
+        ctx.accounts.wallet.key(),
+        WalletEditType::Edit,
+    ));
+
+    Wallet::check_balance(
+        &ctx.accounts.wallet.load()?,
+        &ctx.accounts.wallet.to_account_info(),
+    )?;
+    Ok(())
+}
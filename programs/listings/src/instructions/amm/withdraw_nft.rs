@@ -0,0 +1,134 @@
+use anchor_lang::{prelude::*, solana_program::sysvar};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+use bridgesplit_program_utils::anchor_lang;
+use bridgesplit_program_utils::{state::Metadata, ExtraTransferParams, MplTokenMetadata};
+use vault::utils::get_bump_in_seed_form;
+
+use crate::{
+    state::*,
+    utils::{parse_remaining_accounts, transfer_nft},
+};
+
+#[derive(Accounts)]
+#[instruction()]
+#[event_cpi]
+pub struct WithdrawAmmNft<'info> {
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [AMM_POOL_SEED.as_ref(),
+        amm_pool.market.as_ref(),
+        initializer.key().as_ref(),
+        amm_pool.nonce.as_ref()],
+        bump,
+    )]
+    pub amm_pool: Box<Account<'info, AmmPool>>,
+    #[account(mut)]
+    pub nft_mint: Box<Account<'info, Mint>>,
+    #[account(mut)]
+    pub nft_metadata: Box<Account<'info, Metadata>>,
+    /// CHECK: constraint check in multiple CPI calls
+    pub nft_edition: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        associated_token::mint = nft_mint,
+        associated_token::authority = amm_pool,
+    )]
+    pub amm_pool_nft_ta: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = initializer,
+        associated_token::mint = nft_mint,
+        associated_token::authority = initializer,
+    )]
+    pub initializer_nft_ta: Box<Account<'info, TokenAccount>>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    /// CHECK: checked by constraint and in cpi
+    #[account(address = sysvar::instructions::id())]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_metadata_program: Program<'info, MplTokenMetadata>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+//remaining accounts
+// 0 amm pool token_record or default,
+// 1 authorization_rules or default,
+// 2 authorization_rules_program or default,
+//
+// 4 initializer token record or default
+
+#[inline(always)]
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, WithdrawAmmNft<'info>>) -> Result<()> {
+    msg!(
+        "Withdraw nft from amm pool: {}",
+        ctx.accounts.amm_pool.key()
+    );
+
+    let parsed_accounts = parse_remaining_accounts(
+        ctx.remaining_accounts.to_vec(),
+        ctx.accounts.initializer.key(),
+        false,
+        false,
+        Some(1),
+    );
+    let pnft_params = parsed_accounts.pnft_params;
+
+    let initializer_token_record =
+        if ctx.remaining_accounts.get(4).cloned().unwrap().key() == Pubkey::default() {
+            None
+        } else {
+            ctx.remaining_accounts.get(4).cloned()
+        };
+
+    let bump = &get_bump_in_seed_form(ctx.bumps.get("amm_pool").unwrap());
+    let signer_seeds = &[&[
+        AMM_POOL_SEED.as_ref(),
+        ctx.accounts.amm_pool.market.as_ref(),
+        ctx.accounts.amm_pool.owner.as_ref(),
+        ctx.accounts.amm_pool.nonce.as_ref(),
+        bump,
+    ][..]];
+
+    transfer_nft(
+        ctx.accounts.amm_pool.to_account_info(),
+        ctx.accounts.amm_pool.to_account_info(),
+        ctx.accounts.initializer.to_account_info(),
+        ctx.accounts.initializer.to_account_info(),
+        ctx.accounts.nft_mint.to_account_info(),
+        ctx.accounts.nft_metadata.to_account_info(),
+        ctx.accounts.nft_edition.to_account_info(),
+        ctx.accounts.amm_pool_nft_ta.to_account_info(),
+        ctx.accounts.initializer_nft_ta.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        ctx.accounts.sysvar_instructions.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        ctx.accounts.associated_token_program.to_account_info(),
+        ctx.accounts.token_metadata_program.to_account_info(),
+        ExtraTransferParams {
+            owner_token_record: pnft_params.token_record,
+            dest_token_record: initializer_token_record,
+            authorization_rules: pnft_params.authorization_rules,
+            authorization_rules_program: pnft_params.authorization_rules_program.clone(),
+            authorization_data: None,
+        },
+        signer_seeds,
+    )?;
+
+    AmmPool::remove_nft(
+        &mut ctx.accounts.amm_pool,
+        ctx.accounts.clock.unix_timestamp,
+    )?;
+
+    emit_cpi!(AmmPool::get_edit_event(
+        &ctx.accounts.amm_pool,
+        ctx.accounts.amm_pool.key(),
+        AmmPoolEditType::WithdrawNft,
+    ));
+    Ok(())
+}
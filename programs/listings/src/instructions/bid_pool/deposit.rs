@@ -0,0 +1,84 @@
+use anchor_lang::{prelude::*, solana_program::entrypoint::ProgramResult};
+use bridgesplit_program_utils::anchor_lang;
+
+use crate::{errors::ListingsError, state::*, utils::transfer_sol};
+
+#[derive(Accounts)]
+#[instruction(amount: u64)]
+#[event_cpi]
+pub struct DepositBidPool<'info> {
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [BID_POOL_SEED.as_ref(),
+        bid_pool.manager.as_ref(),
+        bid_pool.nonce.as_ref()],
+        bump,
+    )]
+    pub bid_pool: Box<Account<'info, BidPool>>,
+    #[account(
+        mut,
+        constraint = wallet.load()?.version == WALLET_VERSION @ ListingsError::UnsupportedAccountVersion,
+        seeds = [WALLET_SEED.as_ref(),
+        bid_pool.key().as_ref()],
+        bump,
+    )]
+    pub wallet: AccountLoader<'info, Wallet>,
+    #[account(
+        init_if_needed,
+        seeds = [POOL_SHARE_SEED.as_ref(),
+        bid_pool.key().as_ref(),
+        initializer.key().as_ref()],
+        payer = initializer,
+        space = 8 + std::mem::size_of::<PoolShare>(),
+        bump,
+    )]
+    pub pool_share: Box<Account<'info, PoolShare>>,
+    pub system_program: Program<'info, System>,
+}
+
+/// shares are issued at the current value of the pool, nfts held are valued at cost
+#[inline(always)]
+pub fn handler(ctx: Context<DepositBidPool>, amount: u64) -> ProgramResult {
+    msg!("Deposit to bid pool: {}", ctx.accounts.bid_pool.key());
+
+    let shares = BidPool::deposit(
+        &mut ctx.accounts.bid_pool,
+        amount,
+        ctx.accounts.wallet.load()?.balance,
+    )?;
+    PoolShare::init(
+        &mut ctx.accounts.pool_share,
+        ctx.accounts.bid_pool.key(),
+        ctx.accounts.initializer.key(),
+    );
+    ctx.accounts.pool_share.shares += shares;
+    Wallet::edit_balance(&mut ctx.accounts.wallet.load_mut()?, true, amount)?;
+
+    transfer_sol(
+        ctx.accounts.initializer.to_account_info(),
+        ctx.accounts.wallet.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        None,
+        amount,
+    )?;
+
+    emit_cpi!(BidPool::get_edit_event(
+        &ctx.accounts.bid_pool,
+        ctx.accounts.bid_pool.key(),
+        BidPoolEditType::Deposit,
+        ctx.accounts.initializer.key(),
+        ctx.accounts.pool_share.shares,
+    ));
+    emit_cpi!(Wallet::get_edit_event(
+        &ctx.accounts.wallet.load()?,
+        ctx.accounts.wallet.key(),
+        WalletEditType::Edit,
+    ));
+    Wallet::check_balance(
+        &ctx.accounts.wallet.load()?,
+        &ctx.accounts.wallet.to_account_info(),
+    )?;
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use anchor_lang::{prelude::*, solana_program::entrypoint::ProgramResult};
+use bridgesplit_program_utils::anchor_lang;
+
+use crate::state::*;
+
+#[derive(Accounts)]
+#[instruction(nonce: Pubkey)]
+#[event_cpi]
+pub struct InitBidPool<'info> {
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+    #[account(
+        init,
+        seeds = [BID_POOL_SEED.as_ref(),
+        initializer.key().as_ref(),
+        nonce.as_ref()],
+        payer = initializer,
+        space = 8 + std::mem::size_of::<BidPool>(),
+        bump,
+    )]
+    pub bid_pool: Box<Account<'info, BidPool>>,
+    #[account(
+        init,
+        seeds = [WALLET_SEED.as_ref(),
+        bid_pool.key().as_ref()],
+        payer = initializer,
+        space = 8 + std::mem::size_of::<Wallet>(),
+        bump,
+    )]
+    pub wallet: AccountLoader<'info, Wallet>,
+    pub system_program: Program<'info, System>,
+}
+
+/// initializer is the manager of the pool
+/// the pool owns a bidding wallet and the manager bids as its uncapped delegate
+#[inline(always)]
+pub fn handler(ctx: Context<InitBidPool>, nonce: Pubkey) -> ProgramResult {
+    msg!(
+        "Initializing a new bid pool: {}",
+        ctx.accounts.bid_pool.key()
+    );
+
+    BidPool::init(
+        &mut ctx.accounts.bid_pool,
+        nonce,
+        ctx.accounts.initializer.key(),
+    );
+    let mut wallet = ctx.accounts.wallet.load_init()?;
+    Wallet::init(&mut wallet, ctx.accounts.bid_pool.key(), 0);
+    Wallet::set_delegate(&mut wallet, ctx.accounts.initializer.key(), u64::MAX);
+
+    emit_cpi!(BidPool::get_edit_event(
+        &ctx.accounts.bid_pool,
+        ctx.accounts.bid_pool.key(),
+        BidPoolEditType::Init,
+        Pubkey::default(),
+        0,
+    ));
+    emit_cpi!(Wallet::get_edit_event(
+        &wallet,
+        ctx.accounts.wallet.key(),
+        WalletEditType::Init,
+    ));
+    Wallet::check_balance(&wallet, &ctx.accounts.wallet.to_account_info())?;
+    Ok(())
+}
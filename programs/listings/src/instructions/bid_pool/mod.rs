@@ -0,0 +1,9 @@
+pub mod deposit;
+pub mod init;
+pub mod redeem_nft;
+pub mod redeem_sol;
+
+pub use deposit::*;
+pub use init::*;
+pub use redeem_nft::*;
+pub use redeem_sol::*;
@@ -0,0 +1,177 @@
+use anchor_lang::{prelude::*, solana_program::sysvar};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+use bridgesplit_program_utils::anchor_lang;
+use bridgesplit_program_utils::{state::Metadata, ExtraTransferParams, MplTokenMetadata};
+use vault::utils::get_bump_in_seed_form;
+
+use crate::{
+    errors::ListingsError,
+    state::*,
+    utils::{parse_remaining_accounts, transfer_nft},
+};
+
+#[derive(Accounts)]
+#[instruction()]
+#[event_cpi]
+pub struct RedeemBidPoolNft<'info> {
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [BID_POOL_SEED.as_ref(),
+        bid_pool.manager.as_ref(),
+        bid_pool.nonce.as_ref()],
+        bump,
+    )]
+    pub bid_pool: Box<Account<'info, BidPool>>,
+    #[account(
+        mut,
+        constraint = wallet.load()?.version == WALLET_VERSION @ ListingsError::UnsupportedAccountVersion,
+        seeds = [WALLET_SEED.as_ref(),
+        bid_pool.key().as_ref()],
+        bump,
+    )]
+    pub wallet: AccountLoader<'info, Wallet>,
+    #[account(
+        mut,
+        seeds = [POOL_SHARE_SEED.as_ref(),
+        bid_pool.key().as_ref(),
+        initializer.key().as_ref()],
+        bump,
+    )]
+    pub pool_share: Box<Account<'info, PoolShare>>,
+    #[account(mut)]
+    pub nft_mint: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [POOL_NFT_SEED.as_ref(),
+        bid_pool.key().as_ref(),
+        nft_mint.key().as_ref()],
+        bump,
+    )]
+    pub pool_nft: Box<Account<'info, PoolNft>>,
+    #[account(mut)]
+    pub nft_metadata: Box<Account<'info, Metadata>>,
+    /// CHECK: constraint check in multiple CPI calls
+    pub nft_edition: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        associated_token::mint = nft_mint,
+        associated_token::authority = bid_pool,
+    )]
+    pub bid_pool_nft_ta: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = initializer,
+        associated_token::mint = nft_mint,
+        associated_token::authority = initializer,
+    )]
+    pub initializer_nft_ta: Box<Account<'info, TokenAccount>>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    /// CHECK: checked by constraint and in cpi
+    #[account(address = sysvar::instructions::id())]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_metadata_program: Program<'info, MplTokenMetadata>,
+}
+
+//remaining accounts
+// 0 bid pool token_record or default,
+// 1 authorization_rules or default,
+// 2 authorization_rules_program or default,
+//
+// 4 initializer token record or default
+
+/// any nft held by the pool can be redeemed, each is valued at the price the pool paid for it
+#[inline(always)]
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, RedeemBidPoolNft<'info>>) -> Result<()> {
+    msg!("Redeem nft from bid pool: {}", ctx.accounts.bid_pool.key());
+
+    let parsed_accounts = parse_remaining_accounts(
+        ctx.remaining_accounts.to_vec(),
+        ctx.accounts.initializer.key(),
+        false,
+        false,
+        Some(1),
+    );
+    let pnft_params = parsed_accounts.pnft_params;
+
+    let nft_cost = PoolNft::redeem(&mut ctx.accounts.pool_nft)?;
+    let shares = BidPool::redeem_nft(
+        &mut ctx.accounts.bid_pool,
+        nft_cost,
+        ctx.accounts.wallet.load()?.balance,
+    )?;
+    ctx.accounts.pool_share.shares = ctx
+        .accounts
+        .pool_share
+        .shares
+        .checked_sub(shares)
+        .ok_or(ListingsError::InsufficientShares)?;
+
+    let initializer_token_record =
+        if ctx.remaining_accounts.get(4).cloned().unwrap().key() == Pubkey::default() {
+            None
+        } else {
+            ctx.remaining_accounts.get(4).cloned()
+        };
+
+    let bump = &get_bump_in_seed_form(ctx.bumps.get("bid_pool").unwrap());
+    let signer_seeds = &[&[
+        BID_POOL_SEED.as_ref(),
+        ctx.accounts.bid_pool.manager.as_ref(),
+        ctx.accounts.bid_pool.nonce.as_ref(),
+        bump,
+    ][..]];
+
+    transfer_nft(
+        ctx.accounts.bid_pool.to_account_info(),
+        ctx.accounts.bid_pool.to_account_info(),
+        ctx.accounts.initializer.to_account_info(),
+        ctx.accounts.initializer.to_account_info(),
+        ctx.accounts.nft_mint.to_account_info(),
+        ctx.accounts.nft_metadata.to_account_info(),
+        ctx.accounts.nft_edition.to_account_info(),
+        ctx.accounts.bid_pool_nft_ta.to_account_info(),
+        ctx.accounts.initializer_nft_ta.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        ctx.accounts.sysvar_instructions.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        ctx.accounts.associated_token_program.to_account_info(),
+        ctx.accounts.token_metadata_program.to_account_info(),
+        ExtraTransferParams {
+            owner_token_record: pnft_params.token_record,
+            dest_token_record: initializer_token_record,
+            authorization_rules: pnft_params.authorization_rules,
+            authorization_rules_program: pnft_params.authorization_rules_program.clone(),
+            authorization_data: None,
+        },
+        signer_seeds,
+    )?;
+
+    // the record of the mint is closed with its last unit, its rent goes to the pool wallet
+    if ctx.accounts.pool_nft.count == 0 {
+        let rent = ctx.accounts.pool_nft.to_account_info().lamports();
+        ctx.accounts
+            .pool_nft
+            .close(ctx.accounts.wallet.to_account_info())?;
+        Wallet::edit_balance(&mut ctx.accounts.wallet.load_mut()?, true, rent)?;
+        Wallet::check_balance(
+            &ctx.accounts.wallet.load()?,
+            &ctx.accounts.wallet.to_account_info(),
+        )?;
+    }
+
+    emit_cpi!(BidPool::get_edit_event(
+        &ctx.accounts.bid_pool,
+        ctx.accounts.bid_pool.key(),
+        BidPoolEditType::RedeemNft,
+        ctx.accounts.initializer.key(),
+        ctx.accounts.pool_share.shares,
+    ));
+    Ok(())
+}
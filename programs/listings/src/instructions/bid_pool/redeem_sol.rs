@@ -0,0 +1,76 @@
+use anchor_lang::{prelude::*, solana_program::entrypoint::ProgramResult};
+use bridgesplit_program_utils::anchor_lang;
+use vault::utils::lamport_transfer;
+
+use crate::{errors::ListingsError, state::*};
+
+#[derive(Accounts)]
+#[instruction(shares: u64)]
+#[event_cpi]
+pub struct RedeemBidPoolSol<'info> {
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [BID_POOL_SEED.as_ref(),
+        bid_pool.manager.as_ref(),
+        bid_pool.nonce.as_ref()],
+        bump,
+    )]
+    pub bid_pool: Box<Account<'info, BidPool>>,
+    #[account(
+        mut,
+        constraint = wallet.load()?.version == WALLET_VERSION @ ListingsError::UnsupportedAccountVersion,
+        seeds = [WALLET_SEED.as_ref(),
+        bid_pool.key().as_ref()],
+        bump,
+    )]
+    pub wallet: AccountLoader<'info, Wallet>,
+    #[account(
+        mut,
+        constraint = shares > 0 && shares <= pool_share.shares,
+        seeds = [POOL_SHARE_SEED.as_ref(),
+        bid_pool.key().as_ref(),
+        initializer.key().as_ref()],
+        bump,
+    )]
+    pub pool_share: Box<Account<'info, PoolShare>>,
+}
+
+/// burn shares for their value in sol, fails if the pool has spent too much of its sol on nfts
+#[inline(always)]
+pub fn handler(ctx: Context<RedeemBidPoolSol>, shares: u64) -> ProgramResult {
+    msg!("Redeem sol from bid pool: {}", ctx.accounts.bid_pool.key());
+
+    let lamports = BidPool::redeem_sol(
+        &mut ctx.accounts.bid_pool,
+        shares,
+        ctx.accounts.wallet.load()?.balance,
+    )?;
+    ctx.accounts.pool_share.shares -= shares;
+    Wallet::edit_balance(&mut ctx.accounts.wallet.load_mut()?, false, lamports)?;
+
+    lamport_transfer(
+        ctx.accounts.wallet.to_account_info(),
+        ctx.accounts.initializer.to_account_info(),
+        lamports,
+    )?;
+
+    emit_cpi!(BidPool::get_edit_event(
+        &ctx.accounts.bid_pool,
+        ctx.accounts.bid_pool.key(),
+        BidPoolEditType::RedeemSol,
+        ctx.accounts.initializer.key(),
+        ctx.accounts.pool_share.shares,
+    ));
+    emit_cpi!(Wallet::get_edit_event(
+        &ctx.accounts.wallet.load()?,
+        ctx.accounts.wallet.key(),
+        WalletEditType::Edit,
+    ));
+    Wallet::check_balance(
+        &ctx.accounts.wallet.load()?,
+        &ctx.accounts.wallet.to_account_info(),
+    )?;
+    Ok(())
+}
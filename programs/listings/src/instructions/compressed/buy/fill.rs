@@ -2,11 +2,19 @@ use anchor_lang::{prelude::*, solana_program::entrypoint::ProgramResult};
 use bridgesplit_program_utils::anchor_lang;
 use bridgesplit_program_utils::{
     compressed_transfer,
-    mpl_bubblegum::{cpi::accounts::Transfer, program::Bubblegum},
+    mpl_bubblegum::{cpi::accounts::Transfer, program::Bubblegum, utils::get_asset_id},
 };
 use vault::utils::lamport_transfer;
 
-use crate::{instructions::compressed::CompressedFillOrderData, state::*, utils::get_fee_amount};
+use crate::{
+    errors::ListingsError,
+    instructions::compressed::CompressedFillOrderData,
+    state::*,
+    utils::{
+        get_fee_amount, get_ovol_fees_on, pay_compressed_royalties, price_trailing_bid,
+        sync_order_book,
+    },
+};
 
 #[derive(Accounts)]
 #[instruction()]
@@ -16,17 +24,20 @@ pub struct CompressedFillBuyOrder<'info> {
     pub initializer: Signer<'info>,
     #[account(
         mut,
-        constraint = order.owner == buyer.key(),
+        constraint = order.load()?.owner == buyer.key(),
+        // bid pools only hold uncompressed nfts
+        constraint = !BidPool::is_bid_pool(&buyer),
     )]
     /// CHECK: constraint check
     pub buyer: UncheckedAccount<'info>,
     #[account(
         mut,
+        constraint = wallet.load()?.version == WALLET_VERSION @ ListingsError::UnsupportedAccountVersion,
         seeds = [WALLET_SEED.as_ref(),
-        order.owner.as_ref()],
+        order.load()?.owner.as_ref()],
         bump,
     )]
-    pub wallet: Box<Account<'info, Wallet>>,
+    pub wallet: AccountLoader<'info, Wallet>,
     #[account(
         constraint = Market::is_active(market.state),
         seeds = [MARKET_SEED.as_ref(),
@@ -34,17 +45,27 @@ pub struct CompressedFillBuyOrder<'info> {
         bump,
     )]
     pub market: Box<Account<'info, Market>>,
+    /// order book of the market, required if the market has one
     #[account(
         mut,
-        constraint = Order::is_active(order.state),
-        constraint = order.market == market.key(),
+        constraint = order_book.load()?.version == ORDER_BOOK_VERSION @ ListingsError::UnsupportedAccountVersion,
+        seeds = [ORDER_BOOK_SEED.as_ref(),
+        market.key().as_ref()],
+        bump,
+    )]
+    pub order_book: Option<AccountLoader<'info, OrderBook>>,
+    #[account(
+        mut,
+        constraint = order.load()?.version == ORDER_VERSION @ ListingsError::UnsupportedAccountVersion,
+        constraint = Order::is_fillable(order.load()?.state),
+        constraint = order.load()?.market == market.key(),
         seeds = [ORDER_SEED.as_ref(),
-        order.nonce.as_ref(),
-        order.market.as_ref(),
-        order.owner.as_ref()],
+        order.load()?.nonce.as_ref(),
+        order.load()?.market.as_ref(),
+        order.load()?.owner.as_ref()],
         bump,
     )]
-    pub order: Box<Account<'info, Order>>,
+    pub order: AccountLoader<'info, Order>,
     /// CHECK: constraint
     #[account(
         mut,
@@ -93,17 +114,46 @@ impl<'info> CompressedFillBuyOrder<'info> {
 
 /// seller is initializer and is transferring the nft to buyer who is the owner of the order account
 /// buyer is the owner of the order account and is transferring sol to seller via bidding wallet
+/// remaining accounts are the 2 ovol accounts or default pubkeys, one account per creator of the
+/// nft and the proof of the leaf
 #[inline(always)]
 pub fn handler<'info>(
     ctx: Context<'_, '_, '_, 'info, CompressedFillBuyOrder<'info>>,
     data: CompressedFillOrderData,
 ) -> ProgramResult {
+    price_trailing_bid(
+        &ctx.accounts.order_book,
+        &mut ctx.accounts.order.load_mut()?,
+    )?;
+
     // edit wallet account to decrease balance
     msg!("Edit wallet balance: {}", ctx.accounts.wallet.key());
-    Wallet::edit_balance(&mut ctx.accounts.wallet, false, ctx.accounts.order.price);
+    Wallet::edit_balance(
+        &mut ctx.accounts.wallet.load_mut()?,
+        false,
+        ctx.accounts.order.load()?.price,
+    )?;
+
+    let creator_count = data.creators.len();
+    let fees_on = get_ovol_fees_on(
+        ctx.remaining_accounts,
+        ctx.accounts.initializer.key(),
+        ctx.accounts.order.load()?.is_fees_on(),
+    );
+    let creator_accounts = ctx
+        .remaining_accounts
+        .get(2..2 + creator_count)
+        .unwrap_or_default()
+        .to_vec();
+    let proof_accounts = ctx
+        .remaining_accounts
+        .get(2 + creator_count..)
+        .unwrap_or_default()
+        .to_vec();
 
+    data.verify_royalties()?;
     ctx.accounts.transfer_compressed_nft(
-        ctx.remaining_accounts.to_vec(),
+        proof_accounts,
         data.root,
         data.data_hash,
         data.creator_hash,
@@ -111,31 +161,74 @@ pub fn handler<'info>(
         data.index,
     )?;
 
+    let price = ctx.accounts.order.load()?.price;
+    // the fee comes out of the price so the wallet pays exactly what was deducted from the balance
+    let fee_amount = if fees_on { get_fee_amount(price) } else { 0 };
+
+    // seller pays the royalties out of the proceeds, paid straight from the bidding wallet
+    let royalties = pay_compressed_royalties(
+        price,
+        data.seller_fee_basis_points,
+        &data.creators,
+        ctx.accounts.wallet.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        creator_accounts,
+        true,
+        None,
+    )?;
+    let royalty_total: u64 = royalties.iter().map(|royalty| royalty.amount).sum();
+
     // transfer sol from buyer to seller
     lamport_transfer(
         ctx.accounts.wallet.to_account_info(),
         ctx.accounts.initializer.to_account_info(),
-        ctx.accounts.order.price,
+        price
+            .checked_sub(fee_amount)
+            .and_then(|amount| amount.checked_sub(royalty_total))
+            .ok_or::<Error>(ListingsError::OrdersDoNotCross.into())?,
     )?;
 
-    let fee_amount = get_fee_amount(ctx.accounts.order.price);
     // transfer fee to treasury
-    lamport_transfer(
-        ctx.accounts.wallet.to_account_info(),
-        ctx.accounts.treasury.to_account_info(),
+    if fee_amount > 0 {
+        lamport_transfer(
+            ctx.accounts.wallet.to_account_info(),
+            ctx.accounts.treasury.to_account_info(),
+            fee_amount,
+        )?;
+    }
+
+    emit_cpi!(Order::get_fill_event(
+        &ctx.accounts.order.load()?,
+        ctx.accounts.order.key(),
+        ctx.accounts.market.pool_mint,
+        ctx.accounts.buyer.key(),
+        ctx.accounts.initializer.key(),
+        get_asset_id(&ctx.accounts.merkle_tree.key(), data.index as u64),
         fee_amount,
-    )?;
+        royalties,
+        !fees_on,
+        ctx.accounts.clock.unix_timestamp,
+    ));
 
     // edit order
-    let price = ctx.accounts.order.price;
-    let size = ctx.accounts.order.size;
+    let size = ctx.accounts.order.load()?.size;
 
-    Order::edit_buy(
-        &mut ctx.accounts.order,
+    Order::fill(
+        &mut ctx.accounts.order.load_mut()?,
         price,
-        size - 1,
         ctx.accounts.clock.unix_timestamp,
-    );
+    )?;
+    Wallet::release_filled_exposure(
+        &mut ctx.accounts.wallet.load_mut()?,
+        &mut ctx.accounts.order.load_mut()?,
+    )?;
+    sync_order_book(
+        &ctx.accounts.order_book,
+        &ctx.accounts.market,
+        ctx.accounts.order.key(),
+        &ctx.accounts.order.load()?,
+        Some(&ctx.accounts.wallet.load()?),
+    )?;
 
     if size == 1 {
         // close order account
@@ -145,18 +238,19 @@ pub fn handler<'info>(
             ctx.accounts.market.pool_mint
         );
         emit_cpi!(Order::get_edit_event(
-            &mut ctx.accounts.order.clone(),
+            &ctx.accounts.order.load()?,
             ctx.accounts.order.key(),
             ctx.accounts.market.pool_mint,
             OrderEditType::FillAndClose,
         ));
-        ctx.accounts.order.state = OrderState::Closed.into();
+        ctx.accounts.order.load_mut()?.state = OrderState::Closed.into();
+        Wallet::remove_open_order(&mut ctx.accounts.wallet.load_mut()?, true)?;
         ctx.accounts
             .order
             .close(ctx.accounts.buyer.to_account_info())?;
     } else {
         emit_cpi!(Order::get_edit_event(
-            &mut ctx.accounts.order.clone(),
+            &ctx.accounts.order.load()?,
             ctx.accounts.order.key(),
             ctx.accounts.market.pool_mint,
             OrderEditType::Fill,
@@ -164,5 +258,9 @@ pub fn handler<'info>(
         msg!("Filled buy order: {}", ctx.accounts.order.key());
     }
 
+    Wallet::check_balance(
+        &ctx.accounts.wallet.load()?,
+        &ctx.accounts.wallet.to_account_info(),
+    )?;
     Ok(())
 }
@@ -1,6 +1,8 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, solana_program::keccak};
 use bridgesplit_program_utils::anchor_lang;
 
+use crate::errors::ListingsError;
+
 #[derive(AnchorDeserialize, AnchorSerialize, Clone)]
 pub struct CompressedOrderData {
     pub order_nonce: Pubkey,
@@ -12,12 +14,55 @@ pub struct CompressedOrderData {
     pub index: u32,
 }
 
+#[derive(AnchorDeserialize, AnchorSerialize, Clone)]
+pub struct CompressedCreator {
+    pub address: Pubkey,
+    pub verified: bool,
+    pub share: u8,
+}
+
 #[derive(AnchorDeserialize, AnchorSerialize, Clone)]
 pub struct CompressedFillOrderData {
     pub root: [u8; 32],
     pub data_hash: [u8; 32],
     pub creator_hash: [u8; 32],
     pub index: u32,
+    /// hash of the metadata args, the data hash of the leaf is derived from it and the royalties
+    pub metadata_args_hash: [u8; 32],
+    pub seller_fee_basis_points: u16,
+    pub creators: Vec<CompressedCreator>,
+}
+
+impl CompressedFillOrderData {
+    /// royalties and creators are only trusted if they hash to the leaf proven in the transfer
+    pub fn verify_royalties(&self) -> Result<()> {
+        let data_hash = keccak::hashv(&[
+            &self.metadata_args_hash,
+            &self.seller_fee_basis_points.to_le_bytes(),
+        ]);
+        let creator_data = self
+            .creators
+            .iter()
+            .map(|creator| {
+                [
+                    creator.address.as_ref(),
+                    &[creator.verified as u8],
+                    &[creator.share],
+                ]
+                .concat()
+            })
+            .collect::<Vec<_>>();
+        let creator_hash = keccak::hashv(
+            &creator_data
+                .iter()
+                .map(|creator| creator.as_slice())
+                .collect::<Vec<&[u8]>>(),
+        );
+        if data_hash.to_bytes() != self.data_hash || creator_hash.to_bytes() != self.creator_hash {
+            return Err(ListingsError::InvalidCompressedRoyalties.into());
+        }
+        Ok(())
+    }
 }
 
 pub mod buy;
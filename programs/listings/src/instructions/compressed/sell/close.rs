@@ -7,7 +7,10 @@ use bridgesplit_program_utils::{
 };
 use vault::utils::get_bump_in_seed_form;
 
-use crate::{instructions::compressed::CompressedOrderData, state::*};
+use crate::{
+    errors::ListingsError, instructions::compressed::CompressedOrderData, state::*,
+    utils::sync_order_book,
+};
 
 #[derive(Accounts)]
 #[instruction()]
@@ -17,17 +20,18 @@ pub struct CompressedCloseSellOrder<'info> {
     pub initializer: Signer<'info>,
     #[account(
         mut,
-        constraint = order.owner == initializer.key(),
-        constraint = order.market == market.key(),
-        constraint = Order::is_active(order.state),
+        constraint = order.load()?.version == ORDER_VERSION @ ListingsError::UnsupportedAccountVersion,
+        constraint = order.load()?.owner == initializer.key(),
+        constraint = order.load()?.market == market.key(),
+        constraint = Order::is_active(order.load()?.state),
         seeds = [ORDER_SEED.as_ref(),
-        order.nonce.as_ref(),
-        order.market.as_ref(),
+        order.load()?.nonce.as_ref(),
+        order.load()?.market.as_ref(),
         initializer.key().as_ref()],
         bump,
         close = initializer,
     )]
-    pub order: Box<Account<'info, Order>>,
+    pub order: AccountLoader<'info, Order>,
     #[account(
         constraint = Market::is_active(market.state),
         seeds = [MARKET_SEED.as_ref(),
@@ -35,13 +39,23 @@ pub struct CompressedCloseSellOrder<'info> {
         bump,
     )]
     pub market: Box<Account<'info, Market>>,
+    /// order book of the market, required if the market has one
+    #[account(
+        mut,
+        constraint = order_book.load()?.version == ORDER_BOOK_VERSION @ ListingsError::UnsupportedAccountVersion,
+        seeds = [ORDER_BOOK_SEED.as_ref(),
+        market.key().as_ref()],
+        bump,
+    )]
+    pub order_book: Option<AccountLoader<'info, OrderBook>>,
     #[account(
         mut,
+        constraint = wallet.load()?.version == WALLET_VERSION @ ListingsError::UnsupportedAccountVersion,
         seeds = [WALLET_SEED.as_ref(),
-        order.owner.as_ref()],
+        order.load()?.owner.as_ref()],
         bump,
     )]
-    pub wallet: Box<Account<'info, Wallet>>,
+    pub wallet: AccountLoader<'info, Wallet>,
     /// CHECK: checked in cpi
     pub tree_authority: UncheckedAccount<'info>,
     /// CHECK: checked in cpi
@@ -103,11 +117,8 @@ pub fn handler<'info>(
 
     let bump = &get_bump_in_seed_form(ctx.bumps.get("wallet").unwrap());
 
-    let signer_seeds = &[&[
-        WALLET_SEED.as_ref(),
-        ctx.accounts.order.owner.as_ref(),
-        bump,
-    ][..]];
+    let owner = ctx.accounts.order.load()?.owner;
+    let signer_seeds = &[&[WALLET_SEED.as_ref(), owner.as_ref(), bump][..]];
 
     ctx.accounts.transfer_compressed_nft(
         ctx.remaining_accounts.to_vec(),
@@ -118,13 +129,25 @@ pub fn handler<'info>(
         data.index,
     )?;
 
-    ctx.accounts.order.state = OrderState::Closed.into();
+    ctx.accounts.order.load_mut()?.state = OrderState::Closed.into();
+    Wallet::remove_open_order(&mut ctx.accounts.wallet.load_mut()?, false)?;
 
     emit_cpi!(Order::get_edit_event(
-        &mut ctx.accounts.order.clone(),
+        &ctx.accounts.order.load()?,
         ctx.accounts.order.key(),
         ctx.accounts.market.pool_mint,
         OrderEditType::Close,
     ));
+    Wallet::check_balance(
+        &ctx.accounts.wallet.load()?,
+        &ctx.accounts.wallet.to_account_info(),
+    )?;
+    sync_order_book(
+        &ctx.accounts.order_book,
+        &ctx.accounts.market,
+        ctx.accounts.order.key(),
+        &ctx.accounts.order.load()?,
+        None,
+    )?;
     Ok(())
 }
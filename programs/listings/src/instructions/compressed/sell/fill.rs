@@ -7,9 +7,12 @@ use bridgesplit_program_utils::{
 use vault::utils::get_bump_in_seed_form;
 
 use crate::{
+    errors::ListingsError,
     instructions::compressed::CompressedFillOrderData,
     state::*,
-    utils::{get_fee_amount, transfer_sol},
+    utils::{
+        get_fee_amount, get_ovol_fees_on, pay_compressed_royalties, sync_order_book, transfer_sol,
+    },
 };
 
 #[derive(Accounts)]
@@ -20,17 +23,18 @@ pub struct CompressedFillSellOrder<'info> {
     pub initializer: Signer<'info>,
     #[account(
         mut,
-        constraint = order.owner == seller.key(),
+        constraint = order.load()?.owner == seller.key(),
     )]
     /// CHECK: constraint check
     pub seller: UncheckedAccount<'info>,
     #[account(
         mut,
+        constraint = wallet.load()?.version == WALLET_VERSION @ ListingsError::UnsupportedAccountVersion,
         seeds = [WALLET_SEED.as_ref(),
         seller.key().as_ref()],
         bump,
     )]
-    pub wallet: Box<Account<'info, Wallet>>,
+    pub wallet: AccountLoader<'info, Wallet>,
     #[account(
         constraint = Market::is_active(market.state),
         seeds = [MARKET_SEED.as_ref(),
@@ -38,18 +42,28 @@ pub struct CompressedFillSellOrder<'info> {
         bump,
     )]
     pub market: Box<Account<'info, Market>>,
+    /// order book of the market, required if the market has one
     #[account(
         mut,
-        constraint = Order::is_active(order.state),
-        constraint = order.market == market.key(),
+        constraint = order_book.load()?.version == ORDER_BOOK_VERSION @ ListingsError::UnsupportedAccountVersion,
+        seeds = [ORDER_BOOK_SEED.as_ref(),
+        market.key().as_ref()],
+        bump,
+    )]
+    pub order_book: Option<AccountLoader<'info, OrderBook>>,
+    #[account(
+        mut,
+        constraint = order.load()?.version == ORDER_VERSION @ ListingsError::UnsupportedAccountVersion,
+        constraint = Order::is_fillable(order.load()?.state),
+        constraint = order.load()?.market == market.key(),
         seeds = [ORDER_SEED.as_ref(),
-        order.nonce.as_ref(),
-        order.market.as_ref(),
-        order.owner.as_ref()],
+        order.load()?.nonce.as_ref(),
+        order.load()?.market.as_ref(),
+        order.load()?.owner.as_ref()],
         bump,
         close = seller
     )]
-    pub order: Box<Account<'info, Order>>,
+    pub order: AccountLoader<'info, Order>,
     /// CHECK: constraint
     #[account(
         mut,
@@ -108,6 +122,8 @@ impl<'info> CompressedFillSellOrder<'info> {
     }
 }
 
+/// remaining accounts are the 2 ovol accounts or default pubkeys, one account per creator of the
+/// nft and the proof of the leaf
 #[inline(always)]
 pub fn handler<'info>(
     ctx: Context<'_, '_, '_, 'info, CompressedFillSellOrder<'info>>,
@@ -115,14 +131,29 @@ pub fn handler<'info>(
 ) -> ProgramResult {
     let bump = &get_bump_in_seed_form(ctx.bumps.get("wallet").unwrap());
 
-    let signer_seeds = &[&[
-        WALLET_SEED.as_ref(),
-        ctx.accounts.order.owner.as_ref(),
-        bump,
-    ][..]];
+    let owner = ctx.accounts.order.load()?.owner;
+    let signer_seeds = &[&[WALLET_SEED.as_ref(), owner.as_ref(), bump][..]];
+
+    let creator_count = data.creators.len();
+    let fees_on = get_ovol_fees_on(
+        ctx.remaining_accounts,
+        ctx.accounts.initializer.key(),
+        ctx.accounts.order.load()?.is_fees_on(),
+    );
+    let creator_accounts = ctx
+        .remaining_accounts
+        .get(2..2 + creator_count)
+        .unwrap_or_default()
+        .to_vec();
+    let proof_accounts = ctx
+        .remaining_accounts
+        .get(2 + creator_count..)
+        .unwrap_or_default()
+        .to_vec();
 
+    data.verify_royalties()?;
     ctx.accounts.transfer_compressed_nft(
-        ctx.remaining_accounts.to_vec(),
+        proof_accounts,
         signer_seeds,
         data.root,
         data.data_hash,
@@ -130,35 +161,81 @@ pub fn handler<'info>(
         data.index,
     )?;
 
-    let fee_amount = get_fee_amount(ctx.accounts.order.price);
+    let price = ctx.accounts.order.load()?.price;
+    let fee_amount = if fees_on { get_fee_amount(price) } else { 0 };
 
     // transfer fee to treasury
+    if fee_amount > 0 {
+        transfer_sol(
+            ctx.accounts.initializer.to_account_info(),
+            ctx.accounts.treasury.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            Some(signer_seeds),
+            fee_amount,
+        )?;
+    }
+
+    // transfer sol from buyer to seller
     transfer_sol(
         ctx.accounts.initializer.to_account_info(),
-        ctx.accounts.treasury.to_account_info(),
+        ctx.accounts.seller.to_account_info(),
         ctx.accounts.system_program.to_account_info(),
         Some(signer_seeds),
-        fee_amount,
+        price,
     )?;
 
-    // transfer sol from buyer to seller
-    transfer_sol(
+    // buyer pays the royalties on top of the price
+    let royalties = pay_compressed_royalties(
+        price,
+        data.seller_fee_basis_points,
+        &data.creators,
         ctx.accounts.initializer.to_account_info(),
-        ctx.accounts.seller.to_account_info(),
         ctx.accounts.system_program.to_account_info(),
+        creator_accounts,
+        false,
         Some(signer_seeds),
-        ctx.accounts.order.price,
+    )?;
+
+    emit_cpi!(Order::get_fill_event(
+        &ctx.accounts.order.load()?,
+        ctx.accounts.order.key(),
+        ctx.accounts.market.pool_mint,
+        ctx.accounts.initializer.key(),
+        ctx.accounts.seller.key(),
+        ctx.accounts.order.load()?.nft_mint,
+        fee_amount,
+        royalties,
+        !fees_on,
+        ctx.accounts.clock.unix_timestamp,
+    ));
+
+    Order::fill(
+        &mut ctx.accounts.order.load_mut()?,
+        price,
+        ctx.accounts.clock.unix_timestamp,
+    )?;
+    sync_order_book(
+        &ctx.accounts.order_book,
+        &ctx.accounts.market,
+        ctx.accounts.order.key(),
+        &ctx.accounts.order.load()?,
+        None,
     )?;
 
     // close order account
     msg!("Close sell order account: {}", ctx.accounts.order.key());
-    ctx.accounts.order.state = OrderState::Closed.into();
+    ctx.accounts.order.load_mut()?.state = OrderState::Closed.into();
+    Wallet::remove_open_order(&mut ctx.accounts.wallet.load_mut()?, false)?;
     emit_cpi!(Order::get_edit_event(
-        &mut ctx.accounts.order.clone(),
+        &ctx.accounts.order.load()?,
         ctx.accounts.order.key(),
         ctx.accounts.market.pool_mint,
         OrderEditType::FillAndClose,
     ));
 
+    Wallet::check_balance(
+        &ctx.accounts.wallet.load()?,
+        &ctx.accounts.wallet.to_account_info(),
+    )?;
     Ok(())
 }
@@ -7,7 +7,10 @@ use bridgesplit_program_utils::{
 };
 use vault::state::{Appraisal, APPRAISAL_SEED};
 
-use crate::{instructions::compressed::CompressedOrderData, state::*};
+use crate::{
+    errors::ListingsError, instructions::compressed::CompressedOrderData, state::*,
+    utils::sync_order_book,
+};
 
 #[derive(Accounts)]
 #[instruction(data: CompressedOrderData)]
@@ -17,11 +20,12 @@ pub struct CompressedInitSellOrder<'info> {
     pub initializer: Signer<'info>,
     #[account(
         mut,
+        constraint = wallet.load()?.version == WALLET_VERSION @ ListingsError::UnsupportedAccountVersion,
         seeds = [WALLET_SEED.as_ref(),
         initializer.key().as_ref()],
         bump,
     )]
-    pub wallet: Box<Account<'info, Wallet>>,
+    pub wallet: AccountLoader<'info, Wallet>,
     #[account(
         constraint = Market::is_active(market.state),
         seeds = [MARKET_SEED.as_ref(),
@@ -29,6 +33,15 @@ pub struct CompressedInitSellOrder<'info> {
         bump,
     )]
     pub market: Box<Account<'info, Market>>,
+    /// order book of the market, required if the market has one
+    #[account(
+        mut,
+        constraint = order_book.load()?.version == ORDER_BOOK_VERSION @ ListingsError::UnsupportedAccountVersion,
+        seeds = [ORDER_BOOK_SEED.as_ref(),
+        market.key().as_ref()],
+        bump,
+    )]
+    pub order_book: Option<AccountLoader<'info, OrderBook>>,
     #[account(
         constraint = data.price > 0,
         init,
@@ -40,7 +53,7 @@ pub struct CompressedInitSellOrder<'info> {
         payer = initializer,
         space = 8 + std::mem::size_of::<Order>()
     )]
-    pub order: Box<Account<'info, Order>>,
+    pub order: AccountLoader<'info, Order>,
     #[account(
         seeds = [APPRAISAL_SEED, market.pool_mint.as_ref(), data.mint_id.as_ref()],
         bump,
@@ -97,8 +110,9 @@ pub fn handler<'info>(
     msg!("Initialize a new sell order: {}", ctx.accounts.order.key());
 
     // create a new order with size 1
+    let mut order = ctx.accounts.order.load_init()?;
     Order::init(
-        &mut ctx.accounts.order,
+        &mut order,
         ctx.accounts.market.key(),
         ctx.accounts.initializer.key(),
         ctx.accounts.wallet.key(),
@@ -111,6 +125,7 @@ pub fn handler<'info>(
         OrderState::Ready.into(),
         true,
     );
+    Wallet::add_open_order(&mut ctx.accounts.wallet.load_mut()?, false)?;
 
     ctx.accounts.transfer_compressed_nft(
         ctx.remaining_accounts.to_vec(),
@@ -122,11 +137,22 @@ pub fn handler<'info>(
     )?;
 
     emit_cpi!(Order::get_edit_event(
-        &mut ctx.accounts.order.clone(),
+        &order,
         ctx.accounts.order.key(),
         ctx.accounts.market.pool_mint,
         OrderEditType::Init,
     ));
 
+    Wallet::check_balance(
+        &ctx.accounts.wallet.load()?,
+        &ctx.accounts.wallet.to_account_info(),
+    )?;
+    sync_order_book(
+        &ctx.accounts.order_book,
+        &ctx.accounts.market,
+        ctx.accounts.order.key(),
+        &order,
+        None,
+    )?;
     Ok(())
 }
@@ -0,0 +1,43 @@
+use anchor_lang::{prelude::*, solana_program::entrypoint::ProgramResult};
+use bridgesplit_program_utils::anchor_lang;
+
+use crate::state::*;
+
+#[derive(Accounts)]
+#[instruction()]
+pub struct SetLstConfig<'info> {
+    #[account(
+        mut,
+        constraint = initializer.key().to_string() == PROTOCOL_TREASURY
+    )]
+    pub initializer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        seeds = [LST_CONFIG_SEED.as_ref()],
+        bump,
+        payer = initializer,
+        space = 8 + std::mem::size_of::<LstConfig>()
+    )]
+    pub lst_config: Box<Account<'info, LstConfig>>,
+    /// CHECK: stored in the config, must expose the stake pool deposit and withdraw sol interface
+    pub stake_pool_program: UncheckedAccount<'info>,
+    /// CHECK: stored in the config
+    #[account(owner = stake_pool_program.key())]
+    pub stake_pool: UncheckedAccount<'info>,
+    /// CHECK: stored in the config, checked against the stake pool in cpi
+    pub pool_mint: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[inline(always)]
+pub fn handler(ctx: Context<SetLstConfig>, enabled: bool) -> ProgramResult {
+    msg!("Set lst config: {}", ctx.accounts.stake_pool.key());
+    LstConfig::set(
+        &mut ctx.accounts.lst_config,
+        ctx.accounts.stake_pool_program.key(),
+        ctx.accounts.stake_pool.key(),
+        ctx.accounts.pool_mint.key(),
+        enabled,
+    )?;
+    Ok(())
+}
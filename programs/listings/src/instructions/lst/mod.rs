@@ -0,0 +1,9 @@
+pub mod config;
+pub mod stake;
+pub mod unstake;
+pub mod unwind;
+
+pub use config::*;
+pub use stake::*;
+pub use unstake::*;
+pub use unwind::*;
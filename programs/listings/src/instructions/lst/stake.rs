@@ -0,0 +1,106 @@
+use anchor_lang::{prelude::*, solana_program::entrypoint::ProgramResult};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+use bridgesplit_program_utils::anchor_lang;
+use vault::utils::lamport_transfer;
+
+use crate::{errors::ListingsError, stake_pool::deposit_sol, state::*};
+
+#[derive(Accounts)]
+#[instruction(amount: u64)]
+#[event_cpi]
+pub struct StakeWallet<'info> {
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+    #[account(
+        mut,
+        constraint = wallet.load()?.version == WALLET_VERSION @ ListingsError::UnsupportedAccountVersion,
+        constraint = amount > 0 && amount <= wallet.load()?.balance,
+        seeds = [WALLET_SEED.as_ref(),
+        initializer.key().as_ref()],
+        bump,
+    )]
+    pub wallet: AccountLoader<'info, Wallet>,
+    #[account(
+        mut,
+        constraint = lst_config.enabled,
+        seeds = [LST_CONFIG_SEED.as_ref()],
+        bump,
+    )]
+    pub lst_config: Box<Account<'info, LstConfig>>,
+    /// CHECK: address checked against the config
+    #[account(address = lst_config.stake_pool_program)]
+    pub stake_pool_program: UncheckedAccount<'info>,
+    /// CHECK: address checked against the config
+    #[account(mut, address = lst_config.stake_pool)]
+    pub stake_pool: UncheckedAccount<'info>,
+    /// CHECK: checked in cpi
+    pub stake_pool_withdraw_authority: UncheckedAccount<'info>,
+    /// CHECK: checked in cpi
+    #[account(mut)]
+    pub reserve_stake: UncheckedAccount<'info>,
+    /// CHECK: checked in cpi
+    #[account(mut)]
+    pub manager_fee_account: UncheckedAccount<'info>,
+    #[account(mut, address = lst_config.pool_mint)]
+    pub pool_mint: Box<Account<'info, Mint>>,
+    #[account(
+        init_if_needed,
+        payer = initializer,
+        associated_token::mint = pool_mint,
+        associated_token::authority = wallet,
+    )]
+    pub wallet_pool_ta: Box<Account<'info, TokenAccount>>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[inline(always)]
+pub fn handler(ctx: Context<StakeWallet>, amount: u64) -> ProgramResult {
+    msg!("Stake wallet balance: {}", ctx.accounts.wallet.key());
+
+    // the stake pool only accepts deposits from system accounts, so the owner deposits on behalf of the wallet
+    lamport_transfer(
+        ctx.accounts.wallet.to_account_info(),
+        ctx.accounts.initializer.to_account_info(),
+        amount,
+    )?;
+
+    let pool_tokens_before = ctx.accounts.wallet_pool_ta.amount;
+    deposit_sol(
+        ctx.accounts.stake_pool_program.to_account_info(),
+        ctx.accounts.stake_pool.to_account_info(),
+        ctx.accounts.stake_pool_withdraw_authority.to_account_info(),
+        ctx.accounts.reserve_stake.to_account_info(),
+        ctx.accounts.initializer.to_account_info(),
+        ctx.accounts.wallet_pool_ta.to_account_info(),
+        ctx.accounts.manager_fee_account.to_account_info(),
+        ctx.accounts.pool_mint.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        amount,
+    )?;
+    ctx.accounts.wallet_pool_ta.reload()?;
+    let pool_tokens = ctx
+        .accounts
+        .wallet_pool_ta
+        .amount
+        .saturating_sub(pool_tokens_before);
+
+    Wallet::record_stake(&mut ctx.accounts.wallet.load_mut()?, amount, pool_tokens)?;
+    LstConfig::record_stake(&mut ctx.accounts.lst_config, pool_tokens)?;
+
+    emit_cpi!(Wallet::get_edit_event(
+        &ctx.accounts.wallet.load()?,
+        ctx.accounts.wallet.key(),
+        WalletEditType::Stake,
+    ));
+    Wallet::check_balance(
+        &ctx.accounts.wallet.load()?,
+        &ctx.accounts.wallet.to_account_info(),
+    )?;
+    Ok(())
+}
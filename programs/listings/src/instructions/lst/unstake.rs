@@ -0,0 +1,113 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{entrypoint::ProgramResult, stake, sysvar},
+};
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use bridgesplit_program_utils::anchor_lang;
+use vault::utils::get_bump_in_seed_form;
+
+use crate::{errors::ListingsError, stake_pool::withdraw_sol, state::*};
+
+#[derive(Accounts)]
+#[instruction(pool_tokens: u64)]
+#[event_cpi]
+pub struct UnstakeWallet<'info> {
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+    #[account(
+        mut,
+        constraint = wallet.load()?.version == WALLET_VERSION @ ListingsError::UnsupportedAccountVersion,
+        constraint = pool_tokens > 0 && pool_tokens <= wallet.load()?.staked_balance,
+        seeds = [WALLET_SEED.as_ref(),
+        initializer.key().as_ref()],
+        bump,
+    )]
+    pub wallet: AccountLoader<'info, Wallet>,
+    #[account(
+        mut,
+        seeds = [LST_CONFIG_SEED.as_ref()],
+        bump,
+    )]
+    pub lst_config: Box<Account<'info, LstConfig>>,
+    /// CHECK: address checked against the config
+    #[account(address = lst_config.stake_pool_program)]
+    pub stake_pool_program: UncheckedAccount<'info>,
+    /// CHECK: address checked against the config
+    #[account(mut, address = lst_config.stake_pool)]
+    pub stake_pool: UncheckedAccount<'info>,
+    /// CHECK: checked in cpi
+    pub stake_pool_withdraw_authority: UncheckedAccount<'info>,
+    /// CHECK: checked in cpi
+    #[account(mut)]
+    pub reserve_stake: UncheckedAccount<'info>,
+    /// CHECK: checked in cpi
+    #[account(mut)]
+    pub manager_fee_account: UncheckedAccount<'info>,
+    #[account(mut, address = lst_config.pool_mint)]
+    pub pool_mint: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = pool_mint,
+        associated_token::authority = wallet,
+    )]
+    pub wallet_pool_ta: Box<Account<'info, TokenAccount>>,
+    /// CHECK: checked by constraint and in cpi
+    #[account(address = sysvar::stake_history::ID)]
+    pub stake_history: UncheckedAccount<'info>,
+    /// CHECK: checked by constraint and in cpi
+    #[account(address = stake::program::ID)]
+    pub stake_program: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+#[inline(always)]
+pub fn handler(ctx: Context<UnstakeWallet>, pool_tokens: u64) -> ProgramResult {
+    msg!("Unstake wallet balance: {}", ctx.accounts.wallet.key());
+
+    let bump = &get_bump_in_seed_form(ctx.bumps.get("wallet").unwrap());
+    let signer_seeds = &[&[
+        WALLET_SEED.as_ref(),
+        ctx.accounts.initializer.key.as_ref(),
+        bump,
+    ][..]];
+
+    let lamports_before = ctx.accounts.wallet.to_account_info().lamports();
+    withdraw_sol(
+        ctx.accounts.stake_pool_program.to_account_info(),
+        ctx.accounts.stake_pool.to_account_info(),
+        ctx.accounts.stake_pool_withdraw_authority.to_account_info(),
+        ctx.accounts.wallet.to_account_info(),
+        ctx.accounts.wallet_pool_ta.to_account_info(),
+        ctx.accounts.reserve_stake.to_account_info(),
+        ctx.accounts.wallet.to_account_info(),
+        ctx.accounts.manager_fee_account.to_account_info(),
+        ctx.accounts.pool_mint.to_account_info(),
+        ctx.accounts.clock.to_account_info(),
+        ctx.accounts.stake_history.to_account_info(),
+        ctx.accounts.stake_program.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        signer_seeds,
+        pool_tokens,
+    )?;
+    let lamports = ctx
+        .accounts
+        .wallet
+        .to_account_info()
+        .lamports()
+        .saturating_sub(lamports_before);
+
+    Wallet::record_unstake(&mut ctx.accounts.wallet.load_mut()?, pool_tokens, lamports)?;
+    LstConfig::record_unstake(&mut ctx.accounts.lst_config, pool_tokens)?;
+
+    emit_cpi!(Wallet::get_edit_event(
+        &ctx.accounts.wallet.load()?,
+        ctx.accounts.wallet.key(),
+        WalletEditType::Unstake,
+    ));
+    Wallet::check_balance(
+        &ctx.accounts.wallet.load()?,
+        &ctx.accounts.wallet.to_account_info(),
+    )?;
+    Ok(())
+}
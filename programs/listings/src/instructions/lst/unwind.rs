@@ -0,0 +1,212 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{entrypoint::ProgramResult, stake, sysvar},
+};
+use anchor_spl::{
+    associated_token::get_associated_token_address,
+    token::{Mint, Token, TokenAccount},
+};
+use bridgesplit_program_utils::anchor_lang;
+use vault::utils::get_bump_in_seed_form;
+
+use crate::{
+    errors::ListingsError,
+    stake_pool::{get_pool_tokens_for_lamports, withdraw_sol},
+    state::*,
+};
+
+/// unstake just enough of the wallet to fill the order once
+/// permissionless, sent ahead of fills that do not take the stake pool accounts themselves
+#[derive(Accounts)]
+#[instruction()]
+#[event_cpi]
+pub struct UnwindWallet<'info> {
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+    #[account(
+        mut,
+        constraint = wallet.load()?.version == WALLET_VERSION @ ListingsError::UnsupportedAccountVersion,
+        seeds = [WALLET_SEED.as_ref(),
+        order.load()?.owner.as_ref()],
+        bump,
+    )]
+    pub wallet: AccountLoader<'info, Wallet>,
+    #[account(
+        constraint = order.load()?.version == ORDER_VERSION @ ListingsError::UnsupportedAccountVersion,
+        constraint = Order::is_active(order.load()?.state),
+        constraint = order.load()?.side == u8::from(OrderSide::Buy),
+        constraint = order.load()?.wallet == wallet.key(),
+    )]
+    pub order: AccountLoader<'info, Order>,
+    #[account(
+        mut,
+        seeds = [LST_CONFIG_SEED.as_ref()],
+        bump,
+    )]
+    pub lst_config: Box<Account<'info, LstConfig>>,
+    /// CHECK: address checked against the config
+    #[account(address = lst_config.stake_pool_program)]
+    pub stake_pool_program: UncheckedAccount<'info>,
+    /// CHECK: address checked against the config
+    #[account(mut, address = lst_config.stake_pool)]
+    pub stake_pool: UncheckedAccount<'info>,
+    /// CHECK: checked in cpi
+    pub stake_pool_withdraw_authority: UncheckedAccount<'info>,
+    /// CHECK: checked in cpi
+    #[account(mut)]
+    pub reserve_stake: UncheckedAccount<'info>,
+    /// CHECK: checked in cpi
+    #[account(mut)]
+    pub manager_fee_account: UncheckedAccount<'info>,
+    #[account(mut, address = lst_config.pool_mint)]
+    pub pool_mint: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = pool_mint,
+        associated_token::authority = wallet,
+    )]
+    pub wallet_pool_ta: Box<Account<'info, TokenAccount>>,
+    /// CHECK: checked by constraint and in cpi
+    #[account(address = sysvar::stake_history::ID)]
+    pub stake_history: UncheckedAccount<'info>,
+    /// CHECK: checked by constraint and in cpi
+    #[account(address = stake::program::ID)]
+    pub stake_program: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+/// stake pool accounts of an instruction unstaking on behalf of a wallet
+pub struct UnwindAccounts<'a, 'info> {
+    pub lst_config: &'a mut Account<'info, LstConfig>,
+    pub stake_pool_program: AccountInfo<'info>,
+    pub stake_pool: AccountInfo<'info>,
+    pub stake_pool_withdraw_authority: AccountInfo<'info>,
+    pub reserve_stake: AccountInfo<'info>,
+    pub manager_fee_account: AccountInfo<'info>,
+    pub pool_mint: AccountInfo<'info>,
+    pub wallet_pool_ta: AccountInfo<'info>,
+    pub stake_history: AccountInfo<'info>,
+    pub stake_program: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub clock: AccountInfo<'info>,
+}
+
+impl<'a, 'info> UnwindAccounts<'a, 'info> {
+    /// the accounts are optional in fills, so they are checked here rather than by constraints
+    /// the withdrawal cpi checks the rest against the stake pool
+    fn check(&self, wallet: Pubkey) -> Result<()> {
+        require!(
+            self.stake_pool_program.key() == self.lst_config.stake_pool_program
+                && self.stake_pool.key() == self.lst_config.stake_pool
+                && self.pool_mint.key() == self.lst_config.pool_mint
+                && self.wallet_pool_ta.key()
+                    == get_associated_token_address(&wallet, &self.lst_config.pool_mint)
+                && self.stake_history.key() == sysvar::stake_history::ID
+                && self.stake_program.key() == stake::program::ID
+                && self.token_program.key() == Token::id(),
+            ListingsError::InvalidStakePool
+        );
+        Ok(())
+    }
+}
+
+/// optional stake pool account of a fill, required once the wallet has to be unwound
+pub fn required<'info>(account: &Option<UncheckedAccount<'info>>) -> Result<AccountInfo<'info>> {
+    account
+        .as_ref()
+        .map(|account| account.to_account_info())
+        .ok_or_else(|| ListingsError::MissingStakePoolAccounts.into())
+}
+
+/// unstake just enough of the wallet for its liquid balance to cover `amount`
+/// the withdrawal fee of the stake pool is part of the shortfall, returns the lamports received
+pub fn unwind<'info>(
+    wallet: &AccountLoader<'info, Wallet>,
+    accounts: UnwindAccounts<'_, 'info>,
+    signer_seeds: &[&[&[u8]]],
+    amount: u64,
+) -> Result<u64> {
+    let shortfall = amount.saturating_sub(wallet.load()?.balance);
+    if shortfall == 0 {
+        return Ok(0);
+    }
+    accounts.check(wallet.key())?;
+    let pool_tokens = get_pool_tokens_for_lamports(&accounts.stake_pool, shortfall)?
+        .min(wallet.load()?.staked_balance);
+
+    let lamports_before = wallet.to_account_info().lamports();
+    withdraw_sol(
+        accounts.stake_pool_program,
+        accounts.stake_pool,
+        accounts.stake_pool_withdraw_authority,
+        wallet.to_account_info(),
+        accounts.wallet_pool_ta,
+        accounts.reserve_stake,
+        wallet.to_account_info(),
+        accounts.manager_fee_account,
+        accounts.pool_mint,
+        accounts.clock,
+        accounts.stake_history,
+        accounts.stake_program,
+        accounts.token_program,
+        signer_seeds,
+        pool_tokens,
+    )?;
+    let lamports = wallet
+        .to_account_info()
+        .lamports()
+        .saturating_sub(lamports_before);
+
+    Wallet::record_unstake(&mut wallet.load_mut()?, pool_tokens, lamports)?;
+    LstConfig::record_unstake(accounts.lst_config, pool_tokens)?;
+    Ok(lamports)
+}
+
+#[inline(always)]
+pub fn handler(ctx: Context<UnwindWallet>) -> ProgramResult {
+    msg!("Unwind wallet: {}", ctx.accounts.wallet.key());
+
+    let price = ctx.accounts.order.load()?.price;
+    if ctx.accounts.wallet.load()?.balance >= price {
+        return Err(error!(ListingsError::NothingToUnwind).into());
+    }
+
+    let bump = &get_bump_in_seed_form(ctx.bumps.get("wallet").unwrap());
+    let owner = ctx.accounts.order.load()?.owner;
+    let signer_seeds = &[&[WALLET_SEED.as_ref(), owner.as_ref(), bump][..]];
+
+    unwind(
+        &ctx.accounts.wallet,
+        UnwindAccounts {
+            lst_config: &mut ctx.accounts.lst_config,
+            stake_pool_program: ctx.accounts.stake_pool_program.to_account_info(),
+            stake_pool: ctx.accounts.stake_pool.to_account_info(),
+            stake_pool_withdraw_authority: ctx
+                .accounts
+                .stake_pool_withdraw_authority
+                .to_account_info(),
+            reserve_stake: ctx.accounts.reserve_stake.to_account_info(),
+            manager_fee_account: ctx.accounts.manager_fee_account.to_account_info(),
+            pool_mint: ctx.accounts.pool_mint.to_account_info(),
+            wallet_pool_ta: ctx.accounts.wallet_pool_ta.to_account_info(),
+            stake_history: ctx.accounts.stake_history.to_account_info(),
+            stake_program: ctx.accounts.stake_program.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            clock: ctx.accounts.clock.to_account_info(),
+        },
+        signer_seeds,
+        price,
+    )?;
+
+    emit_cpi!(Wallet::get_edit_event(
+        &ctx.accounts.wallet.load()?,
+        ctx.accounts.wallet.key(),
+        WalletEditType::Unwind,
+    ));
+    Wallet::check_balance(
+        &ctx.accounts.wallet.load()?,
+        &ctx.accounts.wallet.to_account_info(),
+    )?;
+    Ok(())
+}
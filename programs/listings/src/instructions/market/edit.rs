@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+use bridgesplit_program_utils::anchor_lang;
+
+use crate::state::*;
+
+#[derive(Accounts)]
+#[instruction()]
+#[event_cpi]
+pub struct EditMarket<'info> {
+    pub initializer: Signer<'info>,
+    #[account(
+        mut,
+        constraint = market.initializer == initializer.key(),
+        seeds = [MARKET_SEED.as_ref(),
+        market.pool_mint.as_ref()],
+        bump,
+    )]
+    pub market: Box<Account<'info, Market>>,
+}
+
+/// set how new listings of the market hold their nft, existing listings keep their mode
+#[inline(always)]
+pub fn handler(ctx: Context<EditMarket>, listing_mode: u8) -> Result<()> {
+    msg!("Edit market: {}", ctx.accounts.market.key());
+    Market::set_listing_mode(&mut ctx.accounts.market, listing_mode)?;
+
+    emit_cpi!(Market::get_edit_event(
+        &mut ctx.accounts.market.clone(),
+        ctx.accounts.market.key(),
+        MarketEditType::Edit,
+    ));
+    Ok(())
+}
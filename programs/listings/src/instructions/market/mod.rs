@@ -1,3 +1,7 @@
+pub mod edit;
 pub mod init;
+pub mod withdrawal_delay;
 
+pub use edit::*;
 pub use init::*;
+pub use withdrawal_delay::*;
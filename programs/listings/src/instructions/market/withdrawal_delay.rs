@@ -0,0 +1,21 @@
+use anchor_lang::prelude::*;
+use bridgesplit_program_utils::anchor_lang;
+
+use crate::state::*;
+
+use super::EditMarket;
+
+/// set the minimum withdrawal delay of wallets placing or editing bids on the market
+/// bids stay fillable while the delay runs, so sellers can rely on the funds of the bid
+#[inline(always)]
+pub fn handler(ctx: Context<EditMarket>, delay: i64) -> Result<()> {
+    msg!("Set market withdrawal delay: {}", ctx.accounts.market.key());
+    Market::set_min_withdrawal_delay(&mut ctx.accounts.market, delay)?;
+
+    emit_cpi!(Market::get_edit_event(
+        &mut ctx.accounts.market.clone(),
+        ctx.accounts.market.key(),
+        MarketEditType::Edit,
+    ));
+    Ok(())
+}
@@ -0,0 +1,247 @@
+use anchor_lang::prelude::*;
+use bridgesplit_program_utils::anchor_lang;
+use vault::{
+    state::{Appraisal, APPRAISAL_SEED},
+    utils::lamport_transfer,
+};
+
+use crate::{
+    errors::ListingsError,
+    state::*,
+    utils::{
+        get_fee_amount, parse_core_remaining_accounts, pay_core_royalties, price_trailing_bid,
+        sync_order_book, transfer_core_asset,
+    },
+};
+
+#[derive(Accounts)]
+#[instruction()]
+#[event_cpi]
+pub struct CoreFillBuyOrder<'info> {
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+    #[account(
+        mut,
+        constraint = order.load()?.owner == buyer.key(),
+        // bid pools only redeem token program nfts
+        constraint = !BidPool::is_bid_pool(&buyer),
+    )]
+    /// CHECK: constraint check
+    pub buyer: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        constraint = wallet.load()?.version == WALLET_VERSION @ ListingsError::UnsupportedAccountVersion,
+        seeds = [WALLET_SEED.as_ref(),
+        order.load()?.owner.as_ref()],
+        bump,
+    )]
+    pub wallet: AccountLoader<'info, Wallet>,
+    #[account(
+        constraint = Market::is_active(market.state),
+        seeds = [MARKET_SEED.as_ref(),
+        market.pool_mint.as_ref()],
+        bump,
+    )]
+    pub market: Box<Account<'info, Market>>,
+    /// order book of the market, required if the market has one
+    #[account(
+        mut,
+        constraint = order_book.load()?.version == ORDER_BOOK_VERSION @ ListingsError::UnsupportedAccountVersion,
+        seeds = [ORDER_BOOK_SEED.as_ref(),
+        market.key().as_ref()],
+        bump,
+    )]
+    pub order_book: Option<AccountLoader<'info, OrderBook>>,
+    #[account(
+        mut,
+        constraint = order.load()?.version == ORDER_VERSION @ ListingsError::UnsupportedAccountVersion,
+        constraint = Order::is_fillable(order.load()?.state),
+        constraint = order.load()?.market == market.key(),
+        constraint = order.load()?.nft_mint == Pubkey::default() || order.load()?.nft_mint == asset.key(),
+        seeds = [ORDER_SEED.as_ref(),
+        order.load()?.nonce.as_ref(),
+        order.load()?.market.as_ref(),
+        order.load()?.owner.as_ref()],
+        bump,
+    )]
+    pub order: AccountLoader<'info, Order>,
+    #[account(
+        seeds = [APPRAISAL_SEED, market.pool_mint.as_ref(), asset.key().as_ref()],
+        bump,
+        seeds::program = vault::ID,
+    )]
+    pub appraisal: Box<Account<'info, Appraisal>>,
+    /// CHECK: checked in cpi
+    #[account(mut)]
+    pub asset: UncheckedAccount<'info>,
+    /// CHECK: checked in cpi
+    #[account(mut)]
+    pub collection: Option<UncheckedAccount<'info>>,
+    /// CHECK: constraint
+    #[account(
+        mut,
+        constraint = treasury.key().to_string() == PROTOCOL_TREASURY
+    )]
+    pub treasury: AccountInfo<'info>,
+    /// CHECK: checked by address
+    #[account(address = mpl_core::ID)]
+    pub mpl_core_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+//remaining accounts
+// 0 ovol nft ta or default
+// 1 ovol nft metadata or default
+// 2.. creators of the royalties plugin. Will error if a creator with a share is not present
+
+/// seller is initializer and is transferring a core asset to buyer who is the owner of the order account
+/// buyer is the owner of the order account and is transferring sol to seller via bidding wallet
+/// fees and royalties come out of the price
+#[inline(always)]
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, CoreFillBuyOrder<'info>>) -> Result<()> {
+    let parsed_accounts = parse_core_remaining_accounts(
+        ctx.remaining_accounts.to_vec(),
+        ctx.accounts.initializer.key(),
+        ctx.accounts.order.load()?.is_fees_on(),
+    );
+    let collection = ctx
+        .accounts
+        .collection
+        .as_ref()
+        .map(|collection| collection.to_account_info());
+
+    price_trailing_bid(
+        &ctx.accounts.order_book,
+        &mut ctx.accounts.order.load_mut()?,
+    )?;
+
+    // edit wallet account to decrease balance
+    msg!("Edit wallet balance: {}", ctx.accounts.wallet.key());
+    Wallet::edit_balance(
+        &mut ctx.accounts.wallet.load_mut()?,
+        false,
+        ctx.accounts.order.load()?.price,
+    )?;
+
+    transfer_core_asset(
+        ctx.accounts.initializer.to_account_info(),
+        ctx.accounts.initializer.to_account_info(),
+        ctx.accounts.asset.to_account_info(),
+        collection.clone(),
+        ctx.accounts.buyer.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        ctx.accounts.mpl_core_program.to_account_info(),
+        &[],
+    )?;
+
+    let price = ctx.accounts.order.load()?.price;
+    let fee_amount = if parsed_accounts.fees_on {
+        get_fee_amount(price)
+    } else {
+        0
+    };
+    if fee_amount > 0 {
+        lamport_transfer(
+            ctx.accounts.wallet.to_account_info(),
+            ctx.accounts.treasury.to_account_info(),
+            fee_amount,
+        )?;
+    }
+
+    let royalties = pay_core_royalties(
+        price,
+        &ctx.accounts.asset.to_account_info(),
+        collection.as_ref(),
+        ctx.accounts.wallet.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        parsed_accounts.creator_accounts,
+        true,
+        None,
+    )?;
+    let royalty_total: u64 = royalties.iter().map(|royalty| royalty.amount).sum();
+
+    // transfer the rest of the price from buyer to seller
+    let seller_amount = price
+        .checked_sub(fee_amount)
+        .unwrap()
+        .saturating_sub(royalty_total);
+    lamport_transfer(
+        ctx.accounts.wallet.to_account_info(),
+        ctx.accounts.initializer.to_account_info(),
+        seller_amount,
+    )?;
+
+    emit_cpi!(Order::get_fill_event(
+        &ctx.accounts.order.load()?,
+        ctx.accounts.order.key(),
+        ctx.accounts.market.pool_mint,
+        ctx.accounts.buyer.key(),
+        ctx.accounts.initializer.key(),
+        ctx.accounts.asset.key(),
+        fee_amount,
+        royalties,
+        !parsed_accounts.fees_on,
+        ctx.accounts.clock.unix_timestamp,
+    ));
+
+    // edit order
+    let size = ctx.accounts.order.load()?.size;
+
+    Order::fill(
+        &mut ctx.accounts.order.load_mut()?,
+        price,
+        ctx.accounts.clock.unix_timestamp,
+    )?;
+    Wallet::release_filled_exposure(
+        &mut ctx.accounts.wallet.load_mut()?,
+        &mut ctx.accounts.order.load_mut()?,
+    )?;
+    sync_order_book(
+        &ctx.accounts.order_book,
+        &ctx.accounts.market,
+        ctx.accounts.order.key(),
+        &ctx.accounts.order.load()?,
+        Some(&ctx.accounts.wallet.load()?),
+    )?;
+
+    if size == 1 {
+        // close order account
+        msg!(
+            "Close buy order account: {}: {}",
+            ctx.accounts.order.key(),
+            ctx.accounts.market.pool_mint
+        );
+        ctx.accounts.order.load_mut()?.state = OrderState::Closed.into();
+        Wallet::remove_open_order(&mut ctx.accounts.wallet.load_mut()?, true)?;
+        emit_cpi!(Order::get_edit_event(
+            &ctx.accounts.order.load()?,
+            ctx.accounts.order.key(),
+            ctx.accounts.market.pool_mint,
+            OrderEditType::FillAndClose,
+        ));
+        ctx.accounts
+            .order
+            .close(ctx.accounts.buyer.to_account_info())?;
+    } else {
+        emit_cpi!(Order::get_edit_event(
+            &ctx.accounts.order.load()?,
+            ctx.accounts.order.key(),
+            ctx.accounts.market.pool_mint,
+            OrderEditType::Fill,
+        ));
+        msg!("Filled buy order: {}", ctx.accounts.order.key());
+    }
+
+    emit_cpi!(Wallet::get_edit_event(
+        &ctx.accounts.wallet.load()?,
+        ctx.accounts.wallet.key(),
+        WalletEditType::Edit,
+    ));
+
+    Wallet::check_balance(
+        &ctx.accounts.wallet.load()?,
+        &ctx.accounts.wallet.to_account_info(),
+    )?;
+    Ok(())
+}
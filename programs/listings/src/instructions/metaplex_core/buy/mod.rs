@@ -0,0 +1,3 @@
+pub mod fill;
+
+pub use fill::*;
@@ -0,0 +1,7 @@
+//! listings and fills of metaplex core assets
+//! listed assets stay with the seller, frozen by the freeze delegate plugin of the seller's wallet
+pub mod buy;
+pub mod sell;
+
+pub use buy::*;
+pub use sell::*;
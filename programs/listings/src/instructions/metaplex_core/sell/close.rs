@@ -0,0 +1,121 @@
+use anchor_lang::prelude::*;
+use bridgesplit_program_utils::anchor_lang;
+use vault::utils::get_bump_in_seed_form;
+
+use crate::{
+    errors::ListingsError,
+    state::*,
+    utils::{remove_core_delegates, sync_order_book, thaw_core_asset},
+};
+
+#[derive(Accounts)]
+#[instruction()]
+#[event_cpi]
+pub struct CoreCloseSellOrder<'info> {
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+    #[account(
+        mut,
+        constraint = order.load()?.version == ORDER_VERSION @ ListingsError::UnsupportedAccountVersion,
+        constraint = order.load()?.owner == initializer.key(),
+        constraint = order.load()?.market == market.key(),
+        constraint = order.load()?.nft_mint == asset.key(),
+        constraint = Order::is_active(order.load()?.state),
+        seeds = [ORDER_SEED.as_ref(),
+        order.load()?.nonce.as_ref(),
+        order.load()?.market.as_ref(),
+        initializer.key().as_ref()],
+        bump,
+        close = initializer,
+    )]
+    pub order: AccountLoader<'info, Order>,
+    #[account(
+        constraint = Market::is_active(market.state),
+        seeds = [MARKET_SEED.as_ref(),
+        market.pool_mint.as_ref()],
+        bump,
+    )]
+    pub market: Box<Account<'info, Market>>,
+    /// order book of the market, required if the market has one
+    #[account(
+        mut,
+        constraint = order_book.load()?.version == ORDER_BOOK_VERSION @ ListingsError::UnsupportedAccountVersion,
+        seeds = [ORDER_BOOK_SEED.as_ref(),
+        market.key().as_ref()],
+        bump,
+    )]
+    pub order_book: Option<AccountLoader<'info, OrderBook>>,
+    #[account(
+        mut,
+        constraint = wallet.load()?.version == WALLET_VERSION @ ListingsError::UnsupportedAccountVersion,
+        seeds = [WALLET_SEED.as_ref(),
+        order.load()?.owner.as_ref()],
+        bump,
+    )]
+    pub wallet: AccountLoader<'info, Wallet>,
+    /// CHECK: checked in cpi
+    #[account(mut)]
+    pub asset: UncheckedAccount<'info>,
+    /// CHECK: checked in cpi
+    #[account(mut)]
+    pub collection: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked by address
+    #[account(address = mpl_core::ID)]
+    pub mpl_core_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+/// unfreeze the core asset, remove the delegate plugins of the wallet and close the listing
+#[inline(always)]
+pub fn handler(ctx: Context<CoreCloseSellOrder>) -> Result<()> {
+    msg!("Close sell order account: {}", ctx.accounts.order.key());
+
+    let bump = &get_bump_in_seed_form(ctx.bumps.get("wallet").unwrap());
+    let owner = ctx.accounts.order.load()?.owner;
+    let signer_seeds = &[&[WALLET_SEED.as_ref(), owner.as_ref(), bump][..]];
+    let collection = ctx
+        .accounts
+        .collection
+        .as_ref()
+        .map(|collection| collection.to_account_info());
+
+    thaw_core_asset(
+        ctx.accounts.wallet.to_account_info(),
+        ctx.accounts.initializer.to_account_info(),
+        ctx.accounts.asset.to_account_info(),
+        collection.clone(),
+        ctx.accounts.system_program.to_account_info(),
+        ctx.accounts.mpl_core_program.to_account_info(),
+        signer_seeds,
+    )?;
+    remove_core_delegates(
+        ctx.accounts.initializer.to_account_info(),
+        ctx.accounts.asset.to_account_info(),
+        collection,
+        ctx.accounts.system_program.to_account_info(),
+        ctx.accounts.mpl_core_program.to_account_info(),
+    )?;
+
+    ctx.accounts.order.load_mut()?.state = OrderState::Closed.into();
+    Wallet::remove_open_order(&mut ctx.accounts.wallet.load_mut()?, false)?;
+
+    emit_cpi!(Order::get_edit_event(
+        &ctx.accounts.order.load()?,
+        ctx.accounts.order.key(),
+        ctx.accounts.market.pool_mint,
+        OrderEditType::Close,
+    ));
+    Wallet::check_balance(
+        &ctx.accounts.wallet.load()?,
+        &ctx.accounts.wallet.to_account_info(),
+    )?;
+    sync_order_book(
+        &ctx.accounts.order_book,
+        &ctx.accounts.market,
+        ctx.accounts.order.key(),
+        &ctx.accounts.order.load()?,
+        None,
+    )?;
+    Ok(())
+}
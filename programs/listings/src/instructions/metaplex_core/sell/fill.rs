@@ -0,0 +1,207 @@
+use anchor_lang::prelude::*;
+use bridgesplit_program_utils::anchor_lang;
+use vault::utils::get_bump_in_seed_form;
+
+use crate::{
+    errors::ListingsError,
+    state::*,
+    utils::{
+        get_fee_amount, parse_core_remaining_accounts, pay_core_royalties, sync_order_book,
+        thaw_core_asset, transfer_core_asset, transfer_sol,
+    },
+};
+
+#[derive(Accounts)]
+#[instruction()]
+#[event_cpi]
+pub struct CoreFillSellOrder<'info> {
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+    #[account(
+        mut,
+        constraint = order.load()?.owner == seller.key(),
+    )]
+    /// CHECK: constraint check
+    pub seller: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        constraint = wallet.load()?.version == WALLET_VERSION @ ListingsError::UnsupportedAccountVersion,
+        seeds = [WALLET_SEED.as_ref(),
+        seller.key().as_ref()],
+        bump,
+    )]
+    pub wallet: AccountLoader<'info, Wallet>,
+    #[account(
+        constraint = Market::is_active(market.state),
+        seeds = [MARKET_SEED.as_ref(),
+        market.pool_mint.as_ref()],
+        bump,
+    )]
+    pub market: Box<Account<'info, Market>>,
+    /// order book of the market, required if the market has one
+    #[account(
+        mut,
+        constraint = order_book.load()?.version == ORDER_BOOK_VERSION @ ListingsError::UnsupportedAccountVersion,
+        seeds = [ORDER_BOOK_SEED.as_ref(),
+        market.key().as_ref()],
+        bump,
+    )]
+    pub order_book: Option<AccountLoader<'info, OrderBook>>,
+    #[account(
+        mut,
+        constraint = order.load()?.version == ORDER_VERSION @ ListingsError::UnsupportedAccountVersion,
+        constraint = Order::is_fillable(order.load()?.state),
+        constraint = order.load()?.market == market.key(),
+        constraint = order.load()?.nft_mint == asset.key(),
+        seeds = [ORDER_SEED.as_ref(),
+        order.load()?.nonce.as_ref(),
+        order.load()?.market.as_ref(),
+        order.load()?.owner.as_ref()],
+        bump,
+        close = seller
+    )]
+    pub order: AccountLoader<'info, Order>,
+    /// CHECK: checked in cpi
+    #[account(mut)]
+    pub asset: UncheckedAccount<'info>,
+    /// CHECK: checked in cpi
+    #[account(mut)]
+    pub collection: Option<UncheckedAccount<'info>>,
+    /// CHECK: constraint
+    #[account(
+        mut,
+        constraint = treasury.key().to_string() == PROTOCOL_TREASURY
+    )]
+    pub treasury: AccountInfo<'info>,
+    /// CHECK: checked by address
+    #[account(address = mpl_core::ID)]
+    pub mpl_core_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+//remaining accounts
+// 0 ovol nft ta or default
+// 1 ovol nft metadata or default
+// 2.. creators of the royalties plugin. Will error if a creator with a share is not present
+
+/// Initializer is the buyer and is buying a core asset listed by the seller
+/// Buyer transfers sol to seller account and pays fees and royalties on top of the price
+#[inline(always)]
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, CoreFillSellOrder<'info>>) -> Result<()> {
+    let bump = &get_bump_in_seed_form(ctx.bumps.get("wallet").unwrap());
+    let owner = ctx.accounts.order.load()?.owner;
+    let signer_seeds = &[&[WALLET_SEED.as_ref(), owner.as_ref(), bump][..]];
+
+    let parsed_accounts = parse_core_remaining_accounts(
+        ctx.remaining_accounts.to_vec(),
+        ctx.accounts.initializer.key(),
+        ctx.accounts.order.load()?.is_fees_on(),
+    );
+    let collection = ctx
+        .accounts
+        .collection
+        .as_ref()
+        .map(|collection| collection.to_account_info());
+
+    let fee_amount = if parsed_accounts.fees_on {
+        get_fee_amount(ctx.accounts.order.load()?.price)
+    } else {
+        0
+    };
+
+    if fee_amount > 0 {
+        transfer_sol(
+            ctx.accounts.initializer.to_account_info(),
+            ctx.accounts.treasury.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            None,
+            fee_amount,
+        )?;
+    }
+    // transfer sol from buyer to seller
+    transfer_sol(
+        ctx.accounts.initializer.to_account_info(),
+        ctx.accounts.seller.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        None,
+        ctx.accounts.order.load()?.price,
+    )?;
+
+    let royalties = pay_core_royalties(
+        ctx.accounts.order.load()?.price,
+        &ctx.accounts.asset.to_account_info(),
+        collection.as_ref(),
+        ctx.accounts.initializer.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        parsed_accounts.creator_accounts,
+        false,
+        None,
+    )?;
+
+    // unfreeze the asset first so that a transfer can be made
+    thaw_core_asset(
+        ctx.accounts.wallet.to_account_info(),
+        ctx.accounts.initializer.to_account_info(),
+        ctx.accounts.asset.to_account_info(),
+        collection.clone(),
+        ctx.accounts.system_program.to_account_info(),
+        ctx.accounts.mpl_core_program.to_account_info(),
+        signer_seeds,
+    )?;
+
+    transfer_core_asset(
+        ctx.accounts.wallet.to_account_info(),
+        ctx.accounts.initializer.to_account_info(),
+        ctx.accounts.asset.to_account_info(),
+        collection,
+        ctx.accounts.initializer.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        ctx.accounts.mpl_core_program.to_account_info(),
+        signer_seeds,
+    )?;
+
+    emit_cpi!(Order::get_fill_event(
+        &ctx.accounts.order.load()?,
+        ctx.accounts.order.key(),
+        ctx.accounts.market.pool_mint,
+        ctx.accounts.initializer.key(),
+        ctx.accounts.seller.key(),
+        ctx.accounts.asset.key(),
+        fee_amount,
+        royalties,
+        !parsed_accounts.fees_on,
+        ctx.accounts.clock.unix_timestamp,
+    ));
+
+    let price = ctx.accounts.order.load()?.price;
+    Order::fill(
+        &mut ctx.accounts.order.load_mut()?,
+        price,
+        ctx.accounts.clock.unix_timestamp,
+    )?;
+    sync_order_book(
+        &ctx.accounts.order_book,
+        &ctx.accounts.market,
+        ctx.accounts.order.key(),
+        &ctx.accounts.order.load()?,
+        None,
+    )?;
+
+    // close order account
+    msg!("Close sell order account: {}", ctx.accounts.order.key());
+    ctx.accounts.order.load_mut()?.state = OrderState::Closed.into();
+    Wallet::remove_open_order(&mut ctx.accounts.wallet.load_mut()?, false)?;
+    emit_cpi!(Order::get_edit_event(
+        &ctx.accounts.order.load()?,
+        ctx.accounts.order.key(),
+        ctx.accounts.market.pool_mint,
+        OrderEditType::FillAndClose,
+    ));
+
+    Wallet::check_balance(
+        &ctx.accounts.wallet.load()?,
+        &ctx.accounts.wallet.to_account_info(),
+    )?;
+    Ok(())
+}
@@ -0,0 +1,140 @@
+use anchor_lang::prelude::*;
+use bridgesplit_program_utils::anchor_lang;
+use vault::state::{Appraisal, APPRAISAL_SEED};
+
+use crate::{
+    errors::ListingsError,
+    instructions::InitOrderData,
+    state::*,
+    utils::{freeze_core_asset, parse_core_remaining_accounts, sync_order_book},
+};
+
+#[derive(Accounts)]
+#[instruction(data: InitOrderData)]
+#[event_cpi]
+pub struct CoreInitSellOrder<'info> {
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+    #[account(
+        mut,
+        constraint = wallet.load()?.version == WALLET_VERSION @ ListingsError::UnsupportedAccountVersion,
+        seeds = [WALLET_SEED.as_ref(),
+        initializer.key().as_ref()],
+        bump,
+    )]
+    pub wallet: AccountLoader<'info, Wallet>,
+    #[account(
+        constraint = Market::is_active(market.state),
+        seeds = [MARKET_SEED.as_ref(),
+        market.pool_mint.as_ref()],
+        bump,
+    )]
+    pub market: Box<Account<'info, Market>>,
+    /// order book of the market, required if the market has one
+    #[account(
+        mut,
+        constraint = order_book.load()?.version == ORDER_BOOK_VERSION @ ListingsError::UnsupportedAccountVersion,
+        seeds = [ORDER_BOOK_SEED.as_ref(),
+        market.key().as_ref()],
+        bump,
+    )]
+    pub order_book: Option<AccountLoader<'info, OrderBook>>,
+    #[account(
+        constraint = data.price > 0,
+        init,
+        seeds = [ORDER_SEED.as_ref(),
+        data.nonce.as_ref(),
+        market.key().as_ref(),
+        initializer.key().as_ref()],
+        bump,
+        payer = initializer,
+        space = 8 + std::mem::size_of::<Order>()
+    )]
+    pub order: AccountLoader<'info, Order>,
+    #[account(
+        seeds = [APPRAISAL_SEED, market.pool_mint.as_ref(), asset.key().as_ref()],
+        bump,
+        seeds::program = vault::ID,
+    )]
+    pub appraisal: Box<Account<'info, Appraisal>>,
+    /// CHECK: checked in cpi
+    #[account(mut)]
+    pub asset: UncheckedAccount<'info>,
+    /// CHECK: checked in cpi
+    #[account(mut)]
+    pub collection: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked by address
+    #[account(address = mpl_core::ID)]
+    pub mpl_core_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+//remaining accounts
+// 0 ovol nft ta or default
+// 1 ovol nft metadata or default
+
+/// list a core asset, the asset is frozen in place with the seller's wallet as freeze and transfer delegate
+#[inline(always)]
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, CoreInitSellOrder<'info>>,
+    data: InitOrderData,
+) -> Result<()> {
+    msg!("Initialize a new sell order: {}", ctx.accounts.order.key());
+
+    let parsed_accounts = parse_core_remaining_accounts(
+        ctx.remaining_accounts.to_vec(),
+        ctx.accounts.initializer.key(),
+        true,
+    );
+
+    // create a new order with size 1
+    let mut order = ctx.accounts.order.load_init()?;
+    Order::init(
+        &mut order,
+        ctx.accounts.market.key(),
+        ctx.accounts.initializer.key(),
+        ctx.accounts.wallet.key(),
+        data.nonce,
+        ctx.accounts.asset.key(),
+        ctx.accounts.clock.unix_timestamp,
+        OrderSide::Sell.into(),
+        1, // always 1
+        data.price,
+        OrderState::Ready.into(),
+        parsed_accounts.fees_on,
+    );
+    Wallet::add_open_order(&mut ctx.accounts.wallet.load_mut()?, false)?;
+
+    freeze_core_asset(
+        ctx.accounts.initializer.to_account_info(),
+        ctx.accounts.asset.to_account_info(),
+        ctx.accounts
+            .collection
+            .as_ref()
+            .map(|collection| collection.to_account_info()),
+        ctx.accounts.wallet.key(),
+        ctx.accounts.system_program.to_account_info(),
+        ctx.accounts.mpl_core_program.to_account_info(),
+    )?;
+
+    emit_cpi!(Order::get_edit_event(
+        &order,
+        ctx.accounts.order.key(),
+        ctx.accounts.market.pool_mint,
+        OrderEditType::Init,
+    ));
+
+    Wallet::check_balance(
+        &ctx.accounts.wallet.load()?,
+        &ctx.accounts.wallet.to_account_info(),
+    )?;
+    sync_order_book(
+        &ctx.accounts.order_book,
+        &ctx.accounts.market,
+        ctx.accounts.order.key(),
+        &order,
+        None,
+    )?;
+    Ok(())
+}
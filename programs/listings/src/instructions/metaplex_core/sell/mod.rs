@@ -0,0 +1,7 @@
+pub mod close;
+pub mod fill;
+pub mod init;
+
+pub use close::*;
+pub use fill::*;
+pub use init::*;
@@ -0,0 +1,96 @@
+use anchor_lang::{prelude::*, Discriminator};
+use bridgesplit_program_utils::anchor_lang;
+
+use crate::{errors::ListingsError, state::*};
+
+#[derive(Accounts)]
+#[instruction()]
+pub struct MigrateAccount<'info> {
+    pub initializer: Signer<'info>,
+    /// CHECK: dispatched on the discriminator and loaded as the matching account type
+    #[account(mut, owner = crate::ID)]
+    pub account: UncheckedAccount<'info>,
+}
+
+/// upgrade an order, market or wallet account in place to the current layout version
+/// permissionless since a migration only sets the fields added by newer versions
+/// orders of the borsh layout take their migrated wallet as the first remaining account,
+/// the open order counters of the wallet are rebuilt from them
+#[inline(always)]
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, MigrateAccount<'info>>) -> Result<()> {
+    let info = ctx.accounts.account.to_account_info();
+    msg!("Migrate account: {}", info.key());
+
+    let mut discriminator = [0u8; 8];
+    discriminator.copy_from_slice(
+        info.try_borrow_data()?
+            .get(..8)
+            .ok_or(ErrorCode::AccountDiscriminatorNotFound)?,
+    );
+
+    if discriminator == Order::discriminator() {
+        if migrate_zero_copy::<Order>(&info)? {
+            count_legacy_order(&info, ctx.remaining_accounts)?;
+        }
+        Ok(())
+    } else if discriminator == Market::discriminator() {
+        migrate::<Market>(&info)
+    } else if discriminator == Wallet::discriminator() {
+        migrate_zero_copy::<Wallet>(&info).map(|_| ())
+    } else {
+        Err(ErrorCode::AccountDiscriminatorMismatch.into())
+    }
+}
+
+fn migrate<'info, T>(info: &AccountInfo<'info>) -> Result<()>
+where
+    T: Versioned + AccountSerialize + AccountDeserialize + Owner + Clone,
+{
+    let mut account = Account::<T>::try_from(info)?;
+    let from = account.version();
+    require!(account.migrate()?, ListingsError::NothingToMigrate);
+    msg!("Migrated from version {} to {}", from, T::CURRENT_VERSION);
+    account.exit(&crate::ID)
+}
+
+/// borsh layout versions are rewritten with the zero copy layout before the upgrade
+/// returns true if the account had a borsh layout
+fn migrate_zero_copy<'info, T>(info: &AccountInfo<'info>) -> Result<bool>
+where
+    T: ZeroCopyMigration,
+{
+    let legacy = T::read_legacy(info)?;
+    let loader = AccountLoader::<T>::try_from(info)?;
+    let mut account = loader.load_mut()?;
+    let is_legacy = legacy.is_some();
+    if let Some(legacy) = legacy {
+        *account = legacy;
+    }
+    let from = account.version();
+    require!(account.migrate()?, ListingsError::NothingToMigrate);
+    msg!("Migrated from version {} to {}", from, T::CURRENT_VERSION);
+    Ok(is_legacy)
+}
+
+/// count an open order of the borsh layout in the open order counters of its wallet
+/// the wallet migration resets the counters, so it must be migrated before its orders
+fn count_legacy_order<'info>(
+    info: &AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+) -> Result<()> {
+    let order = *AccountLoader::<Order>::try_from(info)?.load()?;
+    let wallet_info = remaining_accounts
+        .first()
+        .filter(|wallet| wallet.key() == order.wallet && wallet.is_writable)
+        .ok_or(ListingsError::MissingOrderWallet)?;
+    let loader = AccountLoader::<Wallet>::try_from(wallet_info)?;
+    let mut wallet = loader.load_mut()?;
+    require!(
+        wallet.version == WALLET_VERSION,
+        ListingsError::UnsupportedAccountVersion
+    );
+    if Order::is_active(order.state) {
+        wallet.add_open_order(order.side == u8::from(OrderSide::Buy))?;
+    }
+    Ok(())
+}
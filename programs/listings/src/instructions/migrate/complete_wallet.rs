@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+use bridgesplit_program_utils::anchor_lang;
+
+use crate::{errors::ListingsError, state::*};
+
+#[derive(Accounts)]
+#[instruction()]
+#[event_cpi]
+pub struct CompleteWalletMigration<'info> {
+    pub initializer: Signer<'info>,
+    #[account(
+        mut,
+        constraint = wallet.load()?.version == WALLET_VERSION @ ListingsError::UnsupportedAccountVersion,
+        constraint = wallet.load()?.uncounted_legacy_orders != 0 @ ListingsError::NothingToMigrate,
+        seeds = [WALLET_SEED.as_ref(),
+        initializer.key().as_ref()],
+        bump,
+    )]
+    pub wallet: AccountLoader<'info, Wallet>,
+}
+
+/// the owner confirms every borsh layout order of the migrated wallet has been migrated,
+/// from then on the open order counters are complete and the wallet can be closed
+/// only the orders of the owner depend on it, so the owner is trusted with the confirmation
+#[inline(always)]
+pub fn handler(ctx: Context<CompleteWalletMigration>) -> Result<()> {
+    msg!("Complete wallet migration: {}", ctx.accounts.wallet.key());
+
+    let mut wallet = ctx.accounts.wallet.load_mut()?;
+    wallet.uncounted_legacy_orders = 0;
+
+    emit_cpi!(Wallet::get_edit_event(
+        &wallet,
+        ctx.accounts.wallet.key(),
+        WalletEditType::CompleteMigration,
+    ));
+    Ok(())
+}
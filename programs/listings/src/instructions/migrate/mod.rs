@@ -0,0 +1,5 @@
+pub mod account;
+pub mod complete_wallet;
+
+pub use account::*;
+pub use complete_wallet::*;
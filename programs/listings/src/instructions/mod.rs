@@ -1,7 +1,21 @@
+pub mod amm;
+pub mod bid_pool;
 pub mod compressed;
+pub mod lst;
 pub mod market;
+pub mod metaplex_core;
+pub mod migrate;
 pub mod order;
+pub mod order_book;
+pub mod view;
 
+pub use amm::*;
+pub use bid_pool::*;
 pub use compressed::*;
+pub use lst::*;
 pub use market::*;
+pub use metaplex_core::*;
+pub use migrate::*;
 pub use order::*;
+pub use order_book::*;
+pub use view::*;
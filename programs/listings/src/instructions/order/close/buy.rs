@@ -2,7 +2,11 @@ use anchor_lang::Key;
 use anchor_lang::{prelude::*, solana_program::entrypoint::ProgramResult};
 use bridgesplit_program_utils::anchor_lang;
 
-use crate::state::*;
+use crate::{
+    errors::ListingsError,
+    state::*,
+    utils::{close_buy_order, sync_order_book},
+};
 
 #[derive(Accounts)]
 #[instruction()]
@@ -10,38 +14,82 @@ use crate::state::*;
 pub struct CloseBuyOrder<'info> {
     #[account(mut)]
     pub initializer: Signer<'info>,
+    /// CHECK: rent is returned to the owner of the order, or to its wallet for a bid pool
+    #[account(mut, address = order.load()?.owner)]
+    pub owner: UncheckedAccount<'info>,
     #[account(
         mut,
-        constraint = order.owner == initializer.key(),
-        constraint = Order::is_active(order.state),
-        // constraint = order.side == OrderSide::Buy.into() || order.side == OrderSide::CompressedBuy.into(),
+        constraint = wallet.load()?.version == WALLET_VERSION @ ListingsError::UnsupportedAccountVersion,
+        // the owner or its delegate
+        constraint = wallet.load()?.is_bidding_authority(initializer.key()),
+        seeds = [WALLET_SEED.as_ref(),
+        order.load()?.owner.as_ref()],
+        bump,
+    )]
+    pub wallet: AccountLoader<'info, Wallet>,
+    #[account(
+        mut,
+        constraint = order.load()?.version == ORDER_VERSION @ ListingsError::UnsupportedAccountVersion,
+        constraint = Order::is_active(order.load()?.state),
+        constraint = order.load()?.side == u8::from(OrderSide::Buy),
         seeds = [ORDER_SEED.as_ref(),
-        order.nonce.as_ref(),
-        order.market.as_ref(),
-        initializer.key().as_ref()],
+        order.load()?.nonce.as_ref(),
+        order.load()?.market.as_ref(),
+        order.load()?.owner.as_ref()],
         bump,
-        close = initializer,
     )]
-    pub order: Box<Account<'info, Order>>,
+    pub order: AccountLoader<'info, Order>,
     #[account(
-        constraint = market.key() == order.market,
+        constraint = market.key() == order.load()?.market,
         seeds = [MARKET_SEED.as_ref(),
         market.pool_mint.as_ref()],
         bump,
     )]
     pub market: Box<Account<'info, Market>>,
+    /// order book of the market, required if the market has one
+    #[account(
+        mut,
+        constraint = order_book.load()?.version == ORDER_BOOK_VERSION @ ListingsError::UnsupportedAccountVersion,
+        seeds = [ORDER_BOOK_SEED.as_ref(),
+        market.key().as_ref()],
+        bump,
+    )]
+    pub order_book: Option<AccountLoader<'info, OrderBook>>,
 }
 
 #[inline(always)]
 pub fn handler(ctx: Context<CloseBuyOrder>) -> ProgramResult {
     msg!("Close buy order account: {}", ctx.accounts.order.key());
-    ctx.accounts.order.state = OrderState::Closed.into();
+    ctx.accounts.order.load_mut()?.state = OrderState::Closed.into();
+    Wallet::remove_open_order(&mut ctx.accounts.wallet.load_mut()?, true)?;
+    let delegated_value = ctx.accounts.order.load()?.delegated_value;
+    Wallet::release_bid_exposure(
+        &mut ctx.accounts.wallet.load_mut()?,
+        &mut ctx.accounts.order.load_mut()?,
+        delegated_value,
+    )?;
 
     emit_cpi!(Order::get_edit_event(
-        &mut ctx.accounts.order.clone(),
+        &ctx.accounts.order.load()?,
         ctx.accounts.order.key(),
         ctx.accounts.market.pool_mint,
         OrderEditType::Close,
     ));
+    sync_order_book(
+        &ctx.accounts.order_book,
+        &ctx.accounts.market,
+        ctx.accounts.order.key(),
+        &ctx.accounts.order.load()?,
+        Some(&ctx.accounts.wallet.load()?),
+    )?;
+    close_buy_order(
+        &ctx.accounts.order,
+        &ctx.accounts.owner,
+        &ctx.accounts.wallet,
+    )?;
+    Wallet::check_balance(
+        &ctx.accounts.wallet.load()?,
+        &ctx.accounts.wallet.to_account_info(),
+    )?;
     Ok(())
 }
@@ -1,17 +1,24 @@
-use anchor_lang::{prelude::*, solana_program::entrypoint::ProgramResult};
+use anchor_lang::prelude::*;
 use anchor_lang::{solana_program::sysvar, Key};
 use anchor_spl::{
-    associated_token::AssociatedToken,
-    token::{Mint, Token, TokenAccount},
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
+    token_interface::{close_account, CloseAccount, Mint, TokenAccount, TokenInterface},
 };
 use bridgesplit_program_utils::anchor_lang;
-use bridgesplit_program_utils::{pnft::utils::get_is_pnft, state::Metadata, ExtraRevokeParams};
+use bridgesplit_program_utils::{
+    pnft::utils::get_is_pnft, state::Metadata, ExtraRevokeParams, ExtraTransferParams,
+};
 use token_metadata::instruction::RevokeArgs;
 use vault::utils::{get_bump_in_seed_form, MplTokenMetadata};
 
 use crate::{
+    errors::ListingsError,
     state::*,
-    utils::{parse_remaining_accounts, revoke_nft, unfreeze_nft},
+    utils::{
+        close_escrow_nft_ta, get_metaplex_nft, is_token22, parse_remaining_accounts,
+        parse_token22_remaining_accounts, revoke_nft, sync_order_book, transfer_nft_amount,
+        transfer_token22_nft, unfreeze_nft,
+    },
 };
 
 #[derive(Accounts)]
@@ -22,17 +29,19 @@ pub struct CloseSellOrder<'info> {
     pub initializer: Signer<'info>,
     #[account(
         mut,
-        constraint = order.owner == initializer.key(),
-        constraint = order.market == market.key(),
-        constraint = Order::is_active(order.state),
+        constraint = order.load()?.version == ORDER_VERSION @ ListingsError::UnsupportedAccountVersion,
+        constraint = order.load()?.owner == initializer.key(),
+        constraint = order.load()?.market == market.key(),
+        constraint = order.load()?.nft_mint == nft_mint.key(),
+        constraint = Order::is_active(order.load()?.state),
         seeds = [ORDER_SEED.as_ref(),
-        order.nonce.as_ref(),
-        order.market.as_ref(),
+        order.load()?.nonce.as_ref(),
+        order.load()?.market.as_ref(),
         initializer.key().as_ref()],
         bump,
         close = initializer,
     )]
-    pub order: Box<Account<'info, Order>>,
+    pub order: AccountLoader<'info, Order>,
     #[account(
         constraint = Market::is_active(market.state),
         seeds = [MARKET_SEED.as_ref(),
@@ -40,27 +49,50 @@ pub struct CloseSellOrder<'info> {
         bump,
     )]
     pub market: Box<Account<'info, Market>>,
+    /// order book of the market, required if the market has one
     #[account(
         mut,
+        constraint = order_book.load()?.version == ORDER_BOOK_VERSION @ ListingsError::UnsupportedAccountVersion,
+        seeds = [ORDER_BOOK_SEED.as_ref(),
+        market.key().as_ref()],
+        bump,
+    )]
+    pub order_book: Option<AccountLoader<'info, OrderBook>>,
+    #[account(
+        mut,
+        constraint = wallet.load()?.version == WALLET_VERSION @ ListingsError::UnsupportedAccountVersion,
         seeds = [WALLET_SEED.as_ref(),
-        order.owner.as_ref()],
+        order.load()?.owner.as_ref()],
         bump,
     )]
-    pub wallet: Box<Account<'info, Wallet>>,
-    #[account(mut)]
-    pub nft_mint: Box<Account<'info, Mint>>,
+    pub wallet: AccountLoader<'info, Wallet>,
+    #[account(
+        mut,
+        mint::token_program = token_program,
+    )]
+    pub nft_mint: Box<InterfaceAccount<'info, Mint>>,
+    /// token metadata accounts, required for token program nfts, none for token-2022 nfts
     #[account(mut)]
-    pub nft_metadata: Box<Account<'info, Metadata>>,
+    pub nft_metadata: Option<Box<Account<'info, Metadata>>>,
     /// CHECK: constraint check in multiple CPI calls
-    pub nft_edition: UncheckedAccount<'info>,
+    pub nft_edition: Option<UncheckedAccount<'info>>,
     #[account(
-        mut,
+        init_if_needed,
+        payer = initializer,
         associated_token::mint = nft_mint,
         associated_token::authority = initializer,
+        associated_token::token_program = token_program,
+    )]
+    pub nft_ta: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: escrow ata of the bidding wallet, only used if the listing is escrowed
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(&wallet.key(), &nft_mint.key(), &token_program.key()),
     )]
-    pub nft_ta: Box<Account<'info, TokenAccount>>,
+    pub escrow_nft_ta: UncheckedAccount<'info>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    /// token program or token-2022 program of the nft
+    pub token_program: Interface<'info, TokenInterface>,
     /// CHECK: checked by constraint and in cpi
     #[account(address = sysvar::instructions::id())]
     pub sysvar_instructions: UncheckedAccount<'info>,
@@ -69,19 +101,29 @@ pub struct CloseSellOrder<'info> {
 }
 
 //remaining accounts
-// 0 token_record or default,
+// 0 token_record, escrow token record if escrowed, or default,
 // 1 authorization_rules or default,
 // 2 authorization_rules_program or default,
-// 4 delegate record or default
+// 3 delegate record, token record of the seller ata if escrowed, or default
+//
+//remaining accounts of token-2022 nfts
+// 0 ovol nft ta or default, unused
+// 1 ovol nft metadata or default, unused
+// 2.. transfer hook program, validation account and extra accounts if the mint has a transfer hook
 
 #[inline(always)]
-pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, CloseSellOrder<'info>>) -> ProgramResult {
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, CloseSellOrder<'info>>) -> Result<()> {
     msg!("Close sell order account: {}", ctx.accounts.order.key());
+    if is_token22(&ctx.accounts.token_program) {
+        return token22_handler(ctx);
+    }
+    let nft = get_metaplex_nft(&ctx.accounts.nft_metadata, &ctx.accounts.nft_edition)?;
 
+    let order = *ctx.accounts.order.load()?;
     let parsed_remaining_accounts = parse_remaining_accounts(
         ctx.remaining_accounts.to_vec(),
         ctx.accounts.initializer.key(),
-        ctx.accounts.order.fees_on,
+        order.is_fees_on(),
         false,
         None,
     );
@@ -90,24 +132,56 @@ pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, CloseSellOrder<'info>>) ->
 
     let bump = &get_bump_in_seed_form(ctx.bumps.get("wallet").unwrap());
 
-    let signer_seeds = &[&[
-        WALLET_SEED.as_ref(),
-        ctx.accounts.order.owner.as_ref(),
-        bump,
-    ][..]];
+    let signer_seeds = &[&[WALLET_SEED.as_ref(), order.owner.as_ref(), bump][..]];
 
-    let is_pnft = get_is_pnft(&ctx.accounts.nft_metadata);
+    let is_pnft = get_is_pnft(&nft.metadata);
 
-    // unfreeze nft if not pnft
-    if !is_pnft {
+    if order.is_escrowed() {
+        // return the unsold units from the escrow token account to the seller
+        transfer_nft_amount(
+            ctx.accounts.wallet.to_account_info(),
+            ctx.accounts.wallet.to_account_info(),
+            ctx.accounts.initializer.to_account_info(),
+            ctx.accounts.initializer.to_account_info(),
+            ctx.accounts.nft_mint.to_account_info(),
+            nft.metadata.to_account_info(),
+            nft.edition.clone(),
+            ctx.accounts.escrow_nft_ta.to_account_info(),
+            ctx.accounts.nft_ta.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.sysvar_instructions.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.associated_token_program.to_account_info(),
+            ctx.accounts.token_metadata_program.to_account_info(),
+            ExtraTransferParams {
+                dest_token_record: parsed_remaining_accounts.delegate_record,
+                owner_token_record: pnft_params.token_record,
+                authorization_rules: pnft_params.authorization_rules,
+                authorization_rules_program: pnft_params.authorization_rules_program,
+                authorization_data: None,
+            },
+            signer_seeds,
+            order.size,
+        )?;
+        if !is_pnft {
+            close_escrow_nft_ta(
+                ctx.accounts.escrow_nft_ta.to_account_info(),
+                ctx.accounts.initializer.to_account_info(),
+                ctx.accounts.wallet.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                signer_seeds,
+            )?;
+        }
+    } else if !is_pnft {
+        // unfreeze nft if not pnft
         unfreeze_nft(
             ctx.accounts.initializer.to_account_info(),
             ctx.accounts.initializer.to_account_info(),
             ctx.accounts.nft_mint.to_account_info(),
             ctx.accounts.nft_ta.to_account_info(),
             ctx.accounts.wallet.to_account_info(),
-            ctx.accounts.nft_metadata.to_account_info(),
-            ctx.accounts.nft_edition.to_account_info(),
+            nft.metadata.to_account_info(),
+            nft.edition.clone(),
             ctx.accounts.system_program.to_account_info(),
             ctx.accounts.sysvar_instructions.to_account_info(),
             ctx.accounts.token_program.to_account_info(),
@@ -124,7 +198,7 @@ pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, CloseSellOrder<'info>>) ->
             ctx.accounts.nft_mint.to_account_info(),
             ctx.accounts.nft_ta.to_account_info(),
             ctx.accounts.wallet.to_account_info(),
-            ctx.accounts.nft_metadata.to_account_info(),
+            nft.metadata.to_account_info(),
             ctx.accounts.system_program.to_account_info(),
             ctx.accounts.sysvar_instructions.to_account_info(),
             ctx.accounts.token_program.to_account_info(),
@@ -132,7 +206,7 @@ pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, CloseSellOrder<'info>>) ->
             signer_seeds,
             ExtraRevokeParams {
                 delegate_record: parsed_remaining_accounts.delegate_record,
-                master_edition: Some(ctx.accounts.nft_edition.to_account_info()),
+                master_edition: Some(nft.edition.clone()),
                 token_record: pnft_params.token_record,
                 authorization_rules: pnft_params.authorization_rules,
                 authorization_rules_program: pnft_params.authorization_rules_program,
@@ -141,13 +215,80 @@ pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, CloseSellOrder<'info>>) ->
         )?;
     }
 
-    ctx.accounts.order.state = OrderState::Closed.into();
+    ctx.accounts.order.load_mut()?.state = OrderState::Closed.into();
+    Wallet::remove_open_order(&mut ctx.accounts.wallet.load_mut()?, false)?;
+
+    emit_cpi!(Order::get_edit_event(
+        &ctx.accounts.order.load()?,
+        ctx.accounts.order.key(),
+        ctx.accounts.market.pool_mint,
+        OrderEditType::Close,
+    ));
+    Wallet::check_balance(
+        &ctx.accounts.wallet.load()?,
+        &ctx.accounts.wallet.to_account_info(),
+    )?;
+    sync_order_book(
+        &ctx.accounts.order_book,
+        &ctx.accounts.market,
+        ctx.accounts.order.key(),
+        &ctx.accounts.order.load()?,
+        None,
+    )?;
+    Ok(())
+}
+
+/// return the escrowed token-2022 nft to the seller and close the listing
+fn token22_handler<'info>(ctx: Context<'_, '_, '_, 'info, CloseSellOrder<'info>>) -> Result<()> {
+    let bump = &get_bump_in_seed_form(ctx.bumps.get("wallet").unwrap());
+    let owner = ctx.accounts.order.load()?.owner;
+    let signer_seeds = &[&[WALLET_SEED.as_ref(), owner.as_ref(), bump][..]];
+
+    let parsed_accounts = parse_token22_remaining_accounts(
+        ctx.remaining_accounts.to_vec(),
+        ctx.accounts.initializer.key(),
+        ctx.accounts.order.load()?.is_fees_on(),
+    );
+
+    transfer_token22_nft(
+        ctx.accounts.wallet.to_account_info(),
+        ctx.accounts.nft_mint.to_account_info(),
+        ctx.accounts.escrow_nft_ta.to_account_info(),
+        ctx.accounts.nft_ta.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        parsed_accounts.transfer_hook_accounts,
+        signer_seeds,
+    )?;
+
+    close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.escrow_nft_ta.to_account_info(),
+            destination: ctx.accounts.initializer.to_account_info(),
+            authority: ctx.accounts.wallet.to_account_info(),
+        },
+        signer_seeds,
+    ))?;
+
+    ctx.accounts.order.load_mut()?.state = OrderState::Closed.into();
+    Wallet::remove_open_order(&mut ctx.accounts.wallet.load_mut()?, false)?;
 
     emit_cpi!(Order::get_edit_event(
-        &mut ctx.accounts.order.clone(),
+        &ctx.accounts.order.load()?,
         ctx.accounts.order.key(),
         ctx.accounts.market.pool_mint,
         OrderEditType::Close,
     ));
+    Wallet::check_balance(
+        &ctx.accounts.wallet.load()?,
+        &ctx.accounts.wallet.to_account_info(),
+    )?;
+    sync_order_book(
+        &ctx.accounts.order_book,
+        &ctx.accounts.market,
+        ctx.accounts.order.key(),
+        &ctx.accounts.order.load()?,
+        None,
+    )?;
     Ok(())
 }
@@ -0,0 +1,89 @@
+use anchor_lang::prelude::*;
+use bridgesplit_program_utils::anchor_lang;
+
+use crate::{errors::ListingsError, state::*};
+
+#[derive(Accounts)]
+#[instruction()]
+#[event_cpi]
+pub struct ActivateOrder<'info> {
+    pub initializer: Signer<'info>,
+    #[account(
+        constraint = market.key() == order.load()?.market,
+        seeds = [MARKET_SEED.as_ref(),
+        market.pool_mint.as_ref()],
+        bump,
+    )]
+    pub market: Box<Account<'info, Market>>,
+    /// order book of the market, the trigger is checked against its bids
+    #[account(
+        mut,
+        constraint = order_book.load()?.version == ORDER_BOOK_VERSION @ ListingsError::UnsupportedAccountVersion,
+        seeds = [ORDER_BOOK_SEED.as_ref(),
+        market.key().as_ref()],
+        bump,
+    )]
+    pub order_book: AccountLoader<'info, OrderBook>,
+    #[account(
+        mut,
+        constraint = order.load()?.version == ORDER_VERSION @ ListingsError::UnsupportedAccountVersion,
+        constraint = order.load()?.state == u8::from(OrderState::Inactive),
+        seeds = [ORDER_SEED.as_ref(),
+        order.load()?.nonce.as_ref(),
+        order.load()?.market.key().as_ref(),
+        order.load()?.owner.as_ref()],
+        bump,
+    )]
+    pub order: AccountLoader<'info, Order>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+//remaining accounts
+// 0.. (order, wallet) of the bids of the order book from the best, up to the first funded bid
+
+/// permissionless, activates the listing if the best funded bid of the order book is below the trigger price
+/// bids whose wallet doesn't cover their price are skipped, a book without a funded bid doesn't activate it
+/// listings of markets without an order book are only activated by their owner
+#[inline(always)]
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, ActivateOrder<'info>>) -> Result<()> {
+    msg!("Activate sell order: {}", ctx.accounts.order.key());
+
+    let mut order = ctx.accounts.order.load_mut()?;
+    let mut order_book = ctx.accounts.order_book.load_mut()?;
+    let mut accounts = ctx.remaining_accounts.chunks(2);
+    let mut best_bid = None;
+    for entry in order_book.get_bids() {
+        let bid_accounts = accounts.next().ok_or(ListingsError::MissingOrderWallet)?;
+        require!(
+            bid_accounts[0].key() == entry.order,
+            ListingsError::InvalidReferenceOrder
+        );
+        let bid = Order::load_current(&bid_accounts[0])?;
+        let wallet = bid_accounts
+            .get(1)
+            .ok_or(ListingsError::MissingOrderWallet)?;
+        require!(
+            wallet.key() == bid.wallet,
+            ListingsError::MissingOrderWallet
+        );
+        if Wallet::load_current(wallet)?.get_bidding_balance() >= bid.price {
+            best_bid = Some(bid.price);
+            break;
+        }
+    }
+    // a market without a funded bid has not crossed the trigger
+    let best_bid = best_bid.ok_or(ListingsError::TriggerNotMet)?;
+    require!(best_bid < order.trigger_price, ListingsError::TriggerNotMet);
+
+    order.state = order.get_fillable_state();
+    order.last_edit_time = ctx.accounts.clock.unix_timestamp;
+
+    emit_cpi!(Order::get_edit_event(
+        &order,
+        ctx.accounts.order.key(),
+        ctx.accounts.market.pool_mint,
+        OrderEditType::Edit,
+    ));
+    order_book.sync(ctx.accounts.order.key(), &order, 0);
+    Ok(())
+}
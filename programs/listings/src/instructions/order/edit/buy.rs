@@ -1,7 +1,7 @@
 use anchor_lang::{prelude::*, solana_program::entrypoint::ProgramResult};
 use bridgesplit_program_utils::anchor_lang;
 
-use crate::state::*;
+use crate::{errors::ListingsError, state::*, utils::sync_order_book};
 
 use super::EditBuyOrderData;
 
@@ -12,33 +12,48 @@ pub struct EditBuyOrder<'info> {
     #[account(mut)]
     pub initializer: Signer<'info>,
     #[account(
-        constraint = market.key() == order.market,
+        constraint = market.key() == order.load()?.market,
         seeds = [MARKET_SEED.as_ref(),
         market.pool_mint.as_ref()],
         bump,
     )]
     pub market: Box<Account<'info, Market>>,
+    /// order book of the market, required if the market has one
     #[account(
         mut,
-        constraint = order.owner == initializer.key(),
+        constraint = order_book.load()?.version == ORDER_BOOK_VERSION @ ListingsError::UnsupportedAccountVersion,
+        seeds = [ORDER_BOOK_SEED.as_ref(),
+        market.key().as_ref()],
+        bump,
+    )]
+    pub order_book: Option<AccountLoader<'info, OrderBook>>,
+    #[account(
+        mut,
+        constraint = order.load()?.version == ORDER_VERSION @ ListingsError::UnsupportedAccountVersion,
         constraint = data.new_size > 0 && data.new_price > 0,
-        constraint = Order::is_active(order.state),
+        constraint = Order::is_active(order.load()?.state),
+        constraint = order.load()?.side == u8::from(OrderSide::Buy),
         seeds = [ORDER_SEED.as_ref(),
-        order.nonce.as_ref(),
-        order.market.key().as_ref(),
-        initializer.key().as_ref()],
+        order.load()?.nonce.as_ref(),
+        order.load()?.market.key().as_ref(),
+        order.load()?.owner.as_ref()],
         bump,
     )]
-    pub order: Box<Account<'info, Order>>,
+    pub order: AccountLoader<'info, Order>,
     #[account(
         mut,
+        constraint = wallet.load()?.version == WALLET_VERSION @ ListingsError::UnsupportedAccountVersion,
         // make sure bidding wallet has enough balance to place the order
-        constraint = wallet.balance >= data.new_price.checked_mul(data.new_size).unwrap(),
+        constraint = wallet.load()?.get_bidding_balance() >= data.new_price.checked_mul(data.new_size).unwrap(),
+        // the owner or its delegate, capped by its spending cap in the handler
+        constraint = wallet.load()?.is_bidding_authority(initializer.key()),
+        // bids of the market are backed by wallets that cannot withdraw instantly
+        constraint = wallet.load()?.withdrawal_delay >= market.min_withdrawal_delay @ ListingsError::WithdrawalDelayTooShort,
         seeds = [WALLET_SEED.as_ref(),
-        initializer.key().as_ref()],
+        order.load()?.owner.as_ref()],
         bump,
     )]
-    pub wallet: Box<Account<'info, Wallet>>,
+    pub wallet: AccountLoader<'info, Wallet>,
     pub system_program: Program<'info, System>,
     pub clock: Sysvar<'info, Clock>,
 }
@@ -46,20 +61,44 @@ pub struct EditBuyOrder<'info> {
 #[inline(always)]
 pub fn handler(ctx: Context<EditBuyOrder>, data: EditBuyOrderData) -> ProgramResult {
     msg!("Edit buy order: {}", ctx.accounts.order.key());
+    // an explicit price turns a trailing bid back into a fixed one
+    Order::set_trailing(
+        &mut ctx.accounts.order.load_mut()?,
+        0,
+        0,
+        ctx.accounts.clock.unix_timestamp,
+    );
     // edit the order with size
     Order::edit_buy(
-        &mut ctx.accounts.order,
+        &mut ctx.accounts.order.load_mut()?,
         data.new_price,
         data.new_size,
         ctx.accounts.clock.unix_timestamp,
-    );
+    )?;
+    Wallet::set_bid_exposure(
+        &mut ctx.accounts.wallet.load_mut()?,
+        ctx.accounts.initializer.key(),
+        &mut ctx.accounts.order.load_mut()?,
+        data.new_price.checked_mul(data.new_size).unwrap(),
+    )?;
 
     emit_cpi!(Order::get_edit_event(
-        &mut ctx.accounts.order.clone(),
+        &ctx.accounts.order.load()?,
         ctx.accounts.order.key(),
         ctx.accounts.market.pool_mint,
         OrderEditType::Edit,
     ));
 
+    Wallet::check_balance(
+        &ctx.accounts.wallet.load()?,
+        &ctx.accounts.wallet.to_account_info(),
+    )?;
+    sync_order_book(
+        &ctx.accounts.order_book,
+        &ctx.accounts.market,
+        ctx.accounts.order.key(),
+        &ctx.accounts.order.load()?,
+        Some(&ctx.accounts.wallet.load()?),
+    )?;
     Ok(())
 }
@@ -12,8 +12,22 @@ pub struct EditSellOrderData {
     pub new_price: u64,
 }
 
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy)]
+pub struct TrailingPriceData {
+    /// discount to the reference listing in bps
+    pub trailing_bps: u16,
+    /// 0 fixes the price again
+    pub max_price: u64,
+}
+
+pub mod activate;
 pub mod buy;
 pub mod sell;
+pub mod trailing;
+pub mod trigger;
 
+pub use activate::*;
 pub use buy::*;
 pub use sell::*;
+pub use trailing::*;
+pub use trigger::*;
@@ -1,4 +1,4 @@
-use crate::state::*;
+use crate::{errors::ListingsError, state::*, utils::sync_order_book};
 use anchor_lang::{prelude::*, solana_program::entrypoint::ProgramResult};
 use bridgesplit_program_utils::anchor_lang;
 
@@ -14,24 +14,34 @@ pub struct EditSellOrder<'info> {
     pub initializer: Signer<'info>,
     #[account(
         mut,
-        constraint = order.owner == initializer.key(),
+        constraint = order.load()?.version == ORDER_VERSION @ ListingsError::UnsupportedAccountVersion,
+        constraint = order.load()?.owner == initializer.key(),
         constraint = data.new_price > 0,
-        constraint = Order::is_active(order.state),
+        constraint = Order::is_active(order.load()?.state),
         seeds = [ORDER_SEED.as_ref(),
-        order.nonce.as_ref(),
-        order.market.key().as_ref(),
-        order.owner.as_ref()],
+        order.load()?.nonce.as_ref(),
+        order.load()?.market.key().as_ref(),
+        order.load()?.owner.as_ref()],
         bump,
     )]
-    pub order: Box<Account<'info, Order>>,
+    pub order: AccountLoader<'info, Order>,
     #[account(
         constraint = Market::is_active(market.state),
-        constraint = market.key() == order.market,
+        constraint = market.key() == order.load()?.market,
         seeds = [MARKET_SEED.as_ref(),
         market.pool_mint.as_ref()],
         bump,
     )]
     pub market: Box<Account<'info, Market>>,
+    /// order book of the market, required if the market has one
+    #[account(
+        mut,
+        constraint = order_book.load()?.version == ORDER_BOOK_VERSION @ ListingsError::UnsupportedAccountVersion,
+        seeds = [ORDER_BOOK_SEED.as_ref(),
+        market.key().as_ref()],
+        bump,
+    )]
+    pub order_book: Option<AccountLoader<'info, OrderBook>>,
     pub clock: Sysvar<'info, Clock>,
 }
 
@@ -40,16 +50,23 @@ pub fn handler(ctx: Context<EditSellOrder>, data: EditSellOrderData) -> ProgramR
     msg!("Edit sell order: {}", ctx.accounts.order.key());
     // update the sell order account
     Order::edit_sell(
-        &mut ctx.accounts.order,
+        &mut ctx.accounts.order.load_mut()?,
         data.new_price,
         ctx.accounts.clock.unix_timestamp,
     );
 
     emit_cpi!(Order::get_edit_event(
-        &mut ctx.accounts.order.clone(),
+        &ctx.accounts.order.load()?,
         ctx.accounts.order.key(),
         ctx.accounts.market.pool_mint,
         OrderEditType::Edit,
     ));
+    sync_order_book(
+        &ctx.accounts.order_book,
+        &ctx.accounts.market,
+        ctx.accounts.order.key(),
+        &ctx.accounts.order.load()?,
+        None,
+    )?;
     Ok(())
 }
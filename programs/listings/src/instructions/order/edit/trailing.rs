@@ -0,0 +1,110 @@
+use anchor_lang::{prelude::*, solana_program::entrypoint::ProgramResult};
+use bridgesplit_program_utils::anchor_lang;
+
+use crate::{errors::ListingsError, state::*, utils::sync_order_book};
+
+use super::TrailingPriceData;
+
+#[derive(Accounts)]
+#[instruction(data: TrailingPriceData)]
+#[event_cpi]
+pub struct SetTrailingPrice<'info> {
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+    #[account(
+        constraint = market.key() == order.load()?.market,
+        seeds = [MARKET_SEED.as_ref(),
+        market.pool_mint.as_ref()],
+        bump,
+    )]
+    pub market: Box<Account<'info, Market>>,
+    /// order book of the market, required if the market has one
+    #[account(
+        mut,
+        constraint = order_book.load()?.version == ORDER_BOOK_VERSION @ ListingsError::UnsupportedAccountVersion,
+        seeds = [ORDER_BOOK_SEED.as_ref(),
+        market.key().as_ref()],
+        bump,
+    )]
+    pub order_book: Option<AccountLoader<'info, OrderBook>>,
+    #[account(
+        mut,
+        constraint = order.load()?.version == ORDER_VERSION @ ListingsError::UnsupportedAccountVersion,
+        constraint = data.trailing_bps < 10000,
+        constraint = Order::is_active(order.load()?.state),
+        constraint = order.load()?.side == u8::from(OrderSide::Buy),
+        seeds = [ORDER_SEED.as_ref(),
+        order.load()?.nonce.as_ref(),
+        order.load()?.market.key().as_ref(),
+        order.load()?.owner.as_ref()],
+        bump,
+    )]
+    pub order: AccountLoader<'info, Order>,
+    #[account(
+        mut,
+        constraint = wallet.load()?.version == WALLET_VERSION @ ListingsError::UnsupportedAccountVersion,
+        // the wallet has to cover the bid at its max price
+        constraint = wallet.load()?.get_bidding_balance() >= data.max_price.checked_mul(order.load()?.size).unwrap(),
+        // the owner or its delegate, capped by its spending cap in the handler
+        constraint = wallet.load()?.is_bidding_authority(initializer.key()),
+        // bids of the market are backed by wallets that cannot withdraw instantly
+        constraint = wallet.load()?.withdrawal_delay >= market.min_withdrawal_delay @ ListingsError::WithdrawalDelayTooShort,
+        seeds = [WALLET_SEED.as_ref(),
+        order.load()?.owner.as_ref()],
+        bump,
+    )]
+    pub wallet: AccountLoader<'info, Wallet>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+/// a trailing bid is priced against the floor of the order book of its market when it is filled
+/// markets without an order book can't fill trailing bids
+#[inline(always)]
+pub fn handler(ctx: Context<SetTrailingPrice>, data: TrailingPriceData) -> ProgramResult {
+    msg!(
+        "Set trailing price of buy order: {}",
+        ctx.accounts.order.key()
+    );
+
+    Order::set_trailing(
+        &mut ctx.accounts.order.load_mut()?,
+        data.trailing_bps,
+        data.max_price,
+        ctx.accounts.clock.unix_timestamp,
+    );
+    // a trailing bid commits up to its max price, a fixed one its price
+    let value = {
+        let order = ctx.accounts.order.load()?;
+        order
+            .max_price
+            .max(order.price)
+            .checked_mul(order.size)
+            .unwrap()
+    };
+    Wallet::set_bid_exposure(
+        &mut ctx.accounts.wallet.load_mut()?,
+        ctx.accounts.initializer.key(),
+        &mut ctx.accounts.order.load_mut()?,
+        value,
+    )?;
+
+    emit_cpi!(Order::get_edit_event(
+        &ctx.accounts.order.load()?,
+        ctx.accounts.order.key(),
+        ctx.accounts.market.pool_mint,
+        OrderEditType::Edit,
+    ));
+
+    Wallet::check_balance(
+        &ctx.accounts.wallet.load()?,
+        &ctx.accounts.wallet.to_account_info(),
+    )?;
+    sync_order_book(
+        &ctx.accounts.order_book,
+        &ctx.accounts.market,
+        ctx.accounts.order.key(),
+        &ctx.accounts.order.load()?,
+        Some(&ctx.accounts.wallet.load()?),
+    )?;
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use anchor_lang::{prelude::*, solana_program::entrypoint::ProgramResult};
+use bridgesplit_program_utils::anchor_lang;
+
+use crate::{errors::ListingsError, state::*, utils::sync_order_book};
+
+#[derive(Accounts)]
+#[instruction()]
+#[event_cpi]
+pub struct SetSellTrigger<'info> {
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+    #[account(
+        constraint = market.key() == order.load()?.market,
+        seeds = [MARKET_SEED.as_ref(),
+        market.pool_mint.as_ref()],
+        bump,
+    )]
+    pub market: Box<Account<'info, Market>>,
+    /// order book of the market, required if the market has one
+    #[account(
+        mut,
+        constraint = order_book.load()?.version == ORDER_BOOK_VERSION @ ListingsError::UnsupportedAccountVersion,
+        seeds = [ORDER_BOOK_SEED.as_ref(),
+        market.key().as_ref()],
+        bump,
+    )]
+    pub order_book: Option<AccountLoader<'info, OrderBook>>,
+    #[account(
+        mut,
+        constraint = order.load()?.version == ORDER_VERSION @ ListingsError::UnsupportedAccountVersion,
+        constraint = order.load()?.owner == initializer.key(),
+        constraint = order.load()?.side == u8::from(OrderSide::Sell),
+        constraint = Order::is_active(order.load()?.state),
+        seeds = [ORDER_SEED.as_ref(),
+        order.load()?.nonce.as_ref(),
+        order.load()?.market.key().as_ref(),
+        initializer.key().as_ref()],
+        bump,
+    )]
+    pub order: AccountLoader<'info, Order>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+/// the nft stays frozen while the listing is inactive
+#[inline(always)]
+pub fn handler(ctx: Context<SetSellTrigger>, trigger_price: u64) -> ProgramResult {
+    msg!("Set trigger of sell order: {}", ctx.accounts.order.key());
+
+    Order::set_trigger(
+        &mut ctx.accounts.order.load_mut()?,
+        trigger_price,
+        ctx.accounts.clock.unix_timestamp,
+    );
+
+    emit_cpi!(Order::get_edit_event(
+        &ctx.accounts.order.load()?,
+        ctx.accounts.order.key(),
+        ctx.accounts.market.pool_mint,
+        OrderEditType::Edit,
+    ));
+    sync_order_book(
+        &ctx.accounts.order_book,
+        &ctx.accounts.market,
+        ctx.accounts.order.key(),
+        &ctx.accounts.order.load()?,
+        None,
+    )?;
+    Ok(())
+}
@@ -1,15 +1,25 @@
 use anchor_lang::{prelude::*, solana_program::sysvar};
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{Mint, Token, TokenAccount},
+    token::Token,
+    token_interface::{Mint, TokenAccount, TokenInterface},
 };
 use bridgesplit_program_utils::{anchor_lang, pnft::utils::get_is_pnft};
 use bridgesplit_program_utils::{state::Metadata, ExtraTransferParams, MplTokenMetadata};
-use vault::utils::lamport_transfer;
+use vault::{
+    state::{Appraisal, APPRAISAL_SEED},
+    utils::{get_bump_in_seed_form, lamport_transfer},
+};
 
 use crate::{
+    errors::ListingsError,
+    instructions::lst::{required, unwind, UnwindAccounts},
     state::*,
-    utils::{get_fee_amount, parse_remaining_accounts, pay_royalties, transfer_nft},
+    utils::{
+        check_token22_nft, close_buy_order, get_fee_amount, get_metaplex_nft, is_token22,
+        parse_remaining_accounts, parse_token22_remaining_accounts, pay_royalties,
+        price_trailing_bid, sync_order_book, transfer_nft, transfer_token22_nft,
+    },
 };
 
 #[derive(Accounts)]
@@ -20,17 +30,18 @@ pub struct FillBuyOrder<'info> {
     pub initializer: Signer<'info>,
     #[account(
         mut,
-        constraint = order.owner == buyer.key(),
+        constraint = order.load()?.owner == buyer.key(),
     )]
     /// CHECK: constraint check
     pub buyer: UncheckedAccount<'info>,
     #[account(
         mut,
+        constraint = wallet.load()?.version == WALLET_VERSION @ ListingsError::UnsupportedAccountVersion,
         seeds = [WALLET_SEED.as_ref(),
-        order.owner.as_ref()],
+        order.load()?.owner.as_ref()],
         bump,
     )]
-    pub wallet: Box<Account<'info, Wallet>>,
+    pub wallet: AccountLoader<'info, Wallet>,
     #[account(
         constraint = Market::is_active(market.state),
         seeds = [MARKET_SEED.as_ref(),
@@ -38,39 +49,61 @@ pub struct FillBuyOrder<'info> {
         bump,
     )]
     pub market: Box<Account<'info, Market>>,
+    /// order book of the market, required if the market has one
+    #[account(
+        mut,
+        constraint = order_book.load()?.version == ORDER_BOOK_VERSION @ ListingsError::UnsupportedAccountVersion,
+        seeds = [ORDER_BOOK_SEED.as_ref(),
+        market.key().as_ref()],
+        bump,
+    )]
+    pub order_book: Option<AccountLoader<'info, OrderBook>>,
     #[account(
         mut,
-        constraint = Order::is_active(order.state),
-        constraint = order.market == market.key(),
+        constraint = order.load()?.version == ORDER_VERSION @ ListingsError::UnsupportedAccountVersion,
+        constraint = Order::is_fillable(order.load()?.state),
+        constraint = order.load()?.market == market.key(),
         seeds = [ORDER_SEED.as_ref(),
-        order.nonce.as_ref(),
-        order.market.as_ref(),
-        order.owner.as_ref()],
+        order.load()?.nonce.as_ref(),
+        order.load()?.market.as_ref(),
+        order.load()?.owner.as_ref()],
         bump,
     )]
-    pub order: Box<Account<'info, Order>>,
+    pub order: AccountLoader<'info, Order>,
     #[account(
         mut,
-        constraint = order.nft_mint == Pubkey::default() || order.nft_mint == nft_mint.key()
+        mint::token_program = token_program,
+        constraint = order.load()?.nft_mint == Pubkey::default() || order.load()?.nft_mint == nft_mint.key()
     )]
-    pub nft_mint: Box<Account<'info, Mint>>,
+    pub nft_mint: Box<InterfaceAccount<'info, Mint>>,
+    /// token metadata accounts, required for token program nfts, none for token-2022 nfts
     #[account(mut)]
-    pub nft_metadata: Box<Account<'info, Metadata>>,
+    pub nft_metadata: Option<Box<Account<'info, Metadata>>>,
     /// CHECK: constraint check in multiple CPI calls
-    pub nft_edition: UncheckedAccount<'info>,
+    pub nft_edition: Option<UncheckedAccount<'info>>,
+    /// proves the nft belongs to the market, required for token-2022 nfts, which have no metaplex
+    /// collection, and for bids of bid pools
+    #[account(
+        seeds = [APPRAISAL_SEED, market.pool_mint.as_ref(), nft_mint.key().as_ref()],
+        bump,
+        seeds::program = vault::ID,
+    )]
+    pub appraisal: Option<Box<Account<'info, Appraisal>>>,
     #[account(
         mut,
         associated_token::mint = nft_mint,
         associated_token::authority = initializer,
+        associated_token::token_program = token_program,
     )]
-    pub seller_nft_ta: Box<Account<'info, TokenAccount>>,
+    pub seller_nft_ta: Box<InterfaceAccount<'info, TokenAccount>>,
     #[account(
         init_if_needed,
         payer = initializer,
         associated_token::mint = nft_mint,
         associated_token::authority = buyer,
+        associated_token::token_program = token_program,
     )]
-    pub buyer_nft_ta: Box<Account<'info, TokenAccount>>,
+    pub buyer_nft_ta: Box<InterfaceAccount<'info, TokenAccount>>,
     /// CHECK: constraint
     #[account(
         mut,
@@ -78,44 +111,136 @@ pub struct FillBuyOrder<'info> {
     )]
     pub treasury: AccountInfo<'info>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    /// token program or token-2022 program of the nft
+    pub token_program: Interface<'info, TokenInterface>,
     /// CHECK: checked by constraint and in cpi
     #[account(address = sysvar::instructions::id())]
     pub sysvar_instructions: UncheckedAccount<'info>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_metadata_program: Program<'info, MplTokenMetadata>,
     pub clock: Sysvar<'info, Clock>,
+    /// CHECK: cost record of the nft, only created if the buyer is a bid pool
+    #[account(mut)]
+    pub pool_nft: Option<UncheckedAccount<'info>>,
+    // stake pool accounts, only required if the liquid balance of the wallet doesn't cover the bid
+    #[account(
+        mut,
+        seeds = [LST_CONFIG_SEED.as_ref()],
+        bump,
+    )]
+    pub lst_config: Option<Box<Account<'info, LstConfig>>>,
+    /// CHECK: checked against the config when unwinding
+    pub stake_pool_program: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked against the config when unwinding
+    #[account(mut)]
+    pub stake_pool: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked in cpi
+    pub stake_pool_withdraw_authority: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked in cpi
+    #[account(mut)]
+    pub reserve_stake: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked in cpi
+    #[account(mut)]
+    pub manager_fee_account: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked against the config when unwinding
+    #[account(mut)]
+    pub lst_mint: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked to be the lst ata of the wallet when unwinding
+    #[account(mut)]
+    pub wallet_lst_ta: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked when unwinding and in cpi
+    pub stake_history: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked when unwinding and in cpi
+    pub stake_program: Option<UncheckedAccount<'info>>,
+    /// token program of the lst mint, the nft may be a token-2022 nft
+    pub lst_token_program: Option<Program<'info, Token>>,
 }
 
 //remaining accounts
 // 0 token_record or default,
 // 1 authorization_rules or default,
 // 2 authorization_rules_program or default,
+// 3 unused, default
+// 4 buyer token record or default,
+// 5 ovol nft ta or default
+// 6 ovol nft metadata default
+// 7-12 optional creator accounts in order of metadata. Will error if is pnft and correct creator accounts are not present
 //
-// 4 delegate record or default,
-// 5 seller token record or default,
-// 6 ovol nft ta or default
-// 7 ovol nft metadata default
-// 8-13 optional creator accounts in order of metadata. Will error if is pnft and correct creator accounts are not present
+//remaining accounts of token-2022 nfts
+// 0 ovol nft ta or default
+// 1 ovol nft metadata or default
+// 2.. transfer hook program, validation account and extra accounts if the mint has a transfer hook
 
 /// seller is initializer and is transferring the nft to buyer who is the owner of the order account
 /// buyer is the owner of the order account and is transferring sol to seller via bidding wallet
 #[inline(always)]
 pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, FillBuyOrder<'info>>) -> Result<()> {
+    price_trailing_bid(
+        &ctx.accounts.order_book,
+        &mut ctx.accounts.order.load_mut()?,
+    )?;
+    let price = ctx.accounts.order.load()?.price;
+
+    // unstake the part of the bid that the liquid balance doesn't cover
+    if ctx.accounts.wallet.load()?.balance < price {
+        msg!("Unwind wallet: {}", ctx.accounts.wallet.key());
+        let bump = &get_bump_in_seed_form(ctx.bumps.get("wallet").unwrap());
+        let owner = ctx.accounts.order.load()?.owner;
+        let signer_seeds = &[&[WALLET_SEED.as_ref(), owner.as_ref(), bump][..]];
+        unwind(
+            &ctx.accounts.wallet,
+            UnwindAccounts {
+                lst_config: ctx
+                    .accounts
+                    .lst_config
+                    .as_deref_mut()
+                    .ok_or(ListingsError::MissingStakePoolAccounts)?,
+                stake_pool_program: required(&ctx.accounts.stake_pool_program)?,
+                stake_pool: required(&ctx.accounts.stake_pool)?,
+                stake_pool_withdraw_authority: required(
+                    &ctx.accounts.stake_pool_withdraw_authority,
+                )?,
+                reserve_stake: required(&ctx.accounts.reserve_stake)?,
+                manager_fee_account: required(&ctx.accounts.manager_fee_account)?,
+                pool_mint: required(&ctx.accounts.lst_mint)?,
+                wallet_pool_ta: required(&ctx.accounts.wallet_lst_ta)?,
+                stake_history: required(&ctx.accounts.stake_history)?,
+                stake_program: required(&ctx.accounts.stake_program)?,
+                token_program: ctx
+                    .accounts
+                    .lst_token_program
+                    .as_ref()
+                    .ok_or(ListingsError::MissingStakePoolAccounts)?
+                    .to_account_info(),
+                clock: ctx.accounts.clock.to_account_info(),
+            },
+            signer_seeds,
+            price,
+        )?;
+        emit_cpi!(Wallet::get_edit_event(
+            &ctx.accounts.wallet.load()?,
+            ctx.accounts.wallet.key(),
+            WalletEditType::Unwind,
+        ));
+    }
+
+    // edit wallet account to decrease balance
+    msg!("Edit wallet balance: {}", ctx.accounts.wallet.key());
+    Wallet::edit_balance(&mut ctx.accounts.wallet.load_mut()?, false, price)?;
+
+    if is_token22(&ctx.accounts.token_program) {
+        return token22_handler(ctx, price);
+    }
+    let nft = get_metaplex_nft(&ctx.accounts.nft_metadata, &ctx.accounts.nft_edition)?;
     let parsed_accounts = parse_remaining_accounts(
         ctx.remaining_accounts.to_vec(),
         ctx.accounts.initializer.key(),
-        ctx.accounts.order.fees_on,
+        ctx.accounts.order.load()?.is_fees_on(),
         false,
         Some(1),
     );
-
     let pnft_params = parsed_accounts.pnft_params;
 
-    // edit wallet account to decrease balance
-    msg!("Edit wallet balance: {}", ctx.accounts.wallet.key());
-    Wallet::edit_balance(&mut ctx.accounts.wallet, false, ctx.accounts.order.price);
-
     let buyer_token_record =
         if ctx.remaining_accounts.get(4).cloned().unwrap().key() == Pubkey::default() {
             None
@@ -130,8 +255,8 @@ pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, FillBuyOrder<'info>>) -> R
         ctx.accounts.initializer.to_account_info(),
         ctx.accounts.buyer.to_account_info(),
         ctx.accounts.nft_mint.to_account_info(),
-        ctx.accounts.nft_metadata.to_account_info(),
-        ctx.accounts.nft_edition.to_account_info(),
+        nft.metadata.to_account_info(),
+        nft.edition.clone(),
         ctx.accounts.seller_nft_ta.to_account_info(),
         ctx.accounts.buyer_nft_ta.to_account_info(),
         ctx.accounts.system_program.to_account_info(),
@@ -149,14 +274,23 @@ pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, FillBuyOrder<'info>>) -> R
         &[],
     )?;
 
-    if parsed_accounts.fees_on {
-        let fee_amount = get_fee_amount(ctx.accounts.order.price);
+    let fee_amount = if parsed_accounts.fees_on {
+        get_fee_amount(ctx.accounts.order.load()?.price)
+    } else {
+        0
+    };
 
+    if parsed_accounts.fees_on {
         // transfer sol from buyer to seller
         lamport_transfer(
             ctx.accounts.wallet.to_account_info(),
             ctx.accounts.initializer.to_account_info(),
-            ctx.accounts.order.price.checked_sub(fee_amount).unwrap(),
+            ctx.accounts
+                .order
+                .load()?
+                .price
+                .checked_sub(fee_amount)
+                .unwrap(),
         )?;
 
         // pay platform fees
@@ -169,33 +303,211 @@ pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, FillBuyOrder<'info>>) -> R
         lamport_transfer(
             ctx.accounts.wallet.to_account_info(),
             ctx.accounts.initializer.to_account_info(),
-            ctx.accounts.order.price,
+            ctx.accounts.order.load()?.price,
         )?;
     }
 
     // edit order
-    let price = ctx.accounts.order.price;
-    let size = ctx.accounts.order.size;
+    let size = ctx.accounts.order.load()?.size;
 
-    Order::edit_buy(
-        &mut ctx.accounts.order,
+    Order::fill(
+        &mut ctx.accounts.order.load_mut()?,
         price,
-        size - 1,
         ctx.accounts.clock.unix_timestamp,
-    );
+    )?;
+    Wallet::release_filled_exposure(
+        &mut ctx.accounts.wallet.load_mut()?,
+        &mut ctx.accounts.order.load_mut()?,
+    )?;
+    sync_order_book(
+        &ctx.accounts.order_book,
+        &ctx.accounts.market,
+        ctx.accounts.order.key(),
+        &ctx.accounts.order.load()?,
+        Some(&ctx.accounts.wallet.load()?),
+    )?;
 
-    if get_is_pnft(&ctx.accounts.nft_metadata) {
+    let royalties = if get_is_pnft(&nft.metadata) {
         pay_royalties(
-            ctx.accounts.order.price,
-            ctx.accounts.nft_metadata.clone(),
+            ctx.accounts.order.load()?.price,
+            nft.metadata.clone(),
             ctx.accounts.initializer.to_account_info(),
             ctx.accounts.system_program.to_account_info(),
             parsed_accounts.creator_accounts,
             true,
             None,
+        )?
+    } else {
+        Vec::new()
+    };
+
+    // seller pays the royalties out of the proceeds
+    emit_cpi!(Order::get_fill_event(
+        &ctx.accounts.order.load()?,
+        ctx.accounts.order.key(),
+        ctx.accounts.market.pool_mint,
+        ctx.accounts.buyer.key(),
+        ctx.accounts.initializer.key(),
+        ctx.accounts.nft_mint.key(),
+        fee_amount,
+        royalties,
+        !parsed_accounts.fees_on,
+        ctx.accounts.clock.unix_timestamp,
+    ));
+
+    if size == 1 {
+        // close order account
+        msg!(
+            "Close buy order account: {}: {}",
+            ctx.accounts.order.key(),
+            ctx.accounts.market.pool_mint
+        );
+        ctx.accounts.order.load_mut()?.state = OrderState::Closed.into();
+        Wallet::remove_open_order(&mut ctx.accounts.wallet.load_mut()?, true)?;
+        emit_cpi!(Order::get_edit_event(
+            &ctx.accounts.order.load()?,
+            ctx.accounts.order.key(),
+            ctx.accounts.market.pool_mint,
+            OrderEditType::FillAndClose,
+        ));
+        close_buy_order(
+            &ctx.accounts.order,
+            &ctx.accounts.buyer,
+            &ctx.accounts.wallet,
+        )?;
+    } else {
+        emit_cpi!(Order::get_edit_event(
+            &ctx.accounts.order.load()?,
+            ctx.accounts.order.key(),
+            ctx.accounts.market.pool_mint,
+            OrderEditType::Fill,
+        ));
+        msg!("Filled buy order: {}", ctx.accounts.order.key());
+    }
+
+    // nfts bought by a bid pool are held by the pool, valued at the price paid
+    if BidPool::is_bid_pool(&ctx.accounts.buyer) {
+        // the pool spends the sol of its depositors, only on nfts of the market
+        require!(
+            ctx.accounts.appraisal.is_some(),
+            ListingsError::MissingAppraisal
+        );
+        let pool_nft = ctx
+            .accounts
+            .pool_nft
+            .as_ref()
+            .ok_or(ListingsError::InvalidPoolNft)?;
+        PoolNft::record_fill(
+            pool_nft,
+            ctx.accounts.buyer.key(),
+            ctx.accounts.nft_mint.key(),
+            &ctx.accounts.initializer,
+            &ctx.accounts.system_program,
+            price,
+        )?;
+        emit_cpi!(BidPool::record_fill(&ctx.accounts.buyer, price)?);
+    }
+
+    emit_cpi!(Wallet::get_edit_event(
+        &ctx.accounts.wallet.load()?,
+        ctx.accounts.wallet.key(),
+        WalletEditType::Edit,
+    ));
+
+    Wallet::check_balance(
+        &ctx.accounts.wallet.load()?,
+        &ctx.accounts.wallet.to_account_info(),
+    )?;
+    Ok(())
+}
+
+/// transfer a token-2022 nft to the buyer, the wallet balance is already deducted
+fn token22_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, FillBuyOrder<'info>>,
+    price: u64,
+) -> Result<()> {
+    // bid pools only redeem token program nfts
+    require!(
+        !BidPool::is_bid_pool(&ctx.accounts.buyer),
+        ListingsError::InvalidToken22Nft
+    );
+    // token-2022 nfts have no metaplex collection, the appraisal proves they belong to the market
+    require!(
+        ctx.accounts.appraisal.is_some(),
+        ListingsError::InvalidToken22Nft
+    );
+    check_token22_nft(&ctx.accounts.nft_mint.to_account_info())?;
+
+    let parsed_accounts = parse_token22_remaining_accounts(
+        ctx.remaining_accounts.to_vec(),
+        ctx.accounts.initializer.key(),
+        ctx.accounts.order.load()?.is_fees_on(),
+    );
+
+    transfer_token22_nft(
+        ctx.accounts.initializer.to_account_info(),
+        ctx.accounts.nft_mint.to_account_info(),
+        ctx.accounts.seller_nft_ta.to_account_info(),
+        ctx.accounts.buyer_nft_ta.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        parsed_accounts.transfer_hook_accounts,
+        &[],
+    )?;
+
+    // the fee comes out of the price so the wallet pays exactly what was deducted from the balance
+    let fee_amount = if parsed_accounts.fees_on {
+        get_fee_amount(price)
+    } else {
+        0
+    };
+
+    // transfer sol from buyer to seller
+    lamport_transfer(
+        ctx.accounts.wallet.to_account_info(),
+        ctx.accounts.initializer.to_account_info(),
+        price.checked_sub(fee_amount).unwrap(),
+    )?;
+    if fee_amount > 0 {
+        lamport_transfer(
+            ctx.accounts.wallet.to_account_info(),
+            ctx.accounts.treasury.to_account_info(),
+            fee_amount,
         )?;
     }
 
+    emit_cpi!(Order::get_fill_event(
+        &ctx.accounts.order.load()?,
+        ctx.accounts.order.key(),
+        ctx.accounts.market.pool_mint,
+        ctx.accounts.buyer.key(),
+        ctx.accounts.initializer.key(),
+        ctx.accounts.nft_mint.key(),
+        fee_amount,
+        Vec::new(),
+        !parsed_accounts.fees_on,
+        ctx.accounts.clock.unix_timestamp,
+    ));
+
+    // edit order
+    let size = ctx.accounts.order.load()?.size;
+
+    Order::fill(
+        &mut ctx.accounts.order.load_mut()?,
+        price,
+        ctx.accounts.clock.unix_timestamp,
+    )?;
+    Wallet::release_filled_exposure(
+        &mut ctx.accounts.wallet.load_mut()?,
+        &mut ctx.accounts.order.load_mut()?,
+    )?;
+    sync_order_book(
+        &ctx.accounts.order_book,
+        &ctx.accounts.market,
+        ctx.accounts.order.key(),
+        &ctx.accounts.order.load()?,
+        Some(&ctx.accounts.wallet.load()?),
+    )?;
+
     if size == 1 {
         // close order account
         msg!(
@@ -203,19 +515,22 @@ pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, FillBuyOrder<'info>>) -> R
             ctx.accounts.order.key(),
             ctx.accounts.market.pool_mint
         );
-        ctx.accounts.order.state = OrderState::Closed.into();
+        ctx.accounts.order.load_mut()?.state = OrderState::Closed.into();
+        Wallet::remove_open_order(&mut ctx.accounts.wallet.load_mut()?, true)?;
         emit_cpi!(Order::get_edit_event(
-            &mut ctx.accounts.order.clone(),
+            &ctx.accounts.order.load()?,
             ctx.accounts.order.key(),
             ctx.accounts.market.pool_mint,
             OrderEditType::FillAndClose,
         ));
-        ctx.accounts
-            .order
-            .close(ctx.accounts.buyer.to_account_info())?;
+        close_buy_order(
+            &ctx.accounts.order,
+            &ctx.accounts.buyer,
+            &ctx.accounts.wallet,
+        )?;
     } else {
         emit_cpi!(Order::get_edit_event(
-            &mut ctx.accounts.order.clone(),
+            &ctx.accounts.order.load()?,
             ctx.accounts.order.key(),
             ctx.accounts.market.pool_mint,
             OrderEditType::Fill,
@@ -224,10 +539,14 @@ pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, FillBuyOrder<'info>>) -> R
     }
 
     emit_cpi!(Wallet::get_edit_event(
-        &mut ctx.accounts.wallet.clone(),
+        &ctx.accounts.wallet.load()?,
         ctx.accounts.wallet.key(),
         WalletEditType::Edit,
     ));
 
+    Wallet::check_balance(
+        &ctx.accounts.wallet.load()?,
+        &ctx.accounts.wallet.to_account_info(),
+    )?;
     Ok(())
 }
@@ -0,0 +1,515 @@
+use anchor_lang::{prelude::*, solana_program::sysvar};
+use anchor_spl::{
+    associated_token::{get_associated_token_address, AssociatedToken},
+    token::{Mint, Token, TokenAccount},
+};
+use bridgesplit_program_utils::anchor_lang;
+use bridgesplit_program_utils::{pnft::utils::get_is_pnft, state::Metadata, ExtraTransferParams};
+use vault::{
+    state::{Appraisal, APPRAISAL_SEED},
+    utils::{get_bump_in_seed_form, lamport_transfer, MplTokenMetadata},
+};
+
+use crate::{
+    errors::ListingsError,
+    instructions::lst::{required, unwind, UnwindAccounts},
+    state::*,
+    utils::{
+        close_buy_order, close_escrow_nft_ta, get_fee_amount, get_match_fee_amount,
+        parse_remaining_accounts, pay_royalties, sync_order_book, transfer_nft, unfreeze_nft,
+    },
+};
+
+#[derive(Accounts)]
+#[instruction()]
+#[event_cpi]
+pub struct MatchOrders<'info> {
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+    #[account(
+        mut,
+        constraint = sell_order.load()?.owner == seller.key(),
+    )]
+    /// CHECK: constraint check
+    pub seller: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        constraint = seller_wallet.load()?.version == WALLET_VERSION @ ListingsError::UnsupportedAccountVersion,
+        seeds = [WALLET_SEED.as_ref(),
+        seller.key().as_ref()],
+        bump,
+    )]
+    pub seller_wallet: AccountLoader<'info, Wallet>,
+    #[account(
+        mut,
+        constraint = buy_order.load()?.owner == buyer.key(),
+        constraint = buyer.key() != seller.key(),
+    )]
+    /// CHECK: constraint check
+    pub buyer: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        constraint = buyer_wallet.load()?.version == WALLET_VERSION @ ListingsError::UnsupportedAccountVersion,
+        seeds = [WALLET_SEED.as_ref(),
+        buyer.key().as_ref()],
+        bump,
+    )]
+    pub buyer_wallet: AccountLoader<'info, Wallet>,
+    #[account(
+        constraint = Market::is_active(market.state),
+        seeds = [MARKET_SEED.as_ref(),
+        market.pool_mint.as_ref()],
+        bump,
+    )]
+    pub market: Box<Account<'info, Market>>,
+    /// order book of the market, required if the market has one
+    #[account(
+        mut,
+        constraint = order_book.load()?.version == ORDER_BOOK_VERSION @ ListingsError::UnsupportedAccountVersion,
+        seeds = [ORDER_BOOK_SEED.as_ref(),
+        market.key().as_ref()],
+        bump,
+    )]
+    pub order_book: Option<AccountLoader<'info, OrderBook>>,
+    #[account(
+        mut,
+        constraint = sell_order.load()?.version == ORDER_VERSION @ ListingsError::UnsupportedAccountVersion,
+        constraint = Order::is_fillable(sell_order.load()?.state),
+        constraint = sell_order.load()?.market == market.key(),
+        constraint = sell_order.load()?.side == u8::from(OrderSide::Sell),
+        constraint = sell_order.load()?.nft_mint == nft_mint.key(),
+        seeds = [ORDER_SEED.as_ref(),
+        sell_order.load()?.nonce.as_ref(),
+        sell_order.load()?.market.as_ref(),
+        sell_order.load()?.owner.as_ref()],
+        bump,
+    )]
+    pub sell_order: AccountLoader<'info, Order>,
+    #[account(
+        mut,
+        constraint = buy_order.load()?.version == ORDER_VERSION @ ListingsError::UnsupportedAccountVersion,
+        constraint = Order::is_fillable(buy_order.load()?.state),
+        constraint = buy_order.load()?.market == market.key(),
+        constraint = buy_order.load()?.side == u8::from(OrderSide::Buy),
+        constraint = buy_order.load()?.price >= sell_order.load()?.price @ ListingsError::OrdersDoNotCross,
+        // trailing bids are only priced when a seller fills them
+        constraint = !buy_order.load()?.is_trailing(),
+        constraint = buy_order.load()?.nft_mint == Pubkey::default() || buy_order.load()?.nft_mint == nft_mint.key(),
+        seeds = [ORDER_SEED.as_ref(),
+        buy_order.load()?.nonce.as_ref(),
+        buy_order.load()?.market.as_ref(),
+        buy_order.load()?.owner.as_ref()],
+        bump,
+    )]
+    pub buy_order: AccountLoader<'info, Order>,
+    #[account(mut)]
+    pub nft_mint: Box<Account<'info, Mint>>,
+    #[account(mut)]
+    pub nft_metadata: Box<Account<'info, Metadata>>,
+    /// CHECK: constraint check in multiple CPI calls
+    pub nft_edition: UncheckedAccount<'info>,
+    /// proves the nft belongs to the market, required for bids of bid pools
+    #[account(
+        seeds = [APPRAISAL_SEED, market.pool_mint.as_ref(), nft_mint.key().as_ref()],
+        bump,
+        seeds::program = vault::ID,
+    )]
+    pub appraisal: Option<Box<Account<'info, Appraisal>>>,
+    /// ata of the seller or the escrow ata of the seller wallet if the listing is escrowed
+    #[account(
+        mut,
+        address = get_associated_token_address(&sell_order.load()?.get_nft_holder(), &nft_mint.key()),
+    )]
+    pub seller_nft_ta: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = initializer,
+        associated_token::mint = nft_mint,
+        associated_token::authority = buyer,
+    )]
+    pub buyer_nft_ta: Box<Account<'info, TokenAccount>>,
+    /// CHECK: constraint
+    #[account(
+        mut,
+        constraint = treasury.key().to_string() == PROTOCOL_TREASURY
+    )]
+    pub treasury: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    /// CHECK: checked by constraint and in cpi
+    #[account(address = sysvar::instructions::id())]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_metadata_program: Program<'info, MplTokenMetadata>,
+    pub clock: Sysvar<'info, Clock>,
+    /// CHECK: cost record of the nft, only created if the buyer is a bid pool
+    #[account(mut)]
+    pub pool_nft: Option<UncheckedAccount<'info>>,
+    // stake pool accounts, only required if the liquid balance of the buyer wallet doesn't cover the bid
+    #[account(
+        mut,
+        seeds = [LST_CONFIG_SEED.as_ref()],
+        bump,
+    )]
+    pub lst_config: Option<Box<Account<'info, LstConfig>>>,
+    /// CHECK: checked against the config when unwinding
+    pub stake_pool_program: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked against the config when unwinding
+    #[account(mut)]
+    pub stake_pool: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked in cpi
+    pub stake_pool_withdraw_authority: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked in cpi
+    #[account(mut)]
+    pub reserve_stake: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked in cpi
+    #[account(mut)]
+    pub manager_fee_account: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked against the config when unwinding
+    #[account(mut)]
+    pub lst_mint: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked to be the lst ata of the buyer wallet when unwinding
+    #[account(mut)]
+    pub wallet_lst_ta: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked when unwinding and in cpi
+    pub stake_history: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked when unwinding and in cpi
+    pub stake_program: Option<UncheckedAccount<'info>>,
+}
+
+//remaining accounts
+// 0 seller token_record, escrow token record if escrowed, or default,
+// 1 authorization_rules or default,
+// 2 authorization_rules_program or default,
+// 3 unused, default
+// 4 buyer token record or default,
+// 5 ovol nft ta of the buyer or default
+// 6 ovol nft metadata or default
+// 7-12 optional creator accounts in order of metadata. Will error if is pnft and correct creator accounts are not present
+
+/// initializer is a permissionless crank settling a bid against a listing priced at or below it
+/// the trade executes at the listing price, the seller receives the full price
+/// the buyer wallet pays the price, fees and royalties, which must fit in the bid price
+/// the crank earns the remaining spread, capped at MATCH_FEE_BPS of the price
+#[inline(always)]
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, MatchOrders<'info>>) -> Result<()> {
+    // unstake the part of the bid that the liquid balance of the buyer doesn't cover,
+    // the bid price caps what the buyer wallet pays
+    let bid_price = ctx.accounts.buy_order.load()?.price;
+    if ctx.accounts.buyer_wallet.load()?.balance < bid_price {
+        msg!("Unwind wallet: {}", ctx.accounts.buyer_wallet.key());
+        let bump = &get_bump_in_seed_form(ctx.bumps.get("buyer_wallet").unwrap());
+        let buyer = ctx.accounts.buyer.key();
+        let signer_seeds = &[&[WALLET_SEED.as_ref(), buyer.as_ref(), bump][..]];
+        unwind(
+            &ctx.accounts.buyer_wallet,
+            UnwindAccounts {
+                lst_config: ctx
+                    .accounts
+                    .lst_config
+                    .as_deref_mut()
+                    .ok_or(ListingsError::MissingStakePoolAccounts)?,
+                stake_pool_program: required(&ctx.accounts.stake_pool_program)?,
+                stake_pool: required(&ctx.accounts.stake_pool)?,
+                stake_pool_withdraw_authority: required(
+                    &ctx.accounts.stake_pool_withdraw_authority,
+                )?,
+                reserve_stake: required(&ctx.accounts.reserve_stake)?,
+                manager_fee_account: required(&ctx.accounts.manager_fee_account)?,
+                pool_mint: required(&ctx.accounts.lst_mint)?,
+                wallet_pool_ta: required(&ctx.accounts.wallet_lst_ta)?,
+                stake_history: required(&ctx.accounts.stake_history)?,
+                stake_program: required(&ctx.accounts.stake_program)?,
+                token_program: ctx.accounts.token_program.to_account_info(),
+                clock: ctx.accounts.clock.to_account_info(),
+            },
+            signer_seeds,
+            bid_price,
+        )?;
+        emit_cpi!(Wallet::get_edit_event(
+            &ctx.accounts.buyer_wallet.load()?,
+            ctx.accounts.buyer_wallet.key(),
+            WalletEditType::Unwind,
+        ));
+    }
+
+    let parsed_accounts = parse_remaining_accounts(
+        ctx.remaining_accounts.to_vec(),
+        ctx.accounts.buyer.key(),
+        ctx.accounts.sell_order.load()?.is_fees_on(),
+        false,
+        Some(1),
+    );
+    let pnft_params = parsed_accounts.pnft_params;
+
+    let dest_token_record =
+        if ctx.remaining_accounts.get(4).cloned().unwrap().key() == Pubkey::default() {
+            None
+        } else {
+            ctx.remaining_accounts.get(4).cloned()
+        };
+
+    let bump = &get_bump_in_seed_form(ctx.bumps.get("seller_wallet").unwrap());
+    let seller = ctx.accounts.sell_order.load()?.owner;
+    let signer_seeds = &[&[WALLET_SEED.as_ref(), seller.as_ref(), bump][..]];
+
+    let price = ctx.accounts.sell_order.load()?.price;
+    let fee_amount = if parsed_accounts.fees_on {
+        get_fee_amount(price)
+    } else {
+        0
+    };
+
+    // transfer sol from the buyer wallet to the seller and the treasury
+    lamport_transfer(
+        ctx.accounts.buyer_wallet.to_account_info(),
+        ctx.accounts.seller.to_account_info(),
+        price,
+    )?;
+    if fee_amount > 0 {
+        lamport_transfer(
+            ctx.accounts.buyer_wallet.to_account_info(),
+            ctx.accounts.treasury.to_account_info(),
+            fee_amount,
+        )?;
+    }
+
+    let is_pnft = get_is_pnft(&ctx.accounts.nft_metadata);
+    let is_escrowed = ctx.accounts.sell_order.load()?.is_escrowed();
+    let nft_holder = if is_escrowed {
+        ctx.accounts.seller_wallet.to_account_info()
+    } else {
+        ctx.accounts.seller.to_account_info()
+    };
+
+    // unfreeze nft first so that a transfer can be made
+    if !is_pnft && !is_escrowed {
+        unfreeze_nft(
+            ctx.accounts.seller.to_account_info(),
+            ctx.accounts.initializer.to_account_info(),
+            ctx.accounts.nft_mint.to_account_info(),
+            ctx.accounts.seller_nft_ta.to_account_info(),
+            ctx.accounts.seller_wallet.to_account_info(),
+            ctx.accounts.nft_metadata.to_account_info(),
+            ctx.accounts.nft_edition.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.sysvar_instructions.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.associated_token_program.to_account_info(),
+            ctx.accounts.token_metadata_program.to_account_info(),
+            signer_seeds,
+            pnft_params.clone(),
+        )?;
+    }
+
+    // transfer nft
+    transfer_nft(
+        ctx.accounts.seller_wallet.to_account_info(),
+        nft_holder,
+        ctx.accounts.buyer.to_account_info(),
+        ctx.accounts.initializer.to_account_info(),
+        ctx.accounts.nft_mint.to_account_info(),
+        ctx.accounts.nft_metadata.to_account_info(),
+        ctx.accounts.nft_edition.to_account_info(),
+        ctx.accounts.seller_nft_ta.to_account_info(),
+        ctx.accounts.buyer_nft_ta.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        ctx.accounts.sysvar_instructions.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        ctx.accounts.associated_token_program.to_account_info(),
+        ctx.accounts.token_metadata_program.to_account_info(),
+        ExtraTransferParams {
+            dest_token_record,
+            owner_token_record: pnft_params.token_record.clone(),
+            authorization_rules: pnft_params.authorization_rules.clone(),
+            authorization_rules_program: pnft_params.authorization_rules_program.clone(),
+            authorization_data: None,
+        },
+        signer_seeds,
+    )?;
+
+    if is_escrowed && !is_pnft {
+        close_escrow_nft_ta(
+            ctx.accounts.seller_nft_ta.to_account_info(),
+            ctx.accounts.seller.to_account_info(),
+            ctx.accounts.seller_wallet.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            signer_seeds,
+        )?;
+    }
+
+    let royalties = if is_pnft {
+        pay_royalties(
+            price,
+            ctx.accounts.nft_metadata.clone(),
+            ctx.accounts.buyer_wallet.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            parsed_accounts.creator_accounts,
+            true,
+            None,
+        )?
+    } else {
+        Vec::new()
+    };
+
+    // the bid price is the most the buyer wallet spends on the nft
+    let royalty_total: u64 = royalties.iter().map(|royalty| royalty.amount).sum();
+    let cost = price
+        .checked_add(fee_amount)
+        .and_then(|cost| cost.checked_add(royalty_total))
+        .ok_or(ListingsError::BalanceOverflow)?;
+    let spread = ctx
+        .accounts
+        .buy_order
+        .load()?
+        .price
+        .checked_sub(cost)
+        .ok_or(ListingsError::OrdersDoNotCross)?;
+    let match_fee = spread.min(get_match_fee_amount(price));
+    if match_fee > 0 {
+        lamport_transfer(
+            ctx.accounts.buyer_wallet.to_account_info(),
+            ctx.accounts.initializer.to_account_info(),
+            match_fee,
+        )?;
+    }
+
+    msg!("Edit wallet balance: {}", ctx.accounts.buyer_wallet.key());
+    Wallet::edit_balance(
+        &mut ctx.accounts.buyer_wallet.load_mut()?,
+        false,
+        cost.checked_add(match_fee).unwrap(),
+    )?;
+
+    emit_cpi!(Order::get_fill_event(
+        &ctx.accounts.sell_order.load()?,
+        ctx.accounts.sell_order.key(),
+        ctx.accounts.market.pool_mint,
+        ctx.accounts.buyer.key(),
+        ctx.accounts.seller.key(),
+        ctx.accounts.nft_mint.key(),
+        fee_amount,
+        royalties,
+        !parsed_accounts.fees_on,
+        ctx.accounts.clock.unix_timestamp,
+    ));
+
+    // a listing of a semi-fungible asset stays open until all units are sold
+    Order::fill(
+        &mut ctx.accounts.sell_order.load_mut()?,
+        price,
+        ctx.accounts.clock.unix_timestamp,
+    )?;
+    sync_order_book(
+        &ctx.accounts.order_book,
+        &ctx.accounts.market,
+        ctx.accounts.sell_order.key(),
+        &ctx.accounts.sell_order.load()?,
+        None,
+    )?;
+    if ctx.accounts.sell_order.load()?.size == 0 {
+        msg!(
+            "Close sell order account: {}",
+            ctx.accounts.sell_order.key()
+        );
+        ctx.accounts.sell_order.load_mut()?.state = OrderState::Closed.into();
+        Wallet::remove_open_order(&mut ctx.accounts.seller_wallet.load_mut()?, false)?;
+        emit_cpi!(Order::get_edit_event(
+            &ctx.accounts.sell_order.load()?,
+            ctx.accounts.sell_order.key(),
+            ctx.accounts.market.pool_mint,
+            OrderEditType::FillAndClose,
+        ));
+        ctx.accounts
+            .sell_order
+            .close(ctx.accounts.seller.to_account_info())?;
+    } else {
+        emit_cpi!(Order::get_edit_event(
+            &ctx.accounts.sell_order.load()?,
+            ctx.accounts.sell_order.key(),
+            ctx.accounts.market.pool_mint,
+            OrderEditType::Fill,
+        ));
+    }
+
+    // edit buy order, the bid is filled at the listing price
+    let size = ctx.accounts.buy_order.load()?.size;
+    Order::fill(
+        &mut ctx.accounts.buy_order.load_mut()?,
+        price,
+        ctx.accounts.clock.unix_timestamp,
+    )?;
+    Wallet::release_filled_exposure(
+        &mut ctx.accounts.buyer_wallet.load_mut()?,
+        &mut ctx.accounts.buy_order.load_mut()?,
+    )?;
+    sync_order_book(
+        &ctx.accounts.order_book,
+        &ctx.accounts.market,
+        ctx.accounts.buy_order.key(),
+        &ctx.accounts.buy_order.load()?,
+        Some(&ctx.accounts.buyer_wallet.load()?),
+    )?;
+    if size == 1 {
+        msg!("Close buy order account: {}", ctx.accounts.buy_order.key());
+        ctx.accounts.buy_order.load_mut()?.state = OrderState::Closed.into();
+        Wallet::remove_open_order(&mut ctx.accounts.buyer_wallet.load_mut()?, true)?;
+        emit_cpi!(Order::get_edit_event(
+            &ctx.accounts.buy_order.load()?,
+            ctx.accounts.buy_order.key(),
+            ctx.accounts.market.pool_mint,
+            OrderEditType::FillAndClose,
+        ));
+        close_buy_order(
+            &ctx.accounts.buy_order,
+            &ctx.accounts.buyer,
+            &ctx.accounts.buyer_wallet,
+        )?;
+    } else {
+        emit_cpi!(Order::get_edit_event(
+            &ctx.accounts.buy_order.load()?,
+            ctx.accounts.buy_order.key(),
+            ctx.accounts.market.pool_mint,
+            OrderEditType::Fill,
+        ));
+    }
+
+    // nfts bought by a bid pool are held by the pool, valued at the price paid
+    if BidPool::is_bid_pool(&ctx.accounts.buyer) {
+        // the pool spends the sol of its depositors, only on nfts of the market
+        require!(
+            ctx.accounts.appraisal.is_some(),
+            ListingsError::MissingAppraisal
+        );
+        let pool_nft = ctx
+            .accounts
+            .pool_nft
+            .as_ref()
+            .ok_or(ListingsError::InvalidPoolNft)?;
+        PoolNft::record_fill(
+            pool_nft,
+            ctx.accounts.buyer.key(),
+            ctx.accounts.nft_mint.key(),
+            &ctx.accounts.initializer,
+            &ctx.accounts.system_program,
+            cost,
+        )?;
+        emit_cpi!(BidPool::record_fill(&ctx.accounts.buyer, cost)?);
+    }
+
+    emit_cpi!(Wallet::get_edit_event(
+        &ctx.accounts.buyer_wallet.load()?,
+        ctx.accounts.buyer_wallet.key(),
+        WalletEditType::Edit,
+    ));
+
+    Wallet::check_balance(
+        &ctx.accounts.seller_wallet.load()?,
+        &ctx.accounts.seller_wallet.to_account_info(),
+    )?;
+    Wallet::check_balance(
+        &ctx.accounts.buyer_wallet.load()?,
+        &ctx.accounts.buyer_wallet.to_account_info(),
+    )?;
+    Ok(())
+}
@@ -1,5 +1,7 @@
 pub mod buy;
+pub mod match_orders;
 pub mod sell;
 
 pub use buy::*;
+pub use match_orders::*;
 pub use sell::*;
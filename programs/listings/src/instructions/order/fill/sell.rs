@@ -1,7 +1,7 @@
 use anchor_lang::{prelude::*, solana_program::sysvar};
 use anchor_spl::{
-    associated_token::AssociatedToken,
-    token::{Mint, Token, TokenAccount},
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
+    token_interface::{close_account, CloseAccount, Mint, TokenAccount, TokenInterface},
 };
 use bridgesplit_program_utils::anchor_lang;
 use bridgesplit_program_utils::{pnft::utils::get_is_pnft, state::Metadata, ExtraTransferParams};
@@ -11,10 +11,12 @@ use vault::{
 };
 
 use crate::{
+    errors::ListingsError,
     state::*,
     utils::{
-        get_fee_amount, parse_remaining_accounts, pay_royalties, transfer_nft, transfer_sol,
-        unfreeze_nft,
+        check_token22_nft, close_escrow_nft_ta, get_fee_amount, get_metaplex_nft, is_token22,
+        parse_remaining_accounts, parse_token22_remaining_accounts, pay_royalties, sync_order_book,
+        transfer_nft, transfer_sol, transfer_token22_nft, unfreeze_nft,
     },
 };
 
@@ -26,17 +28,18 @@ pub struct FillSellOrder<'info> {
     pub initializer: Signer<'info>,
     #[account(
         mut,
-        constraint = order.owner == seller.key(),
+        constraint = order.load()?.owner == seller.key(),
     )]
     /// CHECK: constraint check
     pub seller: UncheckedAccount<'info>,
     #[account(
         mut,
+        constraint = wallet.load()?.version == WALLET_VERSION @ ListingsError::UnsupportedAccountVersion,
         seeds = [WALLET_SEED.as_ref(),
         seller.key().as_ref()],
         bump,
     )]
-    pub wallet: Box<Account<'info, Wallet>>,
+    pub wallet: AccountLoader<'info, Wallet>,
     #[account(
         constraint = Market::is_active(market.state),
         seeds = [MARKET_SEED.as_ref(),
@@ -44,37 +47,52 @@ pub struct FillSellOrder<'info> {
         bump,
     )]
     pub market: Box<Account<'info, Market>>,
+    /// order book of the market, required if the market has one
     #[account(
         mut,
-        constraint = Order::is_active(order.state),
-        constraint = order.market == market.key(),
+        constraint = order_book.load()?.version == ORDER_BOOK_VERSION @ ListingsError::UnsupportedAccountVersion,
+        seeds = [ORDER_BOOK_SEED.as_ref(),
+        market.key().as_ref()],
+        bump,
+    )]
+    pub order_book: Option<AccountLoader<'info, OrderBook>>,
+    #[account(
+        mut,
+        constraint = order.load()?.version == ORDER_VERSION @ ListingsError::UnsupportedAccountVersion,
+        constraint = Order::is_fillable(order.load()?.state),
+        constraint = order.load()?.market == market.key(),
+        constraint = order.load()?.nft_mint == nft_mint.key(),
         seeds = [ORDER_SEED.as_ref(),
-        order.nonce.as_ref(),
-        order.market.as_ref(),
-        order.owner.as_ref()],
+        order.load()?.nonce.as_ref(),
+        order.load()?.market.as_ref(),
+        order.load()?.owner.as_ref()],
         bump,
-        close = seller
     )]
-    pub order: Box<Account<'info, Order>>,
-    #[account(mut)]
-    pub nft_mint: Box<Account<'info, Mint>>,
+    pub order: AccountLoader<'info, Order>,
+    #[account(
+        mut,
+        mint::token_program = token_program,
+    )]
+    pub nft_mint: Box<InterfaceAccount<'info, Mint>>,
+    /// token metadata accounts, required for token program nfts, none for token-2022 nfts
     #[account(mut)]
-    pub nft_metadata: Box<Account<'info, Metadata>>,
+    pub nft_metadata: Option<Box<Account<'info, Metadata>>>,
     /// CHECK: constraint check in multiple CPI calls
-    pub nft_edition: UncheckedAccount<'info>,
+    pub nft_edition: Option<UncheckedAccount<'info>>,
+    /// ata of the seller or the escrow ata of the wallet if the listing is escrowed
     #[account(
         mut,
-        associated_token::mint = nft_mint,
-        associated_token::authority = seller,
+        address = get_associated_token_address_with_program_id(&order.load()?.get_nft_holder(), &nft_mint.key(), &token_program.key()),
     )]
-    pub seller_nft_ta: Box<Account<'info, TokenAccount>>,
+    pub seller_nft_ta: Box<InterfaceAccount<'info, TokenAccount>>,
     #[account(
         init_if_needed,
         payer = initializer,
         associated_token::mint = nft_mint,
         associated_token::authority = initializer,
+        associated_token::token_program = token_program,
     )]
-    pub buyer_nft_ta: Box<Account<'info, TokenAccount>>,
+    pub buyer_nft_ta: Box<InterfaceAccount<'info, TokenAccount>>,
     /// CHECK: constraint
     #[account(
         mut,
@@ -82,7 +100,8 @@ pub struct FillSellOrder<'info> {
     )]
     pub treasury: AccountInfo<'info>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    /// token program or token-2022 program of the nft
+    pub token_program: Interface<'info, TokenInterface>,
     /// CHECK: checked by constraint and in cpi
     #[account(address = sysvar::instructions::id())]
     pub sysvar_instructions: UncheckedAccount<'info>,
@@ -92,26 +111,35 @@ pub struct FillSellOrder<'info> {
 }
 
 //remaining accounts
-// 0 token_record or default,
+// 0 token_record, escrow token record if escrowed, or default,
 // 1 authorization_rules or default,
 // 2 authorization_rules_program or default,
-// 4 delegate record or default,
-// 5 buyer token record or default,
-// 6 ovol nft ta or default
-// 7 ovol nft metadata or default
-// 8-13 optional creator accounts in order of metadata. Will error if is pnft and correct creator accounts are not present
+// 3 unused, default
+// 4 buyer token record or default,
+// 5 ovol nft ta or default
+// 6 ovol nft metadata or default
+// 7-12 optional creator accounts in order of metadata. Will error if is pnft and correct creator accounts are not present
+//
+//remaining accounts of token-2022 nfts
+// 0 ovol nft ta or default
+// 1 ovol nft metadata or default
+// 2.. transfer hook program, validation account and extra accounts if the mint has a transfer hook
 
 /// Initializer is the buyer and is buying an nft from the seller
 /// The seller is the owner of the order account
 /// Buyer transfers sol to seller account
 #[inline(always)]
 pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, FillSellOrder<'info>>) -> Result<()> {
+    if is_token22(&ctx.accounts.token_program) {
+        return token22_handler(ctx);
+    }
+    let nft = get_metaplex_nft(&ctx.accounts.nft_metadata, &ctx.accounts.nft_edition)?;
     let bump = &get_bump_in_seed_form(ctx.bumps.get("wallet").unwrap());
 
     let parsed_accounts = parse_remaining_accounts(
         ctx.remaining_accounts.to_vec(),
         ctx.accounts.initializer.key(),
-        ctx.accounts.order.fees_on,
+        ctx.accounts.order.load()?.is_fees_on(),
         false,
         Some(1),
     );
@@ -125,22 +153,24 @@ pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, FillSellOrder<'info>>) ->
 
     let pnft_params = parsed_accounts.pnft_params;
 
-    let signer_seeds = &[&[
-        WALLET_SEED.as_ref(),
-        ctx.accounts.order.owner.as_ref(),
-        bump,
-    ][..]];
+    let owner = ctx.accounts.order.load()?.owner;
+    let signer_seeds = &[&[WALLET_SEED.as_ref(), owner.as_ref(), bump][..]];
 
     let nft_authority = ctx.accounts.wallet.to_account_info();
     let sol_holder = ctx.accounts.initializer.to_account_info();
 
     // validate seller
-    if ctx.accounts.order.owner != ctx.accounts.seller.key() {
+    if owner != ctx.accounts.seller.key() {
         return Err(SpecificErrorCode::WrongAccount.into());
     }
 
+    let fee_amount = if parsed_accounts.fees_on {
+        get_fee_amount(ctx.accounts.order.load()?.price)
+    } else {
+        0
+    };
+
     if parsed_accounts.fees_on {
-        let fee_amount = get_fee_amount(ctx.accounts.order.price);
         transfer_sol(
             sol_holder.clone(),
             ctx.accounts.treasury.to_account_info(),
@@ -154,7 +184,7 @@ pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, FillSellOrder<'info>>) ->
             ctx.accounts.seller.to_account_info(),
             ctx.accounts.system_program.to_account_info(),
             Some(signer_seeds),
-            ctx.accounts.order.price,
+            ctx.accounts.order.load()?.price,
         )?;
     } else {
         // transfer sol from buyer to seller
@@ -163,22 +193,28 @@ pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, FillSellOrder<'info>>) ->
             ctx.accounts.seller.to_account_info(),
             ctx.accounts.system_program.to_account_info(),
             Some(signer_seeds),
-            ctx.accounts.order.price,
+            ctx.accounts.order.load()?.price,
         )?;
     }
 
-    let is_pnft = get_is_pnft(&ctx.accounts.nft_metadata);
+    let is_pnft = get_is_pnft(&nft.metadata);
+    let is_escrowed = ctx.accounts.order.load()?.is_escrowed();
+    let nft_holder = if is_escrowed {
+        ctx.accounts.wallet.to_account_info()
+    } else {
+        ctx.accounts.seller.to_account_info()
+    };
 
     // unfreeze nft first so that a transfer can be made
-    if !is_pnft {
+    if !is_pnft && !is_escrowed {
         unfreeze_nft(
             ctx.accounts.seller.to_account_info(),
             ctx.accounts.initializer.to_account_info(),
             ctx.accounts.nft_mint.to_account_info(),
             ctx.accounts.seller_nft_ta.to_account_info(),
             ctx.accounts.wallet.to_account_info(),
-            ctx.accounts.nft_metadata.to_account_info(),
-            ctx.accounts.nft_edition.to_account_info(),
+            nft.metadata.to_account_info(),
+            nft.edition.clone(),
             ctx.accounts.system_program.to_account_info(),
             ctx.accounts.sysvar_instructions.to_account_info(),
             ctx.accounts.token_program.to_account_info(),
@@ -192,12 +228,12 @@ pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, FillSellOrder<'info>>) ->
     // transfer nft
     transfer_nft(
         nft_authority,
-        ctx.accounts.seller.to_account_info(),
+        nft_holder,
         ctx.accounts.initializer.to_account_info(),
         ctx.accounts.initializer.to_account_info(),
         ctx.accounts.nft_mint.to_account_info(),
-        ctx.accounts.nft_metadata.to_account_info(),
-        ctx.accounts.nft_edition.to_account_info(),
+        nft.metadata.to_account_info(),
+        nft.edition.clone(),
         ctx.accounts.seller_nft_ta.to_account_info(),
         ctx.accounts.buyer_nft_ta.to_account_info(),
         ctx.accounts.system_program.to_account_info(),
@@ -215,27 +251,193 @@ pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, FillSellOrder<'info>>) ->
         signer_seeds,
     )?;
 
-    if is_pnft {
+    if is_escrowed && !is_pnft {
+        close_escrow_nft_ta(
+            ctx.accounts.seller_nft_ta.to_account_info(),
+            ctx.accounts.seller.to_account_info(),
+            ctx.accounts.wallet.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            signer_seeds,
+        )?;
+    }
+
+    let royalties = if is_pnft {
         pay_royalties(
-            ctx.accounts.order.price,
-            ctx.accounts.nft_metadata.clone(),
+            ctx.accounts.order.load()?.price,
+            nft.metadata.clone(),
             ctx.accounts.initializer.to_account_info(),
             ctx.accounts.system_program.to_account_info(),
             parsed_accounts.creator_accounts,
             false,
             Some(signer_seeds),
+        )?
+    } else {
+        Vec::new()
+    };
+
+    // buyer pays fees and royalties on top of the price
+    emit_cpi!(Order::get_fill_event(
+        &ctx.accounts.order.load()?,
+        ctx.accounts.order.key(),
+        ctx.accounts.market.pool_mint,
+        ctx.accounts.initializer.key(),
+        ctx.accounts.seller.key(),
+        ctx.accounts.nft_mint.key(),
+        fee_amount,
+        royalties,
+        !parsed_accounts.fees_on,
+        ctx.accounts.clock.unix_timestamp,
+    ));
+
+    // a listing of a semi-fungible asset stays open until all units are sold
+    let price = ctx.accounts.order.load()?.price;
+    Order::fill(
+        &mut ctx.accounts.order.load_mut()?,
+        price,
+        ctx.accounts.clock.unix_timestamp,
+    )?;
+    sync_order_book(
+        &ctx.accounts.order_book,
+        &ctx.accounts.market,
+        ctx.accounts.order.key(),
+        &ctx.accounts.order.load()?,
+        None,
+    )?;
+
+    if ctx.accounts.order.load()?.size == 0 {
+        // close order account
+        msg!("Close sell order account: {}", ctx.accounts.order.key());
+        ctx.accounts.order.load_mut()?.state = OrderState::Closed.into();
+        Wallet::remove_open_order(&mut ctx.accounts.wallet.load_mut()?, false)?;
+        emit_cpi!(Order::get_edit_event(
+            &ctx.accounts.order.load()?,
+            ctx.accounts.order.key(),
+            ctx.accounts.market.pool_mint,
+            OrderEditType::FillAndClose,
+        ));
+        ctx.accounts
+            .order
+            .close(ctx.accounts.seller.to_account_info())?;
+    } else {
+        emit_cpi!(Order::get_edit_event(
+            &ctx.accounts.order.load()?,
+            ctx.accounts.order.key(),
+            ctx.accounts.market.pool_mint,
+            OrderEditType::Fill,
+        ));
+        msg!("Filled sell order: {}", ctx.accounts.order.key());
+    }
+
+    Wallet::check_balance(
+        &ctx.accounts.wallet.load()?,
+        &ctx.accounts.wallet.to_account_info(),
+    )?;
+    Ok(())
+}
+
+/// Initializer is the buyer and is buying a token-2022 nft escrowed by the seller's wallet
+/// Buyer transfers sol to seller account, token-2022 nfts carry no metaplex royalties
+fn token22_handler<'info>(ctx: Context<'_, '_, '_, 'info, FillSellOrder<'info>>) -> Result<()> {
+    let bump = &get_bump_in_seed_form(ctx.bumps.get("wallet").unwrap());
+    let owner = ctx.accounts.order.load()?.owner;
+    let signer_seeds = &[&[WALLET_SEED.as_ref(), owner.as_ref(), bump][..]];
+    check_token22_nft(&ctx.accounts.nft_mint.to_account_info())?;
+
+    let parsed_accounts = parse_token22_remaining_accounts(
+        ctx.remaining_accounts.to_vec(),
+        ctx.accounts.initializer.key(),
+        ctx.accounts.order.load()?.is_fees_on(),
+    );
+
+    let fee_amount = if parsed_accounts.fees_on {
+        get_fee_amount(ctx.accounts.order.load()?.price)
+    } else {
+        0
+    };
+
+    if fee_amount > 0 {
+        transfer_sol(
+            ctx.accounts.initializer.to_account_info(),
+            ctx.accounts.treasury.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            None,
+            fee_amount,
         )?;
     }
+    // transfer sol from buyer to seller
+    transfer_sol(
+        ctx.accounts.initializer.to_account_info(),
+        ctx.accounts.seller.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        None,
+        ctx.accounts.order.load()?.price,
+    )?;
+
+    transfer_token22_nft(
+        ctx.accounts.wallet.to_account_info(),
+        ctx.accounts.nft_mint.to_account_info(),
+        ctx.accounts.seller_nft_ta.to_account_info(),
+        ctx.accounts.buyer_nft_ta.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        parsed_accounts.transfer_hook_accounts,
+        signer_seeds,
+    )?;
+
+    // return the rent of the escrow account to the seller
+    close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.seller_nft_ta.to_account_info(),
+            destination: ctx.accounts.seller.to_account_info(),
+            authority: ctx.accounts.wallet.to_account_info(),
+        },
+        signer_seeds,
+    ))?;
+
+    emit_cpi!(Order::get_fill_event(
+        &ctx.accounts.order.load()?,
+        ctx.accounts.order.key(),
+        ctx.accounts.market.pool_mint,
+        ctx.accounts.initializer.key(),
+        ctx.accounts.seller.key(),
+        ctx.accounts.nft_mint.key(),
+        fee_amount,
+        Vec::new(),
+        !parsed_accounts.fees_on,
+        ctx.accounts.clock.unix_timestamp,
+    ));
+
+    let price = ctx.accounts.order.load()?.price;
+    Order::fill(
+        &mut ctx.accounts.order.load_mut()?,
+        price,
+        ctx.accounts.clock.unix_timestamp,
+    )?;
+    sync_order_book(
+        &ctx.accounts.order_book,
+        &ctx.accounts.market,
+        ctx.accounts.order.key(),
+        &ctx.accounts.order.load()?,
+        None,
+    )?;
 
     // close order account
     msg!("Close sell order account: {}", ctx.accounts.order.key());
-    ctx.accounts.order.state = OrderState::Closed.into();
+    ctx.accounts.order.load_mut()?.state = OrderState::Closed.into();
+    Wallet::remove_open_order(&mut ctx.accounts.wallet.load_mut()?, false)?;
     emit_cpi!(Order::get_edit_event(
-        &mut ctx.accounts.order.clone(),
+        &ctx.accounts.order.load()?,
         ctx.accounts.order.key(),
         ctx.accounts.market.pool_mint,
         OrderEditType::FillAndClose,
     ));
+    ctx.accounts
+        .order
+        .close(ctx.accounts.seller.to_account_info())?;
 
+    Wallet::check_balance(
+        &ctx.accounts.wallet.load()?,
+        &ctx.accounts.wallet.to_account_info(),
+    )?;
     Ok(())
 }
@@ -1,7 +1,11 @@
 use anchor_lang::{prelude::*, solana_program::entrypoint::ProgramResult};
 use bridgesplit_program_utils::anchor_lang;
 
-use crate::{state::*, utils::parse_remaining_accounts};
+use crate::{
+    errors::ListingsError,
+    state::*,
+    utils::{parse_remaining_accounts, sync_order_book},
+};
 
 use super::InitOrderData;
 
@@ -13,32 +17,46 @@ pub struct InitBuyOrder<'info> {
     pub initializer: Signer<'info>,
     #[account(
         mut,
+        constraint = wallet.load()?.version == WALLET_VERSION @ ListingsError::UnsupportedAccountVersion,
         // make sure bidding wallet has enough balance to place the order
-        constraint = wallet.balance >= data.price.checked_mul(data.size).unwrap(),
+        constraint = wallet.load()?.get_bidding_balance() >= data.price.checked_mul(data.size).unwrap(),
+        // the owner or its delegate, capped by its spending cap in the handler
+        constraint = wallet.load()?.is_bidding_authority(initializer.key()),
         seeds = [WALLET_SEED.as_ref(),
-        initializer.key().as_ref()],
+        wallet.load()?.owner.as_ref()],
         bump,
     )]
-    pub wallet: Box<Account<'info, Wallet>>,
+    pub wallet: AccountLoader<'info, Wallet>,
     #[account(
         constraint = Market::is_active(market.state),
+        // bids of the market are backed by wallets that cannot withdraw instantly
+        constraint = wallet.load()?.withdrawal_delay >= market.min_withdrawal_delay @ ListingsError::WithdrawalDelayTooShort,
         seeds = [MARKET_SEED.as_ref(),
         market.pool_mint.as_ref()],
         bump,
     )]
     pub market: Box<Account<'info, Market>>,
+    /// order book of the market, required if the market has one
+    #[account(
+        mut,
+        constraint = order_book.load()?.version == ORDER_BOOK_VERSION @ ListingsError::UnsupportedAccountVersion,
+        seeds = [ORDER_BOOK_SEED.as_ref(),
+        market.key().as_ref()],
+        bump,
+    )]
+    pub order_book: Option<AccountLoader<'info, OrderBook>>,
     #[account(
         constraint = data.price > 0 && data.size > 0,
         init,
         seeds = [ORDER_SEED.as_ref(),
         data.nonce.as_ref(),
         market.key().as_ref(),
-        initializer.key().as_ref()],
+        wallet.load()?.owner.as_ref()],
         bump,
         payer = initializer,
         space = 8 + std::mem::size_of::<Order>()
     )]
-    pub order: Box<Account<'info, Order>>,
+    pub order: AccountLoader<'info, Order>,
     /// CHECK: can be anything
     pub nft_mint: UncheckedAccount<'info>,
     pub system_program: Program<'info, System>,
@@ -49,26 +67,24 @@ pub struct InitBuyOrder<'info> {
 // 0 token_record or default,
 // 1 authorization_rules or default,
 // 2 authorization_rules_program or default,
-// 3 ovol nft ta [optional]
-// 4 ovol nft metadata [optional]
+// 3 unused delegate record slot, default
+// 4 ovol nft ta [optional]
+// 5 ovol nft metadata [optional]
 
 #[inline(always)]
 pub fn handler(ctx: Context<InitBuyOrder>, data: InitOrderData) -> ProgramResult {
     msg!("Initialize a new buy order: {}", ctx.accounts.order.key());
 
-    let parsed_accounts = parse_remaining_accounts(
-        ctx.remaining_accounts.to_vec(),
-        ctx.accounts.initializer.key(),
-        true,
-        false,
-        None,
-    );
+    let owner = ctx.accounts.wallet.load()?.owner;
+    let parsed_accounts =
+        parse_remaining_accounts(ctx.remaining_accounts.to_vec(), owner, true, false, None);
 
     // create a new order with size 1
+    let mut order = ctx.accounts.order.load_init()?;
     Order::init(
-        &mut ctx.accounts.order,
+        &mut order,
         ctx.accounts.market.key(),
-        ctx.accounts.initializer.key(),
+        owner,
         ctx.accounts.wallet.key(),
         data.nonce,
         ctx.accounts.nft_mint.key(),
@@ -79,13 +95,31 @@ pub fn handler(ctx: Context<InitBuyOrder>, data: InitOrderData) -> ProgramResult
         OrderState::Ready.into(),
         parsed_accounts.fees_on,
     );
+    Wallet::add_open_order(&mut ctx.accounts.wallet.load_mut()?, true)?;
+    Wallet::set_bid_exposure(
+        &mut ctx.accounts.wallet.load_mut()?,
+        ctx.accounts.initializer.key(),
+        &mut order,
+        data.price.checked_mul(data.size).unwrap(),
+    )?;
 
     emit_cpi!(Order::get_edit_event(
-        &mut ctx.accounts.order.clone(),
+        &order,
         ctx.accounts.order.key(),
         ctx.accounts.market.pool_mint,
         OrderEditType::Init,
     ));
 
+    Wallet::check_balance(
+        &ctx.accounts.wallet.load()?,
+        &ctx.accounts.wallet.to_account_info(),
+    )?;
+    sync_order_book(
+        &ctx.accounts.order_book,
+        &ctx.accounts.market,
+        ctx.accounts.order.key(),
+        &order,
+        Some(&ctx.accounts.wallet.load()?),
+    )?;
     Ok(())
 }
@@ -1,21 +1,26 @@
-use anchor_lang::{
-    prelude::*,
-    solana_program::{entrypoint::ProgramResult, sysvar},
-};
+use anchor_lang::{prelude::*, solana_program::sysvar};
 use anchor_spl::{
-    associated_token::AssociatedToken,
-    token::{Mint, Token, TokenAccount},
+    associated_token::{
+        create_idempotent, get_associated_token_address_with_program_id, AssociatedToken, Create,
+    },
+    token_interface::{Mint, TokenAccount, TokenInterface},
 };
 use bridgesplit_program_utils::{anchor_lang, pnft::utils::get_is_pnft};
 use bridgesplit_program_utils::{
-    get_bump_in_seed_form, state::Metadata, ExtraDelegateParams, MplTokenMetadata,
+    get_bump_in_seed_form, state::Metadata, ExtraDelegateParams, ExtraTransferParams,
+    MplTokenMetadata,
 };
 use token_metadata::instruction::DelegateArgs;
 use vault::state::{Appraisal, APPRAISAL_SEED};
 
 use crate::{
+    errors::ListingsError,
     state::*,
-    utils::{delegate_nft, freeze_nft, parse_remaining_accounts},
+    utils::{
+        check_token22_nft, delegate_nft, freeze_nft, get_is_fungible_asset, get_metaplex_nft,
+        is_token22, parse_remaining_accounts, parse_token22_remaining_accounts, sync_order_book,
+        transfer_nft_amount, transfer_token22_nft,
+    },
 };
 
 use super::InitOrderData;
@@ -28,11 +33,12 @@ pub struct InitSellOrder<'info> {
     pub initializer: Signer<'info>,
     #[account(
         mut,
+        constraint = wallet.load()?.version == WALLET_VERSION @ ListingsError::UnsupportedAccountVersion,
         seeds = [WALLET_SEED.as_ref(),
         initializer.key().as_ref()],
         bump,
     )]
-    pub wallet: Box<Account<'info, Wallet>>,
+    pub wallet: AccountLoader<'info, Wallet>,
     #[account(
         constraint = Market::is_active(market.state),
         seeds = [MARKET_SEED.as_ref(),
@@ -40,6 +46,15 @@ pub struct InitSellOrder<'info> {
         bump,
     )]
     pub market: Box<Account<'info, Market>>,
+    /// order book of the market, required if the market has one
+    #[account(
+        mut,
+        constraint = order_book.load()?.version == ORDER_BOOK_VERSION @ ListingsError::UnsupportedAccountVersion,
+        seeds = [ORDER_BOOK_SEED.as_ref(),
+        market.key().as_ref()],
+        bump,
+    )]
+    pub order_book: Option<AccountLoader<'info, OrderBook>>,
     #[account(
         constraint = data.price > 0 && data.size > 0,
         init,
@@ -51,31 +66,42 @@ pub struct InitSellOrder<'info> {
         payer = initializer,
         space = 8 + std::mem::size_of::<Order>()
     )]
-    pub order: Box<Account<'info, Order>>,
+    pub order: AccountLoader<'info, Order>,
     #[account(
         seeds = [APPRAISAL_SEED, market.pool_mint.as_ref(), nft_mint.key().as_ref()],
         bump,
         seeds::program = vault::ID,
     )]
     pub appraisal: Box<Account<'info, Appraisal>>,
+    #[account(
+        mut,
+        mint::token_program = token_program,
+    )]
+    pub nft_mint: Box<InterfaceAccount<'info, Mint>>,
+    /// token metadata accounts, required for token program nfts, none for token-2022 nfts
     #[account(mut)]
-    pub nft_mint: Box<Account<'info, Mint>>,
-    #[account(mut)]
-    pub nft_metadata: Box<Account<'info, Metadata>>,
+    pub nft_metadata: Option<Box<Account<'info, Metadata>>>,
     /// CHECK: checked in cpi
     #[account(mut)]
-    pub nft_edition: UncheckedAccount<'info>,
+    pub nft_edition: Option<UncheckedAccount<'info>>,
     #[account(
         mut,
         constraint = nft_ta.owner == initializer.key(),
         constraint = nft_ta.mint == nft_mint.key(),
     )]
-    pub nft_ta: Box<Account<'info, TokenAccount>>,
+    pub nft_ta: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: escrow ata of the bidding wallet, only used by escrow listings and created in cpi
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(&wallet.key(), &nft_mint.key(), &token_program.key()),
+    )]
+    pub escrow_nft_ta: UncheckedAccount<'info>,
     /// CHECK: checked by constraint and in cpi
     #[account(address = sysvar::instructions::id())]
     pub sysvar_instructions: UncheckedAccount<'info>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    /// token program or token-2022 program of the nft
+    pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_metadata_program: Program<'info, MplTokenMetadata>,
     pub clock: Sysvar<'info, Clock>,
@@ -84,18 +110,27 @@ pub struct InitSellOrder<'info> {
 // 0 token_record or default,
 // 1 authorization_rules or default,
 // 2 authorization_rules_program or default,
-// 4 delegate record or default,
-// 5 existing delegate or default,
-// 6 existing delegate record or default
-// 7 ovol nft ta [optional]
-// 8 ovol nft metadata [optional]
+// 3 delegate record or escrow token record in escrow markets or default,
+// 4 existing delegate or default,
+// 5 existing delegate record or default
+// 6 ovol nft ta [optional]
+// 7 ovol nft metadata [optional]
+//
+//remaining accounts of token-2022 nfts
+// 0 ovol nft ta or default
+// 1 ovol nft metadata or default
+// 2.. transfer hook program, validation account and extra accounts if the mint has a transfer hook
 
 #[inline(always)]
 pub fn handler<'info>(
     ctx: Context<'_, '_, '_, 'info, InitSellOrder<'info>>,
     data: InitOrderData,
-) -> ProgramResult {
+) -> Result<()> {
     msg!("Initialize a new sell order: {}", ctx.accounts.order.key());
+    if is_token22(&ctx.accounts.token_program) {
+        return token22_handler(ctx, data);
+    }
+    let nft = get_metaplex_nft(&ctx.accounts.nft_metadata, &ctx.accounts.nft_edition)?;
 
     let parsed_accounts = parse_remaining_accounts(
         ctx.remaining_accounts.to_vec(),
@@ -107,9 +142,16 @@ pub fn handler<'info>(
 
     let pnft_params = parsed_accounts.pnft_params;
 
-    // create a new order with size 1
+    // semi-fungible assets can be listed with a size above 1
+    let is_fungible_asset = get_is_fungible_asset(&nft.metadata);
+    require!(
+        data.size == 1 || is_fungible_asset,
+        ListingsError::InvalidOrderSize
+    );
+
+    let mut order = ctx.accounts.order.load_init()?;
     Order::init(
-        &mut ctx.accounts.order,
+        &mut order,
         ctx.accounts.market.key(),
         ctx.accounts.initializer.key(),
         ctx.accounts.wallet.key(),
@@ -117,75 +159,197 @@ pub fn handler<'info>(
         ctx.accounts.nft_mint.key(),
         ctx.accounts.clock.unix_timestamp,
         OrderSide::Sell.into(),
-        1, // always 1
+        data.size,
         data.price,
         OrderState::Ready.into(),
         parsed_accounts.fees_on,
     );
+    // semi-fungible assets have no edition to freeze with and are always escrowed
+    order.listing_mode = if is_fungible_asset {
+        ListingMode::Escrow.into()
+    } else {
+        ctx.accounts.market.listing_mode
+    };
+    Wallet::add_open_order(&mut ctx.accounts.wallet.load_mut()?, false)?;
 
-    let bump = &get_bump_in_seed_form(ctx.bumps.get("wallet").unwrap());
+    if order.is_escrowed() {
+        // move the listed units into the escrow token account of the bidding wallet
+        transfer_nft_amount(
+            ctx.accounts.initializer.to_account_info(),
+            ctx.accounts.initializer.to_account_info(),
+            ctx.accounts.initializer.to_account_info(),
+            ctx.accounts.wallet.to_account_info(),
+            ctx.accounts.nft_mint.to_account_info(),
+            nft.metadata.to_account_info(),
+            nft.edition.clone(),
+            ctx.accounts.nft_ta.to_account_info(),
+            ctx.accounts.escrow_nft_ta.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.sysvar_instructions.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.associated_token_program.to_account_info(),
+            ctx.accounts.token_metadata_program.to_account_info(),
+            ExtraTransferParams {
+                dest_token_record: parsed_accounts.delegate_record,
+                owner_token_record: pnft_params.token_record,
+                authorization_rules: pnft_params.authorization_rules,
+                authorization_rules_program: pnft_params.authorization_rules_program,
+                authorization_data: None,
+            },
+            &[],
+            data.size,
+        )?;
+    } else {
+        let bump = &get_bump_in_seed_form(ctx.bumps.get("wallet").unwrap());
 
-    let signer_seeds = &[&[
-        WALLET_SEED.as_ref(),
-        ctx.accounts.initializer.key.as_ref(),
-        bump,
-    ][..]];
+        let signer_seeds = &[&[
+            WALLET_SEED.as_ref(),
+            ctx.accounts.initializer.key.as_ref(),
+            bump,
+        ][..]];
 
-    let is_pnft = get_is_pnft(&ctx.accounts.nft_metadata);
+        let is_pnft = get_is_pnft(&nft.metadata);
 
-    // freeze the nft of the seller with the bidding wallet account as the authority
-    delegate_nft(
-        ctx.accounts.initializer.to_account_info(),
-        ctx.accounts.initializer.to_account_info(),
-        ctx.accounts.nft_mint.to_account_info(),
-        ctx.accounts.nft_ta.to_account_info(),
-        ctx.accounts.nft_metadata.to_account_info(),
-        ctx.accounts.wallet.to_account_info(),
-        ctx.accounts.system_program.to_account_info(),
-        ctx.accounts.sysvar_instructions.to_account_info(),
-        ctx.accounts.token_program.to_account_info(),
-        ctx.accounts.token_metadata_program.to_account_info(),
-        signer_seeds,
-        ExtraDelegateParams {
-            master_edition: Some(ctx.accounts.nft_edition.to_account_info()),
-            delegate_record: parsed_accounts.delegate_record.clone(),
-            token_record: pnft_params.token_record.clone(),
-            authorization_rules_program: pnft_params.authorization_rules_program.clone(),
-            authorization_rules: pnft_params.authorization_rules.clone(),
-            token: Some(ctx.accounts.nft_ta.to_account_info()),
-            spl_token_program: Some(ctx.accounts.token_program.to_account_info()),
-            delegate_args: DelegateArgs::SaleV1 {
-                amount: 1,
-                authorization_data: None,
-            },
-            existing_delegate_params: parsed_accounts.existing_delegate_params,
-        },
-    )?;
-    if !is_pnft {
-        freeze_nft(
+        // freeze the nft of the seller with the bidding wallet account as the authority
+        delegate_nft(
             ctx.accounts.initializer.to_account_info(),
             ctx.accounts.initializer.to_account_info(),
             ctx.accounts.nft_mint.to_account_info(),
             ctx.accounts.nft_ta.to_account_info(),
-            ctx.accounts.nft_metadata.to_account_info(),
-            ctx.accounts.nft_edition.to_account_info(),
+            nft.metadata.to_account_info(),
             ctx.accounts.wallet.to_account_info(),
             ctx.accounts.system_program.to_account_info(),
             ctx.accounts.sysvar_instructions.to_account_info(),
             ctx.accounts.token_program.to_account_info(),
-            ctx.accounts.associated_token_program.to_account_info(),
             ctx.accounts.token_metadata_program.to_account_info(),
             signer_seeds,
-            pnft_params,
+            ExtraDelegateParams {
+                master_edition: Some(nft.edition.clone()),
+                delegate_record: parsed_accounts.delegate_record.clone(),
+                token_record: pnft_params.token_record.clone(),
+                authorization_rules_program: pnft_params.authorization_rules_program.clone(),
+                authorization_rules: pnft_params.authorization_rules.clone(),
+                token: Some(ctx.accounts.nft_ta.to_account_info()),
+                spl_token_program: Some(ctx.accounts.token_program.to_account_info()),
+                // semi-fungible assets are escrowed, a delegated listing is a single nft
+                delegate_args: DelegateArgs::SaleV1 {
+                    amount: 1,
+                    authorization_data: None,
+                },
+                existing_delegate_params: parsed_accounts.existing_delegate_params,
+            },
         )?;
+        if !is_pnft {
+            freeze_nft(
+                ctx.accounts.initializer.to_account_info(),
+                ctx.accounts.initializer.to_account_info(),
+                ctx.accounts.nft_mint.to_account_info(),
+                ctx.accounts.nft_ta.to_account_info(),
+                nft.metadata.to_account_info(),
+                nft.edition.clone(),
+                ctx.accounts.wallet.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                ctx.accounts.sysvar_instructions.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.associated_token_program.to_account_info(),
+                ctx.accounts.token_metadata_program.to_account_info(),
+                signer_seeds,
+                pnft_params,
+            )?;
+        }
     }
 
     emit_cpi!(Order::get_edit_event(
-        &mut ctx.accounts.order.clone(),
+        &order,
+        ctx.accounts.order.key(),
+        ctx.accounts.market.pool_mint,
+        OrderEditType::Init,
+    ));
+
+    Wallet::check_balance(
+        &ctx.accounts.wallet.load()?,
+        &ctx.accounts.wallet.to_account_info(),
+    )?;
+    sync_order_book(
+        &ctx.accounts.order_book,
+        &ctx.accounts.market,
+        ctx.accounts.order.key(),
+        &order,
+        None,
+    )?;
+    Ok(())
+}
+
+/// list a token-2022 nft, the nft is escrowed in a token account of the seller's wallet
+fn token22_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, InitSellOrder<'info>>,
+    data: InitOrderData,
+) -> Result<()> {
+    check_token22_nft(&ctx.accounts.nft_mint.to_account_info())?;
+    require!(data.size == 1, ListingsError::InvalidOrderSize);
+
+    let parsed_accounts = parse_token22_remaining_accounts(
+        ctx.remaining_accounts.to_vec(),
+        ctx.accounts.initializer.key(),
+        true,
+    );
+
+    let mut order = ctx.accounts.order.load_init()?;
+    Order::init(
+        &mut order,
+        ctx.accounts.market.key(),
+        ctx.accounts.initializer.key(),
+        ctx.accounts.wallet.key(),
+        data.nonce,
+        ctx.accounts.nft_mint.key(),
+        ctx.accounts.clock.unix_timestamp,
+        OrderSide::Sell.into(),
+        1, // always 1
+        data.price,
+        OrderState::Ready.into(),
+        parsed_accounts.fees_on,
+    );
+    order.listing_mode = ListingMode::Escrow.into();
+    Wallet::add_open_order(&mut ctx.accounts.wallet.load_mut()?, false)?;
+
+    create_idempotent(CpiContext::new(
+        ctx.accounts.associated_token_program.to_account_info(),
+        Create {
+            payer: ctx.accounts.initializer.to_account_info(),
+            associated_token: ctx.accounts.escrow_nft_ta.to_account_info(),
+            authority: ctx.accounts.wallet.to_account_info(),
+            mint: ctx.accounts.nft_mint.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+        },
+    ))?;
+    transfer_token22_nft(
+        ctx.accounts.initializer.to_account_info(),
+        ctx.accounts.nft_mint.to_account_info(),
+        ctx.accounts.nft_ta.to_account_info(),
+        ctx.accounts.escrow_nft_ta.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        parsed_accounts.transfer_hook_accounts,
+        &[],
+    )?;
+
+    emit_cpi!(Order::get_edit_event(
+        &order,
         ctx.accounts.order.key(),
         ctx.accounts.market.pool_mint,
         OrderEditType::Init,
     ));
 
+    Wallet::check_balance(
+        &ctx.accounts.wallet.load()?,
+        &ctx.accounts.wallet.to_account_info(),
+    )?;
+    sync_order_book(
+        &ctx.accounts.order_book,
+        &ctx.accounts.market,
+        ctx.accounts.order.key(),
+        &order,
+        None,
+    )?;
     Ok(())
 }
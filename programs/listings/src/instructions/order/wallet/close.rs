@@ -0,0 +1,40 @@
+use anchor_lang::{prelude::*, solana_program::entrypoint::ProgramResult};
+use bridgesplit_program_utils::anchor_lang;
+
+use crate::{errors::ListingsError, state::*};
+
+#[derive(Accounts)]
+#[instruction()]
+#[event_cpi]
+pub struct CloseBiddingWallet<'info> {
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+    #[account(
+        mut,
+        constraint = wallet.load()?.version == WALLET_VERSION @ ListingsError::UnsupportedAccountVersion,
+        // no bids funded by the wallet and no nfts delegated to it
+        constraint = !wallet.load()?.has_open_orders(),
+        // orders of a migrated wallet may not be counted yet
+        constraint = wallet.load()?.uncounted_legacy_orders == 0 @ ListingsError::WalletMigrationIncomplete,
+        // staked funds must be unstaked first
+        constraint = wallet.load()?.staked_balance == 0,
+        seeds = [WALLET_SEED.as_ref(),
+        initializer.key().as_ref()],
+        bump,
+        close = initializer,
+    )]
+    pub wallet: AccountLoader<'info, Wallet>,
+}
+
+#[inline(always)]
+pub fn handler(ctx: Context<CloseBiddingWallet>) -> ProgramResult {
+    msg!("Close wallet account: {}", ctx.accounts.wallet.key());
+
+    // balance and rent are returned to the owner when the account is closed
+    emit_cpi!(Wallet::get_edit_event(
+        &ctx.accounts.wallet.load()?,
+        ctx.accounts.wallet.key(),
+        WalletEditType::Close,
+    ));
+    Ok(())
+}
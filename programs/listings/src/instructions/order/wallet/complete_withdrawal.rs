@@ -0,0 +1,54 @@
+use anchor_lang::{prelude::*, solana_program::entrypoint::ProgramResult};
+use bridgesplit_program_utils::anchor_lang;
+use vault::utils::lamport_transfer;
+
+use crate::{errors::ListingsError, state::*};
+
+#[derive(Accounts)]
+#[instruction()]
+#[event_cpi]
+pub struct CompleteWithdrawal<'info> {
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+    #[account(
+        mut,
+        constraint = wallet.load()?.version == WALLET_VERSION @ ListingsError::UnsupportedAccountVersion,
+        seeds = [WALLET_SEED.as_ref(),
+        initializer.key().as_ref()],
+        bump,
+    )]
+    pub wallet: AccountLoader<'info, Wallet>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+#[inline(always)]
+pub fn handler(ctx: Context<CompleteWithdrawal>) -> ProgramResult {
+    msg!("Complete withdrawal: {}", ctx.accounts.wallet.key());
+
+    if !ctx
+        .accounts
+        .wallet
+        .load()?
+        .is_withdrawal_unlocked(ctx.accounts.clock.unix_timestamp)
+    {
+        return Err(error!(ListingsError::WithdrawalLocked).into());
+    }
+
+    let amount = Wallet::complete_withdrawal(&mut ctx.accounts.wallet.load_mut()?)?;
+    lamport_transfer(
+        ctx.accounts.wallet.to_account_info(),
+        ctx.accounts.initializer.to_account_info(),
+        amount,
+    )?;
+
+    emit_cpi!(Wallet::get_edit_event(
+        &ctx.accounts.wallet.load()?,
+        ctx.accounts.wallet.key(),
+        WalletEditType::CompleteWithdrawal,
+    ));
+    Wallet::check_balance(
+        &ctx.accounts.wallet.load()?,
+        &ctx.accounts.wallet.to_account_info(),
+    )?;
+    Ok(())
+}
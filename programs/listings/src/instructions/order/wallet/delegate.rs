@@ -0,0 +1,44 @@
+use anchor_lang::{prelude::*, solana_program::entrypoint::ProgramResult};
+use bridgesplit_program_utils::anchor_lang;
+
+use crate::{errors::ListingsError, state::*};
+
+#[derive(Accounts)]
+#[instruction(delegate: Pubkey, spending_cap: u64)]
+#[event_cpi]
+pub struct SetWalletDelegate<'info> {
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+    #[account(
+        mut,
+        constraint = wallet.load()?.version == WALLET_VERSION @ ListingsError::UnsupportedAccountVersion,
+        constraint = delegate != initializer.key(),
+        seeds = [WALLET_SEED.as_ref(),
+        initializer.key().as_ref()],
+        bump,
+    )]
+    pub wallet: AccountLoader<'info, Wallet>,
+}
+
+#[inline(always)]
+pub fn handler(
+    ctx: Context<SetWalletDelegate>,
+    delegate: Pubkey,
+    spending_cap: u64,
+) -> ProgramResult {
+    msg!("Set wallet delegate: {}", ctx.accounts.wallet.key());
+
+    // the default pubkey removes the delegate
+    Wallet::set_delegate(&mut ctx.accounts.wallet.load_mut()?, delegate, spending_cap);
+
+    emit_cpi!(Wallet::get_edit_event(
+        &ctx.accounts.wallet.load()?,
+        ctx.accounts.wallet.key(),
+        WalletEditType::Delegate,
+    ));
+    Wallet::check_balance(
+        &ctx.accounts.wallet.load()?,
+        &ctx.accounts.wallet.to_account_info(),
+    )?;
+    Ok(())
+}
@@ -2,7 +2,7 @@ use anchor_lang::{prelude::*, solana_program::entrypoint::ProgramResult};
 use bridgesplit_program_utils::anchor_lang;
 use vault::utils::{get_bump_in_seed_form, lamport_transfer};
 
-use crate::{state::*, utils::transfer_sol};
+use crate::{errors::ListingsError, state::*, utils::transfer_sol};
 
 #[derive(Accounts)]
 #[instruction(amount_change: u64, is_increase: bool)]
@@ -12,12 +12,14 @@ pub struct EditBiddingWallet<'info> {
     pub initializer: Signer<'info>,
     #[account(
         mut,
-        constraint = is_increase || amount_change <= wallet.balance,
+        constraint = wallet.load()?.version == WALLET_VERSION @ ListingsError::UnsupportedAccountVersion,
+        // instant withdrawals are only possible without a withdrawal delay
+        constraint = is_increase || (amount_change <= wallet.load()?.balance && wallet.load()?.withdrawal_delay == 0),
         seeds = [WALLET_SEED.as_ref(),
         initializer.key().as_ref()],
         bump,
     )]
-    pub wallet: Box<Account<'info, Wallet>>,
+    pub wallet: AccountLoader<'info, Wallet>,
     pub system_program: Program<'info, System>,
 }
 
@@ -37,7 +39,11 @@ pub fn handler(
         bump,
     ][..]];
 
-    Wallet::edit_balance(&mut ctx.accounts.wallet, is_increase, amount_change);
+    Wallet::edit_balance(
+        &mut ctx.accounts.wallet.load_mut()?,
+        is_increase,
+        amount_change,
+    )?;
 
     // transfer the amount to the wallet account to initializer if it is a deposit
     // transfer the amount from the wallet account to initializer if it is a withdraw
@@ -58,9 +64,13 @@ pub fn handler(
     }
 
     emit_cpi!(Wallet::get_edit_event(
-        &mut ctx.accounts.wallet.clone(),
+        &ctx.accounts.wallet.load()?,
         ctx.accounts.wallet.key(),
         WalletEditType::Edit,
     ));
+    Wallet::check_balance(
+        &ctx.accounts.wallet.load()?,
+        &ctx.accounts.wallet.to_account_info(),
+    )?;
     Ok(())
 }
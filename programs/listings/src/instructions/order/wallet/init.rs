@@ -18,7 +18,7 @@ pub struct InitBiddingWallet<'info> {
         space = 8 + std::mem::size_of::<Wallet>(),
         bump,
     )]
-    pub wallet: Box<Account<'info, Wallet>>,
+    pub wallet: AccountLoader<'info, Wallet>,
     pub system_program: Program<'info, System>,
 }
 
@@ -45,16 +45,14 @@ pub fn handler(ctx: Context<InitBiddingWallet>, amount: u64) -> ProgramResult {
         )?;
     }
 
-    Wallet::init(
-        &mut ctx.accounts.wallet,
-        ctx.accounts.initializer.key(),
-        amount,
-    );
+    let mut wallet = ctx.accounts.wallet.load_init()?;
+    Wallet::init(&mut wallet, ctx.accounts.initializer.key(), amount);
 
     emit_cpi!(Wallet::get_edit_event(
-        &mut ctx.accounts.wallet.clone(),
+        &wallet,
         ctx.accounts.wallet.key(),
         WalletEditType::Init,
     ));
+    Wallet::check_balance(&wallet, &ctx.accounts.wallet.to_account_info())?;
     Ok(())
 }
@@ -1,5 +1,17 @@
+pub mod close;
+pub mod complete_withdrawal;
+pub mod delegate;
 pub mod edit;
 pub mod init;
+pub mod request_withdrawal;
+pub mod sync;
+pub mod withdrawal_delay;
 
+pub use close::*;
+pub use complete_withdrawal::*;
+pub use delegate::*;
 pub use edit::*;
 pub use init::*;
+pub use request_withdrawal::*;
+pub use sync::*;
+pub use withdrawal_delay::*;
@@ -0,0 +1,45 @@
+use anchor_lang::{prelude::*, solana_program::entrypoint::ProgramResult};
+use bridgesplit_program_utils::anchor_lang;
+
+use crate::{errors::ListingsError, state::*};
+
+#[derive(Accounts)]
+#[instruction(amount: u64)]
+#[event_cpi]
+pub struct RequestWithdrawal<'info> {
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+    #[account(
+        mut,
+        constraint = wallet.load()?.version == WALLET_VERSION @ ListingsError::UnsupportedAccountVersion,
+        constraint = amount <= wallet.load()?.balance,
+        seeds = [WALLET_SEED.as_ref(),
+        initializer.key().as_ref()],
+        bump,
+    )]
+    pub wallet: AccountLoader<'info, Wallet>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+#[inline(always)]
+pub fn handler(ctx: Context<RequestWithdrawal>, amount: u64) -> ProgramResult {
+    msg!("Request withdrawal: {}", ctx.accounts.wallet.key());
+
+    // the amount stays in the balance so bids remain fillable until the withdrawal is completed
+    Wallet::request_withdrawal(
+        &mut ctx.accounts.wallet.load_mut()?,
+        amount,
+        ctx.accounts.clock.unix_timestamp,
+    )?;
+
+    emit_cpi!(Wallet::get_edit_event(
+        &ctx.accounts.wallet.load()?,
+        ctx.accounts.wallet.key(),
+        WalletEditType::RequestWithdrawal,
+    ));
+    Wallet::check_balance(
+        &ctx.accounts.wallet.load()?,
+        &ctx.accounts.wallet.to_account_info(),
+    )?;
+    Ok(())
+}
@@ -0,0 +1,43 @@
+use anchor_lang::{prelude::*, solana_program::entrypoint::ProgramResult};
+use bridgesplit_program_utils::anchor_lang;
+
+use crate::{errors::ListingsError, state::*};
+
+#[derive(Accounts)]
+#[instruction(delay: i64)]
+#[event_cpi]
+pub struct SetWithdrawalDelay<'info> {
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+    #[account(
+        mut,
+        constraint = wallet.load()?.version == WALLET_VERSION @ ListingsError::UnsupportedAccountVersion,
+        seeds = [WALLET_SEED.as_ref(),
+        initializer.key().as_ref()],
+        bump,
+    )]
+    pub wallet: AccountLoader<'info, Wallet>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+#[inline(always)]
+pub fn handler(ctx: Context<SetWithdrawalDelay>, delay: i64) -> ProgramResult {
+    msg!("Set withdrawal delay: {}", ctx.accounts.wallet.key());
+
+    Wallet::set_withdrawal_delay(
+        &mut ctx.accounts.wallet.load_mut()?,
+        delay,
+        ctx.accounts.clock.unix_timestamp,
+    )?;
+
+    emit_cpi!(Wallet::get_edit_event(
+        &ctx.accounts.wallet.load()?,
+        ctx.accounts.wallet.key(),
+        WalletEditType::WithdrawalDelay,
+    ));
+    Wallet::check_balance(
+        &ctx.accounts.wallet.load()?,
+        &ctx.accounts.wallet.to_account_info(),
+    )?;
+    Ok(())
+}
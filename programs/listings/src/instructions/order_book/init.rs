@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+use bridgesplit_program_utils::anchor_lang;
+
+use crate::state::*;
+
+#[derive(Accounts)]
+#[instruction()]
+pub struct InitOrderBook<'info> {
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+    #[account(
+        mut,
+        constraint = market.initializer == initializer.key(),
+        seeds = [MARKET_SEED.as_ref(),
+        market.pool_mint.as_ref()],
+        bump,
+    )]
+    pub market: Box<Account<'info, Market>>,
+    #[account(
+        init,
+        seeds = [ORDER_BOOK_SEED.as_ref(),
+        market.key().as_ref()],
+        bump,
+        payer = initializer,
+        space = 8 + std::mem::size_of::<OrderBook>()
+    )]
+    pub order_book: AccountLoader<'info, OrderBook>,
+    pub system_program: Program<'info, System>,
+}
+
+/// create the order book of a market, orders placed before are added with `sync_order_book`
+#[inline(always)]
+pub fn handler(ctx: Context<InitOrderBook>) -> Result<()> {
+    msg!("Initialize order book: {}", ctx.accounts.order_book.key());
+    OrderBook::init(
+        &mut ctx.accounts.order_book.load_init()?,
+        ctx.accounts.market.key(),
+    );
+    ctx.accounts.market.has_order_book = true;
+    Ok(())
+}
@@ -0,0 +1,5 @@
+pub mod init;
+pub mod sync;
+
+pub use init::*;
+pub use sync::*;
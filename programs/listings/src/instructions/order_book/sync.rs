@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+use bridgesplit_program_utils::anchor_lang;
+
+use crate::{errors::ListingsError, state::*};
+
+#[derive(Accounts)]
+#[instruction()]
+pub struct SyncOrderBook<'info> {
+    #[account(
+        mut,
+        constraint = order_book.load()?.version == ORDER_BOOK_VERSION @ ListingsError::UnsupportedAccountVersion,
+        seeds = [ORDER_BOOK_SEED.as_ref(),
+        order_book.load()?.market.as_ref()],
+        bump,
+    )]
+    pub order_book: AccountLoader<'info, OrderBook>,
+}
+
+//remaining accounts
+// 0..n orders of the market, each followed by the wallet of the order
+
+/// permissionless, add orders placed before the order book or that fell off a full side
+/// bids are only added while the bidding balance of their wallet covers their price
+#[inline(always)]
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, SyncOrderBook<'info>>) -> Result<()> {
+    msg!("Sync order book: {}", ctx.accounts.order_book.key());
+    let mut order_book = ctx.accounts.order_book.load_mut()?;
+    for accounts in ctx.remaining_accounts.chunks(2) {
+        let order = Order::load_current(&accounts[0])?;
+        require!(
+            order.market == order_book.market,
+            ListingsError::InvalidOrderMarket
+        );
+        let wallet = accounts.get(1).ok_or(ListingsError::MissingOrderWallet)?;
+        require!(
+            wallet.key() == order.wallet,
+            ListingsError::MissingOrderWallet
+        );
+        let wallet = Wallet::load_current(wallet)?;
+        OrderBook::sync(
+            &mut order_book,
+            accounts[0].key(),
+            &order,
+            wallet.get_bidding_balance(),
+        );
+    }
+    Ok(())
+}
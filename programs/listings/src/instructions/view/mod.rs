@@ -0,0 +1,3 @@
+pub mod quote;
+
+pub use quote::*;
@@ -0,0 +1,153 @@
+use anchor_lang::prelude::*;
+use bridgesplit_program_utils::anchor_lang;
+
+use crate::{errors::ListingsError, state::*};
+
+#[derive(Accounts)]
+#[instruction()]
+pub struct GetQuote<'info> {
+    #[account(
+        seeds = [MARKET_SEED.as_ref(),
+        market.pool_mint.as_ref()],
+        bump,
+    )]
+    pub market: Box<Account<'info, Market>>,
+    /// order book of the market, read instead of candidate orders when passed
+    #[account(
+        constraint = order_book.load()?.version == ORDER_BOOK_VERSION @ ListingsError::UnsupportedAccountVersion,
+        seeds = [ORDER_BOOK_SEED.as_ref(),
+        market.key().as_ref()],
+        bump,
+    )]
+    pub order_book: Option<AccountLoader<'info, OrderBook>>,
+}
+
+//remaining accounts
+// floor: 0..n candidate orders of the market in increasing order of address [optional]
+// best bid: 0..n (order, wallet) of the candidate bids in increasing order of address,
+// or of bids of the order book in book order, bids without their accounts are skipped
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, Debug, Default, PartialEq)]
+/// best order of one side of a market, returned through the return data
+/// an empty side returns the default quote
+pub struct Quote {
+    /// best order, default if the side is empty
+    pub order: Pubkey,
+    /// price of a single unit of the best order in lamports
+    pub price: u64,
+    /// units left to fill on the best order
+    pub size: u64,
+    /// number of fillable orders considered
+    pub order_count: u32,
+    /// units left to fill across the orders considered
+    pub depth: u64,
+}
+
+impl Quote {
+    /// add an order, it becomes the best order if its price beats the current one
+    /// earlier orders stay best on equal prices
+    fn add(&mut self, order: Pubkey, price: u64, size: u64, side: u8) {
+        let is_better = self.order_count == 0
+            || if side == u8::from(OrderSide::Buy) {
+                price > self.price
+            } else {
+                price < self.price
+            };
+        if is_better {
+            self.order = order;
+            self.price = price;
+            self.size = size;
+        }
+        self.order_count += 1;
+        self.depth = self.depth.saturating_add(size);
+    }
+}
+
+/// add a bid capped to the units the bidding balance of its wallet covers, unfunded bids are skipped
+fn add_bid(
+    quote: &mut Quote,
+    address: Pubkey,
+    order: &Order,
+    wallet: Option<&AccountInfo>,
+) -> Result<()> {
+    let wallet = wallet.ok_or(ListingsError::MissingOrderWallet)?;
+    require!(
+        wallet.key() == order.wallet,
+        ListingsError::MissingOrderWallet
+    );
+    let bidding_balance = Wallet::load_current(wallet)?.get_bidding_balance();
+    if bidding_balance < order.price {
+        return Ok(());
+    }
+    let size = order.size.min(
+        bidding_balance
+            .checked_div(order.price)
+            .unwrap_or(order.size),
+    );
+    quote.add(address, order.price, size, u8::from(OrderSide::Buy));
+    Ok(())
+}
+
+/// best order of a side of the market and the depth of that side
+/// the order book of the market is read when passed, otherwise the candidate orders
+/// candidate orders are passed in increasing order of address so none is counted twice
+/// the order book only holds the best orders of each side, so its depth is capped
+/// bids are skipped or capped by the bidding balance of their wallet
+#[inline(always)]
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, GetQuote<'info>>,
+    side: OrderSide,
+) -> Result<Quote> {
+    let side: u8 = side.into();
+    let is_bid = side == u8::from(OrderSide::Buy);
+    let mut quote = Quote::default();
+
+    if let Some(order_book) = &ctx.accounts.order_book {
+        let order_book = order_book.load()?;
+        if !is_bid {
+            for entry in order_book.get_asks() {
+                quote.add(entry.order, entry.price, entry.size, side);
+            }
+            return Ok(quote);
+        }
+        let mut accounts = ctx.remaining_accounts.chunks(2).peekable();
+        for entry in order_book.get_bids() {
+            let bid_accounts = match accounts.next_if(|accounts| accounts[0].key() == entry.order) {
+                Some(bid_accounts) => bid_accounts,
+                None => continue,
+            };
+            let order = Order::load_current(&bid_accounts[0])?;
+            add_bid(&mut quote, entry.order, &order, bid_accounts.get(1))?;
+        }
+        // accounts out of book order or of bids outside the book would be ignored
+        require!(
+            accounts.next().is_none(),
+            ListingsError::InvalidReferenceOrder
+        );
+        return Ok(quote);
+    }
+
+    // increasing addresses keep an order from being counted twice
+    let mut last_order: Option<Pubkey> = None;
+    for accounts in ctx.remaining_accounts.chunks(if is_bid { 2 } else { 1 }) {
+        require!(
+            last_order.map_or(true, |last_order| accounts[0].key() > last_order),
+            ListingsError::UnsortedCandidateOrders
+        );
+        last_order = Some(accounts[0].key());
+        let order = Order::load_current(&accounts[0])?;
+        require!(
+            order.market == ctx.accounts.market.key(),
+            ListingsError::InvalidOrderMarket
+        );
+        if order.side != side || !Order::is_fillable(order.state) || order.size == 0 {
+            continue;
+        }
+        if is_bid {
+            add_bid(&mut quote, accounts[0].key(), &order, accounts.get(1))?;
+        } else {
+            quote.add(accounts[0].key(), order.price, order.size, side);
+        }
+    }
+    Ok(quote)
+}
@@ -1,11 +1,16 @@
 use anchor_lang::{prelude::*, solana_program::entrypoint::ProgramResult};
 use bridgesplit_program_utils::anchor_lang;
 
-mod instructions;
+pub mod errors;
+pub mod instructions;
+pub mod stake_pool;
 pub mod state;
 pub mod utils;
+#[cfg(feature = "cpi")]
+pub mod view_cpi;
 
 use instructions::*;
+use state::OrderSide;
 
 // mrkTzoWMVEBJ3AUrgd2eXNLXrnBuhhQRQyxahtaeTie - prod program id
 // tsthbYzhRwHcVgoGJVv87QFFa13V7fLnKMrpgFMEgRa - staging program id
@@ -22,6 +27,19 @@ pub mod listings {
         instructions::market::init::handler(ctx)
     }
 
+    /// set the listing mode of a market, only callable by the market initializer
+    #[inline(always)]
+    pub fn edit_market(ctx: Context<EditMarket>, listing_mode: u8) -> Result<()> {
+        instructions::market::edit::handler(ctx, listing_mode)
+    }
+
+    /// set the minimum withdrawal delay of wallets bidding on a market,
+    /// only callable by the market initializer
+    #[inline(always)]
+    pub fn set_min_withdrawal_delay(ctx: Context<EditMarket>, delay: i64) -> Result<()> {
+        instructions::market::withdrawal_delay::handler(ctx, delay)
+    }
+
     /// initializer a new bid
     #[inline(always)]
     pub fn init_buy_order(ctx: Context<InitBuyOrder>, data: InitOrderData) -> ProgramResult {
@@ -33,7 +51,7 @@ pub mod listings {
     pub fn init_sell_order<'info>(
         ctx: Context<'_, '_, '_, 'info, InitSellOrder<'info>>,
         data: InitOrderData,
-    ) -> ProgramResult {
+    ) -> Result<()> {
         instructions::order::init::sell::handler(ctx, data)
     }
 
@@ -49,6 +67,29 @@ pub mod listings {
         instructions::order::edit::sell::handler(ctx, data)
     }
 
+    /// make a bid follow the listings of its market
+    #[inline(always)]
+    pub fn set_trailing_price(
+        ctx: Context<SetTrailingPrice>,
+        data: TrailingPriceData,
+    ) -> ProgramResult {
+        instructions::order::edit::trailing::handler(ctx, data)
+    }
+
+    /// keep a listing inactive until the best bid is below the trigger price
+    #[inline(always)]
+    pub fn set_sell_trigger(ctx: Context<SetSellTrigger>, trigger_price: u64) -> ProgramResult {
+        instructions::order::edit::trigger::handler(ctx, trigger_price)
+    }
+
+    /// activate a listing whose trigger is met
+    #[inline(always)]
+    pub fn activate_order<'info>(
+        ctx: Context<'_, '_, '_, 'info, ActivateOrder<'info>>,
+    ) -> Result<()> {
+        instructions::order::edit::activate::handler(ctx)
+    }
+
     /// fill a bid
     #[inline(always)]
     pub fn fill_buy_order<'info>(
@@ -65,6 +106,12 @@ pub mod listings {
         instructions::order::fill::sell::handler(ctx)
     }
 
+    /// settle a bid against a listing priced at or below it
+    #[inline(always)]
+    pub fn match_orders<'info>(ctx: Context<'_, '_, '_, 'info, MatchOrders<'info>>) -> Result<()> {
+        instructions::order::fill::match_orders::handler(ctx)
+    }
+
     /// cancel a buy order
     #[inline(always)]
     pub fn close_buy_order(ctx: Context<CloseBuyOrder>) -> ProgramResult {
@@ -75,7 +122,7 @@ pub mod listings {
     #[inline(always)]
     pub fn close_sell_order<'info>(
         ctx: Context<'_, '_, '_, 'info, CloseSellOrder<'info>>,
-    ) -> ProgramResult {
+    ) -> Result<()> {
         instructions::order::close::sell::handler(ctx)
     }
 
@@ -95,6 +142,152 @@ pub mod listings {
         instructions::wallet::edit::handler(ctx, amount_change, increase)
     }
 
+    /// close a bidding wallet without open orders and return its lamports to the owner
+    #[inline(always)]
+    pub fn close_wallet(ctx: Context<CloseBiddingWallet>) -> ProgramResult {
+        instructions::wallet::close::handler(ctx)
+    }
+
+    /// credit lamports sent directly to a bidding wallet to its balance
+    #[inline(always)]
+    pub fn sync_wallet(ctx: Context<SyncBiddingWallet>) -> ProgramResult {
+        instructions::wallet::sync::handler(ctx)
+    }
+
+    /// set the delay between a withdrawal request and its completion
+    #[inline(always)]
+    pub fn set_withdrawal_delay(ctx: Context<SetWithdrawalDelay>, delay: i64) -> ProgramResult {
+        instructions::wallet::withdrawal_delay::handler(ctx, delay)
+    }
+
+    /// request a time locked withdrawal from a bidding wallet
+    #[inline(always)]
+    pub fn request_withdrawal(ctx: Context<RequestWithdrawal>, amount: u64) -> ProgramResult {
+        instructions::wallet::request_withdrawal::handler(ctx, amount)
+    }
+
+    /// complete a withdrawal once its time lock has elapsed
+    #[inline(always)]
+    pub fn complete_withdrawal(ctx: Context<CompleteWithdrawal>) -> ProgramResult {
+        instructions::wallet::complete_withdrawal::handler(ctx)
+    }
+
+    /// set or remove the delegate allowed to manage bids of a bidding wallet
+    #[inline(always)]
+    pub fn set_wallet_delegate(
+        ctx: Context<SetWalletDelegate>,
+        delegate: Pubkey,
+        spending_cap: u64,
+    ) -> ProgramResult {
+        instructions::wallet::delegate::handler(ctx, delegate, spending_cap)
+    }
+
+    /// set the stake pool used for staked wallet balances
+    #[inline(always)]
+    pub fn set_lst_config(ctx: Context<SetLstConfig>, enabled: bool) -> ProgramResult {
+        instructions::lst::config::handler(ctx, enabled)
+    }
+
+    /// deposit part of a wallet balance into the stake pool
+    #[inline(always)]
+    pub fn stake_wallet(ctx: Context<StakeWallet>, amount: u64) -> ProgramResult {
+        instructions::lst::stake::handler(ctx, amount)
+    }
+
+    /// withdraw staked wallet funds back to the balance
+    #[inline(always)]
+    pub fn unstake_wallet(ctx: Context<UnstakeWallet>, pool_tokens: u64) -> ProgramResult {
+        instructions::lst::unstake::handler(ctx, pool_tokens)
+    }
+
+    /// unstake enough of a wallet to fill a bid
+    #[inline(always)]
+    pub fn unwind_wallet(ctx: Context<UnwindWallet>) -> ProgramResult {
+        instructions::lst::unwind::handler(ctx)
+    }
+
+    /// initialize a bid pool managed by the initializer
+    #[inline(always)]
+    pub fn init_bid_pool(ctx: Context<InitBidPool>, nonce: Pubkey) -> ProgramResult {
+        instructions::bid_pool::init::handler(ctx, nonce)
+    }
+
+    /// deposit sol to a bid pool in exchange for shares
+    #[inline(always)]
+    pub fn deposit_bid_pool(ctx: Context<DepositBidPool>, amount: u64) -> ProgramResult {
+        instructions::bid_pool::deposit::handler(ctx, amount)
+    }
+
+    /// redeem bid pool shares for sol
+    #[inline(always)]
+    pub fn redeem_bid_pool_sol(ctx: Context<RedeemBidPoolSol>, shares: u64) -> ProgramResult {
+        instructions::bid_pool::redeem_sol::handler(ctx, shares)
+    }
+
+    /// redeem bid pool shares for an nft held by the pool
+    #[inline(always)]
+    pub fn redeem_bid_pool_nft<'info>(
+        ctx: Context<'_, '_, '_, 'info, RedeemBidPoolNft<'info>>,
+    ) -> Result<()> {
+        instructions::bid_pool::redeem_nft::handler(ctx)
+    }
+
+    /// initialize an amm pool on a market backed by the bidding wallet of the initializer
+    #[inline(always)]
+    pub fn init_amm_pool(
+        ctx: Context<InitAmmPool>,
+        nonce: Pubkey,
+        data: AmmPoolData,
+    ) -> ProgramResult {
+        instructions::amm::init::handler(ctx, nonce, data)
+    }
+
+    /// edit the curve of an amm pool
+    #[inline(always)]
+    pub fn edit_amm_pool(ctx: Context<EditAmmPool>, data: AmmPoolData) -> ProgramResult {
+        instructions::amm::edit::handler(ctx, data)
+    }
+
+    /// close an amm pool without nfts
+    #[inline(always)]
+    pub fn close_amm_pool(ctx: Context<CloseAmmPool>) -> ProgramResult {
+        instructions::amm::close::handler(ctx)
+    }
+
+    /// deposit an nft to the sell side of an amm pool
+    #[inline(always)]
+    pub fn deposit_amm_nft<'info>(
+        ctx: Context<'_, '_, '_, 'info, DepositAmmNft<'info>>,
+    ) -> Result<()> {
+        instructions::amm::deposit_nft::handler(ctx)
+    }
+
+    /// withdraw an nft from an amm pool
+    #[inline(always)]
+    pub fn withdraw_amm_nft<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawAmmNft<'info>>,
+    ) -> Result<()> {
+        instructions::amm::withdraw_nft::handler(ctx)
+    }
+
+    /// buy an nft from an amm pool at its ask, up to max_price
+    #[inline(always)]
+    pub fn amm_buy_nft<'info>(
+        ctx: Context<'_, '_, '_, 'info, AmmBuyNft<'info>>,
+        max_price: u64,
+    ) -> Result<()> {
+        instructions::amm::buy::handler(ctx, max_price)
+    }
+
+    /// sell an nft to an amm pool at its bid, down to min_price
+    #[inline(always)]
+    pub fn amm_sell_nft<'info>(
+        ctx: Context<'_, '_, '_, 'info, AmmSellNft<'info>>,
+        min_price: u64,
+    ) -> Result<()> {
+        instructions::amm::sell::handler(ctx, min_price)
+    }
+
     /// compressed instructions
     #[inline(always)]
     pub fn compressed_init_sell_order<'info>(
@@ -127,4 +320,72 @@ pub mod listings {
     ) -> ProgramResult {
         instructions::compressed::buy::fill::handler(ctx, data)
     }
+
+    /// metaplex core instructions
+    #[inline(always)]
+    pub fn core_init_sell_order<'info>(
+        ctx: Context<'_, '_, '_, 'info, CoreInitSellOrder<'info>>,
+        data: InitOrderData,
+    ) -> Result<()> {
+        instructions::metaplex_core::sell::init::handler(ctx, data)
+    }
+
+    #[inline(always)]
+    pub fn core_fill_sell_order<'info>(
+        ctx: Context<'_, '_, '_, 'info, CoreFillSellOrder<'info>>,
+    ) -> Result<()> {
+        instructions::metaplex_core::sell::fill::handler(ctx)
+    }
+
+    #[inline(always)]
+    pub fn core_close_sell_order(ctx: Context<CoreCloseSellOrder>) -> Result<()> {
+        instructions::metaplex_core::sell::close::handler(ctx)
+    }
+
+    #[inline(always)]
+    pub fn core_fill_buy_order<'info>(
+        ctx: Context<'_, '_, '_, 'info, CoreFillBuyOrder<'info>>,
+    ) -> Result<()> {
+        instructions::metaplex_core::buy::fill::handler(ctx)
+    }
+
+    /// upgrade an order, market or wallet account to the current layout version
+    #[inline(always)]
+    pub fn migrate_account<'info>(
+        ctx: Context<'_, '_, '_, 'info, MigrateAccount<'info>>,
+    ) -> Result<()> {
+        instructions::migrate::account::handler(ctx)
+    }
+
+    /// owner confirms the borsh layout orders of its migrated wallet are migrated
+    #[inline(always)]
+    pub fn complete_wallet_migration(ctx: Context<CompleteWalletMigration>) -> Result<()> {
+        instructions::migrate::complete_wallet::handler(ctx)
+    }
+
+    /// create the order book of a market, only callable by the market initializer
+    #[inline(always)]
+    pub fn init_order_book(ctx: Context<InitOrderBook>) -> Result<()> {
+        instructions::order_book::init::handler(ctx)
+    }
+
+    /// add orders of the market to its order book
+    #[inline(always)]
+    pub fn sync_order_book<'info>(
+        ctx: Context<'_, '_, '_, 'info, SyncOrderBook<'info>>,
+    ) -> Result<()> {
+        instructions::order_book::sync::handler(ctx)
+    }
+
+    /// best bid and bid depth of a market, from candidate orders or the order book
+    #[inline(always)]
+    pub fn get_best_bid<'info>(ctx: Context<'_, '_, '_, 'info, GetQuote<'info>>) -> Result<Quote> {
+        instructions::view::quote::handler(ctx, OrderSide::Buy)
+    }
+
+    /// floor listing and ask depth of a market, from candidate orders or the order book
+    #[inline(always)]
+    pub fn get_floor<'info>(ctx: Context<'_, '_, '_, 'info, GetQuote<'info>>) -> Result<Quote> {
+        instructions::view::quote::handler(ctx, OrderSide::Sell)
+    }
 }
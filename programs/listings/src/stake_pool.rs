@@ -0,0 +1,264 @@
+//! cpi into the spl stake pool `DepositSol` and `WithdrawSol` instructions
+//! the instructions are built by hand to avoid depending on the stake pool crate
+
+use std::convert::TryFrom;
+
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        program::{invoke, invoke_signed},
+    },
+};
+use bridgesplit_program_utils::anchor_lang;
+
+use crate::errors::ListingsError;
+
+pub const DEPOSIT_SOL_TAG: u8 = 14;
+pub const WITHDRAW_SOL_TAG: u8 = 16;
+/// offset of `total_lamports` in the stake pool account
+pub const TOTAL_LAMPORTS_OFFSET: usize = 258;
+/// offset of `pool_token_supply` in the stake pool account
+pub const POOL_TOKEN_SUPPLY_OFFSET: usize = 266;
+/// offset of `epoch_fee`, the fields from here on have a variable size
+pub const EPOCH_FEE_OFFSET: usize = 330;
+/// borsh size of a stake pool `Fee`, denominator and numerator
+const FEE_LEN: usize = 16;
+
+fn read_u64(data: &[u8], offset: usize) -> Result<u64> {
+    let bytes = data
+        .get(offset..offset + 8)
+        .ok_or(ListingsError::InvalidStakePool)?;
+    let mut value = [0; 8];
+    value.copy_from_slice(bytes);
+    Ok(u64::from_le_bytes(value))
+}
+
+/// cursor over the variable size fields of the stake pool account
+struct FieldReader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> FieldReader<'a> {
+    fn skip(&mut self, len: usize) -> Result<()> {
+        require!(
+            self.offset + len <= self.data.len(),
+            ListingsError::InvalidStakePool
+        );
+        self.offset += len;
+        Ok(())
+    }
+
+    fn read_u8(&mut self) -> Result<u8> {
+        let value = *self
+            .data
+            .get(self.offset)
+            .ok_or(ListingsError::InvalidStakePool)?;
+        self.offset += 1;
+        Ok(value)
+    }
+
+    /// `Option<Pubkey>`
+    fn skip_option_pubkey(&mut self) -> Result<()> {
+        match self.read_u8()? {
+            0 => Ok(()),
+            _ => self.skip(32),
+        }
+    }
+
+    /// `FutureEpoch<Fee>`, none or a fee applied at one of the next two epochs
+    fn skip_future_fee(&mut self) -> Result<()> {
+        match self.read_u8()? {
+            0 => Ok(()),
+            _ => self.skip(FEE_LEN),
+        }
+    }
+
+    /// `Fee` as (numerator, denominator)
+    fn read_fee(&mut self) -> Result<(u64, u64)> {
+        let denominator = read_u64(self.data, self.offset)?;
+        let numerator = read_u64(self.data, self.offset + 8)?;
+        self.offset += FEE_LEN;
+        Ok((numerator, denominator))
+    }
+}
+
+/// sol withdrawal fee of the stake pool as (numerator, denominator)
+pub fn get_sol_withdrawal_fee(stake_pool: &AccountInfo) -> Result<(u64, u64)> {
+    let data = stake_pool.try_borrow_data()?;
+    let mut reader = FieldReader {
+        data: &data,
+        offset: EPOCH_FEE_OFFSET,
+    };
+    // epoch fee and next epoch fee
+    reader.skip(FEE_LEN)?;
+    reader.skip_future_fee()?;
+    // preferred deposit and withdraw validators
+    reader.skip_option_pubkey()?;
+    reader.skip_option_pubkey()?;
+    // stake deposit, withdrawal and next withdrawal fees, stake referral fee
+    reader.skip(2 * FEE_LEN)?;
+    reader.skip_future_fee()?;
+    reader.skip(1)?;
+    // sol deposit authority, deposit fee and referral fee
+    reader.skip_option_pubkey()?;
+    reader.skip(FEE_LEN + 1)?;
+    // sol withdraw authority
+    reader.skip_option_pubkey()?;
+    reader.read_fee()
+}
+
+/// total lamports and pool token supply of the stake pool
+pub fn get_pool_totals(stake_pool: &AccountInfo) -> Result<(u64, u64)> {
+    let data = stake_pool.try_borrow_data()?;
+    Ok((
+        read_u64(&data, TOTAL_LAMPORTS_OFFSET)?,
+        read_u64(&data, POOL_TOKEN_SUPPLY_OFFSET)?,
+    ))
+}
+
+/// pool tokens to withdraw to receive at least the given lamports
+/// the sol withdrawal fee is taken in pool tokens, rounded up, before the rest is burnt
+pub fn get_pool_tokens_for_lamports(stake_pool: &AccountInfo, lamports: u64) -> Result<u64> {
+    let (total_lamports, pool_token_supply) = get_pool_totals(stake_pool)?;
+    if total_lamports == 0 {
+        return Err(ListingsError::InvalidStakePool.into());
+    }
+    let burnt = div_ceil(
+        (lamports as u128) * (pool_token_supply as u128),
+        total_lamports as u128,
+    );
+
+    let (fee_numerator, fee_denominator) = get_sol_withdrawal_fee(stake_pool)?;
+    if fee_numerator == 0 || fee_denominator == 0 {
+        return u64::try_from(burnt).map_err(|_| ListingsError::InvalidStakePool.into());
+    }
+    require!(
+        fee_numerator < fee_denominator,
+        ListingsError::InvalidStakePool
+    );
+    // pool_tokens - ceil(pool_tokens * fee) >= burnt
+    let pool_tokens = div_ceil(
+        burnt
+            .checked_mul(fee_denominator as u128)
+            .ok_or(ListingsError::InvalidStakePool)?,
+        (fee_denominator - fee_numerator) as u128,
+    );
+    u64::try_from(pool_tokens).map_err(|_| ListingsError::InvalidStakePool.into())
+}
+
+fn div_ceil(numerator: u128, denominator: u128) -> u128 {
+    numerator / denominator + u128::from(numerator % denominator != 0)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn deposit_sol<'info>(
+    stake_pool_program: AccountInfo<'info>,
+    stake_pool: AccountInfo<'info>,
+    withdraw_authority: AccountInfo<'info>,
+    reserve_stake: AccountInfo<'info>,
+    lamports_from: AccountInfo<'info>,
+    pool_tokens_to: AccountInfo<'info>,
+    manager_fee_account: AccountInfo<'info>,
+    pool_mint: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    lamports: u64,
+) -> Result<()> {
+    let mut data = vec![DEPOSIT_SOL_TAG];
+    data.extend_from_slice(&lamports.to_le_bytes());
+    let instruction = Instruction {
+        program_id: stake_pool_program.key(),
+        accounts: vec![
+            AccountMeta::new(stake_pool.key(), false),
+            AccountMeta::new_readonly(withdraw_authority.key(), false),
+            AccountMeta::new(reserve_stake.key(), false),
+            AccountMeta::new(lamports_from.key(), true),
+            AccountMeta::new(pool_tokens_to.key(), false),
+            AccountMeta::new(manager_fee_account.key(), false),
+            // the manager is the referrer
+            AccountMeta::new(manager_fee_account.key(), false),
+            AccountMeta::new(pool_mint.key(), false),
+            AccountMeta::new_readonly(system_program.key(), false),
+            AccountMeta::new_readonly(token_program.key(), false),
+        ],
+        data,
+    };
+    invoke(
+        &instruction,
+        &[
+            stake_pool,
+            withdraw_authority,
+            reserve_stake,
+            lamports_from,
+            pool_tokens_to,
+            manager_fee_account,
+            pool_mint,
+            system_program,
+            token_program,
+            stake_pool_program,
+        ],
+    )?;
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn withdraw_sol<'info>(
+    stake_pool_program: AccountInfo<'info>,
+    stake_pool: AccountInfo<'info>,
+    withdraw_authority: AccountInfo<'info>,
+    user_transfer_authority: AccountInfo<'info>,
+    pool_tokens_from: AccountInfo<'info>,
+    reserve_stake: AccountInfo<'info>,
+    lamports_to: AccountInfo<'info>,
+    manager_fee_account: AccountInfo<'info>,
+    pool_mint: AccountInfo<'info>,
+    clock: AccountInfo<'info>,
+    stake_history: AccountInfo<'info>,
+    stake_program: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+    pool_tokens: u64,
+) -> Result<()> {
+    let mut data = vec![WITHDRAW_SOL_TAG];
+    data.extend_from_slice(&pool_tokens.to_le_bytes());
+    let instruction = Instruction {
+        program_id: stake_pool_program.key(),
+        accounts: vec![
+            AccountMeta::new(stake_pool.key(), false),
+            AccountMeta::new_readonly(withdraw_authority.key(), false),
+            AccountMeta::new_readonly(user_transfer_authority.key(), true),
+            AccountMeta::new(pool_tokens_from.key(), false),
+            AccountMeta::new(reserve_stake.key(), false),
+            AccountMeta::new(lamports_to.key(), false),
+            AccountMeta::new(manager_fee_account.key(), false),
+            AccountMeta::new(pool_mint.key(), false),
+            AccountMeta::new_readonly(clock.key(), false),
+            AccountMeta::new_readonly(stake_history.key(), false),
+            AccountMeta::new_readonly(stake_program.key(), false),
+            AccountMeta::new_readonly(token_program.key(), false),
+        ],
+        data,
+    };
+    invoke_signed(
+        &instruction,
+        &[
+            stake_pool,
+            withdraw_authority,
+            user_transfer_authority,
+            pool_tokens_from,
+            reserve_stake,
+            lamports_to,
+            manager_fee_account,
+            pool_mint,
+            clock,
+            stake_history,
+            stake_program,
+            token_program,
+            stake_pool_program,
+        ],
+        signer_seeds,
+    )?;
+    Ok(())
+}
@@ -0,0 +1,272 @@
+use std::convert::TryFrom;
+
+use anchor_lang::prelude::*;
+use bridgesplit_program_utils::anchor_lang;
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+
+use crate::errors::ListingsError;
+
+use super::{get_seller_amount, CreatorRoyalty, OrderFillEvent, OrderSide, EVENT_VERSION};
+
+pub const AMM_POOL_VERSION: u8 = 1;
+/// spread and exponential delta are in basis points
+pub const BPS: u64 = 10000;
+/// exponential curves move the spot price by at most 100% per trade
+pub const MAX_EXPONENTIAL_DELTA_BPS: u64 = BPS;
+
+#[account()]
+/// amm pool account - two sided liquidity on a market along a bonding curve
+/// sol is held by the bidding wallet of the owner, nfts by the pool account
+pub struct AmmPool {
+    /// amm pool account version
+    pub version: u8,
+    /// nonce for uniqueness
+    pub nonce: Pubkey,
+    /// market the pool trades on
+    pub market: Pubkey,
+    /// owner of the pool and of the bidding wallet backing it
+    pub owner: Pubkey,
+    /// bidding wallet of the owner
+    pub wallet: Pubkey,
+    /// bonding curve - linear/exponential
+    pub curve: u8,
+    /// price the pool buys at, the pool sells at spot price + spread
+    pub spot_price: u64,
+    /// price change after each trade, lamports for linear curves, bps for exponential curves
+    pub delta: u64,
+    /// markup of the ask over the bid in bps, earned by the owner
+    pub spread_bps: u64,
+    /// number of nfts the pool still buys
+    pub buy_size: u64,
+    /// nfts held by the pool
+    pub nft_count: u64,
+    /// amm pool account creation time
+    pub init_time: i64,
+    /// last time the pool was edited or traded
+    pub last_edit_time: i64,
+    /// reserved space for future changes
+    reserve: [u8; 128],
+}
+
+#[derive(
+    AnchorDeserialize, AnchorSerialize, Clone, Copy, PartialEq, IntoPrimitive, TryFromPrimitive,
+)]
+#[repr(u8)]
+/// price curve of an amm pool
+pub enum AmmCurve {
+    /// spot price moves by delta lamports
+    Linear,
+    /// spot price moves by delta bps
+    Exponential,
+}
+
+#[derive(IntoPrimitive)]
+#[repr(u8)]
+pub enum AmmPoolEditType {
+    Init,
+    Edit,
+    DepositNft,
+    WithdrawNft,
+    Buy,
+    Sell,
+    Close,
+}
+
+#[event]
+pub struct AmmPoolEditEvent {
+    /// layout version of the event
+    pub event_version: u8,
+    pub edit_type: u8,
+    pub address: Pubkey,
+    pub version: u8,
+    pub market: Pubkey,
+    pub owner: Pubkey,
+    pub wallet: Pubkey,
+    pub curve: u8,
+    pub spot_price: u64,
+    pub delta: u64,
+    pub spread_bps: u64,
+    pub buy_size: u64,
+    pub nft_count: u64,
+    pub last_edit_time: i64,
+}
+
+impl AmmPool {
+    /// initialize a new amm pool account
+    pub fn init(
+        &mut self,
+        nonce: Pubkey,
+        market: Pubkey,
+        owner: Pubkey,
+        wallet: Pubkey,
+        time: i64,
+    ) {
+        self.version = AMM_POOL_VERSION;
+        self.nonce = nonce;
+        self.market = market;
+        self.owner = owner;
+        self.wallet = wallet;
+        self.init_time = time;
+        self.last_edit_time = time;
+    }
+
+    /// set the curve parameters of the pool
+    pub fn edit(
+        &mut self,
+        curve: u8,
+        spot_price: u64,
+        delta: u64,
+        spread_bps: u64,
+        buy_size: u64,
+        time: i64,
+    ) -> Result<()> {
+        let amm_curve = AmmCurve::try_from(curve).map_err(|_| ListingsError::InvalidCurve)?;
+        require!(
+            spot_price > 0 && spread_bps <= BPS,
+            ListingsError::InvalidCurve
+        );
+        require!(
+            amm_curve == AmmCurve::Linear || delta <= MAX_EXPONENTIAL_DELTA_BPS,
+            ListingsError::InvalidCurve
+        );
+        self.curve = curve;
+        self.spot_price = spot_price;
+        self.delta = delta;
+        self.spread_bps = spread_bps;
+        self.buy_size = buy_size;
+        self.last_edit_time = time;
+        Ok(())
+    }
+
+    /// bps of a markup on top of the price
+    fn add_bps(bps: u64) -> Result<u64> {
+        BPS.checked_add(bps)
+            .ok_or_else(|| ListingsError::PriceOutOfRange.into())
+    }
+
+    fn mul_div(amount: u64, numerator: u64, denominator: u64) -> Result<u64> {
+        let result = (amount as u128) * (numerator as u128) / (denominator as u128);
+        u64::try_from(result).map_err(|_| ListingsError::PriceOutOfRange.into())
+    }
+
+    /// price the pool buys an nft at
+    pub fn get_bid_price(&self) -> u64 {
+        self.spot_price
+    }
+
+    /// price the pool sells an nft at
+    pub fn get_ask_price(&self) -> Result<u64> {
+        Self::mul_div(self.spot_price, Self::add_bps(self.spread_bps)?, BPS)
+    }
+
+    /// spot price after the pool sold an nft
+    fn get_next_price_up(&self) -> Result<u64> {
+        if self.curve == u8::from(AmmCurve::Linear) {
+            self.spot_price
+                .checked_add(self.delta)
+                .ok_or_else(|| ListingsError::PriceOutOfRange.into())
+        } else {
+            Self::mul_div(self.spot_price, Self::add_bps(self.delta)?, BPS)
+        }
+    }
+
+    /// spot price after the pool bought an nft, can reach 0 which stops the pool from buying
+    fn get_next_price_down(&self) -> Result<u64> {
+        if self.curve == u8::from(AmmCurve::Linear) {
+            Ok(self.spot_price.saturating_sub(self.delta))
+        } else {
+            Self::mul_div(self.spot_price, BPS, Self::add_bps(self.delta)?)
+        }
+    }
+
+    /// record an nft deposited to the sell side of the pool
+    pub fn add_nft(&mut self, time: i64) -> Result<()> {
+        self.nft_count = self
+            .nft_count
+            .checked_add(1)
+            .ok_or(ListingsError::AmmPoolCountOverflow)?;
+        self.last_edit_time = time;
+        Ok(())
+    }
+
+    /// record an nft withdrawn from the sell side of the pool
+    pub fn remove_nft(&mut self, time: i64) -> Result<()> {
+        self.nft_count = self
+            .nft_count
+            .checked_sub(1)
+            .ok_or(ListingsError::AmmPoolCountOverflow)?;
+        self.last_edit_time = time;
+        Ok(())
+    }
+
+    /// record an nft bought by the pool at the bid price
+    pub fn record_buy(&mut self, time: i64) -> Result<()> {
+        self.spot_price = self.get_next_price_down()?;
+        self.buy_size = self
+            .buy_size
+            .checked_sub(1)
+            .ok_or(ListingsError::AmmPoolCountOverflow)?;
+        self.add_nft(time)
+    }
+
+    /// record an nft sold by the pool at the ask price
+    pub fn record_sell(&mut self, time: i64) -> Result<()> {
+        self.spot_price = self.get_next_price_up()?;
+        self.remove_nft(time)
+    }
+
+    pub fn get_edit_event(&self, address: Pubkey, edit_type: AmmPoolEditType) -> AmmPoolEditEvent {
+        AmmPoolEditEvent {
+            event_version: EVENT_VERSION,
+            edit_type: edit_type.into(),
+            address,
+            version: self.version,
+            market: self.market,
+            owner: self.owner,
+            wallet: self.wallet,
+            curve: self.curve,
+            spot_price: self.spot_price,
+            delta: self.delta,
+            spread_bps: self.spread_bps,
+            buy_size: self.buy_size,
+            nft_count: self.nft_count,
+            last_edit_time: self.last_edit_time,
+        }
+    }
+
+    /// fill event in the layout of order fills, side is the side of the pool
+    #[allow(clippy::too_many_arguments)]
+    pub fn get_fill_event(
+        &self,
+        address: Pubkey,
+        pool_mint: Pubkey,
+        side: OrderSide,
+        buyer: Pubkey,
+        seller: Pubkey,
+        nft_mint: Pubkey,
+        price: u64,
+        protocol_fee: u64,
+        royalties: Vec<CreatorRoyalty>,
+        ovol_exempt: bool,
+        time: i64,
+    ) -> OrderFillEvent {
+        let royalty_total: u64 = royalties.iter().map(|royalty| royalty.amount).sum();
+        OrderFillEvent {
+            event_version: EVENT_VERSION,
+            address,
+            market: self.market,
+            pool_mint,
+            side: side.into(),
+            buyer,
+            seller,
+            nft_mint,
+            price,
+            seller_amount: get_seller_amount(side.into(), price, protocol_fee, royalty_total),
+            protocol_fee,
+            royalty_total,
+            royalties,
+            ovol_exempt,
+            fill_time: time,
+        }
+    }
+}
@@ -0,0 +1,353 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{
+        allocate, assign, create_account, transfer, Allocate, Assign, CreateAccount, Transfer,
+    },
+};
+use bridgesplit_program_utils::anchor_lang;
+use num_enum::IntoPrimitive;
+
+use crate::errors::ListingsError;
+
+use super::{EVENT_VERSION, POOL_NFT_SEED};
+
+pub const BID_POOL_VERSION: u8 = 1;
+
+#[account()]
+/// bid pool account - owner of a bidding wallet funded by several depositors
+/// the manager bids as delegate of the wallet, nfts bought are held by the pool
+pub struct BidPool {
+    /// bid pool account version
+    pub version: u8,
+    /// nonce for uniqueness
+    pub nonce: Pubkey,
+    /// places and manages the bids of the pool
+    pub manager: Pubkey,
+    /// shares issued to the depositors
+    pub total_shares: u64,
+    /// nfts bought by the pool and not yet redeemed
+    pub nft_count: u64,
+    /// price paid for the nfts held by the pool
+    pub nft_cost: u64,
+    /// reserved space for future changes
+    reserve: [u8; 256],
+}
+
+#[account()]
+/// shares of a depositor in a bid pool
+pub struct PoolShare {
+    /// pool share account version
+    pub version: u8,
+    pub bid_pool: Pubkey,
+    pub depositor: Pubkey,
+    pub shares: u64,
+    /// reserved space for future changes
+    reserve: [u8; 64],
+}
+
+#[account()]
+/// cost of the units of a mint bought by a bid pool, redeemed units are valued at this cost
+pub struct PoolNft {
+    /// pool nft account version
+    pub version: u8,
+    pub bid_pool: Pubkey,
+    pub nft_mint: Pubkey,
+    /// units of the mint held by the pool
+    pub count: u64,
+    /// price paid for the units held
+    pub cost: u64,
+    /// reserved space for future changes
+    reserve: [u8; 64],
+}
+
+#[derive(IntoPrimitive)]
+#[repr(u8)]
+pub enum BidPoolEditType {
+    Init,
+    Deposit,
+    RedeemSol,
+    RedeemNft,
+    Fill,
+}
+
+#[event]
+pub struct BidPoolEditEvent {
+    /// layout version of the event
+    pub event_version: u8,
+    pub edit_type: u8,
+    pub address: Pubkey,
+    pub version: u8,
+    pub manager: Pubkey,
+    pub total_shares: u64,
+    pub nft_count: u64,
+    pub nft_cost: u64,
+    /// depositor of the edit, default for pool wide edits
+    pub depositor: Pubkey,
+    pub depositor_shares: u64,
+}
+
+impl BidPool {
+    /// initialize a new bid pool account
+    pub fn init(&mut self, nonce: Pubkey, manager: Pubkey) {
+        self.version = BID_POOL_VERSION;
+        self.nonce = nonce;
+        self.manager = manager;
+    }
+
+    /// return true if the account is a bid pool of this program
+    pub fn is_bid_pool(account: &AccountInfo) -> bool {
+        account.owner == &crate::ID
+            && account
+                .try_borrow_data()
+                .map(|data| data.starts_with(&BidPool::discriminator()))
+                .unwrap_or(false)
+    }
+
+    /// value of the pool, nfts are valued at cost
+    pub fn get_value(&self, wallet_balance: u64) -> u64 {
+        wallet_balance.saturating_add(self.nft_cost)
+    }
+
+    fn mul_div(amount: u64, numerator: u64, denominator: u64, round_up: bool) -> Result<u64> {
+        let product = (amount as u128) * (numerator as u128);
+        let denominator = denominator as u128;
+        if denominator == 0 {
+            return Err(ListingsError::ZeroShares.into());
+        }
+        let result = if round_up {
+            (product + denominator - 1) / denominator
+        } else {
+            product / denominator
+        };
+        Ok(result as u64)
+    }
+
+    /// issue shares for a deposit at the current value of the pool
+    pub fn deposit(&mut self, amount: u64, wallet_balance: u64) -> Result<u64> {
+        let value = self.get_value(wallet_balance);
+        let shares = if self.total_shares == 0 || value == 0 {
+            amount
+        } else {
+            Self::mul_div(amount, self.total_shares, value, false)?
+        };
+        if shares == 0 {
+            return Err(ListingsError::ZeroShares.into());
+        }
+        self.total_shares = self
+            .total_shares
+            .checked_add(shares)
+            .ok_or(ListingsError::BalanceOverflow)?;
+        Ok(shares)
+    }
+
+    /// burn shares for their value in lamports, only the sol held by the pool can be redeemed
+    pub fn redeem_sol(&mut self, shares: u64, wallet_balance: u64) -> Result<u64> {
+        let lamports = Self::mul_div(
+            shares,
+            self.get_value(wallet_balance),
+            self.total_shares,
+            false,
+        )?;
+        if lamports > wallet_balance {
+            return Err(ListingsError::InsufficientPoolLiquidity.into());
+        }
+        self.total_shares = self
+            .total_shares
+            .checked_sub(shares)
+            .ok_or(ListingsError::InsufficientShares)?;
+        Ok(lamports)
+    }
+
+    /// burn the shares worth the cost of the redeemed nft and return the shares burnt
+    pub fn redeem_nft(&mut self, nft_cost: u64, wallet_balance: u64) -> Result<u64> {
+        let shares = Self::mul_div(
+            nft_cost,
+            self.total_shares,
+            self.get_value(wallet_balance),
+            true,
+        )?;
+        self.total_shares = self
+            .total_shares
+            .checked_sub(shares)
+            .ok_or(ListingsError::InsufficientShares)?;
+        self.nft_count = self
+            .nft_count
+            .checked_sub(1)
+            .ok_or(ListingsError::NoPoolNfts)?;
+        self.nft_cost = self
+            .nft_cost
+            .checked_sub(nft_cost)
+            .ok_or(ListingsError::BalanceOverflow)?;
+        Ok(shares)
+    }
+
+    /// record an nft bought by a bid of the pool, the price moves from the wallet to the nfts
+    pub fn record_fill(account: &AccountInfo, price: u64) -> Result<BidPoolEditEvent> {
+        let mut bid_pool = BidPool::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+        bid_pool.nft_count = bid_pool
+            .nft_count
+            .checked_add(1)
+            .ok_or(ListingsError::BalanceOverflow)?;
+        bid_pool.nft_cost = bid_pool
+            .nft_cost
+            .checked_add(price)
+            .ok_or(ListingsError::BalanceOverflow)?;
+        bid_pool.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])?;
+        Ok(bid_pool.get_edit_event(account.key(), BidPoolEditType::Fill, Pubkey::default(), 0))
+    }
+
+    pub fn get_edit_event(
+        &self,
+        address: Pubkey,
+        edit_type: BidPoolEditType,
+        depositor: Pubkey,
+        depositor_shares: u64,
+    ) -> BidPoolEditEvent {
+        BidPoolEditEvent {
+            event_version: EVENT_VERSION,
+            edit_type: edit_type.into(),
+            address,
+            version: self.version,
+            manager: self.manager,
+            total_shares: self.total_shares,
+            nft_count: self.nft_count,
+            nft_cost: self.nft_cost,
+            depositor,
+            depositor_shares,
+        }
+    }
+}
+
+impl PoolShare {
+    /// initialize the pool share account of a depositor on the first deposit
+    pub fn init(&mut self, bid_pool: Pubkey, depositor: Pubkey) {
+        if self.version == 0 {
+            self.version = BID_POOL_VERSION;
+            self.bid_pool = bid_pool;
+            self.depositor = depositor;
+        }
+    }
+}
+
+impl PoolNft {
+    /// record the price paid for a unit of the mint, the account is created with the first unit
+    pub fn record_fill<'info>(
+        account: &AccountInfo<'info>,
+        bid_pool: Pubkey,
+        nft_mint: Pubkey,
+        payer: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+        price: u64,
+    ) -> Result<()> {
+        let (address, bump) = Pubkey::find_program_address(
+            &[POOL_NFT_SEED.as_ref(), bid_pool.as_ref(), nft_mint.as_ref()],
+            &crate::ID,
+        );
+        if account.key() != address {
+            return Err(ListingsError::InvalidPoolNft.into());
+        }
+        let mut pool_nft = if account.owner == &crate::ID {
+            PoolNft::try_deserialize(&mut &account.try_borrow_data()?[..])?
+        } else {
+            let signer_seeds: &[&[&[u8]]] = &[&[
+                POOL_NFT_SEED.as_ref(),
+                bid_pool.as_ref(),
+                nft_mint.as_ref(),
+                &[bump],
+            ]];
+            Self::create(account, payer, system_program, signer_seeds)?;
+            PoolNft {
+                version: BID_POOL_VERSION,
+                bid_pool,
+                nft_mint,
+                count: 0,
+                cost: 0,
+                reserve: [0; 64],
+            }
+        };
+        pool_nft.count = pool_nft
+            .count
+            .checked_add(1)
+            .ok_or(ListingsError::BalanceOverflow)?;
+        pool_nft.cost = pool_nft
+            .cost
+            .checked_add(price)
+            .ok_or(ListingsError::BalanceOverflow)?;
+        pool_nft.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])?;
+        Ok(())
+    }
+
+    /// create the account, a prefunded address is topped up to the rent exemption
+    fn create<'info>(
+        account: &AccountInfo<'info>,
+        payer: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let space = 8 + std::mem::size_of::<PoolNft>();
+        let rent = Rent::get()?.minimum_balance(space);
+        if account.lamports() == 0 {
+            return create_account(
+                CpiContext::new_with_signer(
+                    system_program.clone(),
+                    CreateAccount {
+                        from: payer.clone(),
+                        to: account.clone(),
+                    },
+                    signer_seeds,
+                ),
+                rent,
+                space as u64,
+                &crate::ID,
+            );
+        }
+        let top_up = rent.saturating_sub(account.lamports());
+        if top_up > 0 {
+            transfer(
+                CpiContext::new(
+                    system_program.clone(),
+                    Transfer {
+                        from: payer.clone(),
+                        to: account.clone(),
+                    },
+                ),
+                top_up,
+            )?;
+        }
+        allocate(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                Allocate {
+                    account_to_allocate: account.clone(),
+                },
+                signer_seeds,
+            ),
+            space as u64,
+        )?;
+        assign(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                Assign {
+                    account_to_assign: account.clone(),
+                },
+                signer_seeds,
+            ),
+            &crate::ID,
+        )
+    }
+
+    /// take a unit out of the record and return its cost, the last unit takes the remainder
+    pub fn redeem(&mut self) -> Result<u64> {
+        if self.count == 0 {
+            return Err(ListingsError::NoPoolNfts.into());
+        }
+        let unit_cost = if self.count == 1 {
+            self.cost
+        } else {
+            self.cost / self.count
+        };
+        self.count -= 1;
+        self.cost -= unit_cost;
+        Ok(unit_cost)
+    }
+}
@@ -0,0 +1,134 @@
+//! superseded event and account layouts
+//! string encoded events emitted before the switch to `Pubkey` fields are kept in the idl
+//! so indexers can still decode historical transactions
+//! layouts of the `Pubkey` events at event version 1, their order fill event is unchanged
+//! borsh account layouts are read once to migrate the account to its zero copy layout
+use anchor_lang::prelude::*;
+use bridgesplit_program_utils::anchor_lang;
+
+#[event]
+pub struct OrderEditEvent {
+    pub edit_type: u8,
+    pub address: String,
+    pub version: u8,
+    pub nonce: String,
+    pub market: String,
+    pub owner: String,
+    pub wallet: String,
+    pub side: u8,
+    pub size: u64,
+    pub price: u64,
+    pub state: u8,
+    pub init_time: i64,
+    pub last_edit_time: i64,
+    pub nft_mint: String,
+    pub pool_mint: String,
+}
+
+#[event]
+pub struct WalletEditEvent {
+    pub edit_type: u8,
+    pub address: String,
+    pub version: u8,
+    pub owner: String,
+    pub balance: u64,
+}
+
+#[event]
+pub struct MarketEditEvent {
+    pub edit_type: u8,
+    pub address: String,
+    pub version: u8,
+    pub pool_mint: String,
+    pub initializer: String,
+    pub state: u8,
+}
+
+/// `OrderEditEventV2` at event version 1
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct OrderEditEventV1 {
+    pub event_version: u8,
+    pub edit_type: u8,
+    pub address: Pubkey,
+    pub version: u8,
+    pub nonce: Pubkey,
+    pub market: Pubkey,
+    pub owner: Pubkey,
+    pub wallet: Pubkey,
+    pub side: u8,
+    pub size: u64,
+    pub price: u64,
+    pub state: u8,
+    pub init_time: i64,
+    pub last_edit_time: i64,
+    pub nft_mint: Pubkey,
+    pub pool_mint: Pubkey,
+}
+
+/// `WalletEditEventV2` at event version 1
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct WalletEditEventV1 {
+    pub event_version: u8,
+    pub edit_type: u8,
+    pub address: Pubkey,
+    pub version: u8,
+    pub owner: Pubkey,
+    pub balance: u64,
+}
+
+/// `MarketEditEventV2` at event version 1
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct MarketEditEventV1 {
+    pub event_version: u8,
+    pub edit_type: u8,
+    pub address: Pubkey,
+    pub version: u8,
+    pub pool_mint: Pubkey,
+    pub initializer: Pubkey,
+    pub state: u8,
+}
+
+/// borsh layout of order versions 1 and 2, the trailing reserve is not read
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct LegacyOrder {
+    pub version: u8,
+    pub nonce: Pubkey,
+    pub market: Pubkey,
+    pub owner: Pubkey,
+    pub wallet: Pubkey,
+    pub side: u8,
+    pub size: u64,
+    pub price: u64,
+    pub state: u8,
+    pub init_time: i64,
+    pub last_edit_time: i64,
+    pub nft_mint: Pubkey,
+    pub fees_on: bool,
+    pub price_mode: u8,
+    pub trailing_bps: u16,
+    pub max_price: u64,
+    pub trigger_price: u64,
+    pub listing_mode: u8,
+    pub original_size: u64,
+    pub filled_size: u64,
+    pub filled_value: u64,
+}
+
+/// borsh layout of wallet version 1, the trailing reserve is not read
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct LegacyWallet {
+    pub version: u8,
+    pub owner: Pubkey,
+    pub balance: u64,
+    pub delegate: Pubkey,
+    pub delegate_spending_cap: u64,
+    pub open_buy_orders: u32,
+    pub open_sell_orders: u32,
+    pub withdrawal_delay: i64,
+    pub pending_withdrawal: u64,
+    pub withdrawal_unlock_time: i64,
+    pub pending_withdrawal_delay: i64,
+    pub withdrawal_delay_unlock_time: i64,
+    pub staked_balance: u64,
+    pub staked_lamports: u64,
+}
@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+use bridgesplit_program_utils::anchor_lang;
+
+use crate::errors::ListingsError;
+
+pub const LST_CONFIG_VERSION: u8 = 1;
+
+#[account()]
+/// protocol wide liquid staking config - stake pool holding staked wallet balances
+pub struct LstConfig {
+    /// config account version
+    pub version: u8,
+    /// spl stake pool program or a program with the same deposit and withdraw sol interface
+    pub stake_pool_program: Pubkey,
+    /// stake pool wallets deposit into
+    pub stake_pool: Pubkey,
+    /// liquid staking token minted by the stake pool
+    pub pool_mint: Pubkey,
+    /// new deposits are rejected when disabled, unstaking is always possible
+    pub enabled: bool,
+    /// pool tokens held by all wallets, the stake pool can't change while any are held
+    pub total_staked: u64,
+    /// reserved space for future changes
+    reserve: [u8; 120],
+}
+
+impl LstConfig {
+    /// set the stake pool used for wallet deposits
+    /// wallets only hold tokens of the current pool, so it can only change once all are unstaked
+    pub fn set(
+        &mut self,
+        stake_pool_program: Pubkey,
+        stake_pool: Pubkey,
+        pool_mint: Pubkey,
+        enabled: bool,
+    ) -> Result<()> {
+        let is_same_pool = self.stake_pool_program == stake_pool_program
+            && self.stake_pool == stake_pool
+            && self.pool_mint == pool_mint;
+        require!(
+            is_same_pool || self.total_staked == 0,
+            ListingsError::StakePoolInUse
+        );
+        self.version = LST_CONFIG_VERSION;
+        self.stake_pool_program = stake_pool_program;
+        self.stake_pool = stake_pool;
+        self.pool_mint = pool_mint;
+        self.enabled = enabled;
+        Ok(())
+    }
+
+    /// track pool tokens deposited by a wallet
+    pub fn record_stake(&mut self, pool_tokens: u64) -> Result<()> {
+        self.total_staked = self
+            .total_staked
+            .checked_add(pool_tokens)
+            .ok_or(ListingsError::BalanceOverflow)?;
+        Ok(())
+    }
+
+    /// track pool tokens withdrawn by a wallet
+    pub fn record_unstake(&mut self, pool_tokens: u64) -> Result<()> {
+        self.total_staked = self
+            .total_staked
+            .checked_sub(pool_tokens)
+            .ok_or(ListingsError::BalanceOverflow)?;
+        Ok(())
+    }
+}
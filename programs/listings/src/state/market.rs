@@ -1,8 +1,12 @@
 use anchor_lang::prelude::*;
 use bridgesplit_program_utils::anchor_lang;
-use num_enum::IntoPrimitive;
+use num_enum::{IntoPrimitive, TryFromPrimitive};
 
-pub const MARKET_VERSION: u8 = 1;
+use crate::errors::ListingsError;
+
+use super::{EVENT_VERSION, MAX_WITHDRAWAL_DELAY};
+
+pub const MARKET_VERSION: u8 = 2;
 
 #[account()]
 pub struct Market {
@@ -14,8 +18,14 @@ pub struct Market {
     pub initializer: Pubkey,
     /// state representing the market - open/closed
     pub state: u8,
+    /// how new listings hold their nft - frozen in place/escrowed
+    pub listing_mode: u8,
+    /// minimum withdrawal delay of the wallets funding bids of the market, 0 for none
+    pub min_withdrawal_delay: i64,
+    /// true once the order book of the market is created, its orders then keep it in sync
+    pub has_order_book: bool,
     /// reserved space for future changes
-    pub reserve: [u8; 512],
+    pub reserve: [u8; 502],
 }
 
 #[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, PartialEq, IntoPrimitive)]
@@ -27,20 +37,37 @@ pub enum MarketState {
     Closed,
 }
 
+#[derive(
+    AnchorDeserialize, AnchorSerialize, Clone, Copy, PartialEq, IntoPrimitive, TryFromPrimitive,
+)]
+#[repr(u8)]
+/// how a sell order of the market holds the listed nft
+pub enum ListingMode {
+    /// nft stays in the seller's token account, frozen with the bidding wallet as delegate
+    FreezeInPlace,
+    /// nft is moved into a token account owned by the bidding wallet
+    Escrow,
+}
+
 #[derive(IntoPrimitive)]
 #[repr(u8)]
 pub enum MarketEditType {
     Init,
+    Edit,
 }
 
 #[event]
-pub struct MarketEditEvent {
+pub struct MarketEditEventV2 {
+    /// layout version of the event
+    pub event_version: u8,
     pub edit_type: u8,
-    pub address: String,
+    pub address: Pubkey,
     pub version: u8,
-    pub pool_mint: String,
-    pub initializer: String,
+    pub pool_mint: Pubkey,
+    pub initializer: Pubkey,
     pub state: u8,
+    pub listing_mode: u8,
+    pub min_withdrawal_delay: i64,
 }
 
 impl Market {
@@ -57,18 +84,39 @@ impl Market {
         state != <MarketState as Into<u8>>::into(MarketState::Closed)
     }
 
+    /// set the listing mode of new sell orders, existing orders keep their mode
+    pub fn set_listing_mode(&mut self, listing_mode: u8) -> Result<()> {
+        ListingMode::try_from(listing_mode).map_err(|_| ListingsError::InvalidListingMode)?;
+        self.listing_mode = listing_mode;
+        Ok(())
+    }
+
+    /// set the minimum withdrawal delay of wallets placing or editing bids on the market
+    /// open bids are not affected
+    pub fn set_min_withdrawal_delay(&mut self, delay: i64) -> Result<()> {
+        require!(
+            (0..=MAX_WITHDRAWAL_DELAY).contains(&delay),
+            ListingsError::InvalidWithdrawalDelay
+        );
+        self.min_withdrawal_delay = delay;
+        Ok(())
+    }
+
     pub fn get_edit_event(
         &mut self,
         address: Pubkey,
         edit_type: MarketEditType,
-    ) -> MarketEditEvent {
-        MarketEditEvent {
+    ) -> MarketEditEventV2 {
+        MarketEditEventV2 {
+            event_version: EVENT_VERSION,
             edit_type: edit_type.into(),
-            address: address.to_string(),
+            address,
             version: self.version,
-            pool_mint: self.pool_mint.to_string(),
-            initializer: self.initializer.to_string(),
+            pool_mint: self.pool_mint,
+            initializer: self.initializer,
             state: self.state,
+            listing_mode: self.listing_mode,
+            min_withdrawal_delay: self.min_withdrawal_delay,
         }
     }
 }
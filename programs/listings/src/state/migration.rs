@@ -0,0 +1,134 @@
+use anchor_lang::{prelude::*, ZeroCopy};
+use bridgesplit_program_utils::anchor_lang;
+
+use crate::errors::ListingsError;
+
+use super::{
+    legacy::{LegacyOrder, LegacyWallet},
+    ListingMode, Market, Order, Wallet, MARKET_VERSION, ORDER_VERSION, WALLET_VERSION,
+};
+
+/// account with a layout version that can be upgraded in place
+/// new fields are carved out of the reserve, which reads as zero on accounts of older versions
+pub trait Versioned {
+    /// layout version written by this program
+    const CURRENT_VERSION: u8;
+
+    fn version(&self) -> u8;
+
+    fn set_version(&mut self, version: u8);
+
+    /// set the fields added by the version after `version`
+    fn upgrade_from(&mut self, version: u8);
+
+    /// upgrade the account one version at a time, returns false if it is already current
+    /// uninitialized accounts and versions written by a newer program are rejected
+    fn migrate(&mut self) -> Result<bool> {
+        let version = self.version();
+        require!(
+            version != 0 && version <= Self::CURRENT_VERSION,
+            ListingsError::UnsupportedAccountVersion
+        );
+        if version == Self::CURRENT_VERSION {
+            return Ok(false);
+        }
+        for from in version..Self::CURRENT_VERSION {
+            self.upgrade_from(from);
+        }
+        self.set_version(Self::CURRENT_VERSION);
+        Ok(true)
+    }
+}
+
+/// zero copy account whose older versions were written with a borsh layout
+/// the version is the first byte of both layouts, so it tells them apart
+pub trait ZeroCopyMigration: Versioned + ZeroCopy + Owner {
+    /// first version written with the zero copy layout
+    const ZERO_COPY_VERSION: u8;
+    /// borsh layout of the versions before the switch
+    type Legacy: AnchorDeserialize + Into<Self>;
+
+    /// read an account of a borsh layout version, none if it already has the zero copy layout
+    fn read_legacy(info: &AccountInfo) -> Result<Option<Self>> {
+        let data = info.try_borrow_data()?;
+        let version = *data.get(8).ok_or(ErrorCode::AccountDidNotDeserialize)?;
+        if version == 0 || version >= Self::ZERO_COPY_VERSION {
+            return Ok(None);
+        }
+        let legacy = Self::Legacy::deserialize(&mut &data[8..])?;
+        Ok(Some(legacy.into()))
+    }
+}
+
+impl Versioned for Order {
+    const CURRENT_VERSION: u8 = ORDER_VERSION;
+
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn set_version(&mut self, version: u8) {
+        self.version = version;
+    }
+
+    fn upgrade_from(&mut self, version: u8) {
+        // 2 - original size of the fill progress
+        if version == 1 {
+            self.original_size = self.filled_size + self.size;
+        }
+        // 3 - zero copy layout, converted by `ZeroCopyMigration::read_legacy`
+    }
+}
+
+impl ZeroCopyMigration for Order {
+    const ZERO_COPY_VERSION: u8 = 3;
+    type Legacy = LegacyOrder;
+}
+
+impl Versioned for Market {
+    const CURRENT_VERSION: u8 = MARKET_VERSION;
+
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn set_version(&mut self, version: u8) {
+        self.version = version;
+    }
+
+    fn upgrade_from(&mut self, version: u8) {
+        // 2 - listing mode, markets created before escrow listings freeze in place
+        if version == 1 {
+            self.listing_mode = ListingMode::FreezeInPlace.into();
+        }
+    }
+}
+
+impl Versioned for Wallet {
+    const CURRENT_VERSION: u8 = WALLET_VERSION;
+
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn set_version(&mut self, version: u8) {
+        self.version = version;
+    }
+
+    fn upgrade_from(&mut self, version: u8) {
+        // 2 - zero copy layout, converted by `ZeroCopyMigration::read_legacy`
+        // orders opened before the counters existed were never counted, the counters are
+        // rebuilt by migrating each borsh layout order of the wallet, see `migrate_account`
+        // until then the counters may miss open orders, so closing the wallet is blocked
+        if version == 1 {
+            self.open_buy_orders = 0;
+            self.open_sell_orders = 0;
+            self.uncounted_legacy_orders = 1;
+        }
+    }
+}
+
+impl ZeroCopyMigration for Wallet {
+    const ZERO_COPY_VERSION: u8 = 2;
+    type Legacy = LegacyWallet;
+}
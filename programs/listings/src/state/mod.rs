@@ -1,14 +1,36 @@
 pub const MARKET_SEED: &str = "market";
 pub const ORDER_SEED: &str = "order";
 pub const WALLET_SEED: &str = "wallet";
+pub const LST_CONFIG_SEED: &str = "lst_config";
+pub const BID_POOL_SEED: &str = "bid_pool";
+pub const POOL_SHARE_SEED: &str = "pool_share";
+pub const POOL_NFT_SEED: &str = "pool_nft";
+pub const AMM_POOL_SEED: &str = "amm_pool";
+pub const ORDER_BOOK_SEED: &str = "order_book";
 
 pub const PROTOCOL_FEES_BPS: u64 = 50;
+/// max share of the price paid to the crank matching a crossing bid and listing
+pub const MATCH_FEE_BPS: u64 = 10;
 pub const PROTOCOL_TREASURY: &str = "ovo1kT7RqrAZwFtgSGEgNfa7nHjeZoK6ykg1GknJEXG";
 
+/// layout version of the emitted events, bumped once per release that changes an event layout
+pub const EVENT_VERSION: u8 = 2;
+
+pub mod amm;
+pub mod bid_pool;
+pub mod legacy;
+pub mod lst;
 pub mod market;
+pub mod migration;
 pub mod order;
+pub mod order_book;
 pub mod wallet;
 
+pub use amm::*;
+pub use bid_pool::*;
+pub use lst::*;
 pub use market::*;
+pub use migration::*;
 pub use order::*;
+pub use order_book::*;
 pub use wallet::*;
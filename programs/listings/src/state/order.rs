@@ -2,13 +2,30 @@ use anchor_lang::prelude::*;
 use bridgesplit_program_utils::anchor_lang;
 use num_enum::IntoPrimitive;
 
-pub const ORDER_VERSION: u8 = 1;
+use crate::errors::ListingsError;
 
-#[account()]
+use super::{legacy::LegacyOrder, ListingMode, EVENT_VERSION};
+
+pub const ORDER_VERSION: u8 = 3;
+
+#[account(zero_copy)]
 /// order account - each listing has one order account
+/// fields are ordered by alignment so the repr(C) layout needs no implicit padding
 pub struct Order {
     /// order account version
     pub version: u8,
+    /// type of order - buy/sell
+    pub side: u8,
+    /// order state - ready/partial/closed
+    pub state: u8,
+    /// fees on for this order, 0 or 1
+    pub fees_on: u8,
+    /// how the price of a buy order is set - fixed/trailing listing
+    pub price_mode: u8,
+    /// listing mode of the market when the sell order was created
+    pub listing_mode: u8,
+    /// discount of a trailing bid to the reference listing in bps
+    pub trailing_bps: u16,
     /// nonce for uniqueness
     pub nonce: Pubkey,
     /// market to which the order belongs to, must be init'd
@@ -17,37 +34,36 @@ pub struct Order {
     pub owner: Pubkey,
     /// bidding wallet of the owner
     pub wallet: Pubkey,
-    /// type of order - buy/sell
-    pub side: u8,
-    /// number of bids order is making
-    /// always for 1 for sell
+    /// nft mint in case order is a sell order
+    pub nft_mint: Pubkey,
+    /// number of units left, bids for a buy order and listed units for a sell order
+    /// above 1 for a sell order only for semi-fungible assets
     pub size: u64,
     /// bid amount in lamports
     pub price: u64,
-    /// order state - ready/partial/closed
-    pub state: u8,
     /// order account creation time
     pub init_time: i64,
     /// last time the order was edited
     pub last_edit_time: i64,
-    /// nft mint in case order is a sell order
-    pub nft_mint: Pubkey,
-    /// fees on for this order
-    pub fees_on: bool,
-    /// reserved space for future changes split up due to serialization constraints
-    reserve_0: [u8; 256],
-    /// reserved space for future changes
-    reserve_1: [u8; 128],
-    /// reserved space for future changes
-    reserve_2: [u8; 64],
-    /// reserved space for future changes
-    reserve_3: [u8; 30],
-    /// reserved space for future changes
-    reserve_4: [u8; 30],
-    /// reserved space for future changes
-    reserve_5: [u8; 3],
+    /// cap of the price of a trailing bid, the wallet has to cover it
+    pub max_price: u64,
+    /// an inactive listing is activated once the best bid of the market is below this price
+    pub trigger_price: u64,
+    /// size of the order before any fills, grows and shrinks with size edits of a bid
+    pub original_size: u64,
+    /// number of units filled so far
+    pub filled_size: u64,
+    /// sum of the prices of all fills in lamports
+    pub filled_value: u64,
+    /// value of a bid placed or last edited by the delegate of the wallet, counted in its exposure
+    pub delegated_value: u64,
+    /// reserved space for future changes, keeps the account size of the borsh layout
+    _padding: [u8; 464],
 }
 
+// accounts of the borsh layout are migrated in place, the size must not change
+const _: () = assert!(std::mem::size_of::<Order>() == 712);
+
 #[derive(IntoPrimitive)]
 #[repr(u8)]
 pub enum OrderEditType {
@@ -59,22 +75,75 @@ pub enum OrderEditType {
 }
 
 #[event]
-pub struct OrderEditEvent {
+pub struct OrderEditEventV2 {
+    /// layout version of the event
+    pub event_version: u8,
     pub edit_type: u8,
-    pub address: String,
+    pub address: Pubkey,
     pub version: u8,
-    pub nonce: String,
-    pub market: String,
-    pub owner: String,
-    pub wallet: String,
+    pub nonce: Pubkey,
+    pub market: Pubkey,
+    pub owner: Pubkey,
+    pub wallet: Pubkey,
     pub side: u8,
     pub size: u64,
     pub price: u64,
     pub state: u8,
     pub init_time: i64,
     pub last_edit_time: i64,
-    pub nft_mint: String,
-    pub pool_mint: String,
+    pub nft_mint: Pubkey,
+    pub pool_mint: Pubkey,
+    pub price_mode: u8,
+    pub trailing_bps: u16,
+    pub max_price: u64,
+    pub trigger_price: u64,
+    pub listing_mode: u8,
+    pub original_size: u64,
+    pub filled_size: u64,
+    pub filled_value: u64,
+}
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone)]
+/// royalty paid to a single creator during a fill
+pub struct CreatorRoyalty {
+    pub creator: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+/// settlement breakdown of a single fill
+pub struct OrderFillEvent {
+    /// layout version of the event
+    pub event_version: u8,
+    pub address: Pubkey,
+    pub market: Pubkey,
+    pub pool_mint: Pubkey,
+    pub side: u8,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub nft_mint: Pubkey,
+    /// order price the fill was executed at
+    pub price: u64,
+    /// lamports received by the seller, see [`get_seller_amount`]
+    pub seller_amount: u64,
+    pub protocol_fee: u64,
+    pub royalty_total: u64,
+    pub royalties: Vec<CreatorRoyalty>,
+    /// true if protocol fees were waived for an ovol holder
+    pub ovol_exempt: bool,
+    pub fill_time: i64,
+}
+
+/// lamports the seller receives from a fill, the seller pays fees and royalties out of the price
+/// when a bid is filled while the buyer pays them on top of the price of a listing
+pub fn get_seller_amount(side: u8, price: u64, protocol_fee: u64, royalty_total: u64) -> u64 {
+    if side == u8::from(OrderSide::Buy) {
+        price
+            .saturating_sub(protocol_fee)
+            .saturating_sub(royalty_total)
+    } else {
+        price
+    }
 }
 
 #[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, IntoPrimitive)]
@@ -97,6 +166,18 @@ pub enum OrderState {
     Partial,
     /// all bids have been filled and the order account is now closed
     Closed,
+    /// listing waiting for its trigger, can be edited and closed but not filled
+    Inactive,
+}
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, PartialEq, IntoPrimitive)]
+#[repr(u8)]
+/// how the price of a buy order is set
+pub enum OrderPriceMode {
+    /// price set by the owner
+    Fixed,
+    /// price follows an active listing of the market minus a discount, up to a max price
+    TrailingListing,
 }
 
 impl Order {
@@ -124,20 +205,31 @@ impl Order {
         self.nft_mint = nft_mint;
         self.side = side;
         self.size = size;
+        self.original_size = size;
         self.price = price;
         self.state = state;
         self.init_time = time;
         self.last_edit_time = time;
-        self.fees_on = fees_on;
+        self.fees_on = fees_on.into();
+    }
+
+    /// return true if protocol fees are charged on fills of the order
+    pub fn is_fees_on(&self) -> bool {
+        self.fees_on != 0
     }
 
     /// edit a buy order account
     /// if size is 0, order is closed
     /// any size change is considered partial
-    pub fn edit_buy(&mut self, new_price: u64, new_size: u64, time: i64) {
+    pub fn edit_buy(&mut self, new_price: u64, new_size: u64, time: i64) -> Result<()> {
+        self.original_size = self
+            .filled_size
+            .checked_add(new_size)
+            .ok_or(ListingsError::OrderFillOverflow)?;
         self.size = new_size;
         self.price = new_price;
         self.last_edit_time = time;
+        Ok(())
     }
 
     /// edit a sell order account
@@ -146,33 +238,209 @@ impl Order {
         self.last_edit_time = time;
     }
 
+    /// record the fill of a single unit at the given price
+    /// an order with units left is partially filled
+    pub fn fill(&mut self, price: u64, time: i64) -> Result<()> {
+        // orders created before fills were tracked start counting at their first fill
+        let size = self
+            .filled_size
+            .checked_add(self.size)
+            .ok_or(ListingsError::OrderFillOverflow)?;
+        self.original_size = self.original_size.max(size);
+        self.size = self
+            .size
+            .checked_sub(1)
+            .ok_or(ListingsError::OrderFillOverflow)?;
+        self.filled_size = self
+            .filled_size
+            .checked_add(1)
+            .ok_or(ListingsError::OrderFillOverflow)?;
+        self.filled_value = self.filled_value.saturating_add(price);
+        if self.size > 0 {
+            self.state = OrderState::Partial.into();
+        }
+        self.last_edit_time = time;
+        Ok(())
+    }
+
+    /// state of an order that can be filled, partial once a unit has been filled
+    pub fn get_fillable_state(&self) -> u8 {
+        if self.filled_size > 0 {
+            OrderState::Partial.into()
+        } else {
+            OrderState::Ready.into()
+        }
+    }
+
+    /// make a buy order follow the listings of its market, a max price of 0 fixes the price again
+    pub fn set_trailing(&mut self, trailing_bps: u16, max_price: u64, time: i64) {
+        if max_price == 0 {
+            self.price_mode = OrderPriceMode::Fixed.into();
+            self.trailing_bps = 0;
+        } else {
+            self.price_mode = OrderPriceMode::TrailingListing.into();
+            self.trailing_bps = trailing_bps;
+        }
+        self.max_price = max_price;
+        self.last_edit_time = time;
+    }
+
+    /// return true if the price follows the listings of the market
+    pub fn is_trailing(&self) -> bool {
+        self.price_mode == u8::from(OrderPriceMode::TrailingListing)
+    }
+
+    /// price of a trailing bid for a reference listing price
+    pub fn get_trailing_price(&self, reference_price: u64) -> Result<u64> {
+        let bps = 10000u128
+            .checked_sub(self.trailing_bps as u128)
+            .ok_or(ListingsError::PriceOutOfRange)?;
+        let price = (reference_price as u128) * bps / 10000;
+        let price = (price as u64).min(self.max_price);
+        require!(price > 0, ListingsError::PriceOutOfRange);
+        Ok(price)
+    }
+
+    /// read an order passed as a remaining account, only orders of the current layout are read
+    pub fn load_current(account: &AccountInfo) -> Result<Order> {
+        let order = *AccountLoader::<Order>::try_from(account)?.load()?;
+        require!(
+            order.version == ORDER_VERSION,
+            ListingsError::UnsupportedAccountVersion
+        );
+        Ok(order)
+    }
+
     /// return true if the order is active
     pub fn is_active(state: u8) -> bool {
         state != <OrderState as Into<u8>>::into(OrderState::Closed)
     }
 
+    /// return true if the order can be filled
+    pub fn is_fillable(state: u8) -> bool {
+        Order::is_active(state) && state != u8::from(OrderState::Inactive)
+    }
+
+    /// return true if the listed nft is held in the escrow token account of the bidding wallet
+    pub fn is_escrowed(&self) -> bool {
+        self.listing_mode == u8::from(ListingMode::Escrow)
+    }
+
+    /// owner of the token account holding the listed nft
+    pub fn get_nft_holder(&self) -> Pubkey {
+        if self.is_escrowed() {
+            self.wallet
+        } else {
+            self.owner
+        }
+    }
+
+    /// make a listing wait until the best bid is below the trigger price, 0 activates it again
+    pub fn set_trigger(&mut self, trigger_price: u64, time: i64) {
+        self.trigger_price = trigger_price;
+        self.state = if trigger_price == 0 {
+            self.get_fillable_state()
+        } else {
+            OrderState::Inactive.into()
+        };
+        self.last_edit_time = time;
+    }
+
     pub fn get_edit_event(
-        &mut self,
+        &self,
         address: Pubkey,
         pool_mint: Pubkey,
         edit_type: OrderEditType,
-    ) -> OrderEditEvent {
-        OrderEditEvent {
+    ) -> OrderEditEventV2 {
+        OrderEditEventV2 {
+            event_version: EVENT_VERSION,
             edit_type: edit_type.into(),
-            address: address.to_string(),
+            address,
             version: self.version,
-            nonce: self.nonce.to_string(),
-            market: self.market.to_string(),
-            owner: self.owner.to_string(),
-            wallet: self.wallet.to_string(),
+            nonce: self.nonce,
+            market: self.market,
+            owner: self.owner,
+            wallet: self.wallet,
             side: self.side,
             size: self.size,
             price: self.price,
             state: self.state,
             init_time: self.init_time,
             last_edit_time: self.last_edit_time,
-            nft_mint: self.nft_mint.to_string(),
-            pool_mint: pool_mint.to_string(),
+            nft_mint: self.nft_mint,
+            pool_mint,
+            price_mode: self.price_mode,
+            trailing_bps: self.trailing_bps,
+            max_price: self.max_price,
+            trigger_price: self.trigger_price,
+            listing_mode: self.listing_mode,
+            original_size: self.original_size,
+            filled_size: self.filled_size,
+            filled_value: self.filled_value,
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn get_fill_event(
+        &self,
+        address: Pubkey,
+        pool_mint: Pubkey,
+        buyer: Pubkey,
+        seller: Pubkey,
+        nft_mint: Pubkey,
+        protocol_fee: u64,
+        royalties: Vec<CreatorRoyalty>,
+        ovol_exempt: bool,
+        time: i64,
+    ) -> OrderFillEvent {
+        let royalty_total: u64 = royalties.iter().map(|royalty| royalty.amount).sum();
+        OrderFillEvent {
+            event_version: EVENT_VERSION,
+            address,
+            market: self.market,
+            pool_mint,
+            side: self.side,
+            buyer,
+            seller,
+            nft_mint,
+            price: self.price,
+            seller_amount: get_seller_amount(self.side, self.price, protocol_fee, royalty_total),
+            protocol_fee,
+            royalty_total,
+            royalties,
+            ovol_exempt,
+            fill_time: time,
+        }
+    }
+}
+
+impl From<LegacyOrder> for Order {
+    /// keeps the version of the legacy account, upgraded by the migration afterwards
+    fn from(legacy: LegacyOrder) -> Self {
+        Order {
+            version: legacy.version,
+            side: legacy.side,
+            state: legacy.state,
+            fees_on: legacy.fees_on.into(),
+            price_mode: legacy.price_mode,
+            listing_mode: legacy.listing_mode,
+            trailing_bps: legacy.trailing_bps,
+            nonce: legacy.nonce,
+            market: legacy.market,
+            owner: legacy.owner,
+            wallet: legacy.wallet,
+            nft_mint: legacy.nft_mint,
+            size: legacy.size,
+            price: legacy.price,
+            init_time: legacy.init_time,
+            last_edit_time: legacy.last_edit_time,
+            max_price: legacy.max_price,
+            trigger_price: legacy.trigger_price,
+            original_size: legacy.original_size,
+            filled_size: legacy.filled_size,
+            filled_value: legacy.filled_value,
+            delegated_value: 0,
+            _padding: [0; 464],
         }
     }
 }
@@ -0,0 +1,133 @@
+use anchor_lang::prelude::*;
+use bridgesplit_program_utils::anchor_lang;
+
+use super::{Order, OrderSide};
+
+pub const ORDER_BOOK_VERSION: u8 = 1;
+/// number of bids and of asks held by an order book
+pub const ORDER_BOOK_DEPTH: usize = 32;
+
+#[zero_copy]
+/// order of an order book side
+pub struct BookEntry {
+    /// address of the order account
+    pub order: Pubkey,
+    /// price of a single unit in lamports
+    pub price: u64,
+    /// units left to fill
+    pub size: u64,
+}
+
+#[account(zero_copy)]
+/// optional order book of a market - the best bids and asks, kept in sync by the order handlers
+/// orders that fall off a full side come back once they are synced again
+pub struct OrderBook {
+    /// order book account version
+    pub version: u8,
+    /// number of bids in use
+    pub bid_count: u8,
+    /// number of asks in use
+    pub ask_count: u8,
+    /// aligns the fields after the counts
+    _version_padding: [u8; 5],
+    /// market of the orders
+    pub market: Pubkey,
+    /// fillable bids from the highest price, earlier orders first on equal prices
+    pub bids: [BookEntry; ORDER_BOOK_DEPTH],
+    /// fillable asks from the lowest price, earlier orders first on equal prices
+    pub asks: [BookEntry; ORDER_BOOK_DEPTH],
+}
+
+impl OrderBook {
+    /// initialize an empty order book
+    pub fn init(&mut self, market: Pubkey) {
+        self.version = ORDER_BOOK_VERSION;
+        self.market = market;
+    }
+
+    /// bids in use, best first
+    pub fn get_bids(&self) -> &[BookEntry] {
+        &self.bids[..self.bid_count as usize]
+    }
+
+    /// asks in use, best first
+    pub fn get_asks(&self) -> &[BookEntry] {
+        &self.asks[..self.ask_count as usize]
+    }
+
+    /// highest bid of the book
+    pub fn get_best_bid(&self) -> Option<&BookEntry> {
+        self.get_bids().first()
+    }
+
+    /// lowest ask of the book
+    pub fn get_floor(&self) -> Option<&BookEntry> {
+        self.get_asks().first()
+    }
+
+    /// reflect the current price, size and state of the order
+    /// fillable orders are inserted or moved, any other order is removed
+    /// a bid is only inserted while the bidding balance of its wallet covers its price
+    pub fn sync(&mut self, address: Pubkey, order: &Order, bidding_balance: u64) {
+        self.remove(address);
+        if !Order::is_fillable(order.state) || order.size == 0 {
+            return;
+        }
+        if order.side == u8::from(OrderSide::Buy) && bidding_balance < order.price {
+            return;
+        }
+        let entry = BookEntry {
+            order: address,
+            price: order.price,
+            size: order.size,
+        };
+        if order.side == u8::from(OrderSide::Buy) {
+            insert(&mut self.bids, &mut self.bid_count, entry, |a, b| a > b);
+        } else {
+            insert(&mut self.asks, &mut self.ask_count, entry, |a, b| a < b);
+        }
+    }
+
+    /// remove the order from the book, if it is in it
+    pub fn remove(&mut self, address: Pubkey) {
+        remove(&mut self.bids, &mut self.bid_count, address);
+        remove(&mut self.asks, &mut self.ask_count, address);
+    }
+}
+
+/// insert the entry after every entry at least as good, the worst entry of a full side is dropped
+fn insert(
+    entries: &mut [BookEntry; ORDER_BOOK_DEPTH],
+    count: &mut u8,
+    entry: BookEntry,
+    is_better: fn(u64, u64) -> bool,
+) {
+    let len = *count as usize;
+    let index = entries[..len]
+        .iter()
+        .position(|existing| is_better(entry.price, existing.price))
+        .unwrap_or(len);
+    if index == ORDER_BOOK_DEPTH {
+        return;
+    }
+    let end = len.min(ORDER_BOOK_DEPTH - 1);
+    entries.copy_within(index..end, index + 1);
+    entries[index] = entry;
+    *count = (end + 1) as u8;
+}
+
+fn remove(entries: &mut [BookEntry; ORDER_BOOK_DEPTH], count: &mut u8, address: Pubkey) {
+    let len = *count as usize;
+    if let Some(index) = entries[..len]
+        .iter()
+        .position(|entry| entry.order == address)
+    {
+        entries.copy_within(index + 1..len, index);
+        entries[len - 1] = BookEntry {
+            order: Pubkey::default(),
+            price: 0,
+            size: 0,
+        };
+        *count -= 1;
+    }
+}
@@ -2,35 +2,95 @@ use anchor_lang::prelude::*;
 use bridgesplit_program_utils::anchor_lang;
 use num_enum::IntoPrimitive;
 
-pub const WALLET_VERSION: u8 = 1;
+use crate::errors::ListingsError;
 
-#[account()]
+use super::{legacy::LegacyWallet, Order, EVENT_VERSION};
+
+pub const WALLET_VERSION: u8 = 2;
+/// longest withdrawal delay a wallet or market can set, 30 days
+pub const MAX_WITHDRAWAL_DELAY: i64 = 30 * 24 * 60 * 60;
+
+#[account(zero_copy)]
 /// wallet account - bidding authority and funds holder
+/// fields are ordered by alignment so the repr(C) layout needs no implicit padding
 pub struct Wallet {
     /// order account version
     pub version: u8,
+    /// aligns the fields after the version
+    _version_padding: [u8; 7],
     /// Owner of the wallet
     pub owner: Pubkey,
+    /// key allowed to manage buy orders on behalf of the owner, default if none
+    pub delegate: Pubkey,
     /// wallet balance
     pub balance: u64,
-    /// reserved space for future changes
-    reserve: [u8; 512],
+    /// max total value (price * size) of the open buy orders placed or edited by the delegate
+    pub delegate_spending_cap: u64,
+    /// seconds between a withdrawal request and its completion, 0 for instant withdrawals
+    pub withdrawal_delay: i64,
+    /// amount requested for withdrawal, still part of the balance until completed
+    pub pending_withdrawal: u64,
+    /// time after which the pending withdrawal can be completed
+    pub withdrawal_unlock_time: i64,
+    /// requested lower withdrawal delay, applied once the current delay has elapsed
+    pub pending_withdrawal_delay: i64,
+    /// time after which the pending withdrawal delay can be applied
+    pub withdrawal_delay_unlock_time: i64,
+    /// liquid staking tokens held by the wallet token account
+    pub staked_balance: u64,
+    /// lamports deposited for the staked balance, counted towards bids
+    pub staked_lamports: u64,
+    /// value of the open buy orders placed or edited by the delegate, capped by its spending cap
+    pub delegate_exposure: u64,
+    /// number of open buy orders funded by the wallet
+    pub open_buy_orders: u32,
+    /// number of open sell orders with an nft delegated to the wallet
+    pub open_sell_orders: u32,
+    /// 1 once migrated from the borsh layout, whose orders are only counted when they are migrated
+    /// the wallet can't be closed until its owner completes the migration
+    pub uncounted_legacy_orders: u8,
+    /// reserved space for future changes, keeps the account size of the borsh layout
+    _padding: [u8; 399],
 }
 
+// accounts of the borsh layout are migrated in place, the size must not change
+const _: () = assert!(std::mem::size_of::<Wallet>() == 560);
+
 #[derive(IntoPrimitive)]
 #[repr(u8)]
 pub enum WalletEditType {
     Init,
     Edit,
+    Delegate,
+    Close,
+    Sync,
+    WithdrawalDelay,
+    RequestWithdrawal,
+    CompleteWithdrawal,
+    Stake,
+    Unstake,
+    Unwind,
+    CompleteMigration,
 }
 
 #[event]
-pub struct WalletEditEvent {
+pub struct WalletEditEventV2 {
+    /// layout version of the event
+    pub event_version: u8,
     pub edit_type: u8,
-    pub address: String,
+    pub address: Pubkey,
     pub version: u8,
-    pub owner: String,
+    pub owner: Pubkey,
     pub balance: u64,
+    pub delegate: Pubkey,
+    pub delegate_spending_cap: u64,
+    pub open_buy_orders: u32,
+    pub open_sell_orders: u32,
+    pub withdrawal_delay: i64,
+    pub pending_withdrawal: u64,
+    pub withdrawal_unlock_time: i64,
+    pub staked_balance: u64,
+    pub staked_lamports: u64,
 }
 
 impl Wallet {
@@ -41,25 +101,276 @@ impl Wallet {
         self.balance = amount;
     }
 
-    pub fn edit_balance(&mut self, is_increase: bool, amount: u64) {
-        if is_increase {
-            self.balance += amount;
+    /// read a wallet passed as a remaining account, only wallets of the current layout are read
+    pub fn load_current(account: &AccountInfo) -> Result<Wallet> {
+        let wallet = *AccountLoader::<Wallet>::try_from(account)?.load()?;
+        require!(
+            wallet.version == WALLET_VERSION,
+            ListingsError::UnsupportedAccountVersion
+        );
+        Ok(wallet)
+    }
+
+    /// set or remove (with the default pubkey) the delegate of the wallet
+    pub fn set_delegate(&mut self, delegate: Pubkey, spending_cap: u64) {
+        self.delegate = delegate;
+        self.delegate_spending_cap = spending_cap;
+    }
+
+    /// return true if the key is the delegate of the wallet
+    pub fn is_delegate(&self, key: Pubkey) -> bool {
+        self.delegate != Pubkey::default() && self.delegate == key
+    }
+
+    /// return true if the key can manage the buy orders of the wallet
+    pub fn is_bidding_authority(&self, key: Pubkey) -> bool {
+        key == self.owner || self.is_delegate(key)
+    }
+
+    /// record the value of a bid placed or edited by a bidding authority
+    /// the owner is only limited by the balance, the bids of the delegate are capped in total
+    /// an edit by the owner takes the bid out of the exposure of the delegate
+    pub fn set_bid_exposure(&mut self, key: Pubkey, order: &mut Order, value: u64) -> Result<()> {
+        self.release_bid_exposure(order, order.delegated_value)?;
+        if key == self.owner {
+            return Ok(());
+        }
+        let exposure = self
+            .delegate_exposure
+            .checked_add(value)
+            .ok_or(ListingsError::BalanceOverflow)?;
+        require!(
+            exposure <= self.delegate_spending_cap,
+            ListingsError::DelegateCapExceeded
+        );
+        self.delegate_exposure = exposure;
+        order.delegated_value = value;
+        Ok(())
+    }
+
+    /// take part of the value of a bid out of the exposure of the delegate
+    pub fn release_bid_exposure(&mut self, order: &mut Order, value: u64) -> Result<()> {
+        let value = value.min(order.delegated_value);
+        order.delegated_value -= value;
+        self.delegate_exposure = self
+            .delegate_exposure
+            .checked_sub(value)
+            .ok_or(ListingsError::BalanceOverflow)?;
+        Ok(())
+    }
+
+    /// release the share of the unit just filled, the whole value once the bid is fully filled
+    pub fn release_filled_exposure(&mut self, order: &mut Order) -> Result<()> {
+        // the size has already been reduced by the fill
+        let released = if order.size == 0 {
+            order.delegated_value
+        } else {
+            order.delegated_value / (order.size + 1)
+        };
+        self.release_bid_exposure(order, released)
+    }
+
+    /// track a newly opened order
+    pub fn add_open_order(&mut self, is_buy: bool) -> Result<()> {
+        let count = if is_buy {
+            &mut self.open_buy_orders
+        } else {
+            &mut self.open_sell_orders
+        };
+        *count = count
+            .checked_add(1)
+            .ok_or(ListingsError::OpenOrderCountMismatch)?;
+        Ok(())
+    }
+
+    /// track a closed or fully filled order
+    /// orders opened before the counters existed are counted when migrated, see `migrate_account`
+    pub fn remove_open_order(&mut self, is_buy: bool) -> Result<()> {
+        let count = if is_buy {
+            &mut self.open_buy_orders
+        } else {
+            &mut self.open_sell_orders
+        };
+        *count = count
+            .checked_sub(1)
+            .ok_or(ListingsError::OpenOrderCountMismatch)?;
+        Ok(())
+    }
+
+    /// return true if the wallet still backs bids or holds delegated listings
+    pub fn has_open_orders(&self) -> bool {
+        self.open_buy_orders > 0 || self.open_sell_orders > 0
+    }
+
+    /// a higher delay applies immediately
+    /// a lower delay is recorded and applied by calling again once the current delay has elapsed,
+    /// otherwise lowering the delay would allow an instant withdrawal
+    pub fn set_withdrawal_delay(&mut self, delay: i64, now: i64) -> Result<()> {
+        require!(
+            (0..=MAX_WITHDRAWAL_DELAY).contains(&delay),
+            ListingsError::InvalidWithdrawalDelay
+        );
+        if delay >= self.withdrawal_delay {
+            self.withdrawal_delay = delay;
+            self.pending_withdrawal_delay = 0;
+            self.withdrawal_delay_unlock_time = 0;
+        } else if self.pending_withdrawal_delay == delay
+            && self.withdrawal_delay_unlock_time != 0
+            && now >= self.withdrawal_delay_unlock_time
+        {
+            self.withdrawal_delay = delay;
+            self.pending_withdrawal_delay = 0;
+            self.withdrawal_delay_unlock_time = 0;
         } else {
-            self.balance -= amount;
+            self.pending_withdrawal_delay = delay;
+            self.withdrawal_delay_unlock_time = self.get_unlock_time(now)?;
         }
+        Ok(())
     }
 
-    pub fn get_edit_event(
-        &mut self,
-        address: Pubkey,
-        edit_type: WalletEditType,
-    ) -> WalletEditEvent {
-        WalletEditEvent {
+    /// record a withdrawal, replacing any pending one, 0 cancels it
+    pub fn request_withdrawal(&mut self, amount: u64, now: i64) -> Result<()> {
+        self.pending_withdrawal = amount;
+        self.withdrawal_unlock_time = if amount == 0 {
+            0
+        } else {
+            self.get_unlock_time(now)?
+        };
+        Ok(())
+    }
+
+    /// time at which the withdrawal delay elapses if started now
+    fn get_unlock_time(&self, now: i64) -> Result<i64> {
+        now.checked_add(self.withdrawal_delay)
+            .ok_or::<Error>(ListingsError::UnlockTimeOverflow.into())
+    }
+
+    /// return true if the pending withdrawal can be completed
+    pub fn is_withdrawal_unlocked(&self, now: i64) -> bool {
+        self.pending_withdrawal > 0 && now >= self.withdrawal_unlock_time
+    }
+
+    /// clear the pending withdrawal and return the amount to transfer
+    /// bids stay fillable during the delay so the balance may have dropped below the request
+    pub fn complete_withdrawal(&mut self) -> Result<u64> {
+        let amount = self.pending_withdrawal.min(self.balance);
+        self.pending_withdrawal = 0;
+        self.withdrawal_unlock_time = 0;
+        self.edit_balance(false, amount)?;
+        Ok(amount)
+    }
+
+    /// funds available to back bids, liquid and staked
+    /// staked funds are counted at their deposit value and unwound when a bid is filled
+    pub fn get_bidding_balance(&self) -> u64 {
+        self.balance.saturating_add(self.staked_lamports)
+    }
+
+    /// move lamports from the balance to the staked balance
+    pub fn record_stake(&mut self, lamports: u64, pool_tokens: u64) -> Result<()> {
+        self.edit_balance(false, lamports)?;
+        self.staked_balance = self
+            .staked_balance
+            .checked_add(pool_tokens)
+            .ok_or(ListingsError::BalanceOverflow)?;
+        self.staked_lamports = self
+            .staked_lamports
+            .checked_add(lamports)
+            .ok_or(ListingsError::BalanceOverflow)?;
+        Ok(())
+    }
+
+    /// credit the lamports received for the burnt pool tokens to the balance
+    /// the deposit value is reduced in proportion to the pool tokens burnt
+    pub fn record_unstake(&mut self, pool_tokens: u64, lamports: u64) -> Result<()> {
+        let deposit_value = (self.staked_lamports as u128)
+            .checked_mul(pool_tokens as u128)
+            .and_then(|value| value.checked_div(self.staked_balance as u128))
+            .ok_or(ListingsError::BalanceOverflow)? as u64;
+        self.staked_balance = self
+            .staked_balance
+            .checked_sub(pool_tokens)
+            .ok_or(ListingsError::BalanceOverflow)?;
+        self.staked_lamports = self.staked_lamports.saturating_sub(deposit_value);
+        self.edit_balance(true, lamports)
+    }
+
+    pub fn edit_balance(&mut self, is_increase: bool, amount: u64) -> Result<()> {
+        let balance = if is_increase {
+            self.balance.checked_add(amount)
+        } else {
+            self.balance.checked_sub(amount)
+        };
+        self.balance = balance.ok_or(ListingsError::BalanceOverflow)?;
+        Ok(())
+    }
+
+    /// lamports of the wallet account available as balance
+    /// rent is derived from the fixed account size, the data may be borrowed by a loaded wallet
+    pub fn get_available_lamports(wallet: &AccountInfo) -> Result<u64> {
+        let rent = Rent::get()?.minimum_balance(8 + std::mem::size_of::<Wallet>());
+        Ok(wallet.lamports().saturating_sub(rent))
+    }
+
+    /// set the balance to the lamports held above rent exemption
+    /// credits sol sent directly to the wallet account
+    pub fn sync_balance(&mut self, wallet: &AccountInfo) -> Result<()> {
+        self.balance = Self::get_available_lamports(wallet)?;
+        Ok(())
+    }
+
+    /// balance + rent <= lamports, must hold at the end of every handler touching the wallet
+    pub fn check_balance(&self, wallet: &AccountInfo) -> Result<()> {
+        require!(
+            self.balance <= Self::get_available_lamports(wallet)?,
+            ListingsError::WalletUnderfunded
+        );
+        Ok(())
+    }
+
+    pub fn get_edit_event(&self, address: Pubkey, edit_type: WalletEditType) -> WalletEditEventV2 {
+        WalletEditEventV2 {
+            event_version: EVENT_VERSION,
             edit_type: edit_type.into(),
-            address: address.to_string(),
+            address,
             version: self.version,
-            owner: self.owner.to_string(),
+            owner: self.owner,
             balance: self.balance,
+            delegate: self.delegate,
+            delegate_spending_cap: self.delegate_spending_cap,
+            open_buy_orders: self.open_buy_orders,
+            open_sell_orders: self.open_sell_orders,
+            withdrawal_delay: self.withdrawal_delay,
+            pending_withdrawal: self.pending_withdrawal,
+            withdrawal_unlock_time: self.withdrawal_unlock_time,
+            staked_balance: self.staked_balance,
+            staked_lamports: self.staked_lamports,
+        }
+    }
+}
+
+impl From<LegacyWallet> for Wallet {
+    /// keeps the version of the legacy account, upgraded by the migration afterwards
+    fn from(legacy: LegacyWallet) -> Self {
+        Wallet {
+            version: legacy.version,
+            _version_padding: [0; 7],
+            owner: legacy.owner,
+            delegate: legacy.delegate,
+            balance: legacy.balance,
+            delegate_spending_cap: legacy.delegate_spending_cap,
+            withdrawal_delay: legacy.withdrawal_delay,
+            pending_withdrawal: legacy.pending_withdrawal,
+            withdrawal_unlock_time: legacy.withdrawal_unlock_time,
+            pending_withdrawal_delay: legacy.pending_withdrawal_delay,
+            withdrawal_delay_unlock_time: legacy.withdrawal_delay_unlock_time,
+            staked_balance: legacy.staked_balance,
+            staked_lamports: legacy.staked_lamports,
+            open_buy_orders: legacy.open_buy_orders,
+            open_sell_orders: legacy.open_sell_orders,
+            delegate_exposure: 0,
+            uncounted_legacy_orders: 0,
+            _padding: [0; 399],
         }
     }
 }
@@ -1,24 +1,51 @@
 use std::collections::HashMap;
 
 use anchor_lang::{
-    prelude::{Account, AccountInfo, CpiContext, Error, Pubkey},
+    prelude::{
+        Account, AccountInfo, AccountLoader, AccountMeta, CpiContext, Error, Pubkey,
+        UncheckedAccount,
+    },
     solana_program::{
         entrypoint::ProgramResult,
         program::{invoke, invoke_signed},
         system_instruction::transfer,
     },
-    AccountDeserialize, ToAccountInfo,
+    AccountDeserialize, AccountsClose, ToAccountInfo,
+};
+use anchor_spl::token::{close_account, CloseAccount, TokenAccount};
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{
+        mint_close_authority::MintCloseAuthority, non_transferable::NonTransferable,
+        permanent_delegate::PermanentDelegate, BaseStateWithExtensions, StateWithExtensions,
+    },
+    state::Mint as Token22Mint,
 };
-use anchor_spl::token::TokenAccount;
 use bridgesplit_program_utils::{
     anchor_lang, bridgesplit_delegate, bridgesplit_freeze, bridgesplit_revoke, bridgesplit_thaw,
     pnft::utils::ExistingDelegateParams, state::Metadata as BS_Metadata, BridgesplitDelegate,
     BridgesplitFreeze, BridgesplitRevoke,
 };
-use token_metadata::state::{Metadata, TokenMetadataAccount};
+use mpl_core::{
+    accounts::{BaseAssetV1, BaseCollectionV1},
+    fetch_plugin,
+    instructions::{
+        AddPluginV1CpiBuilder, RemovePluginV1CpiBuilder, TransferV1CpiBuilder,
+        UpdatePluginV1CpiBuilder,
+    },
+    types::{FreezeDelegate, Plugin, PluginAuthority, PluginType, Royalties, TransferDelegate},
+};
+use token_metadata::state::{Metadata, TokenMetadataAccount, TokenStandard};
 use vault::utils::{get_index_fee_bp, lamport_transfer};
 
-use crate::state::{Order, PROTOCOL_FEES_BPS};
+use crate::{
+    errors::ListingsError,
+    instructions::compressed::CompressedCreator,
+    state::{
+        BidPool, CreatorRoyalty, Market, Order, OrderBook, OrderSide, Wallet, MATCH_FEE_BPS,
+        PROTOCOL_FEES_BPS,
+    },
+};
 use bridgesplit_program_utils::{
     bridgesplit_transfer, pnft::utils::PnftParams, BridgesplitTransfer, ExtraDelegateParams,
     ExtraRevokeParams, ExtraTransferParams,
@@ -42,6 +69,48 @@ pub fn transfer_nft<'info>(
     token_metadata_program: AccountInfo<'info>,
     transfer_params: ExtraTransferParams<'info>,
     signer_seeds: &[&[&[u8]]],
+) -> Result<(), anchor_lang::prelude::Error> {
+    transfer_nft_amount(
+        authority,
+        token_owner,
+        payer,
+        to,
+        nft_mint,
+        nft_metadata,
+        nft_edition,
+        from_nft_ta,
+        to_nft_ta,
+        system_program,
+        instructions_program,
+        token_program,
+        associated_token_program,
+        token_metadata_program,
+        transfer_params,
+        signer_seeds,
+        1,
+    )
+}
+
+/// transfer a number of units of a semi-fungible asset, the transfer of an nft moves a single unit
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_nft_amount<'info>(
+    authority: AccountInfo<'info>,
+    token_owner: AccountInfo<'info>,
+    payer: AccountInfo<'info>,
+    to: AccountInfo<'info>,
+    nft_mint: AccountInfo<'info>,
+    nft_metadata: AccountInfo<'info>,
+    nft_edition: AccountInfo<'info>,
+    from_nft_ta: AccountInfo<'info>,
+    to_nft_ta: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    instructions_program: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    associated_token_program: AccountInfo<'info>,
+    token_metadata_program: AccountInfo<'info>,
+    transfer_params: ExtraTransferParams<'info>,
+    signer_seeds: &[&[&[u8]]],
+    amount: u64,
 ) -> Result<(), anchor_lang::prelude::Error> {
     let cpi_program = token_metadata_program.to_account_info();
     let cpi_accounts = BridgesplitTransfer {
@@ -60,7 +129,35 @@ pub fn transfer_nft<'info>(
         ata_program: associated_token_program.to_account_info(),
     };
     let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
-    bridgesplit_transfer(cpi_ctx, transfer_params, 1)
+    bridgesplit_transfer(cpi_ctx, transfer_params, amount)
+}
+
+/// close the emptied escrow token account of a bidding wallet and return the rent to the seller
+/// pnft token accounts stay locked by token metadata and are left open
+/// the account stays open while it holds units of other listings of the same asset
+pub fn close_escrow_nft_ta<'info>(
+    escrow_nft_ta: AccountInfo<'info>,
+    destination: AccountInfo<'info>,
+    wallet: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<(), anchor_lang::prelude::Error> {
+    let amount = {
+        let data = escrow_nft_ta.try_borrow_data()?;
+        TokenAccount::try_deserialize(&mut data.as_ref())?.amount
+    };
+    if amount > 0 {
+        return Ok(());
+    }
+    close_account(CpiContext::new_with_signer(
+        token_program,
+        CloseAccount {
+            account: escrow_nft_ta,
+            destination,
+            authority: wallet,
+        },
+        signer_seeds,
+    ))
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -383,8 +480,417 @@ pub fn parse_remaining_accounts(
     }
 }
 
-pub fn get_fees_on(order: Box<Account<'_, Order>>, ovol_fees_on: bool) -> bool {
-    order.fees_on && ovol_fees_on
+/// result of parsing the remaining accounts of token-2022 instructions
+pub struct ParsedToken22Accounts<'info> {
+    // apply fee on listings
+    pub fees_on: bool,
+    // transfer hook program, its validation account and the extra accounts it requires
+    pub transfer_hook_accounts: Vec<AccountInfo<'info>>,
+}
+
+/// the ovol accounts lead the remaining accounts, default pubkeys fall back to the fees of the order
+pub fn get_ovol_fees_on(
+    remaining_accounts: &[AccountInfo],
+    initializer: Pubkey,
+    fees_in_order: bool,
+) -> bool {
+    match remaining_accounts.get(0) {
+        Some(ovol_nft_ta) if ovol_nft_ta.key != &Pubkey::default() => {
+            !check_ovol_holder(remaining_accounts.to_vec(), initializer)
+        }
+        _ => fees_in_order,
+    }
+}
+
+/// first 2 are the ovol accounts or default pubkeys, the rest is forwarded to the transfer hook
+pub fn parse_token22_remaining_accounts(
+    remaining_accounts: Vec<AccountInfo>,
+    initializer: Pubkey,
+    fees_in_order: bool,
+) -> ParsedToken22Accounts {
+    let fees_on = get_ovol_fees_on(&remaining_accounts, initializer, fees_in_order);
+    let transfer_hook_accounts = remaining_accounts.get(2..).unwrap_or_default().to_vec();
+    ParsedToken22Accounts {
+        fees_on,
+        transfer_hook_accounts,
+    }
+}
+
+/// return true if the nft of the instruction is a token-2022 nft
+pub fn is_token22(token_program: &AccountInfo) -> bool {
+    token_program.key == &spl_token_2022::ID
+}
+
+/// token metadata accounts of a token program nft
+pub struct MetaplexNft<'info> {
+    pub metadata: Box<Account<'info, BS_Metadata>>,
+    pub edition: AccountInfo<'info>,
+}
+
+/// token metadata accounts are optional in instructions shared with token-2022 nfts,
+/// token program nfts can't be moved without them
+pub fn get_metaplex_nft<'info>(
+    metadata: &Option<Box<Account<'info, BS_Metadata>>>,
+    edition: &Option<UncheckedAccount<'info>>,
+) -> Result<MetaplexNft<'info>, Error> {
+    match (metadata, edition) {
+        (Some(metadata), Some(edition)) => Ok(MetaplexNft {
+            metadata: metadata.clone(),
+            edition: edition.to_account_info(),
+        }),
+        _ => Err(ListingsError::MissingNftMetadata.into()),
+    }
+}
+
+/// a token-2022 nft has no decimals, a supply of 1, no mint authority and can be transferred
+/// a permanent delegate could take the escrowed nft and a close authority could reuse the mint
+pub fn check_token22_nft(mint: &AccountInfo) -> Result<(), Error> {
+    let data = mint.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<Token22Mint>::unpack(&data)?;
+    if mint_state.base.decimals != 0
+        || mint_state.base.supply != 1
+        || mint_state.base.mint_authority.is_some()
+    {
+        return Err(ListingsError::InvalidToken22Nft.into());
+    }
+    if mint_state.get_extension::<NonTransferable>().is_ok() {
+        return Err(ListingsError::NonTransferableNft.into());
+    }
+    if mint_state.get_extension::<PermanentDelegate>().is_ok()
+        || mint_state.get_extension::<MintCloseAuthority>().is_ok()
+    {
+        return Err(ListingsError::UnsupportedToken22Extension.into());
+    }
+    Ok(())
+}
+
+/// transfer a token-2022 nft
+/// the accounts of a transfer hook are appended so the token program can forward them
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_token22_nft<'info>(
+    authority: AccountInfo<'info>,
+    nft_mint: AccountInfo<'info>,
+    from_nft_ta: AccountInfo<'info>,
+    to_nft_ta: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    transfer_hook_accounts: Vec<AccountInfo<'info>>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mut instruction = spl_token_2022::instruction::transfer_checked(
+        token_program.key,
+        from_nft_ta.key,
+        nft_mint.key,
+        to_nft_ta.key,
+        authority.key,
+        &[],
+        1,
+        0,
+    )?;
+    let mut account_infos = vec![from_nft_ta, nft_mint, to_nft_ta, authority, token_program];
+    for account in transfer_hook_accounts {
+        instruction.accounts.push(AccountMeta {
+            pubkey: *account.key,
+            is_signer: false,
+            is_writable: account.is_writable,
+        });
+        account_infos.push(account);
+    }
+    invoke_signed(&instruction, &account_infos, signer_seeds)
+}
+
+/// result of parsing the remaining accounts of metaplex core instructions
+pub struct ParsedCoreAccounts<'info> {
+    // apply fee on listings
+    pub fees_on: bool,
+    // creators of the royalties plugin
+    pub creator_accounts: Vec<AccountInfo<'info>>,
+}
+
+/// first 2 are the ovol accounts or default pubkeys, the rest are the royalty creators
+pub fn parse_core_remaining_accounts(
+    remaining_accounts: Vec<AccountInfo>,
+    initializer: Pubkey,
+    fees_in_order: bool,
+) -> ParsedCoreAccounts {
+    let fees_on = get_ovol_fees_on(&remaining_accounts, initializer, fees_in_order);
+    let creator_accounts = remaining_accounts.get(2..).unwrap_or_default().to_vec();
+    ParsedCoreAccounts {
+        fees_on,
+        creator_accounts,
+    }
+}
+
+/// freeze a listed core asset in place and let the wallet transfer it
+#[allow(clippy::too_many_arguments)]
+pub fn freeze_core_asset<'info>(
+    owner: AccountInfo<'info>,
+    asset: AccountInfo<'info>,
+    collection: Option<AccountInfo<'info>>,
+    delegate: Pubkey,
+    system_program: AccountInfo<'info>,
+    mpl_core_program: AccountInfo<'info>,
+) -> ProgramResult {
+    for plugin in [
+        Plugin::FreezeDelegate(FreezeDelegate { frozen: true }),
+        Plugin::TransferDelegate(TransferDelegate {}),
+    ] {
+        AddPluginV1CpiBuilder::new(&mpl_core_program)
+            .asset(&asset)
+            .collection(collection.as_ref())
+            .payer(&owner)
+            .authority(Some(&owner))
+            .system_program(&system_program)
+            .plugin(plugin)
+            .init_authority(PluginAuthority::Address { address: delegate })
+            .invoke()?;
+    }
+    Ok(())
+}
+
+/// unfreeze a core asset through the freeze delegate
+#[allow(clippy::too_many_arguments)]
+pub fn thaw_core_asset<'info>(
+    delegate: AccountInfo<'info>,
+    payer: AccountInfo<'info>,
+    asset: AccountInfo<'info>,
+    collection: Option<AccountInfo<'info>>,
+    system_program: AccountInfo<'info>,
+    mpl_core_program: AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    UpdatePluginV1CpiBuilder::new(&mpl_core_program)
+        .asset(&asset)
+        .collection(collection.as_ref())
+        .payer(&payer)
+        .authority(Some(&delegate))
+        .system_program(&system_program)
+        .plugin(Plugin::FreezeDelegate(FreezeDelegate { frozen: false }))
+        .invoke_signed(signer_seeds)
+}
+
+/// remove the delegate plugins of a thawed core asset, signed by its owner
+pub fn remove_core_delegates<'info>(
+    owner: AccountInfo<'info>,
+    asset: AccountInfo<'info>,
+    collection: Option<AccountInfo<'info>>,
+    system_program: AccountInfo<'info>,
+    mpl_core_program: AccountInfo<'info>,
+) -> ProgramResult {
+    for plugin_type in [PluginType::FreezeDelegate, PluginType::TransferDelegate] {
+        RemovePluginV1CpiBuilder::new(&mpl_core_program)
+            .asset(&asset)
+            .collection(collection.as_ref())
+            .payer(&owner)
+            .authority(Some(&owner))
+            .system_program(&system_program)
+            .plugin_type(plugin_type)
+            .invoke()?;
+    }
+    Ok(())
+}
+
+/// transfer a core asset, the authority is its owner or its transfer delegate
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_core_asset<'info>(
+    authority: AccountInfo<'info>,
+    payer: AccountInfo<'info>,
+    asset: AccountInfo<'info>,
+    collection: Option<AccountInfo<'info>>,
+    new_owner: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    mpl_core_program: AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    TransferV1CpiBuilder::new(&mpl_core_program)
+        .asset(&asset)
+        .collection(collection.as_ref())
+        .payer(&payer)
+        .authority(Some(&authority))
+        .new_owner(&new_owner)
+        .system_program(Some(&system_program))
+        .invoke_signed(signer_seeds)
+}
+
+/// pay the royalties of the royalties plugin of a core asset, or of its collection
+/// returns the amount paid to each creator
+#[allow(clippy::too_many_arguments)]
+pub fn pay_core_royalties<'info>(
+    price: u64,
+    asset: &AccountInfo<'info>,
+    collection: Option<&AccountInfo<'info>>,
+    payer: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    creator_accounts: Vec<AccountInfo<'info>>,
+    use_lamports_transfer: bool,
+    signer_seeds: Option<&[&[&[u8]]; 1]>,
+) -> Result<Vec<CreatorRoyalty>, Error> {
+    let royalties = match fetch_plugin::<BaseAssetV1, Royalties>(asset, PluginType::Royalties) {
+        Ok((_, royalties, _)) => Some(royalties),
+        Err(_) => collection.and_then(|collection| {
+            fetch_plugin::<BaseCollectionV1, Royalties>(collection, PluginType::Royalties)
+                .ok()
+                .map(|(_, royalties, _)| royalties)
+        }),
+    };
+    let royalties = match royalties {
+        Some(royalties) => royalties,
+        None => return Ok(Vec::new()),
+    };
+
+    let creator_accounts_map: HashMap<Pubkey, AccountInfo<'info>> = creator_accounts
+        .into_iter()
+        .map(|creator_account| (*creator_account.key, creator_account))
+        .collect();
+    let royalty_total = (price as u128)
+        .checked_mul(royalties.basis_points.into())
+        .unwrap()
+        .checked_div(10000)
+        .unwrap() as u64;
+    let mut paid_royalties = Vec::new();
+    for creator in royalties.creators {
+        if creator.percentage == 0 {
+            continue;
+        }
+        let creator_account = creator_accounts_map
+            .get(&creator.address)
+            .ok_or(ListingsError::MissingRoyaltyCreator)?
+            .to_account_info();
+        let amount = royalty_total
+            .checked_mul(creator.percentage.into())
+            .unwrap()
+            .checked_div(100)
+            .unwrap();
+        if use_lamports_transfer {
+            lamport_transfer(payer.clone(), creator_account, amount)?;
+        } else {
+            transfer_sol(
+                payer.clone(),
+                creator_account,
+                system_program.clone(),
+                signer_seeds,
+                amount,
+            )?;
+        }
+        paid_royalties.push(CreatorRoyalty {
+            creator: creator.address,
+            amount,
+        });
+    }
+    Ok(paid_royalties)
+}
+
+/// pay the royalties of a compressed nft, creators must be verified against the leaf beforehand
+pub fn pay_compressed_royalties<'info>(
+    price: u64,
+    seller_fee_basis_points: u16,
+    creators: &[CompressedCreator],
+    payer: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    creator_accounts: Vec<AccountInfo<'info>>,
+    use_lamports_transfer: bool,
+    signer_seeds: Option<&[&[&[u8]]; 1]>,
+) -> Result<Vec<CreatorRoyalty>, Error> {
+    let creator_accounts_map: HashMap<Pubkey, AccountInfo<'info>> = creator_accounts
+        .into_iter()
+        .map(|creator_account| (*creator_account.key, creator_account))
+        .collect();
+    let [_, royalties] = get_index_fee_bp(price, seller_fee_basis_points.into())?;
+    let mut paid_royalties = Vec::new();
+    for creator in creators {
+        if creator.share == 0 {
+            continue;
+        }
+        let creator_account = creator_accounts_map
+            .get(&creator.address)
+            .ok_or(ListingsError::MissingRoyaltyCreator)?
+            .to_account_info();
+        let amount = royalties
+            .checked_mul(creator.share.into())
+            .unwrap()
+            .checked_div(100)
+            .unwrap();
+        if use_lamports_transfer {
+            lamport_transfer(payer.clone(), creator_account, amount)?;
+        } else {
+            transfer_sol(
+                payer.clone(),
+                creator_account,
+                system_program.clone(),
+                signer_seeds,
+                amount,
+            )?;
+        }
+        paid_royalties.push(CreatorRoyalty {
+            creator: creator.address,
+            amount,
+        });
+    }
+    Ok(paid_royalties)
+}
+
+/// reflect the order in the order book of its market, markets without an order book are skipped
+/// the address and version of the order book are checked by the constraints of the instruction
+/// bids need the wallet funding them, unfunded bids are kept out of the book
+pub fn sync_order_book(
+    order_book: &Option<AccountLoader<OrderBook>>,
+    market: &Market,
+    address: Pubkey,
+    order: &Order,
+    wallet: Option<&Wallet>,
+) -> Result<(), Error> {
+    let order_book = match order_book {
+        Some(order_book) => order_book,
+        None if market.has_order_book => return Err(ListingsError::MissingOrderBook.into()),
+        None => return Ok(()),
+    };
+    let bidding_balance = match wallet {
+        Some(wallet) => wallet.get_bidding_balance(),
+        None if order.side == u8::from(OrderSide::Buy) => {
+            return Err(ListingsError::MissingOrderWallet.into())
+        }
+        None => 0,
+    };
+    OrderBook::sync(&mut order_book.load_mut()?, address, order, bidding_balance);
+    Ok(())
+}
+
+/// trailing bids are priced against the floor listing of the order book when they are filled
+/// fixed bids keep their price
+pub fn price_trailing_bid(
+    order_book: &Option<AccountLoader<OrderBook>>,
+    order: &mut Order,
+) -> Result<(), Error> {
+    if !order.is_trailing() {
+        return Ok(());
+    }
+    let order_book = order_book
+        .as_ref()
+        .ok_or(ListingsError::MissingOrderBook)?
+        .load()?;
+    let floor = order_book
+        .get_floor()
+        .ok_or(ListingsError::MissingFloorListing)?;
+    order.price = order.get_trailing_price(floor.price)?;
+    Ok(())
+}
+
+/// close a buy order, rent goes back to the owner
+/// a bid pool can't move the lamports of its own account, the rent of its orders goes to its wallet
+pub fn close_buy_order<'info>(
+    order: &AccountLoader<'info, Order>,
+    owner: &AccountInfo<'info>,
+    wallet: &AccountLoader<'info, Wallet>,
+) -> Result<(), Error> {
+    if !BidPool::is_bid_pool(owner) {
+        return order.close(owner.clone());
+    }
+    let rent = order.to_account_info().lamports();
+    order.close(wallet.to_account_info())?;
+    Wallet::edit_balance(&mut wallet.load_mut()?, true, rent)
+}
+
+pub fn get_fees_on(order: &Order, ovol_fees_on: bool) -> bool {
+    order.is_fees_on() && ovol_fees_on
 }
 
 pub fn get_fee_amount(order_price: u64) -> u64 {
@@ -394,6 +900,21 @@ pub fn get_fee_amount(order_price: u64) -> u64 {
         .unwrap()
 }
 
+/// max fee of the crank matching orders, paid out of the spread
+pub fn get_match_fee_amount(order_price: u64) -> u64 {
+    (order_price.checked_mul(MATCH_FEE_BPS))
+        .unwrap()
+        .checked_div(10000)
+        .unwrap()
+}
+
+/// return true if the metadata belongs to a semi-fungible asset that can be listed with a size above 1
+pub fn get_is_fungible_asset(metadata: &BS_Metadata) -> bool {
+    metadata.token_standard == Some(TokenStandard::FungibleAsset)
+}
+
+/// pay royalties to the creators of the nft
+/// returns the amount paid to each creator
 pub fn pay_royalties<'info>(
     price: u64,
     metadata: Box<Account<'info, BS_Metadata>>,
@@ -402,12 +923,13 @@ pub fn pay_royalties<'info>(
     creator_accounts: Vec<AccountInfo<'info>>,
     use_lamports_transfer: bool,
     signer_seeds: Option<&[&[&[u8]]; 1]>,
-) -> Result<(), Error> {
+) -> Result<Vec<CreatorRoyalty>, Error> {
     let creator_accounts_map: HashMap<Pubkey, AccountInfo<'info>> = creator_accounts
         .into_iter()
         .map(|creator_account| (*creator_account.key, creator_account))
         .collect();
     let [_, royalties] = get_index_fee_bp(price, metadata.data.seller_fee_basis_points.into())?;
+    let mut paid_royalties = Vec::new();
     if let Some(creators) = metadata.data.creators.clone() {
         for creator in creators {
             if creator.share != 0 {
@@ -437,8 +959,12 @@ pub fn pay_royalties<'info>(
                         amount,
                     )?;
                 }
+                paid_royalties.push(CreatorRoyalty {
+                    creator: creator.address,
+                    amount,
+                });
             }
         }
     }
-    Ok(())
+    Ok(paid_royalties)
 }
@@ -0,0 +1,51 @@
+//! helpers for other programs to price nfts with the best bid and floor of a market
+//! available with the `cpi` feature, the quote is read from the return data of the view instructions
+use anchor_lang::prelude::*;
+use bridgesplit_program_utils::anchor_lang;
+
+use crate::{
+    instructions::Quote,
+    state::{MARKET_SEED, ORDER_BOOK_SEED},
+};
+
+/// market of a pool mint
+pub fn find_market(pool_mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[MARKET_SEED.as_ref(), pool_mint.as_ref()], &crate::ID).0
+}
+
+/// order book of a market, read by the view instructions instead of candidate orders when passed
+pub fn find_order_book(market: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[ORDER_BOOK_SEED.as_ref(), market.as_ref()], &crate::ID).0
+}
+
+/// best bid of the market in its order book when passed, otherwise among the candidate bids
+/// orders are (order, wallet) pairs, in book order or in increasing order of address for candidates
+pub fn get_best_bid<'info>(
+    listings_program: AccountInfo<'info>,
+    market: AccountInfo<'info>,
+    order_book: Option<AccountInfo<'info>>,
+    orders: Vec<AccountInfo<'info>>,
+) -> Result<Quote> {
+    let cpi_ctx = CpiContext::new(
+        listings_program,
+        crate::cpi::accounts::GetQuote { market, order_book },
+    )
+    .with_remaining_accounts(orders);
+    Ok(crate::cpi::get_best_bid(cpi_ctx)?.get())
+}
+
+/// floor listing of the market in its order book when passed, otherwise among the candidate orders
+/// candidate orders are passed in increasing order of address
+pub fn get_floor<'info>(
+    listings_program: AccountInfo<'info>,
+    market: AccountInfo<'info>,
+    order_book: Option<AccountInfo<'info>>,
+    orders: Vec<AccountInfo<'info>>,
+) -> Result<Quote> {
+    let cpi_ctx = CpiContext::new(
+        listings_program,
+        crate::cpi::accounts::GetQuote { market, order_book },
+    )
+    .with_remaining_accounts(orders);
+    Ok(crate::cpi::get_floor(cpi_ctx)?.get())
+}
@@ -0,0 +1,279 @@
+mod common;
+
+use anchor_spl::associated_token::get_associated_token_address;
+use common::*;
+use listings::{
+    state::{AmmCurve, AmmPool, Wallet, MAX_EXPONENTIAL_DELTA_BPS},
+    utils::get_fee_amount,
+};
+use listings_client::{find_amm_pool, find_market, AmmPoolData, NftAccounts, RemainingAccounts};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+const PRICE: u64 = LAMPORTS_PER_SOL;
+
+struct Amm {
+    owner: Keypair,
+    pool_mint: Pubkey,
+    address: Pubkey,
+    wallet: Pubkey,
+}
+
+async fn init_amm(context: &mut TestContext, curve: AmmCurve, delta: u64, buy_size: u64) -> Amm {
+    let (pool_mint, market) = context.init_market().await;
+    let owner = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    let wallet = context.init_wallet(&owner, 5 * PRICE).await;
+
+    let nonce = Keypair::new().pubkey();
+    context
+        .process(
+            &[listings_client::init_amm_pool(
+                owner.pubkey(),
+                pool_mint,
+                nonce,
+                AmmPoolData {
+                    curve: curve.into(),
+                    spot_price: PRICE,
+                    delta,
+                    spread_bps: 1000,
+                    buy_size,
+                },
+            )],
+            &[&owner],
+        )
+        .await
+        .unwrap();
+
+    Amm {
+        address: find_amm_pool(&market, &owner.pubkey(), &nonce).0,
+        owner,
+        pool_mint,
+        wallet,
+    }
+}
+
+async fn sell_to_pool(context: &mut TestContext, amm: &Amm) -> (Keypair, NftAccounts) {
+    let seller = context.create_user(LAMPORTS_PER_SOL).await;
+    let nft = context.mint_nft(&seller).await;
+    context.add_appraisal(amm.pool_mint, nft.mint).await;
+    let amm_pool: AmmPool = context.get_anchor(amm.address).await;
+    context
+        .process(
+            &[listings_client::amm_sell_nft(
+                seller.pubkey(),
+                amm.pool_mint,
+                amm.address,
+                &amm_pool,
+                nft,
+                amm_pool.get_bid_price(),
+                &RemainingAccounts::default(),
+            )],
+            &[&seller],
+        )
+        .await
+        .unwrap();
+    (seller, nft)
+}
+
+#[tokio::test]
+async fn linear_pool_buys_and_sells_along_the_curve() {
+    let mut context = TestContext::new().await;
+    let amm = init_amm(&mut context, AmmCurve::Linear, PRICE / 10, 2).await;
+
+    // the owner wallet pays the bid, the protocol fee is taken out of it
+    let treasury_lamports = context.lamports(listings_client::treasury()).await;
+    let (_, nft) = sell_to_pool(&mut context, &amm).await;
+    let wallet: Wallet = context.get_zero_copy(amm.wallet).await;
+    assert_eq!(wallet.balance, 4 * PRICE);
+    assert_eq!(
+        context.lamports(listings_client::treasury()).await,
+        treasury_lamports + get_fee_amount(PRICE)
+    );
+    let amm_pool: AmmPool = context.get_anchor(amm.address).await;
+    assert_eq!(amm_pool.spot_price, PRICE - PRICE / 10);
+    assert_eq!(amm_pool.buy_size, 1);
+    assert_eq!(amm_pool.nft_count, 1);
+    let pool_nft_ta = get_associated_token_address(&amm.address, &nft.mint);
+    assert_eq!(context.token_account(pool_nft_ta).await.amount, 1);
+
+    // the ask is the new spot price plus the spread
+    let ask = amm_pool.get_ask_price().unwrap();
+    assert_eq!(ask, (PRICE - PRICE / 10) * 11 / 10);
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    context
+        .process(
+            &[listings_client::amm_buy_nft(
+                buyer.pubkey(),
+                amm.address,
+                &amm_pool,
+                nft,
+                ask,
+                &RemainingAccounts::default(),
+            )],
+            &[&buyer],
+        )
+        .await
+        .unwrap();
+    let buyer_nft_ta = get_associated_token_address(&buyer.pubkey(), &nft.mint);
+    assert_eq!(context.token_account(buyer_nft_ta).await.amount, 1);
+
+    let amm_pool: AmmPool = context.get_anchor(amm.address).await;
+    assert_eq!(amm_pool.spot_price, PRICE);
+    assert_eq!(amm_pool.nft_count, 0);
+    let wallet: Wallet = context.get_zero_copy(amm.wallet).await;
+    assert_eq!(wallet.balance, 5 * PRICE - PRICE + ask);
+}
+
+#[tokio::test]
+async fn pool_stops_buying_at_its_size() {
+    let mut context = TestContext::new().await;
+    let amm = init_amm(&mut context, AmmCurve::Linear, 0, 1).await;
+    sell_to_pool(&mut context, &amm).await;
+
+    let seller = context.create_user(LAMPORTS_PER_SOL).await;
+    let nft = context.mint_nft(&seller).await;
+    context.add_appraisal(amm.pool_mint, nft.mint).await;
+    let amm_pool: AmmPool = context.get_anchor(amm.address).await;
+    let result = context
+        .process(
+            &[listings_client::amm_sell_nft(
+                seller.pubkey(),
+                amm.pool_mint,
+                amm.address,
+                &amm_pool,
+                nft,
+                amm_pool.get_bid_price(),
+                &RemainingAccounts::default(),
+            )],
+            &[&seller],
+        )
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn exponential_pool_with_deposited_nfts() {
+    let mut context = TestContext::new().await;
+    let amm = init_amm(&mut context, AmmCurve::Exponential, 1000, 0).await;
+
+    let nft = context.mint_nft(&amm.owner).await;
+    context.add_appraisal(amm.pool_mint, nft.mint).await;
+    context
+        .process(
+            &[listings_client::deposit_amm_nft(
+                amm.owner.pubkey(),
+                amm.pool_mint,
+                amm.address,
+                nft,
+                &RemainingAccounts::default(),
+            )],
+            &[&amm.owner],
+        )
+        .await
+        .unwrap();
+
+    // nfts have to be withdrawn before closing
+    let close = listings_client::close_amm_pool(amm.owner.pubkey(), amm.address);
+    let result = context.process(&[close.clone()], &[&amm.owner]).await;
+    assert!(result.is_err());
+
+    let amm_pool: AmmPool = context.get_anchor(amm.address).await;
+    assert_eq!(amm_pool.market, find_market(&amm.pool_mint).0);
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    context
+        .process(
+            &[listings_client::amm_buy_nft(
+                buyer.pubkey(),
+                amm.address,
+                &amm_pool,
+                nft,
+                amm_pool.get_ask_price().unwrap(),
+                &RemainingAccounts::default(),
+            )],
+            &[&buyer],
+        )
+        .await
+        .unwrap();
+    let amm_pool: AmmPool = context.get_anchor(amm.address).await;
+    assert_eq!(amm_pool.spot_price, PRICE * 11 / 10);
+
+    context.process(&[close], &[&amm.owner]).await.unwrap();
+    assert!(context.get_account(amm.address).await.is_none());
+    let wallet: Wallet = context.get_zero_copy(amm.wallet).await;
+    assert_eq!(wallet.open_buy_orders, 0);
+}
+
+#[tokio::test]
+async fn trades_fail_past_the_price_limit() {
+    let mut context = TestContext::new().await;
+    let amm = init_amm(&mut context, AmmCurve::Linear, PRICE / 10, 2).await;
+
+    // the seller asks for more than the bid of the pool
+    let seller = context.create_user(LAMPORTS_PER_SOL).await;
+    let nft = context.mint_nft(&seller).await;
+    context.add_appraisal(amm.pool_mint, nft.mint).await;
+    let amm_pool: AmmPool = context.get_anchor(amm.address).await;
+    let result = context
+        .process(
+            &[listings_client::amm_sell_nft(
+                seller.pubkey(),
+                amm.pool_mint,
+                amm.address,
+                &amm_pool,
+                nft,
+                amm_pool.get_bid_price() + 1,
+                &RemainingAccounts::default(),
+            )],
+            &[&seller],
+        )
+        .await;
+    assert!(result.is_err());
+
+    // the buyer signed for the ask before the pool moved up
+    let (_, nft) = sell_to_pool(&mut context, &amm).await;
+    let amm_pool: AmmPool = context.get_anchor(amm.address).await;
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    let result = context
+        .process(
+            &[listings_client::amm_buy_nft(
+                buyer.pubkey(),
+                amm.address,
+                &amm_pool,
+                nft,
+                amm_pool.get_ask_price().unwrap() - 1,
+                &RemainingAccounts::default(),
+            )],
+            &[&buyer],
+        )
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn exponential_delta_is_capped() {
+    let mut context = TestContext::new().await;
+    let amm = init_amm(&mut context, AmmCurve::Exponential, 1000, 1).await;
+
+    let edit = |delta| {
+        listings_client::edit_amm_pool(
+            amm.owner.pubkey(),
+            amm.address,
+            AmmPoolData {
+                curve: AmmCurve::Exponential.into(),
+                spot_price: PRICE,
+                delta,
+                spread_bps: 1000,
+                buy_size: 1,
+            },
+        )
+    };
+    let result = context
+        .process(&[edit(MAX_EXPONENTIAL_DELTA_BPS + 1)], &[&amm.owner])
+        .await;
+    assert!(result.is_err());
+    context
+        .process(&[edit(MAX_EXPONENTIAL_DELTA_BPS)], &[&amm.owner])
+        .await
+        .unwrap();
+    let amm_pool: AmmPool = context.get_anchor(amm.address).await;
+    assert_eq!(amm_pool.delta, MAX_EXPONENTIAL_DELTA_BPS);
+}
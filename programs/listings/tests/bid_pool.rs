@@ -0,0 +1,361 @@
+mod common;
+
+use anchor_spl::associated_token::get_associated_token_address;
+use common::*;
+use listings::state::{BidPool, Order, PoolNft, PoolShare, Wallet};
+use listings_client::{
+    find_bid_pool, find_pool_nft, find_pool_share, find_wallet, InitOrderData, NftAccounts,
+    RemainingAccounts,
+};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+const PRICE: u64 = LAMPORTS_PER_SOL;
+
+struct Pool {
+    bid_pool: Pubkey,
+    manager: Keypair,
+    pool_mint: Pubkey,
+    market: Pubkey,
+}
+
+struct FilledPool {
+    bid_pool: Pubkey,
+    first: Keypair,
+    second: Keypair,
+    nft: NftAccounts,
+    /// rent of the filled order, credited to the pool wallet when it closed
+    order_rent: u64,
+}
+
+/// shares burnt for an nft, rounded up against the redeemer
+fn nft_shares(nft_cost: u64, total_shares: u64, value: u64) -> u64 {
+    let product = nft_cost as u128 * total_shares as u128;
+    ((product + value as u128 - 1) / value as u128) as u64
+}
+
+async fn init_pool(context: &mut TestContext, deposits: &[(&Keypair, u64)]) -> Pool {
+    let (pool_mint, market) = context.init_market().await;
+    let manager = context.create_user(LAMPORTS_PER_SOL).await;
+
+    let pool_nonce = Keypair::new().pubkey();
+    let bid_pool = find_bid_pool(&manager.pubkey(), &pool_nonce).0;
+    context
+        .process(
+            &[listings_client::init_bid_pool(manager.pubkey(), pool_nonce)],
+            &[&manager],
+        )
+        .await
+        .unwrap();
+    for (depositor, amount) in deposits {
+        context
+            .process(
+                &[listings_client::deposit_bid_pool(
+                    depositor.pubkey(),
+                    bid_pool,
+                    *amount,
+                )],
+                &[*depositor],
+            )
+            .await
+            .unwrap();
+    }
+    Pool {
+        bid_pool,
+        manager,
+        pool_mint,
+        market,
+    }
+}
+
+/// the manager bids through the pool wallet as its delegate
+async fn pool_bid(context: &mut TestContext, pool: &Pool, price: u64) -> Pubkey {
+    let nonce = Keypair::new().pubkey();
+    context
+        .process(
+            &[listings_client::init_buy_order(
+                pool.manager.pubkey(),
+                pool.bid_pool,
+                pool.pool_mint,
+                Pubkey::default(),
+                InitOrderData {
+                    nonce,
+                    price,
+                    size: 1,
+                },
+                &RemainingAccounts::default(),
+            )],
+            &[&pool.manager],
+        )
+        .await
+        .unwrap();
+    order_address(nonce, pool.market, pool.bid_pool)
+}
+
+/// bid and fill with a new nft, return the nft and the rent of the closed order
+async fn fill_new_pool_bid(
+    context: &mut TestContext,
+    pool: &Pool,
+    price: u64,
+) -> (NftAccounts, u64) {
+    let address = pool_bid(context, pool, price).await;
+    let seller = context.create_user(LAMPORTS_PER_SOL).await;
+    let nft = context.mint_nft(&seller).await;
+    context.add_appraisal(pool.pool_mint, nft.mint).await;
+    let order: Order = context.get_zero_copy(address).await;
+    let order_rent = context.lamports(address).await;
+    context
+        .process(
+            &[listings_client::fill_bid_pool_order(
+                seller.pubkey(),
+                address,
+                &order,
+                pool.pool_mint,
+                nft,
+                &RemainingAccounts::default(),
+            )],
+            &[&seller],
+        )
+        .await
+        .unwrap();
+    (nft, order_rent)
+}
+
+/// pool funded 2:1 by two depositors, which bought a single nft at PRICE
+async fn fill_pool_bid(context: &mut TestContext) -> FilledPool {
+    let first = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    let second = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    let pool = init_pool(context, &[(&first, 2 * PRICE), (&second, PRICE)]).await;
+    let (nft, order_rent) = fill_new_pool_bid(context, &pool, PRICE).await;
+
+    FilledPool {
+        bid_pool: pool.bid_pool,
+        first,
+        second,
+        nft,
+        order_rent,
+    }
+}
+
+#[tokio::test]
+async fn pool_bid_fill_is_held_by_the_pool() {
+    let mut context = TestContext::new().await;
+    let pool = fill_pool_bid(&mut context).await;
+
+    let bid_pool: BidPool = context.get_anchor(pool.bid_pool).await;
+    assert_eq!(bid_pool.total_shares, 3 * PRICE);
+    assert_eq!(bid_pool.nft_count, 1);
+    assert_eq!(bid_pool.nft_cost, PRICE);
+    let pool_nft: PoolNft = context
+        .get_anchor(find_pool_nft(&pool.bid_pool, &pool.nft.mint).0)
+        .await;
+    assert_eq!(pool_nft.count, 1);
+    assert_eq!(pool_nft.cost, PRICE);
+    // the rent of the filled order can't stay on the pool account, it goes to the wallet
+    let wallet: Wallet = context.get_zero_copy(find_wallet(&pool.bid_pool).0).await;
+    assert_eq!(wallet.balance, 2 * PRICE + pool.order_rent);
+
+    let pool_nft_ta = get_associated_token_address(&pool.bid_pool, &pool.nft.mint);
+    assert_eq!(context.token_account(pool_nft_ta).await.amount, 1);
+}
+
+#[tokio::test]
+async fn depositors_redeem_nfts_and_sol() {
+    let mut context = TestContext::new().await;
+    let pool = fill_pool_bid(&mut context).await;
+    let pool_nft = find_pool_nft(&pool.bid_pool, &pool.nft.mint).0;
+    let pool_nft_rent = context.lamports(pool_nft).await;
+
+    // the nft is worth its price, a bit less than a third of the pool with the order rent
+    let wallet_balance = 2 * PRICE + pool.order_rent;
+    let burnt = nft_shares(PRICE, 3 * PRICE, wallet_balance + PRICE);
+    context
+        .process(
+            &[listings_client::redeem_bid_pool_nft(
+                pool.second.pubkey(),
+                pool.bid_pool,
+                pool.nft,
+                &RemainingAccounts::default(),
+            )],
+            &[&pool.second],
+        )
+        .await
+        .unwrap();
+    let second_nft_ta = get_associated_token_address(&pool.second.pubkey(), &pool.nft.mint);
+    assert_eq!(context.token_account(second_nft_ta).await.amount, 1);
+    let share: PoolShare = context
+        .get_anchor(find_pool_share(&pool.bid_pool, &pool.second.pubkey()).0)
+        .await;
+    assert_eq!(share.shares, PRICE - burnt);
+    // the cost record closes with the last unit, its rent goes to the pool wallet
+    assert!(context.get_account(pool_nft).await.is_none());
+    let wallet_balance = wallet_balance + pool_nft_rent;
+    let wallet: Wallet = context.get_zero_copy(find_wallet(&pool.bid_pool).0).await;
+    assert_eq!(wallet.balance, wallet_balance);
+
+    let first_lamports = context.lamports(pool.first.pubkey()).await;
+    context
+        .process(
+            &[listings_client::redeem_bid_pool_sol(
+                pool.first.pubkey(),
+                pool.bid_pool,
+                2 * PRICE,
+            )],
+            &[&pool.first],
+        )
+        .await
+        .unwrap();
+    let redeemed =
+        (2 * PRICE as u128 * wallet_balance as u128 / (3 * PRICE - burnt) as u128) as u64;
+    assert_eq!(
+        context.lamports(pool.first.pubkey()).await,
+        first_lamports + redeemed
+    );
+    let bid_pool: BidPool = context.get_anchor(pool.bid_pool).await;
+    assert_eq!(bid_pool.total_shares, PRICE - burnt);
+    assert_eq!(bid_pool.nft_count, 0);
+    assert_eq!(bid_pool.nft_cost, 0);
+}
+
+#[tokio::test]
+async fn sol_redemptions_are_limited_to_the_sol_held() {
+    let mut context = TestContext::new().await;
+    let pool = fill_pool_bid(&mut context).await;
+    let redeem_sol = |depositor: &Keypair, shares| {
+        listings_client::redeem_bid_pool_sol(depositor.pubkey(), pool.bid_pool, shares)
+    };
+
+    // the second depositor takes a third of the pool in sol, leaving about 1 sol and the nft
+    context
+        .process(&[redeem_sol(&pool.second, PRICE)], &[&pool.second])
+        .await
+        .unwrap();
+
+    // the shares of the first depositor are worth 2 sol but only about 1 sol is left
+    let result = context
+        .process(&[redeem_sol(&pool.first, 2 * PRICE)], &[&pool.first])
+        .await;
+    assert!(result.is_err());
+
+    context
+        .process(
+            &[listings_client::redeem_bid_pool_nft(
+                pool.first.pubkey(),
+                pool.bid_pool,
+                pool.nft,
+                &RemainingAccounts::default(),
+            )],
+            &[&pool.first],
+        )
+        .await
+        .unwrap();
+    let share: PoolShare = context
+        .get_anchor(find_pool_share(&pool.bid_pool, &pool.first.pubkey()).0)
+        .await;
+    context
+        .process(&[redeem_sol(&pool.first, share.shares)], &[&pool.first])
+        .await
+        .unwrap();
+    let bid_pool: BidPool = context.get_anchor(pool.bid_pool).await;
+    assert_eq!(bid_pool.total_shares, 0);
+    let wallet: Wallet = context.get_zero_copy(find_wallet(&pool.bid_pool).0).await;
+    assert_eq!(wallet.balance, 0);
+}
+
+#[tokio::test]
+async fn redeemed_nfts_are_valued_at_their_own_cost() {
+    let mut context = TestContext::new().await;
+    let depositor = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    let pool = init_pool(&mut context, &[(&depositor, 5 * PRICE)]).await;
+    let (cheap_nft, cheap_rent) = fill_new_pool_bid(&mut context, &pool, PRICE).await;
+    let (expensive_nft, expensive_rent) = fill_new_pool_bid(&mut context, &pool, 3 * PRICE).await;
+
+    // the average cost is 2 sol, the cheap nft only burns the shares worth 1 sol
+    let wallet_balance = PRICE + cheap_rent + expensive_rent;
+    let burnt = nft_shares(PRICE, 5 * PRICE, wallet_balance + 4 * PRICE);
+    context
+        .process(
+            &[listings_client::redeem_bid_pool_nft(
+                depositor.pubkey(),
+                pool.bid_pool,
+                cheap_nft,
+                &RemainingAccounts::default(),
+            )],
+            &[&depositor],
+        )
+        .await
+        .unwrap();
+    let share: PoolShare = context
+        .get_anchor(find_pool_share(&pool.bid_pool, &depositor.pubkey()).0)
+        .await;
+    assert_eq!(share.shares, 5 * PRICE - burnt);
+    let bid_pool: BidPool = context.get_anchor(pool.bid_pool).await;
+    assert_eq!(bid_pool.nft_count, 1);
+    assert_eq!(bid_pool.nft_cost, 3 * PRICE);
+    let pool_nft: PoolNft = context
+        .get_anchor(find_pool_nft(&pool.bid_pool, &expensive_nft.mint).0)
+        .await;
+    assert_eq!(pool_nft.count, 1);
+    assert_eq!(pool_nft.cost, 3 * PRICE);
+}
+
+#[tokio::test]
+async fn closed_pool_bid_rent_goes_to_the_pool_wallet() {
+    let mut context = TestContext::new().await;
+    let depositor = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    let pool = init_pool(&mut context, &[(&depositor, PRICE)]).await;
+    let address = pool_bid(&mut context, &pool, PRICE).await;
+    let order: Order = context.get_zero_copy(address).await;
+    let order_rent = context.lamports(address).await;
+    let bid_pool_lamports = context.lamports(pool.bid_pool).await;
+
+    context
+        .process(
+            &[listings_client::close_buy_order(
+                pool.manager.pubkey(),
+                address,
+                &order,
+            )],
+            &[&pool.manager],
+        )
+        .await
+        .unwrap();
+    assert!(context.get_account(address).await.is_none());
+    assert_eq!(context.lamports(pool.bid_pool).await, bid_pool_lamports);
+    let wallet: Wallet = context.get_zero_copy(find_wallet(&pool.bid_pool).0).await;
+    assert_eq!(wallet.balance, PRICE + order_rent);
+}
+
+#[tokio::test]
+async fn pool_bids_only_buy_appraised_nfts() {
+    let mut context = TestContext::new().await;
+    let depositor = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    let pool = init_pool(&mut context, &[(&depositor, PRICE)]).await;
+    let address = pool_bid(&mut context, &pool, PRICE).await;
+    let order: Order = context.get_zero_copy(address).await;
+
+    let seller = context.create_user(LAMPORTS_PER_SOL).await;
+    let nft = context.mint_nft(&seller).await;
+    for fill in [
+        listings_client::fill_buy_order(
+            seller.pubkey(),
+            address,
+            &order,
+            nft,
+            &RemainingAccounts::default(),
+        ),
+        listings_client::fill_bid_pool_order(
+            seller.pubkey(),
+            address,
+            &order,
+            pool.pool_mint,
+            nft,
+            &RemainingAccounts::default(),
+        ),
+    ] {
+        let result = context.process(&[fill], &[&seller]).await;
+        assert!(result.is_err());
+    }
+    let wallet: Wallet = context.get_zero_copy(find_wallet(&pool.bid_pool).0).await;
+    assert_eq!(wallet.balance, PRICE);
+    assert!(context.get_account(address).await.is_some());
+}
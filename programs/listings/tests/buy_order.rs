@@ -0,0 +1,229 @@
+mod common;
+
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::associated_token::get_associated_token_address;
+use bridgesplit_program_utils::anchor_lang;
+use common::*;
+use listings::state::{Order, OrderSide, OrderState, Wallet};
+use listings_client::{treasury, EditBuyOrderData, InitOrderData, RemainingAccounts};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+const PRICE: u64 = LAMPORTS_PER_SOL;
+
+struct BuyOrder {
+    buyer: Keypair,
+    pool_mint: Pubkey,
+    market: Pubkey,
+    address: Pubkey,
+}
+
+async fn init_buy_order(context: &mut TestContext, size: u64) -> BuyOrder {
+    let (pool_mint, market) = context.init_market().await;
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    context.init_wallet(&buyer, PRICE * size).await;
+
+    let nonce = Keypair::new().pubkey();
+    context
+        .process(
+            &[listings_client::init_buy_order(
+                buyer.pubkey(),
+                buyer.pubkey(),
+                pool_mint,
+                Pubkey::default(),
+                InitOrderData {
+                    nonce,
+                    price: PRICE,
+                    size,
+                },
+                &RemainingAccounts::default(),
+            )],
+            &[&buyer],
+        )
+        .await
+        .unwrap();
+
+    BuyOrder {
+        address: order_address(nonce, market, buyer.pubkey()),
+        buyer,
+        pool_mint,
+        market,
+    }
+}
+
+#[tokio::test]
+async fn init_buy_order_is_ready() {
+    let mut context = TestContext::new().await;
+    let bid = init_buy_order(&mut context, 2).await;
+
+    let order: Order = context.get_zero_copy(bid.address).await;
+    assert_eq!(order.owner, bid.buyer.pubkey());
+    assert_eq!(order.market, bid.market);
+    assert_eq!(order.side, u8::from(OrderSide::Buy));
+    assert_eq!(order.state, u8::from(OrderState::Ready));
+    assert_eq!(order.size, 2);
+    assert_eq!(order.price, PRICE);
+    assert!(order.is_fees_on());
+}
+
+#[tokio::test]
+async fn init_buy_order_without_balance_fails() {
+    let mut context = TestContext::new().await;
+    let (pool_mint, _) = context.init_market().await;
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    context.init_wallet(&buyer, PRICE - 1).await;
+
+    let result = context
+        .process(
+            &[listings_client::init_buy_order(
+                buyer.pubkey(),
+                buyer.pubkey(),
+                pool_mint,
+                Pubkey::default(),
+                InitOrderData {
+                    nonce: Keypair::new().pubkey(),
+                    price: PRICE,
+                    size: 1,
+                },
+                &RemainingAccounts::default(),
+            )],
+            &[&buyer],
+        )
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn edit_and_close_buy_order() {
+    let mut context = TestContext::new().await;
+    let bid = init_buy_order(&mut context, 1).await;
+    let order: Order = context.get_zero_copy(bid.address).await;
+
+    // wallet only holds enough for a single bid
+    let result = context
+        .process(
+            &[listings_client::edit_buy_order(
+                bid.buyer.pubkey(),
+                bid.address,
+                &order,
+                bid.pool_mint,
+                EditBuyOrderData {
+                    new_size: 2,
+                    new_price: PRICE,
+                },
+            )],
+            &[&bid.buyer],
+        )
+        .await;
+    assert!(result.is_err());
+
+    context
+        .process(
+            &[listings_client::edit_buy_order(
+                bid.buyer.pubkey(),
+                bid.address,
+                &order,
+                bid.pool_mint,
+                EditBuyOrderData {
+                    new_size: 2,
+                    new_price: PRICE / 2,
+                },
+            )],
+            &[&bid.buyer],
+        )
+        .await
+        .unwrap();
+    let order: Order = context.get_zero_copy(bid.address).await;
+    assert_eq!(order.size, 2);
+    assert_eq!(order.price, PRICE / 2);
+
+    context
+        .process(
+            &[listings_client::close_buy_order(
+                bid.buyer.pubkey(),
+                bid.address,
+                &order,
+            )],
+            &[&bid.buyer],
+        )
+        .await
+        .unwrap();
+    assert!(context.get_account(bid.address).await.is_none());
+}
+
+#[tokio::test]
+async fn fill_buy_order_with_nft() {
+    let mut context = TestContext::new().await;
+    let bid = init_buy_order(&mut context, 2).await;
+    let seller = context.create_user(LAMPORTS_PER_SOL).await;
+    let nft = context.mint_nft(&seller).await;
+    let wallet = listings_client::find_wallet(&bid.buyer.pubkey()).0;
+
+    let order: Order = context.get_zero_copy(bid.address).await;
+    let seller_lamports = context.lamports(seller.pubkey()).await;
+    let treasury_lamports = context.lamports(treasury()).await;
+    context
+        .process(
+            &[listings_client::fill_buy_order(
+                seller.pubkey(),
+                bid.address,
+                &order,
+                nft,
+                &RemainingAccounts::default(),
+            )],
+            &[&seller],
+        )
+        .await
+        .unwrap();
+
+    let buyer_nft_ta = get_associated_token_address(&bid.buyer.pubkey(), &nft.mint);
+    assert_eq!(context.token_account(buyer_nft_ta).await.amount, 1);
+
+    let fee = listings::utils::get_fee_amount(PRICE);
+    assert_eq!(context.lamports(treasury()).await, treasury_lamports + fee);
+    // seller pays rent for the buyer token account out of the proceeds
+    let token_account_rent = context
+        .ctx
+        .banks_client
+        .get_rent()
+        .await
+        .unwrap()
+        .minimum_balance(spl_token::state::Account::LEN);
+    assert_eq!(
+        context.lamports(seller.pubkey()).await,
+        seller_lamports + PRICE - fee - token_account_rent
+    );
+
+    let wallet_state: Wallet = context.get_zero_copy(wallet).await;
+    assert_eq!(wallet_state.balance, PRICE);
+
+    let order: Order = context.get_zero_copy(bid.address).await;
+    assert_eq!(order.size, 1);
+    assert_eq!(order.state, u8::from(OrderState::Partial));
+    assert_eq!(order.original_size, 2);
+    assert_eq!(order.filled_size, 1);
+    assert_eq!(order.filled_value, PRICE);
+}
+
+#[tokio::test]
+async fn fill_last_bid_closes_order() {
+    let mut context = TestContext::new().await;
+    let bid = init_buy_order(&mut context, 1).await;
+    let seller = context.create_user(LAMPORTS_PER_SOL).await;
+    let nft = context.mint_nft(&seller).await;
+
+    let order: Order = context.get_zero_copy(bid.address).await;
+    context
+        .process(
+            &[listings_client::fill_buy_order(
+                seller.pubkey(),
+                bid.address,
+                &order,
+                nft,
+                &RemainingAccounts::default(),
+            )],
+            &[&seller],
+        )
+        .await
+        .unwrap();
+    assert!(context.get_account(bid.address).await.is_none());
+}
@@ -0,0 +1,894 @@
+//! shared harness for the listings integration tests
+//! the listings and mock stake pool programs are loaded from `target/deploy`,
+//! the mainnet programs of [`fixture_programs`] from `tests/fixtures`
+#![allow(dead_code)]
+
+use anchor_lang::{
+    prelude::{Clock, Pubkey},
+    solana_program::{instruction::Instruction, keccak, program_pack::Pack, system_instruction},
+    AccountDeserialize, AnchorDeserialize, AnchorSerialize, Discriminator, InstructionData,
+    ToAccountMetas, ZeroCopy,
+};
+use anchor_spl::{
+    associated_token::{
+        get_associated_token_address, get_associated_token_address_with_program_id,
+    },
+    token_2022::spl_token_2022::{
+        self,
+        extension::{ExtensionType, StateWithExtensions},
+    },
+};
+use bridgesplit_program_utils::{anchor_lang, mpl_bubblegum};
+use listings_client::{
+    find_lst_config, find_market, find_order, find_tree_authority, find_wallet,
+    CompressedFillOrderData, CompressedNftAccounts, NftAccounts, OvolAccounts, RemainingAccounts,
+    StakePoolAccounts, SPL_ACCOUNT_COMPRESSION_ID, SPL_NOOP_ID,
+};
+use mpl_bubblegum::state::{
+    leaf_schema::LeafSchema,
+    metaplex_adapter::{Creator as CompressedCreator, MetadataArgs, TokenProgramVersion},
+};
+use mpl_core::{
+    accounts::BaseAssetV1,
+    instructions::CreateV1Builder,
+    types::{Creator as CoreCreator, Plugin, PluginAuthorityPair, Royalties, RuleSet},
+};
+use solana_program_test::{BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{account::Account, signature::Keypair, signer::Signer, transaction::Transaction};
+use spl_merkle_tree_reference::{MerkleTree, Node};
+use token_metadata::{
+    instruction::{
+        builders::{CreateBuilder, MintBuilder},
+        CreateArgs, InstructionBuilder, MintArgs,
+    },
+    state::{Collection, Creator, Data, Key, Metadata, TokenStandard, MAX_METADATA_LEN},
+};
+
+pub const OVOL_COLLECTION: &str = "9jnJWH9F9t1xAgw5RGwswVKY4GvY2RXhzLSJgpBAhoaR";
+pub const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+pub const TREE_DEPTH: u32 = 5;
+pub const TREE_BUFFER_SIZE: u32 = 8;
+
+/// mainnet programs the listings program cpis into, dumped into `tests/fixtures` by `dump.sh`
+pub fn fixture_programs() -> Vec<(&'static str, Pubkey)> {
+    vec![
+        ("mpl_token_metadata", token_metadata::ID),
+        ("mpl_token_auth_rules", mpl_token_auth_rules::ID),
+        ("mpl_bubblegum", mpl_bubblegum::ID),
+        ("spl_account_compression", SPL_ACCOUNT_COMPRESSION_ID),
+        ("spl_noop", SPL_NOOP_ID),
+        ("mpl_core", mpl_core::ID),
+        ("vault", vault::ID),
+    ]
+}
+
+pub fn program_test() -> ProgramTest {
+    let mut program_test = ProgramTest::new("listings", listings::ID, None);
+    program_test.prefer_bpf(true);
+    let fixtures = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    for (name, id) in fixture_programs() {
+        let path = fixtures.join(format!("{}.so", name));
+        assert!(
+            path.exists(),
+            "missing fixture {}, run programs/listings/tests/fixtures/dump.sh",
+            path.display()
+        );
+        program_test.add_program(name, id, None);
+    }
+    program_test.add_program("mock_stake_pool", mock_stake_pool::ID, None);
+    program_test
+}
+
+pub struct TestContext {
+    pub ctx: ProgramTestContext,
+}
+
+impl TestContext {
+    pub async fn new() -> Self {
+        Self {
+            ctx: program_test().start_with_context().await,
+        }
+    }
+
+    pub fn payer(&self) -> Pubkey {
+        self.ctx.payer.pubkey()
+    }
+
+    pub async fn process(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), BanksClientError> {
+        let transaction = self.transaction(instructions, signers).await;
+        self.ctx.banks_client.process_transaction(transaction).await
+    }
+
+    /// compute units consumed by the instructions, panics if the transaction fails
+    pub async fn compute_units(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> u64 {
+        let transaction = self.transaction(instructions, signers).await;
+        let processed = self
+            .ctx
+            .banks_client
+            .process_transaction_with_metadata(transaction)
+            .await
+            .unwrap();
+        let metadata = processed.metadata.unwrap();
+        if let Err(error) = processed.result {
+            panic!("{}\n{}", error, metadata.log_messages.join("\n"));
+        }
+        metadata.compute_units_consumed
+    }
+
+    /// return data of the last instruction, decoded as `T`
+    pub async fn return_data<T: AnchorDeserialize>(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> T {
+        let transaction = self.transaction(instructions, signers).await;
+        let processed = self
+            .ctx
+            .banks_client
+            .process_transaction_with_metadata(transaction)
+            .await
+            .unwrap();
+        processed.result.unwrap();
+        let return_data = processed.metadata.unwrap().return_data.unwrap();
+        assert_eq!(return_data.program_id, listings::ID);
+        T::try_from_slice(&return_data.data).unwrap()
+    }
+
+    async fn transaction(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Transaction {
+        let blockhash = self
+            .ctx
+            .banks_client
+            .get_new_latest_blockhash(&self.ctx.last_blockhash)
+            .await
+            .unwrap();
+        self.ctx.last_blockhash = blockhash;
+        let mut all_signers = vec![&self.ctx.payer];
+        all_signers.extend_from_slice(signers);
+        Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.ctx.payer.pubkey()),
+            &all_signers,
+            blockhash,
+        )
+    }
+
+    pub async fn get_account(&mut self, address: Pubkey) -> Option<Account> {
+        self.ctx.banks_client.get_account(address).await.unwrap()
+    }
+
+    pub async fn get_anchor<T: AccountDeserialize>(&mut self, address: Pubkey) -> T {
+        let account = self.get_account(address).await.unwrap();
+        T::try_deserialize(&mut &account.data[..]).unwrap()
+    }
+
+    /// pass the order book of the market to the instruction when the market has one
+    pub async fn with_market_order_book(
+        &mut self,
+        instruction: Instruction,
+        market: Pubkey,
+    ) -> Instruction {
+        let market_account: listings::state::Market = self.get_anchor(market).await;
+        if market_account.has_order_book {
+            listings_client::with_order_book(instruction, &market)
+        } else {
+            instruction
+        }
+    }
+
+    pub async fn get_zero_copy<T: ZeroCopy>(&mut self, address: Pubkey) -> T {
+        let account = self.get_account(address).await.unwrap();
+        assert_eq!(account.data[..8], T::discriminator());
+        bytemuck::pod_read_unaligned(&account.data[8..8 + std::mem::size_of::<T>()])
+    }
+
+    pub async fn lamports(&mut self, address: Pubkey) -> u64 {
+        self.get_account(address)
+            .await
+            .map(|account| account.lamports)
+            .unwrap_or(0)
+    }
+
+    pub async fn token_account(&mut self, address: Pubkey) -> spl_token::state::Account {
+        let account = self.get_account(address).await.unwrap();
+        spl_token::state::Account::unpack(&account.data).unwrap()
+    }
+
+    /// move the clock forward
+    pub async fn warp(&mut self, seconds: i64) {
+        let mut clock: Clock = self.ctx.banks_client.get_sysvar().await.unwrap();
+        clock.unix_timestamp += seconds;
+        self.ctx.set_sysvar(&clock);
+    }
+
+    /// funded keypair
+    pub async fn create_user(&mut self, lamports: u64) -> Keypair {
+        let user = Keypair::new();
+        let payer = self.payer();
+        self.process(
+            &[system_instruction::transfer(
+                &payer,
+                &user.pubkey(),
+                lamports,
+            )],
+            &[],
+        )
+        .await
+        .unwrap();
+        user
+    }
+
+    /// creates a market for a new pool mint and returns (pool_mint, market)
+    pub async fn init_market(&mut self) -> (Pubkey, Pubkey) {
+        let pool_mint = Keypair::new().pubkey();
+        let initializer = self.create_user(LAMPORTS_PER_SOL).await;
+        self.process(
+            &[listings_client::init_market(
+                initializer.pubkey(),
+                pool_mint,
+            )],
+            &[&initializer],
+        )
+        .await
+        .unwrap();
+        (pool_mint, find_market(&pool_mint).0)
+    }
+
+    /// creates a market with an order book for a new pool mint and returns (pool_mint, market)
+    pub async fn init_order_book_market(&mut self) -> (Pubkey, Pubkey) {
+        let pool_mint = Keypair::new().pubkey();
+        let initializer = self.create_user(LAMPORTS_PER_SOL).await;
+        self.process(
+            &[
+                listings_client::init_market(initializer.pubkey(), pool_mint),
+                listings_client::init_order_book(initializer.pubkey(), pool_mint),
+            ],
+            &[&initializer],
+        )
+        .await
+        .unwrap();
+        (pool_mint, find_market(&pool_mint).0)
+    }
+
+    pub async fn init_wallet(&mut self, owner: &Keypair, amount: u64) -> Pubkey {
+        self.process(
+            &[listings_client::init_wallet(owner.pubkey(), amount)],
+            &[owner],
+        )
+        .await
+        .unwrap();
+        find_wallet(&owner.pubkey()).0
+    }
+
+    /// writes an appraisal of the nft into the vault program so that it can be listed
+    /// the listings program only checks the account exists for the pool mint
+    pub async fn add_appraisal(&mut self, pool_mint: Pubkey, nft_mint: Pubkey) {
+        let address = listings_client::find_appraisal(&pool_mint, &nft_mint).0;
+        let mut data = vault::state::Appraisal::discriminator().to_vec();
+        data.resize(8 + std::mem::size_of::<vault::state::Appraisal>(), 0);
+        self.set_program_account(address, vault::ID, data);
+    }
+
+    pub fn set_program_account(&mut self, address: Pubkey, owner: Pubkey, data: Vec<u8>) {
+        let account = Account {
+            lamports: LAMPORTS_PER_SOL,
+            data,
+            owner,
+            executable: false,
+            rent_epoch: 0,
+        };
+        self.ctx.set_account(&address, &account.into());
+    }
+
+    async fn mint(
+        &mut self,
+        owner: &Keypair,
+        token_standard: TokenStandard,
+        creators: Vec<Pubkey>,
+        amount: u64,
+    ) -> NftAccounts {
+        let mint = Keypair::new();
+        let nft = NftAccounts::new(mint.pubkey());
+        let authority = owner.pubkey();
+        let creators = if creators.is_empty() {
+            None
+        } else {
+            let share = (100 / creators.len()) as u8;
+            Some(
+                creators
+                    .iter()
+                    .map(|address| Creator {
+                        address: *address,
+                        verified: false,
+                        share,
+                    })
+                    .collect(),
+            )
+        };
+        // semi-fungible assets have no master edition
+        let is_fungible_asset = token_standard == TokenStandard::FungibleAsset;
+        let mut create_builder = CreateBuilder::new();
+        if !is_fungible_asset {
+            create_builder.master_edition(nft.edition);
+        }
+        let create = create_builder
+            .metadata(nft.metadata)
+            .mint(nft.mint)
+            .authority(authority)
+            .payer(authority)
+            .update_authority(authority)
+            .initialize_mint(true)
+            .update_authority_as_signer(true)
+            .build(CreateArgs::V1 {
+                asset_data: token_metadata::state::AssetData {
+                    name: "listings".to_string(),
+                    symbol: String::new(),
+                    uri: String::new(),
+                    seller_fee_basis_points: 500,
+                    creators,
+                    primary_sale_happened: false,
+                    is_mutable: true,
+                    token_standard,
+                    collection: None,
+                    uses: None,
+                    collection_details: None,
+                    rule_set: None,
+                },
+                decimals: Some(0),
+                print_supply: if is_fungible_asset {
+                    None
+                } else {
+                    Some(token_metadata::state::PrintSupply::Zero)
+                },
+            })
+            .unwrap()
+            .instruction();
+        let token = get_associated_token_address(&authority, &nft.mint);
+        let mut mint_builder = MintBuilder::new();
+        if !is_fungible_asset {
+            mint_builder.master_edition(nft.edition);
+        }
+        mint_builder
+            .token(token)
+            .token_owner(authority)
+            .metadata(nft.metadata)
+            .mint(nft.mint)
+            .authority(authority)
+            .payer(authority);
+        if token_standard == TokenStandard::ProgrammableNonFungible {
+            mint_builder.token_record(listings_client::find_token_record(&nft.mint, &token).0);
+        }
+        let mint_to = mint_builder
+            .build(MintArgs::V1 {
+                amount,
+                authorization_data: None,
+            })
+            .unwrap()
+            .instruction();
+        self.process(&[create, mint_to], &[owner, &mint])
+            .await
+            .unwrap();
+        nft
+    }
+
+    /// legacy nft held in the associated token account of the owner
+    pub async fn mint_nft(&mut self, owner: &Keypair) -> NftAccounts {
+        self.mint(owner, TokenStandard::NonFungible, Vec::new(), 1)
+            .await
+    }
+
+    /// semi-fungible asset with `amount` units held in the associated token account of the owner
+    pub async fn mint_fungible_asset(&mut self, owner: &Keypair, amount: u64) -> NftAccounts {
+        self.mint(owner, TokenStandard::FungibleAsset, Vec::new(), amount)
+            .await
+    }
+
+    /// programmable nft with equal royalty shares for the creators
+    pub async fn mint_pnft(&mut self, owner: &Keypair, creators: Vec<Pubkey>) -> NftAccounts {
+        self.mint(owner, TokenStandard::ProgrammableNonFungible, creators, 1)
+            .await
+    }
+
+    /// core asset of the owner with a royalties plugin split equally between the creators
+    pub async fn create_core_asset(&mut self, owner: &Keypair, creators: Vec<Pubkey>) -> Pubkey {
+        let asset = Keypair::new();
+        let plugins = if creators.is_empty() {
+            Vec::new()
+        } else {
+            let percentage = (100 / creators.len()) as u8;
+            vec![PluginAuthorityPair {
+                plugin: Plugin::Royalties(Royalties {
+                    basis_points: 500,
+                    creators: creators
+                        .iter()
+                        .map(|address| CoreCreator {
+                            address: *address,
+                            percentage,
+                        })
+                        .collect(),
+                    rule_set: RuleSet::None,
+                }),
+                authority: None,
+            }]
+        };
+        let create = CreateV1Builder::new()
+            .asset(asset.pubkey())
+            .payer(owner.pubkey())
+            .name("listings".to_string())
+            .uri(String::new())
+            .plugins(plugins)
+            .instruction();
+        self.process(&[create], &[owner, &asset]).await.unwrap();
+        asset.pubkey()
+    }
+
+    /// owner of a core asset
+    pub async fn core_owner(&mut self, asset: Pubkey) -> Pubkey {
+        let account = self.get_account(asset).await.unwrap();
+        BaseAssetV1::from_bytes(&account.data).unwrap().owner
+    }
+
+    /// token-2022 nft held in the associated token account of the owner, without mint authority
+    pub async fn mint_token22_nft(&mut self, owner: &Keypair, non_transferable: bool) -> Pubkey {
+        let extensions: &[ExtensionType] = if non_transferable {
+            &[ExtensionType::NonTransferable]
+        } else {
+            &[]
+        };
+        self.mint_token22_nft_with_extensions(owner, extensions)
+            .await
+    }
+
+    /// token-2022 nft with the given mint extensions, the owner is the extension authority
+    pub async fn mint_token22_nft_with_extensions(
+        &mut self,
+        owner: &Keypair,
+        extensions: &[ExtensionType],
+    ) -> Pubkey {
+        self.mint_token22(owner, extensions, false).await
+    }
+
+    /// token-2022 token with a supply of one whose owner is still the mint authority
+    pub async fn mint_token22_nft_with_mint_authority(&mut self, owner: &Keypair) -> Pubkey {
+        self.mint_token22(owner, &[], true).await
+    }
+
+    async fn mint_token22(
+        &mut self,
+        owner: &Keypair,
+        extensions: &[ExtensionType],
+        keep_mint_authority: bool,
+    ) -> Pubkey {
+        let mint = Keypair::new();
+        let authority = owner.pubkey();
+        let space = ExtensionType::get_account_len::<spl_token_2022::state::Mint>(extensions);
+        let rent = self.ctx.banks_client.get_rent().await.unwrap();
+        let mut instructions = vec![system_instruction::create_account(
+            &authority,
+            &mint.pubkey(),
+            rent.minimum_balance(space),
+            space as u64,
+            &spl_token_2022::ID,
+        )];
+        for extension in extensions {
+            instructions.push(
+                match extension {
+                    ExtensionType::NonTransferable => {
+                        spl_token_2022::instruction::initialize_non_transferable_mint(
+                            &spl_token_2022::ID,
+                            &mint.pubkey(),
+                        )
+                    }
+                    ExtensionType::PermanentDelegate => {
+                        spl_token_2022::instruction::initialize_permanent_delegate(
+                            &spl_token_2022::ID,
+                            &mint.pubkey(),
+                            &authority,
+                        )
+                    }
+                    ExtensionType::MintCloseAuthority => {
+                        spl_token_2022::instruction::initialize_mint_close_authority(
+                            &spl_token_2022::ID,
+                            &mint.pubkey(),
+                            Some(&authority),
+                        )
+                    }
+                    _ => unimplemented!("mint extension {:?}", extension),
+                }
+                .unwrap(),
+            );
+        }
+        let token = get_associated_token_address_with_program_id(
+            &authority,
+            &mint.pubkey(),
+            &spl_token_2022::ID,
+        );
+        instructions.extend([
+            spl_token_2022::instruction::initialize_mint2(
+                &spl_token_2022::ID,
+                &mint.pubkey(),
+                &authority,
+                None,
+                0,
+            )
+            .unwrap(),
+            spl_associated_token_account::instruction::create_associated_token_account(
+                &authority,
+                &authority,
+                &mint.pubkey(),
+                &spl_token_2022::ID,
+            ),
+            spl_token_2022::instruction::mint_to(
+                &spl_token_2022::ID,
+                &mint.pubkey(),
+                &token,
+                &authority,
+                &[],
+                1,
+            )
+            .unwrap(),
+        ]);
+        if !keep_mint_authority {
+            instructions.push(
+                spl_token_2022::instruction::set_authority(
+                    &spl_token_2022::ID,
+                    &mint.pubkey(),
+                    None,
+                    spl_token_2022::instruction::AuthorityType::MintTokens,
+                    &authority,
+                    &[],
+                )
+                .unwrap(),
+            );
+        }
+        self.process(&instructions, &[owner, &mint]).await.unwrap();
+        mint.pubkey()
+    }
+
+    /// amount held by a token-2022 token account, 0 if it does not exist
+    pub async fn token22_amount(&mut self, address: Pubkey) -> u64 {
+        match self.get_account(address).await {
+            Some(account) => {
+                StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account.data)
+                    .unwrap()
+                    .base
+                    .amount
+            }
+            None => 0,
+        }
+    }
+
+    /// fakes an ovol nft held by the owner, only the token account and metadata are read
+    pub async fn add_ovol_nft(&mut self, owner: Pubkey) -> OvolAccounts {
+        let mint = Keypair::new().pubkey();
+        let nft_ta = Keypair::new().pubkey();
+        let nft_metadata = listings_client::find_metadata(&mint).0;
+
+        let mut token_data = vec![0; spl_token::state::Account::LEN];
+        spl_token::state::Account {
+            mint,
+            owner,
+            amount: 1,
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        }
+        .pack_into_slice(&mut token_data);
+        self.set_program_account(nft_ta, spl_token::ID, token_data);
+
+        let mut metadata_data = Metadata {
+            key: Key::MetadataV1,
+            update_authority: owner,
+            mint,
+            data: Data {
+                name: "ovol".to_string(),
+                symbol: String::new(),
+                uri: String::new(),
+                seller_fee_basis_points: 0,
+                creators: None,
+            },
+            primary_sale_happened: true,
+            is_mutable: true,
+            edition_nonce: None,
+            token_standard: Some(TokenStandard::NonFungible),
+            collection: Some(Collection {
+                verified: true,
+                key: OVOL_COLLECTION.parse().unwrap(),
+            }),
+            uses: None,
+            collection_details: None,
+            programmable_config: None,
+        }
+        .try_to_vec()
+        .unwrap();
+        metadata_data.resize(MAX_METADATA_LEN, 0);
+        self.set_program_account(nft_metadata, token_metadata::ID, metadata_data);
+
+        OvolAccounts {
+            nft_ta,
+            nft_metadata,
+        }
+    }
+}
+
+impl TestContext {
+    /// empty pool of the mock stake pool program and the lst config pointing to it
+    /// the config is written directly since it is set by the protocol treasury
+    pub async fn init_stake_pool(&mut self) -> StakePoolAccounts {
+        let stake_pool = StakePoolAccounts::new(
+            mock_stake_pool::ID,
+            Keypair::new().pubkey(),
+            Keypair::new().pubkey(),
+            Keypair::new().pubkey(),
+            Keypair::new().pubkey(),
+        );
+        self.set_program_account(
+            stake_pool.stake_pool,
+            mock_stake_pool::ID,
+            mock_stake_pool::stake_pool_data(0, 0),
+        );
+        self.set_program_account(stake_pool.reserve_stake, mock_stake_pool::ID, Vec::new());
+
+        let mut mint_data = vec![0; spl_token::state::Mint::LEN];
+        spl_token::state::Mint {
+            mint_authority: Some(stake_pool.withdraw_authority).into(),
+            decimals: 9,
+            is_initialized: true,
+            ..Default::default()
+        }
+        .pack_into_slice(&mut mint_data);
+        self.set_program_account(stake_pool.pool_mint, spl_token::ID, mint_data);
+
+        let mut config_data = listings::state::LstConfig::discriminator().to_vec();
+        config_data.push(listings::state::LST_CONFIG_VERSION);
+        config_data.extend_from_slice(stake_pool.program.as_ref());
+        config_data.extend_from_slice(stake_pool.stake_pool.as_ref());
+        config_data.extend_from_slice(stake_pool.pool_mint.as_ref());
+        config_data.push(true as u8);
+        config_data.resize(8 + std::mem::size_of::<listings::state::LstConfig>(), 0);
+        self.set_program_account(find_lst_config().0, listings::ID, config_data);
+
+        stake_pool
+    }
+
+    /// set the sol withdrawal fee of the stake pool, numerator / denominator
+    pub async fn set_stake_pool_withdrawal_fee(
+        &mut self,
+        stake_pool: &StakePoolAccounts,
+        numerator: u64,
+        denominator: u64,
+    ) {
+        let mut pool = self.get_account(stake_pool.stake_pool).await.unwrap();
+        mock_stake_pool::set_sol_withdrawal_fee(&mut pool.data, numerator, denominator);
+        self.ctx.set_account(&stake_pool.stake_pool, &pool.into());
+    }
+
+    /// simulates staking rewards, pool tokens become worth more lamports
+    pub async fn accrue_stake_pool(&mut self, stake_pool: &StakePoolAccounts, lamports: u64) {
+        let mut pool = self.get_account(stake_pool.stake_pool).await.unwrap();
+        let offset = mock_stake_pool::TOTAL_LAMPORTS_OFFSET;
+        let mut total_lamports = [0; 8];
+        total_lamports.copy_from_slice(&pool.data[offset..offset + 8]);
+        let total_lamports = u64::from_le_bytes(total_lamports) + lamports;
+        pool.data[offset..offset + 8].copy_from_slice(&total_lamports.to_le_bytes());
+        self.ctx.set_account(&stake_pool.stake_pool, &pool.into());
+
+        let mut reserve = self.get_account(stake_pool.reserve_stake).await.unwrap();
+        reserve.lamports += lamports;
+        self.ctx
+            .set_account(&stake_pool.reserve_stake, &reserve.into());
+    }
+}
+
+/// order address for an owner in a market
+pub fn order_address(nonce: Pubkey, market: Pubkey, owner: Pubkey) -> Pubkey {
+    find_order(&nonce, &market, &owner).0
+}
+
+/// compressed nft minted into a [`CompressedTree`]
+#[derive(Clone)]
+pub struct CompressedNft {
+    pub index: u32,
+    pub asset_id: Pubkey,
+    pub owner: Pubkey,
+    pub delegate: Pubkey,
+    pub data_hash: [u8; 32],
+    pub creator_hash: [u8; 32],
+    pub metadata_args_hash: [u8; 32],
+    pub seller_fee_basis_points: u16,
+    pub creators: Vec<CompressedCreator>,
+}
+
+impl CompressedNft {
+    /// royalty creators of the leaf, passed after the ovol accounts of a fill
+    pub fn remaining_accounts(&self) -> RemainingAccounts {
+        RemainingAccounts {
+            creators: self
+                .creators
+                .iter()
+                .map(|creator| creator.address)
+                .collect(),
+            ..RemainingAccounts::default()
+        }
+    }
+}
+
+/// bubblegum tree with an off-chain copy of its leaves to build proofs
+pub struct CompressedTree {
+    pub merkle_tree: Pubkey,
+    pub reference: MerkleTree,
+    pub minted: u32,
+}
+
+impl CompressedTree {
+    pub async fn new(context: &mut TestContext) -> Self {
+        let merkle_tree = Keypair::new();
+        let payer = context.payer();
+        let size = spl_account_compression::state::CONCURRENT_MERKLE_TREE_HEADER_SIZE_V1
+            + std::mem::size_of::<
+                spl_concurrent_merkle_tree::concurrent_merkle_tree::ConcurrentMerkleTree<
+                    { TREE_DEPTH as usize },
+                    { TREE_BUFFER_SIZE as usize },
+                >,
+            >();
+        let rent = context.ctx.banks_client.get_rent().await.unwrap();
+        let allocate = system_instruction::create_account(
+            &payer,
+            &merkle_tree.pubkey(),
+            rent.minimum_balance(size),
+            size as u64,
+            &SPL_ACCOUNT_COMPRESSION_ID,
+        );
+        let create_tree = Instruction {
+            program_id: mpl_bubblegum::ID,
+            accounts: mpl_bubblegum::accounts::CreateTree {
+                tree_authority: find_tree_authority(&merkle_tree.pubkey()).0,
+                merkle_tree: merkle_tree.pubkey(),
+                payer,
+                tree_creator: payer,
+                log_wrapper: SPL_NOOP_ID,
+                compression_program: SPL_ACCOUNT_COMPRESSION_ID,
+                system_program: anchor_lang::system_program::ID,
+            }
+            .to_account_metas(None),
+            data: mpl_bubblegum::instruction::CreateTree {
+                max_depth: TREE_DEPTH,
+                max_buffer_size: TREE_BUFFER_SIZE,
+                public: Some(false),
+            }
+            .data(),
+        };
+        context
+            .process(&[allocate, create_tree], &[&merkle_tree])
+            .await
+            .unwrap();
+
+        Self {
+            merkle_tree: merkle_tree.pubkey(),
+            reference: MerkleTree::new(&vec![Node::default(); 1 << TREE_DEPTH]),
+            minted: 0,
+        }
+    }
+
+    pub async fn mint(&mut self, context: &mut TestContext, owner: Pubkey) -> CompressedNft {
+        let payer = context.payer();
+        // an unverified creator separate from the payer so that royalties can be asserted
+        let creators = vec![CompressedCreator {
+            address: Keypair::new().pubkey(),
+            verified: false,
+            share: 100,
+        }];
+        let metadata = MetadataArgs {
+            name: "compressed".to_string(),
+            symbol: String::new(),
+            uri: String::new(),
+            seller_fee_basis_points: 500,
+            primary_sale_happened: false,
+            is_mutable: true,
+            edition_nonce: None,
+            token_standard: None,
+            collection: None,
+            uses: None,
+            token_program_version: TokenProgramVersion::Original,
+            creators: creators.clone(),
+        };
+        let mint_ix = Instruction {
+            program_id: mpl_bubblegum::ID,
+            accounts: mpl_bubblegum::accounts::MintV1 {
+                tree_authority: find_tree_authority(&self.merkle_tree).0,
+                leaf_owner: owner,
+                leaf_delegate: owner,
+                merkle_tree: self.merkle_tree,
+                payer,
+                tree_delegate: payer,
+                log_wrapper: SPL_NOOP_ID,
+                compression_program: SPL_ACCOUNT_COMPRESSION_ID,
+                system_program: anchor_lang::system_program::ID,
+            }
+            .to_account_metas(None),
+            data: mpl_bubblegum::instruction::MintV1 {
+                message: metadata.clone(),
+            }
+            .data(),
+        };
+        context.process(&[mint_ix], &[]).await.unwrap();
+
+        let index = self.minted;
+        self.minted += 1;
+        let mut nft = CompressedNft {
+            index,
+            asset_id: mpl_bubblegum::utils::get_asset_id(&self.merkle_tree, index as u64),
+            owner,
+            delegate: owner,
+            data_hash: mpl_bubblegum::utils::hash_metadata(&metadata).unwrap(),
+            creator_hash: mpl_bubblegum::utils::hash_creators(&creators).unwrap(),
+            metadata_args_hash: keccak::hash(&metadata.try_to_vec().unwrap()).to_bytes(),
+            seller_fee_basis_points: metadata.seller_fee_basis_points,
+            creators,
+        };
+        self.set_owner(&mut nft, owner);
+        nft
+    }
+
+    /// mirror a transfer of the leaf, owner and delegate are always the same here
+    pub fn set_owner(&mut self, nft: &mut CompressedNft, owner: Pubkey) {
+        nft.owner = owner;
+        nft.delegate = owner;
+        let leaf = LeafSchema::new_v0(
+            nft.asset_id,
+            nft.owner,
+            nft.delegate,
+            nft.index as u64,
+            nft.data_hash,
+            nft.creator_hash,
+        );
+        self.reference.add_leaf(leaf.to_node(), nft.index as usize);
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        self.reference.get_root()
+    }
+
+    /// fill data of a leaf, royalties are checked against its data and creator hash
+    pub fn fill_data(&self, nft: &CompressedNft) -> CompressedFillOrderData {
+        CompressedFillOrderData {
+            root: self.root(),
+            data_hash: nft.data_hash,
+            creator_hash: nft.creator_hash,
+            index: nft.index,
+            metadata_args_hash: nft.metadata_args_hash,
+            seller_fee_basis_points: nft.seller_fee_basis_points,
+            creators: nft
+                .creators
+                .iter()
+                .map(|creator| listings_client::CompressedCreator {
+                    address: creator.address,
+                    verified: creator.verified,
+                    share: creator.share,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn accounts(&self, nft: &CompressedNft) -> CompressedNftAccounts {
+        CompressedNftAccounts {
+            merkle_tree: self.merkle_tree,
+            proof: self
+                .reference
+                .get_proof_of_leaf(nft.index as usize)
+                .into_iter()
+                .map(Pubkey::new_from_array)
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,239 @@
+mod common;
+
+use common::*;
+use listings::{
+    state::{Order, Wallet},
+    utils::get_fee_amount,
+};
+use listings_client::{
+    find_wallet, CompressedCreator, CompressedOrderData, InitOrderData, RemainingAccounts,
+};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+const PRICE: u64 = LAMPORTS_PER_SOL;
+
+fn order_data(nonce: Pubkey, tree: &CompressedTree, nft: &CompressedNft) -> CompressedOrderData {
+    CompressedOrderData {
+        order_nonce: nonce,
+        mint_id: nft.asset_id,
+        price: PRICE,
+        root: tree.root(),
+        data_hash: nft.data_hash,
+        creator_hash: nft.creator_hash,
+        index: nft.index,
+    }
+}
+
+/// royalties of a single creator holding the full share
+fn royalties(nft: &CompressedNft) -> u64 {
+    PRICE * nft.seller_fee_basis_points as u64 / 10000
+}
+
+struct CompressedListing {
+    seller: Keypair,
+    address: Pubkey,
+    tree: CompressedTree,
+    nft: CompressedNft,
+    nonce: Pubkey,
+}
+
+async fn list_compressed_nft(context: &mut TestContext) -> CompressedListing {
+    let (pool_mint, market) = context.init_market().await;
+    let seller = context.create_user(LAMPORTS_PER_SOL).await;
+    let wallet = context.init_wallet(&seller, 0).await;
+    let mut tree = CompressedTree::new(context).await;
+    let mut nft = tree.mint(context, seller.pubkey()).await;
+    context.add_appraisal(pool_mint, nft.asset_id).await;
+
+    let nonce = Keypair::new().pubkey();
+    context
+        .process(
+            &[listings_client::compressed_init_sell_order(
+                seller.pubkey(),
+                pool_mint,
+                &tree.accounts(&nft),
+                order_data(nonce, &tree, &nft),
+            )],
+            &[&seller],
+        )
+        .await
+        .unwrap();
+    // the leaf is held by the bidding wallet while listed
+    tree.set_owner(&mut nft, wallet);
+
+    CompressedListing {
+        address: order_address(nonce, market, seller.pubkey()),
+        seller,
+        tree,
+        nft,
+        nonce,
+    }
+}
+
+#[tokio::test]
+async fn list_and_close_compressed_nft() {
+    let mut context = TestContext::new().await;
+    let mut listing = list_compressed_nft(&mut context).await;
+
+    let order: Order = context.get_zero_copy(listing.address).await;
+    assert_eq!(order.nft_mint, listing.nft.asset_id);
+    assert_eq!(order.wallet, find_wallet(&listing.seller.pubkey()).0);
+
+    context
+        .process(
+            &[listings_client::compressed_close_sell_order(
+                listing.seller.pubkey(),
+                listing.address,
+                &order,
+                &listing.tree.accounts(&listing.nft),
+                order_data(listing.nonce, &listing.tree, &listing.nft),
+            )],
+            &[&listing.seller],
+        )
+        .await
+        .unwrap();
+    listing
+        .tree
+        .set_owner(&mut listing.nft, listing.seller.pubkey());
+    assert!(context.get_account(listing.address).await.is_none());
+}
+
+#[tokio::test]
+async fn fill_compressed_listing() {
+    let mut context = TestContext::new().await;
+    let mut listing = list_compressed_nft(&mut context).await;
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+
+    let order: Order = context.get_zero_copy(listing.address).await;
+    let seller_lamports = context.lamports(listing.seller.pubkey()).await;
+    let buyer_lamports = context.lamports(buyer.pubkey()).await;
+    let order_rent = context.lamports(listing.address).await;
+    let creator = listing.nft.creators[0].address;
+    context
+        .process(
+            &[listings_client::compressed_fill_sell_order(
+                buyer.pubkey(),
+                listing.address,
+                &order,
+                &listing.tree.accounts(&listing.nft),
+                listing.tree.fill_data(&listing.nft),
+                &listing.nft.remaining_accounts(),
+            )],
+            &[&buyer],
+        )
+        .await
+        .unwrap();
+    listing.tree.set_owner(&mut listing.nft, buyer.pubkey());
+
+    assert!(context.get_account(listing.address).await.is_none());
+    // the buyer pays fees and royalties on top of the price
+    assert_eq!(
+        context.lamports(listing.seller.pubkey()).await,
+        seller_lamports + PRICE + order_rent
+    );
+    assert_eq!(context.lamports(creator).await, royalties(&listing.nft));
+    assert_eq!(
+        context.lamports(buyer.pubkey()).await,
+        buyer_lamports - PRICE - get_fee_amount(PRICE) - royalties(&listing.nft)
+    );
+}
+
+#[tokio::test]
+async fn fill_compressed_listing_with_forged_royalties() {
+    let mut context = TestContext::new().await;
+    let listing = list_compressed_nft(&mut context).await;
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+
+    // royalties that do not hash to the leaf are rejected
+    let order: Order = context.get_zero_copy(listing.address).await;
+    let mut data = listing.tree.fill_data(&listing.nft);
+    data.seller_fee_basis_points = 0;
+    data.creators = vec![CompressedCreator {
+        address: buyer.pubkey(),
+        verified: false,
+        share: 100,
+    }];
+    assert!(context
+        .process(
+            &[listings_client::compressed_fill_sell_order(
+                buyer.pubkey(),
+                listing.address,
+                &order,
+                &listing.tree.accounts(&listing.nft),
+                data,
+                &RemainingAccounts {
+                    creators: vec![buyer.pubkey()],
+                    ..RemainingAccounts::default()
+                },
+            )],
+            &[&buyer],
+        )
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn fill_bid_with_compressed_nft() {
+    let mut context = TestContext::new().await;
+    let (pool_mint, market) = context.init_market().await;
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    let seller = context.create_user(LAMPORTS_PER_SOL).await;
+    context.init_wallet(&buyer, 2 * PRICE).await;
+    let mut tree = CompressedTree::new(&mut context).await;
+    let mut nft = tree.mint(&mut context, seller.pubkey()).await;
+
+    let nonce = Keypair::new().pubkey();
+    let address = order_address(nonce, market, buyer.pubkey());
+    context
+        .process(
+            &[listings_client::init_buy_order(
+                buyer.pubkey(),
+                buyer.pubkey(),
+                pool_mint,
+                Pubkey::default(),
+                InitOrderData {
+                    nonce,
+                    price: PRICE,
+                    size: 1,
+                },
+                &RemainingAccounts::default(),
+            )],
+            &[&buyer],
+        )
+        .await
+        .unwrap();
+
+    let order: Order = context.get_zero_copy(address).await;
+    let wallet = find_wallet(&buyer.pubkey()).0;
+    let wallet_lamports = context.lamports(wallet).await;
+    let seller_lamports = context.lamports(seller.pubkey()).await;
+    context
+        .process(
+            &[listings_client::compressed_fill_buy_order(
+                seller.pubkey(),
+                address,
+                &order,
+                &tree.accounts(&nft),
+                tree.fill_data(&nft),
+                &nft.remaining_accounts(),
+            )],
+            &[&seller],
+        )
+        .await
+        .unwrap();
+    tree.set_owner(&mut nft, buyer.pubkey());
+    assert!(context.get_account(address).await.is_none());
+
+    // fee and royalties are taken out of the price, the wallet pays exactly the price
+    let wallet_state: Wallet = context.get_zero_copy(wallet).await;
+    assert_eq!(wallet_state.balance, PRICE);
+    assert_eq!(context.lamports(wallet).await, wallet_lamports - PRICE);
+    assert_eq!(
+        context.lamports(nft.creators[0].address).await,
+        royalties(&nft)
+    );
+    assert_eq!(
+        context.lamports(seller.pubkey()).await,
+        seller_lamports + PRICE - get_fee_amount(PRICE) - royalties(&nft)
+    );
+}
@@ -0,0 +1,327 @@
+mod common;
+
+use common::*;
+use listings::state::{Order, OrderState};
+use listings_client::{
+    find_wallet, with_order_book, EditBuyOrderData, InitOrderData, NftAccounts, RemainingAccounts,
+};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+const PRICE: u64 = LAMPORTS_PER_SOL;
+const TRIGGER_PRICE: u64 = PRICE * 8 / 10;
+
+/// bid of a new buyer whose wallet covers the price, returns (buyer, bid)
+async fn bid(
+    context: &mut TestContext,
+    pool_mint: Pubkey,
+    market: Pubkey,
+    price: u64,
+) -> (Keypair, Pubkey) {
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    context.init_wallet(&buyer, PRICE).await;
+    let nonce = Keypair::new().pubkey();
+    context
+        .process(
+            &[with_order_book(
+                listings_client::init_buy_order(
+                    buyer.pubkey(),
+                    buyer.pubkey(),
+                    pool_mint,
+                    Pubkey::default(),
+                    InitOrderData {
+                        nonce,
+                        price,
+                        size: 1,
+                    },
+                    &RemainingAccounts::default(),
+                ),
+                &market,
+            )],
+            &[&buyer],
+        )
+        .await
+        .unwrap();
+    let address = order_address(nonce, market, buyer.pubkey());
+    (buyer, address)
+}
+
+/// stop loss listing of a new seller, returns (listing, nft)
+async fn list_with_trigger(
+    context: &mut TestContext,
+    pool_mint: Pubkey,
+    market: Pubkey,
+) -> (Pubkey, NftAccounts) {
+    let seller = context.create_user(LAMPORTS_PER_SOL).await;
+    context.init_wallet(&seller, 0).await;
+    let nft = context.mint_nft(&seller).await;
+    context.add_appraisal(pool_mint, nft.mint).await;
+    let nonce = Keypair::new().pubkey();
+    let address = order_address(nonce, market, seller.pubkey());
+    context
+        .process(
+            &[with_order_book(
+                listings_client::init_sell_order(
+                    seller.pubkey(),
+                    pool_mint,
+                    nft,
+                    InitOrderData {
+                        nonce,
+                        price: PRICE,
+                        size: 1,
+                    },
+                    &RemainingAccounts::default(),
+                ),
+                &market,
+            )],
+            &[&seller],
+        )
+        .await
+        .unwrap();
+    let order: Order = context.get_zero_copy(address).await;
+    context
+        .process(
+            &[with_order_book(
+                listings_client::set_sell_trigger(seller.pubkey(), address, &order, TRIGGER_PRICE),
+                &market,
+            )],
+            &[&seller],
+        )
+        .await
+        .unwrap();
+    (address, nft)
+}
+
+#[tokio::test]
+async fn stop_loss_listing_waits_for_the_best_bid() {
+    let mut context = TestContext::new().await;
+    let (pool_mint, market) = context.init_order_book_market().await;
+
+    // bid above the trigger price
+    let (buyer, bid_address) = bid(&mut context, pool_mint, market, PRICE * 9 / 10).await;
+    let (address, nft) = list_with_trigger(&mut context, pool_mint, market).await;
+    let order: Order = context.get_zero_copy(address).await;
+    assert_eq!(order.state, u8::from(OrderState::Inactive));
+
+    let fill = |buyer: &Keypair, nft: NftAccounts, order: &Order| {
+        with_order_book(
+            listings_client::fill_sell_order(
+                buyer.pubkey(),
+                address,
+                order,
+                nft,
+                &RemainingAccounts::default(),
+            ),
+            &market,
+        )
+    };
+    let result = context
+        .process(&[fill(&buyer, nft, &order)], &[&buyer])
+        .await;
+    assert!(result.is_err());
+
+    let cranker = context.create_user(LAMPORTS_PER_SOL).await;
+    let bids = [(bid_address, find_wallet(&buyer.pubkey()).0)];
+    let activate = listings_client::activate_order(cranker.pubkey(), address, &order, &bids);
+    let result = context.process(&[activate.clone()], &[&cranker]).await;
+    assert!(result.is_err());
+
+    // the best bid drops below the trigger
+    let bid: Order = context.get_zero_copy(bid_address).await;
+    context
+        .process(
+            &[with_order_book(
+                listings_client::edit_buy_order(
+                    buyer.pubkey(),
+                    bid_address,
+                    &bid,
+                    pool_mint,
+                    EditBuyOrderData {
+                        new_size: 1,
+                        new_price: PRICE / 2,
+                    },
+                ),
+                &market,
+            )],
+            &[&buyer],
+        )
+        .await
+        .unwrap();
+    context.process(&[activate], &[&cranker]).await.unwrap();
+    let order: Order = context.get_zero_copy(address).await;
+    assert_eq!(order.state, u8::from(OrderState::Ready));
+
+    context
+        .process(&[fill(&buyer, nft, &order)], &[&buyer])
+        .await
+        .unwrap();
+    assert!(context.get_account(address).await.is_none());
+}
+
+#[tokio::test]
+async fn activation_skips_unfunded_bids() {
+    let mut context = TestContext::new().await;
+    let (pool_mint, market) = context.init_order_book_market().await;
+    let (buyer, bid_address) = bid(&mut context, pool_mint, market, PRICE * 9 / 10).await;
+    let (other_buyer, other_bid_address) = bid(&mut context, pool_mint, market, PRICE / 2).await;
+    let (address, _) = list_with_trigger(&mut context, pool_mint, market).await;
+    let order: Order = context.get_zero_copy(address).await;
+    let bids = [
+        (bid_address, find_wallet(&buyer.pubkey()).0),
+        (other_bid_address, find_wallet(&other_buyer.pubkey()).0),
+    ];
+
+    // the bids of the book and their wallets have to be passed
+    let cranker = context.create_user(LAMPORTS_PER_SOL).await;
+    for bids in [
+        vec![],
+        vec![(address, find_wallet(&buyer.pubkey()).0)],
+        vec![(bid_address, bid_address)],
+    ] {
+        let result = context
+            .process(
+                &[listings_client::activate_order(
+                    cranker.pubkey(),
+                    address,
+                    &order,
+                    &bids,
+                )],
+                &[&cranker],
+            )
+            .await;
+        assert!(result.is_err());
+    }
+
+    // the best bid stays in the book but its wallet no longer covers it
+    context
+        .process(
+            &[listings_client::edit_wallet(buyer.pubkey(), PRICE, false)],
+            &[&buyer],
+        )
+        .await
+        .unwrap();
+    context
+        .process(
+            &[listings_client::activate_order(
+                cranker.pubkey(),
+                address,
+                &order,
+                &bids,
+            )],
+            &[&cranker],
+        )
+        .await
+        .unwrap();
+    let order: Order = context.get_zero_copy(address).await;
+    assert_eq!(order.state, u8::from(OrderState::Ready));
+}
+
+#[tokio::test]
+async fn activation_needs_a_funded_bid() {
+    let mut context = TestContext::new().await;
+    let (pool_mint, market) = context.init_order_book_market().await;
+    let (address, _) = list_with_trigger(&mut context, pool_mint, market).await;
+    let order: Order = context.get_zero_copy(address).await;
+
+    // an empty book has not crossed the trigger
+    let cranker = context.create_user(LAMPORTS_PER_SOL).await;
+    let activate = listings_client::activate_order(cranker.pubkey(), address, &order, &[]);
+    let result = context.process(&[activate], &[&cranker]).await;
+    assert!(result.is_err());
+
+    // neither has a book whose only bid is unfunded
+    let (buyer, bid_address) = bid(&mut context, pool_mint, market, PRICE / 2).await;
+    context
+        .process(
+            &[listings_client::edit_wallet(buyer.pubkey(), PRICE, false)],
+            &[&buyer],
+        )
+        .await
+        .unwrap();
+    let result = context
+        .process(
+            &[listings_client::activate_order(
+                cranker.pubkey(),
+                address,
+                &order,
+                &[(bid_address, find_wallet(&buyer.pubkey()).0)],
+            )],
+            &[&cranker],
+        )
+        .await;
+    assert!(result.is_err());
+    let order: Order = context.get_zero_copy(address).await;
+    assert_eq!(order.state, u8::from(OrderState::Inactive));
+}
+
+#[tokio::test]
+async fn activation_needs_the_order_book() {
+    let mut context = TestContext::new().await;
+    let (pool_mint, market) = context.init_market().await;
+    let seller = context.create_user(LAMPORTS_PER_SOL).await;
+    context.init_wallet(&seller, 0).await;
+    let nft = context.mint_nft(&seller).await;
+    context.add_appraisal(pool_mint, nft.mint).await;
+    let nonce = Keypair::new().pubkey();
+    let address = order_address(nonce, market, seller.pubkey());
+    context
+        .process(
+            &[listings_client::init_sell_order(
+                seller.pubkey(),
+                pool_mint,
+                nft,
+                InitOrderData {
+                    nonce,
+                    price: PRICE,
+                    size: 1,
+                },
+                &RemainingAccounts::default(),
+            )],
+            &[&seller],
+        )
+        .await
+        .unwrap();
+    let order: Order = context.get_zero_copy(address).await;
+    context
+        .process(
+            &[listings_client::set_sell_trigger(
+                seller.pubkey(),
+                address,
+                &order,
+                TRIGGER_PRICE,
+            )],
+            &[&seller],
+        )
+        .await
+        .unwrap();
+
+    // without an order book nobody but the seller can activate the listing
+    let cranker = context.create_user(LAMPORTS_PER_SOL).await;
+    let result = context
+        .process(
+            &[listings_client::activate_order(
+                cranker.pubkey(),
+                address,
+                &order,
+                &[],
+            )],
+            &[&cranker],
+        )
+        .await;
+    assert!(result.is_err());
+
+    // the seller can always activate the listing again
+    context
+        .process(
+            &[listings_client::set_sell_trigger(
+                seller.pubkey(),
+                address,
+                &order,
+                0,
+            )],
+            &[&seller],
+        )
+        .await
+        .unwrap();
+    let order: Order = context.get_zero_copy(address).await;
+    assert_eq!(order.state, u8::from(OrderState::Ready));
+}
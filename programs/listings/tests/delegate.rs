@@ -0,0 +1,292 @@
+mod common;
+
+use common::*;
+use listings::state::{Order, Wallet};
+use listings_client::{EditBuyOrderData, InitOrderData, RemainingAccounts};
+use solana_program_test::BanksClientError;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+const PRICE: u64 = LAMPORTS_PER_SOL;
+
+struct Delegated {
+    owner: Keypair,
+    delegate: Keypair,
+    pool_mint: Pubkey,
+    market: Pubkey,
+    wallet: Pubkey,
+}
+
+/// wallet holding two bids worth of balance, with a delegate capped at a single bid
+async fn setup(context: &mut TestContext) -> Delegated {
+    let (pool_mint, market) = context.init_market().await;
+    let owner = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    let delegate = context.create_user(LAMPORTS_PER_SOL).await;
+    let wallet = context.init_wallet(&owner, 2 * PRICE).await;
+
+    context
+        .process(
+            &[listings_client::set_wallet_delegate(
+                owner.pubkey(),
+                delegate.pubkey(),
+                PRICE,
+            )],
+            &[&owner],
+        )
+        .await
+        .unwrap();
+
+    Delegated {
+        owner,
+        delegate,
+        pool_mint,
+        market,
+        wallet,
+    }
+}
+
+async fn delegate_bid(
+    context: &mut TestContext,
+    setup: &Delegated,
+    price: u64,
+) -> Result<Pubkey, BanksClientError> {
+    let nonce = Keypair::new().pubkey();
+    context
+        .process(
+            &[listings_client::init_buy_order(
+                setup.delegate.pubkey(),
+                setup.owner.pubkey(),
+                setup.pool_mint,
+                Pubkey::default(),
+                InitOrderData {
+                    nonce,
+                    price,
+                    size: 1,
+                },
+                &RemainingAccounts::default(),
+            )],
+            &[&setup.delegate],
+        )
+        .await?;
+    Ok(order_address(nonce, setup.market, setup.owner.pubkey()))
+}
+
+#[tokio::test]
+async fn delegate_places_bids_up_to_the_cap() {
+    let mut context = TestContext::new().await;
+    let setup = setup(&mut context).await;
+
+    let wallet: Wallet = context.get_zero_copy(setup.wallet).await;
+    assert_eq!(wallet.delegate, setup.delegate.pubkey());
+    assert_eq!(wallet.delegate_spending_cap, PRICE);
+
+    // order stays keyed to the owner
+    let address = delegate_bid(&mut context, &setup, PRICE).await.unwrap();
+    let order: Order = context.get_zero_copy(address).await;
+    assert_eq!(order.owner, setup.owner.pubkey());
+    assert_eq!(order.wallet, setup.wallet);
+
+    // the balance covers it but the cap does not
+    assert!(delegate_bid(&mut context, &setup, 2 * PRICE).await.is_err());
+
+    // edits are capped as well
+    let edit = |new_price| {
+        listings_client::edit_buy_order(
+            setup.delegate.pubkey(),
+            address,
+            &order,
+            setup.pool_mint,
+            EditBuyOrderData {
+                new_size: 1,
+                new_price,
+            },
+        )
+    };
+    let result = context
+        .process(&[edit(2 * PRICE)], &[&setup.delegate])
+        .await;
+    assert!(result.is_err());
+    context
+        .process(&[edit(PRICE / 2)], &[&setup.delegate])
+        .await
+        .unwrap();
+    let order: Order = context.get_zero_copy(address).await;
+    assert_eq!(order.price, PRICE / 2);
+    assert_eq!(order.delegated_value, PRICE / 2);
+    let wallet: Wallet = context.get_zero_copy(setup.wallet).await;
+    assert_eq!(wallet.delegate_exposure, PRICE / 2);
+}
+
+#[tokio::test]
+async fn delegate_cap_covers_all_open_bids() {
+    let mut context = TestContext::new().await;
+    let setup = setup(&mut context).await;
+
+    // each bid fits in the cap, together they do not
+    let first = delegate_bid(&mut context, &setup, PRICE / 2).await.unwrap();
+    delegate_bid(&mut context, &setup, PRICE / 2).await.unwrap();
+    assert!(delegate_bid(&mut context, &setup, PRICE / 2).await.is_err());
+    let wallet: Wallet = context.get_zero_copy(setup.wallet).await;
+    assert_eq!(wallet.delegate_exposure, PRICE);
+
+    // closing a bid frees its share of the cap
+    let order: Order = context.get_zero_copy(first).await;
+    context
+        .process(
+            &[listings_client::close_buy_order(
+                setup.delegate.pubkey(),
+                first,
+                &order,
+            )],
+            &[&setup.delegate],
+        )
+        .await
+        .unwrap();
+    let wallet: Wallet = context.get_zero_copy(setup.wallet).await;
+    assert_eq!(wallet.delegate_exposure, PRICE / 2);
+    let third = delegate_bid(&mut context, &setup, PRICE / 2).await.unwrap();
+
+    // an edit by the owner takes the bid out of the delegate's exposure
+    let order: Order = context.get_zero_copy(third).await;
+    context
+        .process(
+            &[listings_client::edit_buy_order(
+                setup.owner.pubkey(),
+                third,
+                &order,
+                setup.pool_mint,
+                EditBuyOrderData {
+                    new_size: 1,
+                    new_price: PRICE / 2,
+                },
+            )],
+            &[&setup.owner],
+        )
+        .await
+        .unwrap();
+    let order: Order = context.get_zero_copy(third).await;
+    assert_eq!(order.delegated_value, 0);
+    let wallet: Wallet = context.get_zero_copy(setup.wallet).await;
+    assert_eq!(wallet.delegate_exposure, PRICE / 2);
+}
+
+#[tokio::test]
+async fn delegate_cannot_edit_or_close_sell_order() {
+    let mut context = TestContext::new().await;
+    let setup = setup(&mut context).await;
+    let nft = context.mint_nft(&setup.owner).await;
+    context.add_appraisal(setup.pool_mint, nft.mint).await;
+
+    let nonce = Keypair::new().pubkey();
+    context
+        .process(
+            &[listings_client::init_sell_order(
+                setup.owner.pubkey(),
+                setup.pool_mint,
+                nft,
+                InitOrderData {
+                    nonce,
+                    price: PRICE,
+                    size: 1,
+                },
+                &RemainingAccounts::default(),
+            )],
+            &[&setup.owner],
+        )
+        .await
+        .unwrap();
+    let address = order_address(nonce, setup.market, setup.owner.pubkey());
+    let order: Order = context.get_zero_copy(address).await;
+
+    // the buy order instructions reject the listing of the owner
+    let result = context
+        .process(
+            &[listings_client::edit_buy_order(
+                setup.delegate.pubkey(),
+                address,
+                &order,
+                setup.pool_mint,
+                EditBuyOrderData {
+                    new_size: 1,
+                    new_price: 1,
+                },
+            )],
+            &[&setup.delegate],
+        )
+        .await;
+    assert!(result.is_err());
+    let result = context
+        .process(
+            &[listings_client::close_buy_order(
+                setup.delegate.pubkey(),
+                address,
+                &order,
+            )],
+            &[&setup.delegate],
+        )
+        .await;
+    assert!(result.is_err());
+
+    let order: Order = context.get_zero_copy(address).await;
+    assert_eq!(order.price, PRICE);
+}
+
+#[tokio::test]
+async fn delegate_close_returns_rent_to_owner() {
+    let mut context = TestContext::new().await;
+    let setup = setup(&mut context).await;
+    let address = delegate_bid(&mut context, &setup, PRICE).await.unwrap();
+    let order: Order = context.get_zero_copy(address).await;
+
+    let rent = context.lamports(address).await;
+    let owner_lamports = context.lamports(setup.owner.pubkey()).await;
+    context
+        .process(
+            &[listings_client::close_buy_order(
+                setup.delegate.pubkey(),
+                address,
+                &order,
+            )],
+            &[&setup.delegate],
+        )
+        .await
+        .unwrap();
+    assert!(context.get_account(address).await.is_none());
+    assert_eq!(
+        context.lamports(setup.owner.pubkey()).await,
+        owner_lamports + rent
+    );
+}
+
+#[tokio::test]
+async fn delegate_cannot_withdraw_and_can_be_removed() {
+    let mut context = TestContext::new().await;
+    let setup = setup(&mut context).await;
+
+    // the wallet is derived from the signer, so the delegate never reaches the owner's funds
+    let result = context
+        .process(
+            &[listings_client::edit_wallet(
+                setup.delegate.pubkey(),
+                PRICE,
+                false,
+            )],
+            &[&setup.delegate],
+        )
+        .await;
+    assert!(result.is_err());
+    let wallet: Wallet = context.get_zero_copy(setup.wallet).await;
+    assert_eq!(wallet.balance, 2 * PRICE);
+
+    context
+        .process(
+            &[listings_client::set_wallet_delegate(
+                setup.owner.pubkey(),
+                Pubkey::default(),
+                0,
+            )],
+            &[&setup.owner],
+        )
+        .await
+        .unwrap();
+    assert!(delegate_bid(&mut context, &setup, PRICE).await.is_err());
+}
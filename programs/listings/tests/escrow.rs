@@ -0,0 +1,192 @@
+mod common;
+
+use anchor_spl::associated_token::get_associated_token_address;
+use common::*;
+use listings::state::{ListingMode, Market, Order};
+use listings_client::{find_market, find_wallet, InitOrderData, NftAccounts, RemainingAccounts};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+const PRICE: u64 = LAMPORTS_PER_SOL;
+
+struct EscrowMarket {
+    initializer: Keypair,
+    pool_mint: Pubkey,
+    market: Pubkey,
+}
+
+async fn init_escrow_market(context: &mut TestContext) -> EscrowMarket {
+    let pool_mint = Keypair::new().pubkey();
+    let initializer = context.create_user(LAMPORTS_PER_SOL).await;
+    context
+        .process(
+            &[
+                listings_client::init_market(initializer.pubkey(), pool_mint),
+                listings_client::edit_market(initializer.pubkey(), pool_mint, ListingMode::Escrow),
+            ],
+            &[&initializer],
+        )
+        .await
+        .unwrap();
+    EscrowMarket {
+        initializer,
+        pool_mint,
+        market: find_market(&pool_mint).0,
+    }
+}
+
+struct SellOrder {
+    seller: Keypair,
+    nft: NftAccounts,
+    address: Pubkey,
+}
+
+async fn init_sell_order(context: &mut TestContext, market: &EscrowMarket) -> SellOrder {
+    let seller = context.create_user(LAMPORTS_PER_SOL).await;
+    context.init_wallet(&seller, 0).await;
+    let nft = context.mint_nft(&seller).await;
+    context.add_appraisal(market.pool_mint, nft.mint).await;
+
+    let nonce = Keypair::new().pubkey();
+    context
+        .process(
+            &[listings_client::init_sell_order(
+                seller.pubkey(),
+                market.pool_mint,
+                nft,
+                InitOrderData {
+                    nonce,
+                    price: PRICE,
+                    size: 1,
+                },
+                &RemainingAccounts::default(),
+            )],
+            &[&seller],
+        )
+        .await
+        .unwrap();
+
+    SellOrder {
+        address: order_address(nonce, market.market, seller.pubkey()),
+        seller,
+        nft,
+    }
+}
+
+fn escrow_ata(listing: &SellOrder) -> Pubkey {
+    get_associated_token_address(&find_wallet(&listing.seller.pubkey()).0, &listing.nft.mint)
+}
+
+#[tokio::test]
+async fn edit_market_by_other_user_fails() {
+    let mut context = TestContext::new().await;
+    let market = init_escrow_market(&mut context).await;
+    let market_state: Market = context.get_anchor(market.market).await;
+    assert_eq!(market_state.listing_mode, u8::from(ListingMode::Escrow));
+
+    let other = context.create_user(LAMPORTS_PER_SOL).await;
+    let result = context
+        .process(
+            &[listings_client::edit_market(
+                other.pubkey(),
+                market.pool_mint,
+                ListingMode::FreezeInPlace,
+            )],
+            &[&other],
+        )
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn init_sell_order_moves_nft_into_escrow() {
+    let mut context = TestContext::new().await;
+    let market = init_escrow_market(&mut context).await;
+    let listing = init_sell_order(&mut context, &market).await;
+
+    let order: Order = context.get_zero_copy(listing.address).await;
+    assert!(order.is_escrowed());
+    assert_eq!(
+        order.get_nft_holder(),
+        find_wallet(&listing.seller.pubkey()).0
+    );
+
+    let nft_ta = get_associated_token_address(&listing.seller.pubkey(), &listing.nft.mint);
+    assert_eq!(context.token_account(nft_ta).await.amount, 0);
+    assert_eq!(context.token_account(escrow_ata(&listing)).await.amount, 1);
+}
+
+#[tokio::test]
+async fn close_escrowed_sell_order_returns_nft() {
+    let mut context = TestContext::new().await;
+    let market = init_escrow_market(&mut context).await;
+    let listing = init_sell_order(&mut context, &market).await;
+
+    // existing listings keep their mode when the market changes
+    context
+        .process(
+            &[listings_client::edit_market(
+                market.initializer.pubkey(),
+                market.pool_mint,
+                ListingMode::FreezeInPlace,
+            )],
+            &[&market.initializer],
+        )
+        .await
+        .unwrap();
+
+    let order: Order = context.get_zero_copy(listing.address).await;
+    context
+        .process(
+            &[listings_client::close_sell_order(
+                listing.seller.pubkey(),
+                listing.address,
+                &order,
+                listing.nft,
+                &RemainingAccounts::default(),
+            )],
+            &[&listing.seller],
+        )
+        .await
+        .unwrap();
+    assert!(context.get_account(listing.address).await.is_none());
+    assert!(context.get_account(escrow_ata(&listing)).await.is_none());
+
+    let nft_ta = get_associated_token_address(&listing.seller.pubkey(), &listing.nft.mint);
+    assert_eq!(context.token_account(nft_ta).await.amount, 1);
+}
+
+#[tokio::test]
+async fn fill_escrowed_sell_order() {
+    let mut context = TestContext::new().await;
+    let market = init_escrow_market(&mut context).await;
+    let listing = init_sell_order(&mut context, &market).await;
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+
+    let order: Order = context.get_zero_copy(listing.address).await;
+    let seller_lamports = context.lamports(listing.seller.pubkey()).await;
+    let order_rent = context.lamports(listing.address).await;
+    let escrow_rent = context.lamports(escrow_ata(&listing)).await;
+    context
+        .process(
+            &[listings_client::fill_sell_order(
+                buyer.pubkey(),
+                listing.address,
+                &order,
+                listing.nft,
+                &RemainingAccounts::default(),
+            )],
+            &[&buyer],
+        )
+        .await
+        .unwrap();
+
+    let buyer_nft_ta = get_associated_token_address(&buyer.pubkey(), &listing.nft.mint);
+    assert_eq!(context.token_account(buyer_nft_ta).await.amount, 1);
+    assert!(context.get_account(escrow_ata(&listing)).await.is_none());
+
+    // seller gets the price and the rent of the order and the escrow account
+    assert_eq!(
+        context.lamports(listing.seller.pubkey()).await,
+        seller_lamports + PRICE + order_rent + escrow_rent
+    );
+}
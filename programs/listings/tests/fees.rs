@@ -0,0 +1,150 @@
+mod common;
+
+use common::*;
+use listings::state::Order;
+use listings_client::{treasury, InitOrderData, RemainingAccounts};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+const PRICE: u64 = LAMPORTS_PER_SOL;
+
+#[tokio::test]
+async fn ovol_holder_bid_has_fees_off() {
+    let mut context = TestContext::new().await;
+    let (pool_mint, market) = context.init_market().await;
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    let seller = context.create_user(LAMPORTS_PER_SOL).await;
+    context.init_wallet(&buyer, PRICE).await;
+    let ovol = context.add_ovol_nft(buyer.pubkey()).await;
+    let nft = context.mint_nft(&seller).await;
+
+    let nonce = Keypair::new().pubkey();
+    let address = order_address(nonce, market, buyer.pubkey());
+    context
+        .process(
+            &[listings_client::init_buy_order(
+                buyer.pubkey(),
+                buyer.pubkey(),
+                pool_mint,
+                Pubkey::default(),
+                InitOrderData {
+                    nonce,
+                    price: PRICE,
+                    size: 1,
+                },
+                &RemainingAccounts {
+                    ovol: Some(ovol),
+                    ..Default::default()
+                },
+            )],
+            &[&buyer],
+        )
+        .await
+        .unwrap();
+    let order: Order = context.get_zero_copy(address).await;
+    assert!(!order.is_fees_on());
+
+    // without ovol accounts in the fill the fees of the order apply
+    let treasury_lamports = context.lamports(treasury()).await;
+    context
+        .process(
+            &[listings_client::fill_buy_order(
+                seller.pubkey(),
+                address,
+                &order,
+                nft,
+                &RemainingAccounts::default(),
+            )],
+            &[&seller],
+        )
+        .await
+        .unwrap();
+    assert_eq!(context.lamports(treasury()).await, treasury_lamports);
+}
+
+#[tokio::test]
+async fn ovol_holder_filling_a_listing_pays_no_fees() {
+    let mut context = TestContext::new().await;
+    let (pool_mint, market) = context.init_market().await;
+    let seller = context.create_user(LAMPORTS_PER_SOL).await;
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    context.init_wallet(&seller, 0).await;
+    let ovol = context.add_ovol_nft(buyer.pubkey()).await;
+    let nft = context.mint_nft(&seller).await;
+    context.add_appraisal(pool_mint, nft.mint).await;
+
+    let nonce = Keypair::new().pubkey();
+    let address = order_address(nonce, market, seller.pubkey());
+    context
+        .process(
+            &[listings_client::init_sell_order(
+                seller.pubkey(),
+                pool_mint,
+                nft,
+                InitOrderData {
+                    nonce,
+                    price: PRICE,
+                    size: 1,
+                },
+                &RemainingAccounts::default(),
+            )],
+            &[&seller],
+        )
+        .await
+        .unwrap();
+    let order: Order = context.get_zero_copy(address).await;
+    assert!(order.is_fees_on());
+
+    let treasury_lamports = context.lamports(treasury()).await;
+    context
+        .process(
+            &[listings_client::fill_sell_order(
+                buyer.pubkey(),
+                address,
+                &order,
+                nft,
+                &RemainingAccounts {
+                    ovol: Some(ovol),
+                    ..Default::default()
+                },
+            )],
+            &[&buyer],
+        )
+        .await
+        .unwrap();
+    assert_eq!(context.lamports(treasury()).await, treasury_lamports);
+}
+
+#[tokio::test]
+async fn ovol_accounts_of_someone_else_are_ignored() {
+    let mut context = TestContext::new().await;
+    let (pool_mint, market) = context.init_market().await;
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    context.init_wallet(&buyer, PRICE).await;
+    let ovol = context.add_ovol_nft(Keypair::new().pubkey()).await;
+
+    let nonce = Keypair::new().pubkey();
+    let address = order_address(nonce, market, buyer.pubkey());
+    context
+        .process(
+            &[listings_client::init_buy_order(
+                buyer.pubkey(),
+                buyer.pubkey(),
+                pool_mint,
+                Pubkey::default(),
+                InitOrderData {
+                    nonce,
+                    price: PRICE,
+                    size: 1,
+                },
+                &RemainingAccounts {
+                    ovol: Some(ovol),
+                    ..Default::default()
+                },
+            )],
+            &[&buyer],
+        )
+        .await
+        .unwrap();
+    let order: Order = context.get_zero_copy(address).await;
+    assert!(order.is_fees_on());
+}
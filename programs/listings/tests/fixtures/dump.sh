@@ -0,0 +1,14 @@
+#!/usr/bin/env bash
+# dumps the programs the listings program cpis into so the test suite runs offline
+# the programs and their ids come from `fixture_programs` in tests/common/mod.rs
+# usage: ./dump.sh, set CLUSTER to dump from another cluster than mainnet and FORCE=1 to refresh
+set -e
+cd "$(dirname "$0")"
+
+cargo run --quiet -p listings --example fixture_programs | while read -r name id; do
+    if [ -f "$name.so" ] && [ -z "$FORCE" ]; then
+        continue
+    fi
+    echo "dumping $name $id"
+    solana program dump -u "${CLUSTER:-m}" "$id" "$name.so"
+done
@@ -0,0 +1,172 @@
+mod common;
+
+use anchor_spl::associated_token::get_associated_token_address;
+use common::*;
+use listings::state::{Order, OrderState};
+use listings_client::{find_wallet, InitOrderData, NftAccounts, RemainingAccounts};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+const PRICE: u64 = LAMPORTS_PER_SOL;
+const SIZE: u64 = 3;
+
+struct SellOrder {
+    seller: Keypair,
+    nft: NftAccounts,
+    address: Pubkey,
+}
+
+fn init_sell_order_ix(
+    seller: &Keypair,
+    pool_mint: Pubkey,
+    nft: NftAccounts,
+    nonce: Pubkey,
+    size: u64,
+) -> Instruction {
+    listings_client::init_sell_order(
+        seller.pubkey(),
+        pool_mint,
+        nft,
+        InitOrderData {
+            nonce,
+            price: PRICE,
+            size,
+        },
+        &RemainingAccounts::default(),
+    )
+}
+
+async fn init_sell_order(context: &mut TestContext) -> SellOrder {
+    let (pool_mint, market) = context.init_market().await;
+    let seller = context.create_user(LAMPORTS_PER_SOL).await;
+    context.init_wallet(&seller, 0).await;
+    let nft = context.mint_fungible_asset(&seller, SIZE).await;
+    context.add_appraisal(pool_mint, nft.mint).await;
+
+    let nonce = Keypair::new().pubkey();
+    context
+        .process(
+            &[init_sell_order_ix(&seller, pool_mint, nft, nonce, SIZE)],
+            &[&seller],
+        )
+        .await
+        .unwrap();
+
+    SellOrder {
+        address: order_address(nonce, market, seller.pubkey()),
+        seller,
+        nft,
+    }
+}
+
+async fn fill(context: &mut TestContext, listing: &SellOrder, buyer: &Keypair) {
+    let order: Order = context.get_zero_copy(listing.address).await;
+    context
+        .process(
+            &[listings_client::fill_sell_order(
+                buyer.pubkey(),
+                listing.address,
+                &order,
+                listing.nft,
+                &RemainingAccounts::default(),
+            )],
+            &[buyer],
+        )
+        .await
+        .unwrap();
+}
+
+fn escrow_ata(listing: &SellOrder) -> Pubkey {
+    get_associated_token_address(&find_wallet(&listing.seller.pubkey()).0, &listing.nft.mint)
+}
+
+#[tokio::test]
+async fn fungible_asset_listing_is_escrowed() {
+    let mut context = TestContext::new().await;
+    let listing = init_sell_order(&mut context).await;
+
+    // markets freeze in place by default, semi-fungible assets are escrowed regardless
+    let order: Order = context.get_zero_copy(listing.address).await;
+    assert_eq!(order.size, SIZE);
+    assert!(order.is_escrowed());
+    assert_eq!(
+        context.token_account(escrow_ata(&listing)).await.amount,
+        SIZE
+    );
+}
+
+#[tokio::test]
+async fn partial_fills_decrement_size() {
+    let mut context = TestContext::new().await;
+    let listing = init_sell_order(&mut context).await;
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+
+    fill(&mut context, &listing, &buyer).await;
+    let order: Order = context.get_zero_copy(listing.address).await;
+    assert_eq!(order.size, SIZE - 1);
+    assert_eq!(order.state, u8::from(OrderState::Partial));
+    let buyer_nft_ta = get_associated_token_address(&buyer.pubkey(), &listing.nft.mint);
+    assert_eq!(context.token_account(buyer_nft_ta).await.amount, 1);
+    assert_eq!(
+        context.token_account(escrow_ata(&listing)).await.amount,
+        SIZE - 1
+    );
+
+    for _ in 1..SIZE {
+        context.warp(1).await;
+        fill(&mut context, &listing, &buyer).await;
+    }
+    assert_eq!(context.token_account(buyer_nft_ta).await.amount, SIZE);
+    assert!(context.get_account(listing.address).await.is_none());
+    assert!(context.get_account(escrow_ata(&listing)).await.is_none());
+}
+
+#[tokio::test]
+async fn close_partially_filled_listing_returns_units() {
+    let mut context = TestContext::new().await;
+    let listing = init_sell_order(&mut context).await;
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    fill(&mut context, &listing, &buyer).await;
+
+    let order: Order = context.get_zero_copy(listing.address).await;
+    context
+        .process(
+            &[listings_client::close_sell_order(
+                listing.seller.pubkey(),
+                listing.address,
+                &order,
+                listing.nft,
+                &RemainingAccounts::default(),
+            )],
+            &[&listing.seller],
+        )
+        .await
+        .unwrap();
+    assert!(context.get_account(listing.address).await.is_none());
+
+    let nft_ta = get_associated_token_address(&listing.seller.pubkey(), &listing.nft.mint);
+    assert_eq!(context.token_account(nft_ta).await.amount, SIZE - 1);
+}
+
+#[tokio::test]
+async fn nft_listing_with_size_above_one_fails() {
+    let mut context = TestContext::new().await;
+    let (pool_mint, _) = context.init_market().await;
+    let seller = context.create_user(LAMPORTS_PER_SOL).await;
+    context.init_wallet(&seller, 0).await;
+    let nft = context.mint_nft(&seller).await;
+    context.add_appraisal(pool_mint, nft.mint).await;
+
+    let result = context
+        .process(
+            &[init_sell_order_ix(
+                &seller,
+                pool_mint,
+                nft,
+                Keypair::new().pubkey(),
+                2,
+            )],
+            &[&seller],
+        )
+        .await;
+    assert!(result.is_err());
+}
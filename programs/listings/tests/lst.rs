@@ -0,0 +1,209 @@
+mod common;
+
+use common::*;
+use listings::state::{LstConfig, Order, Wallet};
+use listings_client::find_lst_config;
+use listings_client::{InitOrderData, RemainingAccounts};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+const PRICE: u64 = LAMPORTS_PER_SOL;
+
+#[tokio::test]
+async fn stake_and_unstake_with_rewards() {
+    let mut context = TestContext::new().await;
+    let stake_pool = context.init_stake_pool().await;
+    let owner = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    let wallet = context.init_wallet(&owner, 3 * LAMPORTS_PER_SOL).await;
+
+    context
+        .process(
+            &[listings_client::stake_wallet(
+                owner.pubkey(),
+                &stake_pool,
+                2 * LAMPORTS_PER_SOL,
+            )],
+            &[&owner],
+        )
+        .await
+        .unwrap();
+    let wallet_state: Wallet = context.get_zero_copy(wallet).await;
+    assert_eq!(wallet_state.balance, LAMPORTS_PER_SOL);
+    assert_eq!(wallet_state.staked_balance, 2 * LAMPORTS_PER_SOL);
+    assert_eq!(wallet_state.staked_lamports, 2 * LAMPORTS_PER_SOL);
+    assert_eq!(wallet_state.get_bidding_balance(), 3 * LAMPORTS_PER_SOL);
+
+    // staked funds can't be withdrawn directly
+    let result = context
+        .process(
+            &[listings_client::edit_wallet(
+                owner.pubkey(),
+                2 * LAMPORTS_PER_SOL,
+                false,
+            )],
+            &[&owner],
+        )
+        .await;
+    assert!(result.is_err());
+
+    // 10% rewards
+    context
+        .accrue_stake_pool(&stake_pool, LAMPORTS_PER_SOL / 5)
+        .await;
+    context
+        .process(
+            &[listings_client::unstake_wallet(
+                owner.pubkey(),
+                &stake_pool,
+                2 * LAMPORTS_PER_SOL,
+            )],
+            &[&owner],
+        )
+        .await
+        .unwrap();
+    let wallet_state: Wallet = context.get_zero_copy(wallet).await;
+    assert_eq!(wallet_state.staked_balance, 0);
+    assert_eq!(wallet_state.staked_lamports, 0);
+    assert_eq!(
+        wallet_state.balance,
+        LAMPORTS_PER_SOL + 2 * LAMPORTS_PER_SOL + LAMPORTS_PER_SOL / 5
+    );
+}
+
+#[tokio::test]
+async fn unwind_covers_a_staked_bid_at_fill() {
+    let mut context = TestContext::new().await;
+    let stake_pool = context.init_stake_pool().await;
+    let (pool_mint, market) = context.init_market().await;
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    let wallet = context.init_wallet(&buyer, 2 * PRICE).await;
+
+    // the whole balance is staked, the bid is backed by the staked funds
+    let nonce = Keypair::new().pubkey();
+    context
+        .process(
+            &[
+                listings_client::stake_wallet(buyer.pubkey(), &stake_pool, 2 * PRICE),
+                listings_client::init_buy_order(
+                    buyer.pubkey(),
+                    buyer.pubkey(),
+                    pool_mint,
+                    Pubkey::default(),
+                    InitOrderData {
+                        nonce,
+                        price: PRICE,
+                        size: 1,
+                    },
+                    &RemainingAccounts::default(),
+                ),
+            ],
+            &[&buyer],
+        )
+        .await
+        .unwrap();
+    context
+        .accrue_stake_pool(&stake_pool, LAMPORTS_PER_SOL / 5)
+        .await;
+
+    let seller = context.create_user(LAMPORTS_PER_SOL).await;
+    let nft = context.mint_nft(&seller).await;
+    let address = order_address(nonce, market, buyer.pubkey());
+    let order: Order = context.get_zero_copy(address).await;
+    let fill = listings_client::fill_buy_order(
+        seller.pubkey(),
+        address,
+        &order,
+        nft,
+        &RemainingAccounts::default(),
+    );
+
+    // nothing liquid to pay the seller with
+    let result = context.process(&[fill.clone()], &[&seller]).await;
+    assert!(result.is_err());
+
+    context
+        .process(
+            &[
+                listings_client::unwind_wallet(seller.pubkey(), address, &order, &stake_pool),
+                fill,
+            ],
+            &[&seller],
+        )
+        .await
+        .unwrap();
+    assert!(context.get_account(address).await.is_none());
+
+    // only what the bid needed was unstaked
+    let wallet_state: Wallet = context.get_zero_copy(wallet).await;
+    assert!(wallet_state.balance < 2);
+    assert!(wallet_state.staked_balance > 0);
+    assert!(wallet_state.staked_balance < 2 * PRICE);
+}
+
+#[tokio::test]
+async fn fill_unwinds_staked_bid_with_withdrawal_fee() {
+    let mut context = TestContext::new().await;
+    let stake_pool = context.init_stake_pool().await;
+    let (pool_mint, market) = context.init_market().await;
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    let wallet = context.init_wallet(&buyer, 2 * PRICE).await;
+
+    let nonce = Keypair::new().pubkey();
+    context
+        .process(
+            &[
+                listings_client::stake_wallet(buyer.pubkey(), &stake_pool, 2 * PRICE),
+                listings_client::init_buy_order(
+                    buyer.pubkey(),
+                    buyer.pubkey(),
+                    pool_mint,
+                    Pubkey::default(),
+                    InitOrderData {
+                        nonce,
+                        price: PRICE,
+                        size: 1,
+                    },
+                    &RemainingAccounts::default(),
+                ),
+            ],
+            &[&buyer],
+        )
+        .await
+        .unwrap();
+    let config: LstConfig = context.get_anchor(find_lst_config().0).await;
+    assert_eq!(config.total_staked, 2 * PRICE);
+
+    // 1% withdrawal fee
+    context
+        .set_stake_pool_withdrawal_fee(&stake_pool, 1, 100)
+        .await;
+
+    let seller = context.create_user(LAMPORTS_PER_SOL).await;
+    let nft = context.mint_nft(&seller).await;
+    let address = order_address(nonce, market, buyer.pubkey());
+    let order: Order = context.get_zero_copy(address).await;
+    let seller_lamports = context.lamports(seller.pubkey()).await;
+    context
+        .process(
+            &[listings_client::fill_staked_buy_order(
+                seller.pubkey(),
+                address,
+                &order,
+                nft,
+                &RemainingAccounts::default(),
+                &stake_pool,
+            )],
+            &[&seller],
+        )
+        .await
+        .unwrap();
+    assert!(context.get_account(address).await.is_none());
+    assert!(context.lamports(seller.pubkey()).await > seller_lamports);
+
+    // the fee was unstaked on top of the price, the rest stays staked
+    let wallet_state: Wallet = context.get_zero_copy(wallet).await;
+    assert!(wallet_state.balance < 2);
+    assert!(wallet_state.staked_balance < PRICE - PRICE / 100);
+    assert!(wallet_state.staked_balance > 0);
+    let config: LstConfig = context.get_anchor(find_lst_config().0).await;
+    assert_eq!(config.total_staked, wallet_state.staked_balance);
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use common::*;
+use listings::state::{Market, MarketState, MARKET_VERSION};
+use solana_sdk::{signature::Keypair, signer::Signer};
+
+#[tokio::test]
+async fn init_market() {
+    let mut context = TestContext::new().await;
+    let (pool_mint, market) = context.init_market().await;
+
+    let market: Market = context.get_anchor(market).await;
+    assert_eq!(market.version, MARKET_VERSION);
+    assert_eq!(market.pool_mint, pool_mint);
+    assert_eq!(market.state, u8::from(MarketState::Open));
+}
+
+#[tokio::test]
+async fn init_market_twice_fails() {
+    let mut context = TestContext::new().await;
+    let (pool_mint, _) = context.init_market().await;
+
+    let initializer = context.create_user(LAMPORTS_PER_SOL).await;
+    let result = context
+        .process(
+            &[listings_client::init_market(
+                initializer.pubkey(),
+                pool_mint,
+            )],
+            &[&initializer],
+        )
+        .await;
+    assert!(result.is_err());
+
+    // a different pool mint gets its own market
+    let other_pool_mint = Keypair::new().pubkey();
+    context
+        .process(
+            &[listings_client::init_market(
+                initializer.pubkey(),
+                other_pool_mint,
+            )],
+            &[&initializer],
+        )
+        .await
+        .unwrap();
+}
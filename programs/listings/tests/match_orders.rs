@@ -0,0 +1,226 @@
+mod common;
+
+use anchor_spl::associated_token::get_associated_token_address;
+use common::*;
+use listings::state::{Order, Wallet, MATCH_FEE_BPS, PROTOCOL_FEES_BPS};
+use listings_client::{find_wallet, InitOrderData, NftAccounts, RemainingAccounts};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+const PRICE: u64 = LAMPORTS_PER_SOL;
+
+struct Crossing {
+    pool_mint: Pubkey,
+    buyer: Keypair,
+    seller: Keypair,
+    nft: NftAccounts,
+    buy_address: Pubkey,
+    sell_address: Pubkey,
+}
+
+async fn cross(context: &mut TestContext, bid_price: u64, ask_price: u64) -> Crossing {
+    let (pool_mint, market) = context.init_market().await;
+
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    context.init_wallet(&buyer, 2 * PRICE).await;
+    let nonce = Keypair::new().pubkey();
+    let buy_address = order_address(nonce, market, buyer.pubkey());
+    context
+        .process(
+            &[listings_client::init_buy_order(
+                buyer.pubkey(),
+                buyer.pubkey(),
+                pool_mint,
+                Pubkey::default(),
+                InitOrderData {
+                    nonce,
+                    price: bid_price,
+                    size: 1,
+                },
+                &RemainingAccounts::default(),
+            )],
+            &[&buyer],
+        )
+        .await
+        .unwrap();
+
+    let seller = context.create_user(LAMPORTS_PER_SOL).await;
+    context.init_wallet(&seller, 0).await;
+    let nft = context.mint_nft(&seller).await;
+    context.add_appraisal(pool_mint, nft.mint).await;
+    let nonce = Keypair::new().pubkey();
+    let sell_address = order_address(nonce, market, seller.pubkey());
+    context
+        .process(
+            &[listings_client::init_sell_order(
+                seller.pubkey(),
+                pool_mint,
+                nft,
+                InitOrderData {
+                    nonce,
+                    price: ask_price,
+                    size: 1,
+                },
+                &RemainingAccounts::default(),
+            )],
+            &[&seller],
+        )
+        .await
+        .unwrap();
+
+    Crossing {
+        pool_mint,
+        buyer,
+        seller,
+        nft,
+        buy_address,
+        sell_address,
+    }
+}
+
+#[tokio::test]
+async fn crank_settles_crossing_orders() {
+    let mut context = TestContext::new().await;
+    let crossing = cross(&mut context, PRICE * 11 / 10, PRICE).await;
+    let sell_order: Order = context.get_zero_copy(crossing.sell_address).await;
+    let buy_order: Order = context.get_zero_copy(crossing.buy_address).await;
+    let seller_lamports = context.lamports(crossing.seller.pubkey()).await;
+
+    let cranker = context.create_user(LAMPORTS_PER_SOL).await;
+    context
+        .process(
+            &[listings_client::match_orders(
+                cranker.pubkey(),
+                crossing.sell_address,
+                &sell_order,
+                crossing.buy_address,
+                &buy_order,
+                crossing.pool_mint,
+                crossing.nft,
+                &RemainingAccounts::default(),
+            )],
+            &[&cranker],
+        )
+        .await
+        .unwrap();
+
+    let buyer_nft_ta = get_associated_token_address(&crossing.buyer.pubkey(), &crossing.nft.mint);
+    assert_eq!(context.token_account(buyer_nft_ta).await.amount, 1);
+    assert!(context.get_account(crossing.sell_address).await.is_none());
+    assert!(context.get_account(crossing.buy_address).await.is_none());
+
+    // the seller receives the full price and the order rent
+    assert!(context.lamports(crossing.seller.pubkey()).await >= seller_lamports + PRICE);
+
+    let fee = if sell_order.is_fees_on() {
+        PRICE * PROTOCOL_FEES_BPS / 10000
+    } else {
+        0
+    };
+    let match_fee = PRICE * MATCH_FEE_BPS / 10000;
+    let wallet: Wallet = context
+        .get_zero_copy(find_wallet(&crossing.buyer.pubkey()).0)
+        .await;
+    assert_eq!(wallet.balance, 2 * PRICE - PRICE - fee - match_fee);
+    assert_eq!(wallet.open_buy_orders, 0);
+}
+
+#[tokio::test]
+async fn orders_that_do_not_cross_are_rejected() {
+    let mut context = TestContext::new().await;
+    let crossing = cross(&mut context, PRICE * 9 / 10, PRICE).await;
+    let sell_order: Order = context.get_zero_copy(crossing.sell_address).await;
+    let buy_order: Order = context.get_zero_copy(crossing.buy_address).await;
+
+    let cranker = context.create_user(LAMPORTS_PER_SOL).await;
+    let result = context
+        .process(
+            &[listings_client::match_orders(
+                cranker.pubkey(),
+                crossing.sell_address,
+                &sell_order,
+                crossing.buy_address,
+                &buy_order,
+                crossing.pool_mint,
+                crossing.nft,
+                &RemainingAccounts::default(),
+            )],
+            &[&cranker],
+        )
+        .await;
+    assert!(result.is_err());
+    assert!(context.get_account(crossing.sell_address).await.is_some());
+    assert!(context.get_account(crossing.buy_address).await.is_some());
+}
+
+#[tokio::test]
+async fn crank_unwinds_a_staked_buyer_wallet() {
+    let mut context = TestContext::new().await;
+    let stake_pool = context.init_stake_pool().await;
+    let crossing = cross(&mut context, PRICE * 11 / 10, PRICE).await;
+
+    // the whole balance of the buyer is staked, the bid is backed by the staked funds
+    context
+        .process(
+            &[listings_client::stake_wallet(
+                crossing.buyer.pubkey(),
+                &stake_pool,
+                2 * PRICE,
+            )],
+            &[&crossing.buyer],
+        )
+        .await
+        .unwrap();
+    let sell_order: Order = context.get_zero_copy(crossing.sell_address).await;
+    let buy_order: Order = context.get_zero_copy(crossing.buy_address).await;
+
+    // without the stake pool accounts the liquid balance can't pay the listing
+    let cranker = context.create_user(LAMPORTS_PER_SOL).await;
+    let result = context
+        .process(
+            &[listings_client::match_orders(
+                cranker.pubkey(),
+                crossing.sell_address,
+                &sell_order,
+                crossing.buy_address,
+                &buy_order,
+                crossing.pool_mint,
+                crossing.nft,
+                &RemainingAccounts::default(),
+            )],
+            &[&cranker],
+        )
+        .await;
+    assert!(result.is_err());
+
+    let seller_lamports = context.lamports(crossing.seller.pubkey()).await;
+    context
+        .process(
+            &[listings_client::match_staked_orders(
+                cranker.pubkey(),
+                crossing.sell_address,
+                &sell_order,
+                crossing.buy_address,
+                &buy_order,
+                crossing.pool_mint,
+                crossing.nft,
+                &RemainingAccounts::default(),
+                &stake_pool,
+            )],
+            &[&cranker],
+        )
+        .await
+        .unwrap();
+
+    let buyer_nft_ta = get_associated_token_address(&crossing.buyer.pubkey(), &crossing.nft.mint);
+    assert_eq!(context.token_account(buyer_nft_ta).await.amount, 1);
+    assert!(context.get_account(crossing.sell_address).await.is_none());
+    assert!(context.get_account(crossing.buy_address).await.is_none());
+    assert!(context.lamports(crossing.seller.pubkey()).await >= seller_lamports + PRICE);
+
+    // only what the bid needed was unstaked
+    let wallet: Wallet = context
+        .get_zero_copy(find_wallet(&crossing.buyer.pubkey()).0)
+        .await;
+    assert!(wallet.staked_balance > 0);
+    assert!(wallet.staked_balance < 2 * PRICE);
+}
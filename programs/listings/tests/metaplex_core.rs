@@ -0,0 +1,157 @@
+mod common;
+
+use common::*;
+use listings::state::Order;
+use listings_client::{InitOrderData, RemainingAccounts};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+const PRICE: u64 = LAMPORTS_PER_SOL;
+
+async fn list(
+    context: &mut TestContext,
+    seller: &Keypair,
+    pool_mint: Pubkey,
+    market: Pubkey,
+    asset: Pubkey,
+) -> Pubkey {
+    let nonce = Keypair::new().pubkey();
+    let address = order_address(nonce, market, seller.pubkey());
+    context
+        .process(
+            &[listings_client::core_init_sell_order(
+                seller.pubkey(),
+                pool_mint,
+                asset,
+                None,
+                InitOrderData {
+                    nonce,
+                    price: PRICE,
+                    size: 1,
+                },
+                &RemainingAccounts::default(),
+            )],
+            &[seller],
+        )
+        .await
+        .unwrap();
+    address
+}
+
+#[tokio::test]
+async fn core_listing_pays_royalties_to_the_creators() {
+    let mut context = TestContext::new().await;
+    let (pool_mint, market) = context.init_market().await;
+    let seller = context.create_user(LAMPORTS_PER_SOL).await;
+    context.init_wallet(&seller, 0).await;
+    let creators = vec![Keypair::new().pubkey(), Keypair::new().pubkey()];
+    let asset = context.create_core_asset(&seller, creators.clone()).await;
+    context.add_appraisal(pool_mint, asset).await;
+    let address = list(&mut context, &seller, pool_mint, market, asset).await;
+    let order: Order = context.get_zero_copy(address).await;
+
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    // the creators of the royalties plugin have to be passed
+    let result = context
+        .process(
+            &[listings_client::core_fill_sell_order(
+                buyer.pubkey(),
+                address,
+                &order,
+                None,
+                &RemainingAccounts::default(),
+            )],
+            &[&buyer],
+        )
+        .await;
+    assert!(result.is_err());
+
+    context
+        .process(
+            &[listings_client::core_fill_sell_order(
+                buyer.pubkey(),
+                address,
+                &order,
+                None,
+                &RemainingAccounts {
+                    creators: creators.clone(),
+                    ..Default::default()
+                },
+            )],
+            &[&buyer],
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(context.core_owner(asset).await, buyer.pubkey());
+    assert!(context.get_account(address).await.is_none());
+    // 5% royalties split between 2 creators
+    for creator in creators {
+        assert_eq!(context.lamports(creator).await, PRICE / 40);
+    }
+}
+
+#[tokio::test]
+async fn listed_core_asset_is_frozen_until_closed() {
+    let mut context = TestContext::new().await;
+    let (pool_mint, market) = context.init_market().await;
+    let seller = context.create_user(LAMPORTS_PER_SOL).await;
+    context.init_wallet(&seller, 0).await;
+    let asset = context.create_core_asset(&seller, Vec::new()).await;
+    context.add_appraisal(pool_mint, asset).await;
+    let address = list(&mut context, &seller, pool_mint, market, asset).await;
+
+    // a listed asset cannot fill a bid
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    context.init_wallet(&buyer, PRICE).await;
+    let nonce = Keypair::new().pubkey();
+    let bid_address = order_address(nonce, market, buyer.pubkey());
+    context
+        .process(
+            &[listings_client::init_buy_order(
+                buyer.pubkey(),
+                buyer.pubkey(),
+                pool_mint,
+                Pubkey::default(),
+                InitOrderData {
+                    nonce,
+                    price: PRICE,
+                    size: 1,
+                },
+                &RemainingAccounts::default(),
+            )],
+            &[&buyer],
+        )
+        .await
+        .unwrap();
+    let bid: Order = context.get_zero_copy(bid_address).await;
+    let fill = listings_client::core_fill_buy_order(
+        seller.pubkey(),
+        bid_address,
+        &bid,
+        pool_mint,
+        asset,
+        None,
+        &RemainingAccounts::default(),
+    );
+    let result = context.process(&[fill.clone()], &[&seller]).await;
+    assert!(result.is_err());
+
+    let order: Order = context.get_zero_copy(address).await;
+    context
+        .process(
+            &[listings_client::core_close_sell_order(
+                seller.pubkey(),
+                address,
+                &order,
+                None,
+            )],
+            &[&seller],
+        )
+        .await
+        .unwrap();
+    assert!(context.get_account(address).await.is_none());
+
+    context.process(&[fill], &[&seller]).await.unwrap();
+    assert_eq!(context.core_owner(asset).await, buyer.pubkey());
+    assert!(context.get_account(bid_address).await.is_none());
+}
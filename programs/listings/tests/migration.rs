@@ -0,0 +1,334 @@
+mod common;
+
+use anchor_lang::{AccountSerialize, AnchorSerialize, Discriminator};
+use bridgesplit_program_utils::anchor_lang;
+use common::*;
+use listings::state::{
+    legacy::{LegacyOrder, LegacyWallet},
+    ListingMode, Market, Order, Wallet, MARKET_VERSION, ORDER_VERSION, WALLET_VERSION,
+};
+use listings_client::{InitOrderData, RemainingAccounts};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+const PRICE: u64 = LAMPORTS_PER_SOL;
+
+/// overwrite a program account with an older or newer layout
+fn overwrite_account<T: AccountSerialize>(context: &mut TestContext, address: Pubkey, account: &T) {
+    let mut data = Vec::new();
+    account.try_serialize(&mut data).unwrap();
+    context.set_program_account(address, listings::ID, data);
+}
+
+/// overwrite a zero copy account with its borsh layout, padded to the account size
+fn overwrite_legacy<T: Discriminator, L: AnchorSerialize>(
+    context: &mut TestContext,
+    address: Pubkey,
+    legacy: &L,
+) {
+    let mut data = T::discriminator().to_vec();
+    legacy.serialize(&mut data).unwrap();
+    data.resize(8 + std::mem::size_of::<T>(), 0);
+    context.set_program_account(address, listings::ID, data);
+}
+
+fn legacy_order(order: &Order, version: u8) -> LegacyOrder {
+    LegacyOrder {
+        version,
+        nonce: order.nonce,
+        market: order.market,
+        owner: order.owner,
+        wallet: order.wallet,
+        side: order.side,
+        size: order.size,
+        price: order.price,
+        state: order.state,
+        init_time: order.init_time,
+        last_edit_time: order.last_edit_time,
+        nft_mint: order.nft_mint,
+        fees_on: order.is_fees_on(),
+        price_mode: order.price_mode,
+        trailing_bps: order.trailing_bps,
+        max_price: order.max_price,
+        trigger_price: order.trigger_price,
+        listing_mode: order.listing_mode,
+        // version 1 orders did not track their original size
+        original_size: if version == 1 { 0 } else { order.original_size },
+        filled_size: order.filled_size,
+        filled_value: order.filled_value,
+    }
+}
+
+fn legacy_wallet(wallet: &Wallet) -> LegacyWallet {
+    LegacyWallet {
+        version: 1,
+        owner: wallet.owner,
+        balance: wallet.balance,
+        delegate: Pubkey::default(),
+        delegate_spending_cap: 0,
+        // orders opened before the counters existed are not counted
+        open_buy_orders: 0,
+        open_sell_orders: 0,
+        withdrawal_delay: 60,
+        pending_withdrawal: 0,
+        withdrawal_unlock_time: 0,
+        pending_withdrawal_delay: 0,
+        withdrawal_delay_unlock_time: 0,
+        staked_balance: 0,
+        staked_lamports: 0,
+    }
+}
+
+async fn migrate(context: &mut TestContext, address: Pubkey) -> bool {
+    let cranker = context.create_user(LAMPORTS_PER_SOL).await;
+    context
+        .process(
+            &[listings_client::migrate_account(cranker.pubkey(), address)],
+            &[&cranker],
+        )
+        .await
+        .is_ok()
+}
+
+async fn migrate_legacy_order(context: &mut TestContext, address: Pubkey, wallet: Pubkey) -> bool {
+    let cranker = context.create_user(LAMPORTS_PER_SOL).await;
+    context
+        .process(
+            &[listings_client::migrate_legacy_order(
+                cranker.pubkey(),
+                address,
+                wallet,
+            )],
+            &[&cranker],
+        )
+        .await
+        .is_ok()
+}
+
+/// rewrite a bid and its wallet with the borsh layouts and migrate the wallet
+async fn make_legacy(context: &mut TestContext, bid: &Bid, version: u8) -> Order {
+    let order: Order = context.get_zero_copy(bid.address).await;
+    let wallet: Wallet = context.get_zero_copy(order.wallet).await;
+    overwrite_legacy::<Order, _>(context, bid.address, &legacy_order(&order, version));
+    overwrite_legacy::<Wallet, _>(context, order.wallet, &legacy_wallet(&wallet));
+    assert!(migrate(context, order.wallet).await);
+    order
+}
+
+struct Bid {
+    buyer: Keypair,
+    address: Pubkey,
+}
+
+async fn init_buy_order(context: &mut TestContext) -> Bid {
+    let (pool_mint, market) = context.init_market().await;
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    context.init_wallet(&buyer, 2 * PRICE).await;
+    let nonce = Keypair::new().pubkey();
+    context
+        .process(
+            &[listings_client::init_buy_order(
+                buyer.pubkey(),
+                buyer.pubkey(),
+                pool_mint,
+                Pubkey::default(),
+                InitOrderData {
+                    nonce,
+                    price: PRICE,
+                    size: 2,
+                },
+                &RemainingAccounts::default(),
+            )],
+            &[&buyer],
+        )
+        .await
+        .unwrap();
+    Bid {
+        address: order_address(nonce, market, buyer.pubkey()),
+        buyer,
+    }
+}
+
+#[tokio::test]
+async fn migrate_order_from_borsh_layout() {
+    let mut context = TestContext::new().await;
+    let bid = init_buy_order(&mut context).await;
+    let order = make_legacy(&mut context, &bid, 1).await;
+
+    // the wallet of a legacy order is required
+    assert!(!migrate(&mut context, bid.address).await);
+    assert!(migrate_legacy_order(&mut context, bid.address, order.wallet).await);
+    let migrated: Order = context.get_zero_copy(bid.address).await;
+    assert_eq!(migrated.version, ORDER_VERSION);
+    assert_eq!(migrated.original_size, 2);
+    assert_eq!(migrated.owner, order.owner);
+    assert_eq!(migrated.market, order.market);
+    assert_eq!(migrated.size, order.size);
+    assert_eq!(migrated.price, order.price);
+    assert_eq!(migrated.init_time, order.init_time);
+    assert_eq!(migrated.is_fees_on(), order.is_fees_on());
+
+    // current accounts have nothing to migrate
+    assert!(!migrate(&mut context, bid.address).await);
+}
+
+#[tokio::test]
+async fn migrate_legacy_order_counts_open_order() {
+    let mut context = TestContext::new().await;
+    let bid = init_buy_order(&mut context).await;
+    let order = make_legacy(&mut context, &bid, 2).await;
+
+    let wallet: Wallet = context.get_zero_copy(order.wallet).await;
+    assert_eq!(wallet.open_buy_orders, 0);
+
+    // another wallet does not count the order
+    let other = context.create_user(LAMPORTS_PER_SOL).await;
+    let other_wallet = context.init_wallet(&other, 0).await;
+    assert!(!migrate_legacy_order(&mut context, bid.address, other_wallet).await);
+
+    assert!(migrate_legacy_order(&mut context, bid.address, order.wallet).await);
+    let wallet: Wallet = context.get_zero_copy(order.wallet).await;
+    assert_eq!(wallet.open_buy_orders, 1);
+    assert_eq!(wallet.open_sell_orders, 0);
+
+    // the counted order keeps the wallet open until it is closed
+    let close_wallet = listings_client::close_wallet(bid.buyer.pubkey());
+    let result = context
+        .process(std::slice::from_ref(&close_wallet), &[&bid.buyer])
+        .await;
+    assert!(result.is_err());
+
+    let close = listings_client::close_buy_order(bid.buyer.pubkey(), bid.address, &order);
+    context.process(&[close], &[&bid.buyer]).await.unwrap();
+    let wallet: Wallet = context.get_zero_copy(order.wallet).await;
+    assert_eq!(wallet.open_buy_orders, 0);
+
+    // the wallet stays open until its owner completes the migration
+    let result = context
+        .process(std::slice::from_ref(&close_wallet), &[&bid.buyer])
+        .await;
+    assert!(result.is_err());
+    context
+        .process(
+            &[listings_client::complete_wallet_migration(
+                bid.buyer.pubkey(),
+            )],
+            &[&bid.buyer],
+        )
+        .await
+        .unwrap();
+    context
+        .process(&[close_wallet], &[&bid.buyer])
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn migrated_wallet_is_not_closed_before_its_orders_are_counted() {
+    let mut context = TestContext::new().await;
+    let bid = init_buy_order(&mut context).await;
+    let order = make_legacy(&mut context, &bid, 2).await;
+
+    // the legacy bid is not counted yet
+    let wallet: Wallet = context.get_zero_copy(order.wallet).await;
+    assert!(!wallet.has_open_orders());
+    assert_eq!(wallet.uncounted_legacy_orders, 1);
+    let close_wallet = listings_client::close_wallet(bid.buyer.pubkey());
+    let result = context.process(&[close_wallet], &[&bid.buyer]).await;
+    assert!(result.is_err());
+
+    // only the owner completes the migration
+    let other = context.create_user(LAMPORTS_PER_SOL).await;
+    context.init_wallet(&other, 0).await;
+    let result = context
+        .process(
+            &[listings_client::complete_wallet_migration(other.pubkey())],
+            &[&other],
+        )
+        .await;
+    assert!(result.is_err());
+
+    assert!(migrate_legacy_order(&mut context, bid.address, order.wallet).await);
+    let wallet: Wallet = context.get_zero_copy(order.wallet).await;
+    assert_eq!(wallet.open_buy_orders, 1);
+}
+
+#[tokio::test]
+async fn legacy_order_is_rejected_until_migrated() {
+    let mut context = TestContext::new().await;
+    let bid = init_buy_order(&mut context).await;
+    let order = make_legacy(&mut context, &bid, 2).await;
+
+    let close = listings_client::close_buy_order(bid.buyer.pubkey(), bid.address, &order);
+    let result = context
+        .process(std::slice::from_ref(&close), &[&bid.buyer])
+        .await;
+    assert!(result.is_err());
+
+    assert!(migrate_legacy_order(&mut context, bid.address, order.wallet).await);
+    context.process(&[close], &[&bid.buyer]).await.unwrap();
+    assert!(context.get_account(bid.address).await.is_none());
+}
+
+#[tokio::test]
+async fn migrate_wallet_from_borsh_layout() {
+    let mut context = TestContext::new().await;
+    let owner = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    let address = context.init_wallet(&owner, PRICE).await;
+    let wallet: Wallet = context.get_zero_copy(address).await;
+
+    let legacy = LegacyWallet {
+        open_buy_orders: 3,
+        open_sell_orders: 1,
+        ..legacy_wallet(&wallet)
+    };
+    overwrite_legacy::<Wallet, _>(&mut context, address, &legacy);
+
+    assert!(migrate(&mut context, address).await);
+    let migrated: Wallet = context.get_zero_copy(address).await;
+    assert_eq!(migrated.version, WALLET_VERSION);
+    assert_eq!(migrated.owner, owner.pubkey());
+    assert_eq!(migrated.balance, PRICE);
+    // counters are rebuilt from the migrated orders
+    assert_eq!(migrated.open_buy_orders, 0);
+    assert_eq!(migrated.open_sell_orders, 0);
+    assert_eq!(migrated.uncounted_legacy_orders, 1);
+    assert_eq!(migrated.withdrawal_delay, 60);
+
+    assert!(!migrate(&mut context, address).await);
+}
+
+#[tokio::test]
+async fn migrate_market_sets_listing_mode() {
+    let mut context = TestContext::new().await;
+    let (_, address) = context.init_market().await;
+
+    let mut market: Market = context.get_anchor(address).await;
+    market.version = 1;
+    overwrite_account(&mut context, address, &market);
+
+    assert!(migrate(&mut context, address).await);
+    let market: Market = context.get_anchor(address).await;
+    assert_eq!(market.version, MARKET_VERSION);
+    assert_eq!(market.listing_mode, u8::from(ListingMode::FreezeInPlace));
+}
+
+#[tokio::test]
+async fn migrate_newer_version_fails() {
+    let mut context = TestContext::new().await;
+    let (_, address) = context.init_market().await;
+
+    let mut market: Market = context.get_anchor(address).await;
+    market.version = MARKET_VERSION + 1;
+    overwrite_account(&mut context, address, &market);
+
+    assert!(!migrate(&mut context, address).await);
+}
+
+#[tokio::test]
+async fn migrate_current_wallet_fails() {
+    let mut context = TestContext::new().await;
+    let owner = context.create_user(LAMPORTS_PER_SOL).await;
+    let wallet = context.init_wallet(&owner, 0).await;
+
+    assert!(!migrate(&mut context, wallet).await);
+}
@@ -0,0 +1,306 @@
+mod common;
+
+use common::*;
+use listings::state::{Order, OrderBook};
+use listings_client::{
+    find_market, find_order_book, find_wallet, with_order_book, InitOrderData, RemainingAccounts,
+};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+const PRICE: u64 = LAMPORTS_PER_SOL;
+
+struct BookMarket {
+    pool_mint: Pubkey,
+    market: Pubkey,
+    order_book: Pubkey,
+}
+
+/// market created by the initializer, with an order book
+async fn init_book_market(context: &mut TestContext, initializer: &Keypair) -> BookMarket {
+    let pool_mint = Keypair::new().pubkey();
+    context
+        .process(
+            &[
+                listings_client::init_market(initializer.pubkey(), pool_mint),
+                listings_client::init_order_book(initializer.pubkey(), pool_mint),
+            ],
+            &[initializer],
+        )
+        .await
+        .unwrap();
+    let market = find_market(&pool_mint).0;
+    BookMarket {
+        pool_mint,
+        market,
+        order_book: find_order_book(&market).0,
+    }
+}
+
+async fn bid(context: &mut TestContext, buyer: &Keypair, pool_mint: Pubkey, price: u64) -> Pubkey {
+    let nonce = Keypair::new().pubkey();
+    let market = find_market(&pool_mint).0;
+    let init = listings_client::init_buy_order(
+        buyer.pubkey(),
+        buyer.pubkey(),
+        pool_mint,
+        Pubkey::default(),
+        InitOrderData {
+            nonce,
+            price,
+            size: 1,
+        },
+        &RemainingAccounts::default(),
+    );
+    let init = context.with_market_order_book(init, market).await;
+    context.process(&[init], &[buyer]).await.unwrap();
+    order_address(nonce, market, buyer.pubkey())
+}
+
+#[tokio::test]
+async fn order_book_keeps_bids_sorted() {
+    let mut context = TestContext::new().await;
+    let initializer = context.create_user(LAMPORTS_PER_SOL).await;
+    let book = init_book_market(&mut context, &initializer).await;
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    context.init_wallet(&buyer, 5 * PRICE).await;
+
+    let low = bid(&mut context, &buyer, book.pool_mint, PRICE / 2).await;
+    let high = bid(&mut context, &buyer, book.pool_mint, 2 * PRICE).await;
+    let mid = bid(&mut context, &buyer, book.pool_mint, PRICE).await;
+
+    let order_book: OrderBook = context.get_zero_copy(book.order_book).await;
+    assert_eq!(order_book.market, book.market);
+    let bids: Vec<Pubkey> = order_book
+        .get_bids()
+        .iter()
+        .map(|entry| entry.order)
+        .collect();
+    assert_eq!(bids, vec![high, mid, low]);
+    assert_eq!(order_book.get_best_bid().unwrap().price, 2 * PRICE);
+    assert!(order_book.get_floor().is_none());
+
+    let order: Order = context.get_zero_copy(high).await;
+    context
+        .process(
+            &[with_order_book(
+                listings_client::close_buy_order(buyer.pubkey(), high, &order),
+                &book.market,
+            )],
+            &[&buyer],
+        )
+        .await
+        .unwrap();
+    let order_book: OrderBook = context.get_zero_copy(book.order_book).await;
+    assert_eq!(order_book.bid_count, 2);
+    assert_eq!(order_book.get_best_bid().unwrap().order, mid);
+}
+
+#[tokio::test]
+async fn filled_listing_leaves_the_order_book() {
+    let mut context = TestContext::new().await;
+    let initializer = context.create_user(LAMPORTS_PER_SOL).await;
+    let book = init_book_market(&mut context, &initializer).await;
+    let seller = context.create_user(LAMPORTS_PER_SOL).await;
+    context.init_wallet(&seller, 0).await;
+    let nft = context.mint_nft(&seller).await;
+    context.add_appraisal(book.pool_mint, nft.mint).await;
+
+    let nonce = Keypair::new().pubkey();
+    let address = order_address(nonce, book.market, seller.pubkey());
+    context
+        .process(
+            &[with_order_book(
+                listings_client::init_sell_order(
+                    seller.pubkey(),
+                    book.pool_mint,
+                    nft,
+                    InitOrderData {
+                        nonce,
+                        price: PRICE,
+                        size: 1,
+                    },
+                    &RemainingAccounts::default(),
+                ),
+                &book.market,
+            )],
+            &[&seller],
+        )
+        .await
+        .unwrap();
+    let order_book: OrderBook = context.get_zero_copy(book.order_book).await;
+    let floor = order_book.get_floor().unwrap();
+    assert_eq!(floor.order, address);
+    assert_eq!(floor.price, PRICE);
+    assert_eq!(floor.size, 1);
+
+    let order: Order = context.get_zero_copy(address).await;
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    context
+        .process(
+            &[with_order_book(
+                listings_client::fill_sell_order(
+                    buyer.pubkey(),
+                    address,
+                    &order,
+                    nft,
+                    &RemainingAccounts::default(),
+                ),
+                &book.market,
+            )],
+            &[&buyer],
+        )
+        .await
+        .unwrap();
+    let order_book: OrderBook = context.get_zero_copy(book.order_book).await;
+    assert!(order_book.get_floor().is_none());
+}
+
+#[tokio::test]
+async fn sync_adds_orders_placed_before_the_order_book() {
+    let mut context = TestContext::new().await;
+    let initializer = context.create_user(LAMPORTS_PER_SOL).await;
+    let pool_mint = Keypair::new().pubkey();
+    context
+        .process(
+            &[listings_client::init_market(
+                initializer.pubkey(),
+                pool_mint,
+            )],
+            &[&initializer],
+        )
+        .await
+        .unwrap();
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    context.init_wallet(&buyer, PRICE).await;
+    // markets without an order book still take orders
+    let address = bid(&mut context, &buyer, pool_mint, PRICE).await;
+
+    context
+        .process(
+            &[listings_client::init_order_book(
+                initializer.pubkey(),
+                pool_mint,
+            )],
+            &[&initializer],
+        )
+        .await
+        .unwrap();
+    let market = find_market(&pool_mint).0;
+    let order_book: OrderBook = context.get_zero_copy(find_order_book(&market).0).await;
+    assert!(order_book.get_best_bid().is_none());
+
+    let wallet = find_wallet(&buyer.pubkey()).0;
+    context
+        .process(
+            &[listings_client::sync_order_book(
+                market,
+                &[(address, wallet)],
+            )],
+            &[],
+        )
+        .await
+        .unwrap();
+    let order_book: OrderBook = context.get_zero_copy(find_order_book(&market).0).await;
+    assert_eq!(order_book.get_best_bid().unwrap().order, address);
+
+    // orders of another market are rejected
+    let other_initializer = context.create_user(LAMPORTS_PER_SOL).await;
+    let other = init_book_market(&mut context, &other_initializer).await;
+    let result = context
+        .process(
+            &[listings_client::sync_order_book(
+                other.market,
+                &[(address, wallet)],
+            )],
+            &[],
+        )
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn unfunded_bids_stay_out_of_the_order_book() {
+    let mut context = TestContext::new().await;
+    let initializer = context.create_user(LAMPORTS_PER_SOL).await;
+    let book = init_book_market(&mut context, &initializer).await;
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    context.init_wallet(&buyer, PRICE).await;
+    let address = bid(&mut context, &buyer, book.pool_mint, PRICE).await;
+
+    // the wallet no longer covers the bid, syncing the bid takes it out of the book
+    context
+        .process(
+            &[listings_client::edit_wallet(buyer.pubkey(), PRICE, false)],
+            &[&buyer],
+        )
+        .await
+        .unwrap();
+    let wallet = find_wallet(&buyer.pubkey()).0;
+    context
+        .process(
+            &[listings_client::sync_order_book(
+                book.market,
+                &[(address, wallet)],
+            )],
+            &[],
+        )
+        .await
+        .unwrap();
+    let order_book: OrderBook = context.get_zero_copy(book.order_book).await;
+    assert!(order_book.get_best_bid().is_none());
+
+    // the wallet of the bid has to be passed
+    let result = context
+        .process(
+            &[listings_client::sync_order_book(
+                book.market,
+                &[(address, address)],
+            )],
+            &[],
+        )
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn orders_of_an_order_book_market_need_the_order_book() {
+    let mut context = TestContext::new().await;
+    let initializer = context.create_user(LAMPORTS_PER_SOL).await;
+    let book = init_book_market(&mut context, &initializer).await;
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    context.init_wallet(&buyer, PRICE).await;
+
+    let result = context
+        .process(
+            &[listings_client::init_buy_order(
+                buyer.pubkey(),
+                buyer.pubkey(),
+                book.pool_mint,
+                Pubkey::default(),
+                InitOrderData {
+                    nonce: Keypair::new().pubkey(),
+                    price: PRICE,
+                    size: 1,
+                },
+                &RemainingAccounts::default(),
+            )],
+            &[&buyer],
+        )
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn only_the_market_initializer_creates_the_order_book() {
+    let mut context = TestContext::new().await;
+    let (pool_mint, _) = context.init_market().await;
+    let user = context.create_user(LAMPORTS_PER_SOL).await;
+
+    let result = context
+        .process(
+            &[listings_client::init_order_book(user.pubkey(), pool_mint)],
+            &[&user],
+        )
+        .await;
+    assert!(result.is_err());
+}
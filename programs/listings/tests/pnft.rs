@@ -0,0 +1,149 @@
+mod common;
+
+use anchor_spl::associated_token::get_associated_token_address;
+use common::*;
+use listings::state::Order;
+use listings_client::{
+    find_token_record, treasury, InitOrderData, NftAccounts, PnftAccounts, RemainingAccounts,
+};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+const PRICE: u64 = LAMPORTS_PER_SOL;
+/// seller fee basis points used by the test mints
+const ROYALTY_BPS: u64 = 500;
+
+fn pnft_accounts(nft: &NftAccounts, owner: &Pubkey) -> PnftAccounts {
+    let token = get_associated_token_address(owner, &nft.mint);
+    PnftAccounts {
+        token_record: find_token_record(&nft.mint, &token).0,
+        authorization_rules: Pubkey::default(),
+        authorization_rules_program: mpl_token_auth_rules::ID,
+    }
+}
+
+#[tokio::test]
+async fn list_and_fill_pnft_pays_royalties() {
+    let mut context = TestContext::new().await;
+    let (pool_mint, market) = context.init_market().await;
+    let seller = context.create_user(LAMPORTS_PER_SOL).await;
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    let creators = vec![Keypair::new().pubkey(), Keypair::new().pubkey()];
+    context.init_wallet(&seller, 0).await;
+    let nft = context.mint_pnft(&seller, creators.clone()).await;
+    context.add_appraisal(pool_mint, nft.mint).await;
+
+    let nonce = Keypair::new().pubkey();
+    let address = order_address(nonce, market, seller.pubkey());
+    context
+        .process(
+            &[listings_client::init_sell_order(
+                seller.pubkey(),
+                pool_mint,
+                nft,
+                InitOrderData {
+                    nonce,
+                    price: PRICE,
+                    size: 1,
+                },
+                &RemainingAccounts {
+                    pnft: Some(pnft_accounts(&nft, &seller.pubkey())),
+                    ..Default::default()
+                },
+            )],
+            &[&seller],
+        )
+        .await
+        .unwrap();
+
+    let order: Order = context.get_zero_copy(address).await;
+    let buyer_token = get_associated_token_address(&buyer.pubkey(), &nft.mint);
+    context
+        .process(
+            &[listings_client::fill_sell_order(
+                buyer.pubkey(),
+                address,
+                &order,
+                nft,
+                &RemainingAccounts {
+                    pnft: Some(pnft_accounts(&nft, &seller.pubkey())),
+                    counterparty_token_record: Some(find_token_record(&nft.mint, &buyer_token).0),
+                    creators: creators.clone(),
+                    ..Default::default()
+                },
+            )],
+            &[&buyer],
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(context.token_account(buyer_token).await.amount, 1);
+    let royalty_per_creator = PRICE * ROYALTY_BPS / 10_000 / 2;
+    for creator in creators {
+        assert_eq!(context.lamports(creator).await, royalty_per_creator);
+    }
+}
+
+#[tokio::test]
+async fn fill_bid_with_pnft_pays_royalties() {
+    let mut context = TestContext::new().await;
+    let (pool_mint, market) = context.init_market().await;
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    let seller = context.create_user(LAMPORTS_PER_SOL).await;
+    let creators = vec![Keypair::new().pubkey()];
+    context.init_wallet(&buyer, PRICE).await;
+    let nft = context.mint_pnft(&seller, creators.clone()).await;
+
+    let nonce = Keypair::new().pubkey();
+    let address = order_address(nonce, market, buyer.pubkey());
+    context
+        .process(
+            &[listings_client::init_buy_order(
+                buyer.pubkey(),
+                buyer.pubkey(),
+                pool_mint,
+                Pubkey::default(),
+                InitOrderData {
+                    nonce,
+                    price: PRICE,
+                    size: 1,
+                },
+                &RemainingAccounts::default(),
+            )],
+            &[&buyer],
+        )
+        .await
+        .unwrap();
+
+    let order: Order = context.get_zero_copy(address).await;
+    let buyer_token = get_associated_token_address(&buyer.pubkey(), &nft.mint);
+    let treasury_lamports = context.lamports(treasury()).await;
+    context
+        .process(
+            &[listings_client::fill_buy_order(
+                seller.pubkey(),
+                address,
+                &order,
+                nft,
+                &RemainingAccounts {
+                    pnft: Some(pnft_accounts(&nft, &seller.pubkey())),
+                    counterparty_token_record: Some(find_token_record(&nft.mint, &buyer_token).0),
+                    creators: creators.clone(),
+                    ..Default::default()
+                },
+            )],
+            &[&seller],
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(context.token_account(buyer_token).await.amount, 1);
+    assert_eq!(
+        context.lamports(creators[0]).await,
+        PRICE * ROYALTY_BPS / 10_000
+    );
+    // passing creators without ovol accounts always charges the protocol fee
+    assert_eq!(
+        context.lamports(treasury()).await,
+        treasury_lamports + listings::utils::get_fee_amount(PRICE)
+    );
+}
@@ -0,0 +1,257 @@
+mod common;
+
+use common::*;
+use listings_client::{
+    find_market, find_wallet, with_order_book, InitOrderData, Quote, RemainingAccounts,
+};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+const PRICE: u64 = LAMPORTS_PER_SOL;
+
+async fn bid(
+    context: &mut TestContext,
+    buyer: &Keypair,
+    pool_mint: Pubkey,
+    price: u64,
+    size: u64,
+) -> Pubkey {
+    let nonce = Keypair::new().pubkey();
+    let market = find_market(&pool_mint).0;
+    let init = listings_client::init_buy_order(
+        buyer.pubkey(),
+        buyer.pubkey(),
+        pool_mint,
+        Pubkey::default(),
+        InitOrderData { nonce, price, size },
+        &RemainingAccounts::default(),
+    );
+    let init = context.with_market_order_book(init, market).await;
+    context.process(&[init], &[buyer]).await.unwrap();
+    order_address(nonce, market, buyer.pubkey())
+}
+
+async fn list(context: &mut TestContext, pool_mint: Pubkey, price: u64) -> Pubkey {
+    let seller = context.create_user(LAMPORTS_PER_SOL).await;
+    context.init_wallet(&seller, 0).await;
+    let nft = context.mint_nft(&seller).await;
+    context.add_appraisal(pool_mint, nft.mint).await;
+    let nonce = Keypair::new().pubkey();
+    let market = find_market(&pool_mint).0;
+    let init = listings_client::init_sell_order(
+        seller.pubkey(),
+        pool_mint,
+        nft,
+        InitOrderData {
+            nonce,
+            price,
+            size: 1,
+        },
+        &RemainingAccounts::default(),
+    );
+    let init = context.with_market_order_book(init, market).await;
+    context.process(&[init], &[&seller]).await.unwrap();
+    order_address(nonce, market, seller.pubkey())
+}
+
+#[tokio::test]
+async fn quotes_from_candidate_orders() {
+    let mut context = TestContext::new().await;
+    let (pool_mint, market) = context.init_market().await;
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    context.init_wallet(&buyer, 5 * PRICE).await;
+    let low = bid(&mut context, &buyer, pool_mint, PRICE / 2, 1).await;
+    let high = bid(&mut context, &buyer, pool_mint, PRICE, 2).await;
+    let listing = list(&mut context, pool_mint, 2 * PRICE).await;
+    let wallet = find_wallet(&buyer.pubkey()).0;
+
+    // orders of the other side are skipped
+    let mut bids = [(low, wallet), (listing, wallet), (high, wallet)];
+    bids.sort();
+    let best_bid: Quote = context
+        .return_data(&[listings_client::get_best_bid(market, &bids)], &[])
+        .await;
+    assert_eq!(
+        best_bid,
+        Quote {
+            order: high,
+            price: PRICE,
+            size: 2,
+            order_count: 2,
+            depth: 3,
+        }
+    );
+
+    let mut orders = [low, listing];
+    orders.sort();
+    let floor: Quote = context
+        .return_data(&[listings_client::get_floor(market, &orders)], &[])
+        .await;
+    assert_eq!(floor.order, listing);
+    assert_eq!(floor.price, 2 * PRICE);
+    assert_eq!(floor.order_count, 1);
+
+    // an order passed twice or out of order could be counted twice
+    orders.reverse();
+    for orders in [[listing, listing], orders] {
+        let result = context
+            .process(&[listings_client::get_floor(market, &orders)], &[])
+            .await;
+        assert!(result.is_err());
+    }
+}
+
+#[tokio::test]
+async fn quotes_from_the_order_book() {
+    let mut context = TestContext::new().await;
+    let initializer = context.create_user(LAMPORTS_PER_SOL).await;
+    let pool_mint = Keypair::new().pubkey();
+    let market = find_market(&pool_mint).0;
+
+    // markets without an order book or candidates have an empty quote
+    context
+        .process(
+            &[listings_client::init_market(
+                initializer.pubkey(),
+                pool_mint,
+            )],
+            &[&initializer],
+        )
+        .await
+        .unwrap();
+    let floor: Quote = context
+        .return_data(&[listings_client::get_floor(market, &[])], &[])
+        .await;
+    assert_eq!(floor, Quote::default());
+
+    context
+        .process(
+            &[listings_client::init_order_book(
+                initializer.pubkey(),
+                pool_mint,
+            )],
+            &[&initializer],
+        )
+        .await
+        .unwrap();
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    context.init_wallet(&buyer, 5 * PRICE).await;
+    let low = bid(&mut context, &buyer, pool_mint, PRICE / 2, 1).await;
+    let high = bid(&mut context, &buyer, pool_mint, PRICE, 2).await;
+    let listing = list(&mut context, pool_mint, 2 * PRICE).await;
+    let wallet = find_wallet(&buyer.pubkey()).0;
+
+    let best_bid: Quote = context
+        .return_data(
+            &[with_order_book(
+                listings_client::get_best_bid(market, &[(high, wallet), (low, wallet)]),
+                &market,
+            )],
+            &[],
+        )
+        .await;
+    assert_eq!(best_bid.order, high);
+    assert_eq!(best_bid.order_count, 2);
+    assert_eq!(best_bid.depth, 3);
+
+    let floor: Quote = context
+        .return_data(
+            &[with_order_book(
+                listings_client::get_floor(market, &[]),
+                &market,
+            )],
+            &[],
+        )
+        .await;
+    assert_eq!(floor.order, listing);
+    assert_eq!(floor.price, 2 * PRICE);
+}
+
+#[tokio::test]
+async fn candidate_orders_of_another_market_fail() {
+    let mut context = TestContext::new().await;
+    let (pool_mint, _) = context.init_market().await;
+    let (_, other_market) = context.init_market().await;
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    context.init_wallet(&buyer, PRICE).await;
+    let order = bid(&mut context, &buyer, pool_mint, PRICE, 1).await;
+
+    let result = context
+        .process(
+            &[listings_client::get_best_bid(
+                other_market,
+                &[(order, find_wallet(&buyer.pubkey()).0)],
+            )],
+            &[],
+        )
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn bids_are_capped_by_their_wallet() {
+    let mut context = TestContext::new().await;
+    let (pool_mint, market) = context.init_order_book_market().await;
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    context.init_wallet(&buyer, 2 * PRICE).await;
+    let high = bid(&mut context, &buyer, pool_mint, 2 * PRICE, 1).await;
+    let other_buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    context.init_wallet(&other_buyer, 3 * PRICE).await;
+    let low = bid(&mut context, &other_buyer, pool_mint, PRICE, 3).await;
+
+    // the bids stay in the book but the wallets no longer cover the highest bid
+    // and only two units of the other one
+    for buyer in [&buyer, &other_buyer] {
+        context
+            .process(
+                &[listings_client::edit_wallet(buyer.pubkey(), PRICE, false)],
+                &[buyer],
+            )
+            .await
+            .unwrap();
+    }
+    // bids of the order book are passed in book order
+    let bids = [
+        (high, find_wallet(&buyer.pubkey()).0),
+        (low, find_wallet(&other_buyer.pubkey()).0),
+    ];
+    let expected = Quote {
+        order: low,
+        price: PRICE,
+        size: 2,
+        order_count: 1,
+        depth: 2,
+    };
+    let mut candidates = bids;
+    candidates.sort();
+    let best_bid: Quote = context
+        .return_data(&[listings_client::get_best_bid(market, &candidates)], &[])
+        .await;
+    assert_eq!(best_bid, expected);
+    // the unfunded bid can be left out of the book accounts
+    for bids in [&bids[..], &bids[1..]] {
+        let best_bid: Quote = context
+            .return_data(
+                &[with_order_book(
+                    listings_client::get_best_bid(market, bids),
+                    &market,
+                )],
+                &[],
+            )
+            .await;
+        assert_eq!(best_bid, expected);
+    }
+
+    // passed bids need their wallet and the order of the book
+    for bids in [&[(high, high), bids[1]][..], &[bids[1], bids[0]][..]] {
+        let result = context
+            .process(
+                &[with_order_book(
+                    listings_client::get_best_bid(market, bids),
+                    &market,
+                )],
+                &[],
+            )
+            .await;
+        assert!(result.is_err());
+    }
+}
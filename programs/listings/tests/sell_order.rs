@@ -0,0 +1,223 @@
+mod common;
+
+use anchor_spl::associated_token::get_associated_token_address;
+use common::*;
+use listings::state::{Order, OrderSide, OrderState, Wallet};
+use listings_client::{treasury, EditSellOrderData, InitOrderData, NftAccounts, RemainingAccounts};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+use spl_token::state::AccountState;
+
+const PRICE: u64 = LAMPORTS_PER_SOL;
+
+struct SellOrder {
+    seller: Keypair,
+    pool_mint: Pubkey,
+    nft: NftAccounts,
+    address: Pubkey,
+}
+
+async fn init_sell_order(context: &mut TestContext) -> SellOrder {
+    let (pool_mint, market) = context.init_market().await;
+    let seller = context.create_user(LAMPORTS_PER_SOL).await;
+    context.init_wallet(&seller, 0).await;
+    let nft = context.mint_nft(&seller).await;
+    context.add_appraisal(pool_mint, nft.mint).await;
+
+    let nonce = Keypair::new().pubkey();
+    context
+        .process(
+            &[listings_client::init_sell_order(
+                seller.pubkey(),
+                pool_mint,
+                nft,
+                InitOrderData {
+                    nonce,
+                    price: PRICE,
+                    size: 1,
+                },
+                &RemainingAccounts::default(),
+            )],
+            &[&seller],
+        )
+        .await
+        .unwrap();
+
+    SellOrder {
+        address: order_address(nonce, market, seller.pubkey()),
+        seller,
+        pool_mint,
+        nft,
+    }
+}
+
+#[tokio::test]
+async fn init_sell_order_freezes_nft() {
+    let mut context = TestContext::new().await;
+    let listing = init_sell_order(&mut context).await;
+
+    let order: Order = context.get_zero_copy(listing.address).await;
+    assert_eq!(order.side, u8::from(OrderSide::Sell));
+    assert_eq!(order.state, u8::from(OrderState::Ready));
+    assert_eq!(order.size, 1);
+    assert_eq!(order.nft_mint, listing.nft.mint);
+
+    let nft_ta = get_associated_token_address(&listing.seller.pubkey(), &listing.nft.mint);
+    let token_account = context.token_account(nft_ta).await;
+    assert_eq!(token_account.state, AccountState::Frozen);
+    assert_eq!(
+        Option::<Pubkey>::from(token_account.delegate),
+        Some(listings_client::find_wallet(&listing.seller.pubkey()).0)
+    );
+}
+
+#[tokio::test]
+async fn close_wallet_with_listing_fails() {
+    let mut context = TestContext::new().await;
+    let listing = init_sell_order(&mut context).await;
+
+    let wallet = listings_client::find_wallet(&listing.seller.pubkey()).0;
+    let wallet_state: Wallet = context.get_zero_copy(wallet).await;
+    assert_eq!(wallet_state.open_sell_orders, 1);
+
+    // the listed nft is delegated to the wallet
+    let result = context
+        .process(
+            &[listings_client::close_wallet(listing.seller.pubkey())],
+            &[&listing.seller],
+        )
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn init_sell_order_without_appraisal_fails() {
+    let mut context = TestContext::new().await;
+    let (pool_mint, _) = context.init_market().await;
+    let seller = context.create_user(LAMPORTS_PER_SOL).await;
+    context.init_wallet(&seller, 0).await;
+    let nft = context.mint_nft(&seller).await;
+
+    let result = context
+        .process(
+            &[listings_client::init_sell_order(
+                seller.pubkey(),
+                pool_mint,
+                nft,
+                InitOrderData {
+                    nonce: Keypair::new().pubkey(),
+                    price: PRICE,
+                    size: 1,
+                },
+                &RemainingAccounts::default(),
+            )],
+            &[&seller],
+        )
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn edit_and_close_sell_order() {
+    let mut context = TestContext::new().await;
+    let listing = init_sell_order(&mut context).await;
+
+    context
+        .process(
+            &[listings_client::edit_sell_order(
+                listing.seller.pubkey(),
+                listing.address,
+                listing.pool_mint,
+                EditSellOrderData {
+                    new_price: 2 * PRICE,
+                },
+            )],
+            &[&listing.seller],
+        )
+        .await
+        .unwrap();
+    let order: Order = context.get_zero_copy(listing.address).await;
+    assert_eq!(order.price, 2 * PRICE);
+
+    context
+        .process(
+            &[listings_client::close_sell_order(
+                listing.seller.pubkey(),
+                listing.address,
+                &order,
+                listing.nft,
+                &RemainingAccounts::default(),
+            )],
+            &[&listing.seller],
+        )
+        .await
+        .unwrap();
+    assert!(context.get_account(listing.address).await.is_none());
+
+    let nft_ta = get_associated_token_address(&listing.seller.pubkey(), &listing.nft.mint);
+    assert_eq!(
+        context.token_account(nft_ta).await.state,
+        AccountState::Initialized
+    );
+}
+
+#[tokio::test]
+async fn fill_sell_order_with_nft() {
+    let mut context = TestContext::new().await;
+    let listing = init_sell_order(&mut context).await;
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+
+    let order: Order = context.get_zero_copy(listing.address).await;
+    let seller_lamports = context.lamports(listing.seller.pubkey()).await;
+    let order_rent = context.lamports(listing.address).await;
+    let treasury_lamports = context.lamports(treasury()).await;
+    context
+        .process(
+            &[listings_client::fill_sell_order(
+                buyer.pubkey(),
+                listing.address,
+                &order,
+                listing.nft,
+                &RemainingAccounts::default(),
+            )],
+            &[&buyer],
+        )
+        .await
+        .unwrap();
+
+    let buyer_nft_ta = get_associated_token_address(&buyer.pubkey(), &listing.nft.mint);
+    assert_eq!(context.token_account(buyer_nft_ta).await.amount, 1);
+    assert!(context.get_account(listing.address).await.is_none());
+
+    // seller gets the full price plus the order rent, buyer pays the fee on top
+    assert_eq!(
+        context.lamports(listing.seller.pubkey()).await,
+        seller_lamports + PRICE + order_rent
+    );
+    assert_eq!(
+        context.lamports(treasury()).await,
+        treasury_lamports + listings::utils::get_fee_amount(PRICE)
+    );
+}
+
+#[tokio::test]
+async fn fill_sell_order_from_other_seller_fails() {
+    let mut context = TestContext::new().await;
+    let listing = init_sell_order(&mut context).await;
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+
+    let mut order: Order = context.get_zero_copy(listing.address).await;
+    order.owner = buyer.pubkey();
+    let result = context
+        .process(
+            &[listings_client::fill_sell_order(
+                buyer.pubkey(),
+                listing.address,
+                &order,
+                listing.nft,
+                &RemainingAccounts::default(),
+            )],
+            &[&buyer],
+        )
+        .await;
+    assert!(result.is_err());
+}
@@ -0,0 +1,285 @@
+mod common;
+
+use anchor_spl::{
+    associated_token::get_associated_token_address_with_program_id,
+    token_2022::{self, spl_token_2022::extension::ExtensionType},
+};
+use common::*;
+use listings::state::Order;
+use listings_client::{find_wallet, InitOrderData, RemainingAccounts};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+const PRICE: u64 = LAMPORTS_PER_SOL;
+
+fn token22_ata(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(owner, mint, &token_2022::ID)
+}
+
+async fn list(
+    context: &mut TestContext,
+    seller: &Keypair,
+    pool_mint: Pubkey,
+    market: Pubkey,
+    nft_mint: Pubkey,
+) -> Pubkey {
+    let nonce = Keypair::new().pubkey();
+    let address = order_address(nonce, market, seller.pubkey());
+    context
+        .process(
+            &[listings_client::token22_init_sell_order(
+                seller.pubkey(),
+                pool_mint,
+                nft_mint,
+                InitOrderData {
+                    nonce,
+                    price: PRICE,
+                    size: 1,
+                },
+                &RemainingAccounts::default(),
+            )],
+            &[seller],
+        )
+        .await
+        .unwrap();
+    address
+}
+
+#[tokio::test]
+async fn token22_listing_is_escrowed_and_filled() {
+    let mut context = TestContext::new().await;
+    let (pool_mint, market) = context.init_market().await;
+    let seller = context.create_user(LAMPORTS_PER_SOL).await;
+    context.init_wallet(&seller, 0).await;
+    let nft_mint = context.mint_token22_nft(&seller, false).await;
+    context.add_appraisal(pool_mint, nft_mint).await;
+
+    let address = list(&mut context, &seller, pool_mint, market, nft_mint).await;
+    let escrow = token22_ata(&find_wallet(&seller.pubkey()).0, &nft_mint);
+    assert_eq!(context.token22_amount(escrow).await, 1);
+    assert_eq!(
+        context
+            .token22_amount(token22_ata(&seller.pubkey(), &nft_mint))
+            .await,
+        0
+    );
+
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    let seller_lamports = context.lamports(seller.pubkey()).await;
+    let order: Order = context.get_zero_copy(address).await;
+    context
+        .process(
+            &[listings_client::token22_fill_sell_order(
+                buyer.pubkey(),
+                address,
+                &order,
+                &RemainingAccounts::default(),
+            )],
+            &[&buyer],
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        context
+            .token22_amount(token22_ata(&buyer.pubkey(), &nft_mint))
+            .await,
+        1
+    );
+    assert!(context.get_account(escrow).await.is_none());
+    assert!(context.get_account(address).await.is_none());
+    assert!(context.lamports(seller.pubkey()).await >= seller_lamports + PRICE);
+}
+
+#[tokio::test]
+async fn closing_a_token22_listing_returns_the_nft() {
+    let mut context = TestContext::new().await;
+    let (pool_mint, market) = context.init_market().await;
+    let seller = context.create_user(LAMPORTS_PER_SOL).await;
+    context.init_wallet(&seller, 0).await;
+    let nft_mint = context.mint_token22_nft(&seller, false).await;
+    context.add_appraisal(pool_mint, nft_mint).await;
+
+    let address = list(&mut context, &seller, pool_mint, market, nft_mint).await;
+    let order: Order = context.get_zero_copy(address).await;
+    context
+        .process(
+            &[listings_client::token22_close_sell_order(
+                seller.pubkey(),
+                address,
+                &order,
+                &RemainingAccounts::default(),
+            )],
+            &[&seller],
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        context
+            .token22_amount(token22_ata(&seller.pubkey(), &nft_mint))
+            .await,
+        1
+    );
+    assert!(context.get_account(address).await.is_none());
+}
+
+#[tokio::test]
+async fn token22_nft_fills_a_bid() {
+    let mut context = TestContext::new().await;
+    let (pool_mint, market) = context.init_market().await;
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    context.init_wallet(&buyer, PRICE).await;
+    let nonce = Keypair::new().pubkey();
+    let address = order_address(nonce, market, buyer.pubkey());
+    context
+        .process(
+            &[listings_client::init_buy_order(
+                buyer.pubkey(),
+                buyer.pubkey(),
+                pool_mint,
+                Pubkey::default(),
+                InitOrderData {
+                    nonce,
+                    price: PRICE,
+                    size: 1,
+                },
+                &RemainingAccounts::default(),
+            )],
+            &[&buyer],
+        )
+        .await
+        .unwrap();
+
+    let seller = context.create_user(LAMPORTS_PER_SOL).await;
+    let nft_mint = context.mint_token22_nft(&seller, false).await;
+    let order: Order = context.get_zero_copy(address).await;
+    let fill = listings_client::token22_fill_buy_order(
+        seller.pubkey(),
+        address,
+        &order,
+        pool_mint,
+        nft_mint,
+        &RemainingAccounts::default(),
+    );
+
+    // the nft has to be appraised for the market
+    let result = context.process(&[fill.clone()], &[&seller]).await;
+    assert!(result.is_err());
+
+    context.add_appraisal(pool_mint, nft_mint).await;
+    context.process(&[fill], &[&seller]).await.unwrap();
+    assert_eq!(
+        context
+            .token22_amount(token22_ata(&buyer.pubkey(), &nft_mint))
+            .await,
+        1
+    );
+    assert!(context.get_account(address).await.is_none());
+}
+
+#[tokio::test]
+async fn non_transferable_nft_cannot_be_listed() {
+    let mut context = TestContext::new().await;
+    let (pool_mint, market) = context.init_market().await;
+    let seller = context.create_user(LAMPORTS_PER_SOL).await;
+    context.init_wallet(&seller, 0).await;
+    let nft_mint = context.mint_token22_nft(&seller, true).await;
+    context.add_appraisal(pool_mint, nft_mint).await;
+
+    let nonce = Keypair::new().pubkey();
+    let result = context
+        .process(
+            &[listings_client::token22_init_sell_order(
+                seller.pubkey(),
+                pool_mint,
+                nft_mint,
+                InitOrderData {
+                    nonce,
+                    price: PRICE,
+                    size: 1,
+                },
+                &RemainingAccounts::default(),
+            )],
+            &[&seller],
+        )
+        .await;
+    assert!(result.is_err());
+    let address = order_address(nonce, market, seller.pubkey());
+    assert!(context.get_account(address).await.is_none());
+}
+
+#[tokio::test]
+async fn nft_with_mint_authority_cannot_be_listed() {
+    // the mint authority could mint a second unit of the nft
+    let mut context = TestContext::new().await;
+    let (pool_mint, market) = context.init_market().await;
+    let seller = context.create_user(LAMPORTS_PER_SOL).await;
+    context.init_wallet(&seller, 0).await;
+    let nft_mint = context.mint_token22_nft_with_mint_authority(&seller).await;
+    context.add_appraisal(pool_mint, nft_mint).await;
+
+    let nonce = Keypair::new().pubkey();
+    let result = context
+        .process(
+            &[listings_client::token22_init_sell_order(
+                seller.pubkey(),
+                pool_mint,
+                nft_mint,
+                InitOrderData {
+                    nonce,
+                    price: PRICE,
+                    size: 1,
+                },
+                &RemainingAccounts::default(),
+            )],
+            &[&seller],
+        )
+        .await;
+    assert!(result.is_err());
+    let address = order_address(nonce, market, seller.pubkey());
+    assert!(context.get_account(address).await.is_none());
+}
+
+/// list a token-2022 nft minted with the extension, the listing must fail
+async fn assert_extension_cannot_be_listed(extension: ExtensionType) {
+    let mut context = TestContext::new().await;
+    let (pool_mint, market) = context.init_market().await;
+    let seller = context.create_user(LAMPORTS_PER_SOL).await;
+    context.init_wallet(&seller, 0).await;
+    let nft_mint = context
+        .mint_token22_nft_with_extensions(&seller, &[extension])
+        .await;
+    context.add_appraisal(pool_mint, nft_mint).await;
+
+    let nonce = Keypair::new().pubkey();
+    let result = context
+        .process(
+            &[listings_client::token22_init_sell_order(
+                seller.pubkey(),
+                pool_mint,
+                nft_mint,
+                InitOrderData {
+                    nonce,
+                    price: PRICE,
+                    size: 1,
+                },
+                &RemainingAccounts::default(),
+            )],
+            &[&seller],
+        )
+        .await;
+    assert!(result.is_err());
+    let address = order_address(nonce, market, seller.pubkey());
+    assert!(context.get_account(address).await.is_none());
+}
+
+#[tokio::test]
+async fn permanent_delegate_nft_cannot_be_listed() {
+    // the delegate could pull the escrowed nft out of the wallet
+    assert_extension_cannot_be_listed(ExtensionType::PermanentDelegate).await;
+}
+
+#[tokio::test]
+async fn mint_close_authority_nft_cannot_be_listed() {
+    assert_extension_cannot_be_listed(ExtensionType::MintCloseAuthority).await;
+}
@@ -0,0 +1,220 @@
+mod common;
+
+use common::*;
+use listings::state::{Order, OrderPriceMode, Wallet};
+use listings_client::{
+    find_wallet, with_order_book, EditBuyOrderData, InitOrderData, RemainingAccounts,
+    TrailingPriceData,
+};
+use solana_program_test::BanksClientError;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+const PRICE: u64 = LAMPORTS_PER_SOL;
+const MAX_PRICE: u64 = 2 * PRICE;
+
+struct TrailingBid {
+    buyer: Keypair,
+    pool_mint: Pubkey,
+    market: Pubkey,
+    address: Pubkey,
+}
+
+/// bid trailing the listings by 10%, capped at MAX_PRICE, on a market with an order book
+async fn init_trailing_bid(context: &mut TestContext) -> TrailingBid {
+    let (pool_mint, market) = context.init_order_book_market().await;
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    context.init_wallet(&buyer, MAX_PRICE).await;
+
+    let nonce = Keypair::new().pubkey();
+    let address = order_address(nonce, market, buyer.pubkey());
+    context
+        .process(
+            &[with_order_book(
+                listings_client::init_buy_order(
+                    buyer.pubkey(),
+                    buyer.pubkey(),
+                    pool_mint,
+                    Pubkey::default(),
+                    InitOrderData {
+                        nonce,
+                        price: PRICE,
+                        size: 1,
+                    },
+                    &RemainingAccounts::default(),
+                ),
+                &market,
+            )],
+            &[&buyer],
+        )
+        .await
+        .unwrap();
+    let order: Order = context.get_zero_copy(address).await;
+    context
+        .process(
+            &[with_order_book(
+                listings_client::set_trailing_price(
+                    buyer.pubkey(),
+                    address,
+                    &order,
+                    TrailingPriceData {
+                        trailing_bps: 1000,
+                        max_price: MAX_PRICE,
+                    },
+                ),
+                &market,
+            )],
+            &[&buyer],
+        )
+        .await
+        .unwrap();
+
+    TrailingBid {
+        buyer,
+        pool_mint,
+        market,
+        address,
+    }
+}
+
+async fn list(context: &mut TestContext, bid: &TrailingBid, price: u64) {
+    let seller = context.create_user(LAMPORTS_PER_SOL).await;
+    context.init_wallet(&seller, 0).await;
+    let nft = context.mint_nft(&seller).await;
+    context.add_appraisal(bid.pool_mint, nft.mint).await;
+    context
+        .process(
+            &[with_order_book(
+                listings_client::init_sell_order(
+                    seller.pubkey(),
+                    bid.pool_mint,
+                    nft,
+                    InitOrderData {
+                        nonce: Keypair::new().pubkey(),
+                        price,
+                        size: 1,
+                    },
+                    &RemainingAccounts::default(),
+                ),
+                &bid.market,
+            )],
+            &[&seller],
+        )
+        .await
+        .unwrap();
+}
+
+/// a new seller fills the bid with a new nft
+async fn fill(context: &mut TestContext, bid: &TrailingBid) -> Result<(), BanksClientError> {
+    let seller = context.create_user(LAMPORTS_PER_SOL).await;
+    let nft = context.mint_nft(&seller).await;
+    let order: Order = context.get_zero_copy(bid.address).await;
+    context
+        .process(
+            &[with_order_book(
+                listings_client::fill_buy_order(
+                    seller.pubkey(),
+                    bid.address,
+                    &order,
+                    nft,
+                    &RemainingAccounts::default(),
+                ),
+                &bid.market,
+            )],
+            &[&seller],
+        )
+        .await
+}
+
+#[tokio::test]
+async fn trailing_bid_is_priced_against_the_floor() {
+    let mut context = TestContext::new().await;
+    let bid = init_trailing_bid(&mut context).await;
+
+    let order: Order = context.get_zero_copy(bid.address).await;
+    assert_eq!(order.price_mode, u8::from(OrderPriceMode::TrailingListing));
+    assert_eq!(order.max_price, MAX_PRICE);
+
+    // the higher listing is not the floor
+    list(&mut context, &bid, 3 * PRICE).await;
+    list(&mut context, &bid, 3 * PRICE / 2).await;
+    fill(&mut context, &bid).await.unwrap();
+
+    let wallet: Wallet = context
+        .get_zero_copy(find_wallet(&bid.buyer.pubkey()).0)
+        .await;
+    assert_eq!(wallet.balance, MAX_PRICE - 3 * PRICE / 2 * 9 / 10);
+    assert!(context.get_account(bid.address).await.is_none());
+}
+
+#[tokio::test]
+async fn trailing_price_is_capped() {
+    let mut context = TestContext::new().await;
+    let bid = init_trailing_bid(&mut context).await;
+
+    list(&mut context, &bid, 3 * PRICE).await;
+    fill(&mut context, &bid).await.unwrap();
+
+    let wallet: Wallet = context
+        .get_zero_copy(find_wallet(&bid.buyer.pubkey()).0)
+        .await;
+    assert_eq!(wallet.balance, 0);
+}
+
+#[tokio::test]
+async fn trailing_bid_needs_a_floor_listing() {
+    let mut context = TestContext::new().await;
+    let bid = init_trailing_bid(&mut context).await;
+
+    assert!(fill(&mut context, &bid).await.is_err());
+    let order: Order = context.get_zero_copy(bid.address).await;
+    assert_eq!(order.size, 1);
+}
+
+#[tokio::test]
+async fn cap_has_to_be_covered_and_edits_fix_the_price() {
+    let mut context = TestContext::new().await;
+    let bid = init_trailing_bid(&mut context).await;
+    let order: Order = context.get_zero_copy(bid.address).await;
+
+    // the wallet only holds MAX_PRICE
+    let result = context
+        .process(
+            &[with_order_book(
+                listings_client::set_trailing_price(
+                    bid.buyer.pubkey(),
+                    bid.address,
+                    &order,
+                    TrailingPriceData {
+                        trailing_bps: 1000,
+                        max_price: 2 * MAX_PRICE,
+                    },
+                ),
+                &bid.market,
+            )],
+            &[&bid.buyer],
+        )
+        .await;
+    assert!(result.is_err());
+
+    context
+        .process(
+            &[with_order_book(
+                listings_client::edit_buy_order(
+                    bid.buyer.pubkey(),
+                    bid.address,
+                    &order,
+                    bid.pool_mint,
+                    EditBuyOrderData {
+                        new_size: 1,
+                        new_price: PRICE,
+                    },
+                ),
+                &bid.market,
+            )],
+            &[&bid.buyer],
+        )
+        .await
+        .unwrap();
+    let order: Order = context.get_zero_copy(bid.address).await;
+    assert_eq!(order.price_mode, u8::from(OrderPriceMode::Fixed));
+}
@@ -0,0 +1,185 @@
+mod common;
+
+use anchor_lang::solana_program::system_instruction;
+use bridgesplit_program_utils::anchor_lang;
+use common::*;
+use listings::state::{Order, Wallet};
+use listings_client::{InitOrderData, RemainingAccounts};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+#[tokio::test]
+async fn deposit_and_withdraw() {
+    let mut context = TestContext::new().await;
+    let owner = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    let wallet = context.init_wallet(&owner, LAMPORTS_PER_SOL).await;
+
+    let wallet_state: Wallet = context.get_zero_copy(wallet).await;
+    assert_eq!(wallet_state.owner, owner.pubkey());
+    assert_eq!(wallet_state.balance, LAMPORTS_PER_SOL);
+
+    // deposit
+    let wallet_lamports = context.lamports(wallet).await;
+    context
+        .process(
+            &[listings_client::edit_wallet(
+                owner.pubkey(),
+                2 * LAMPORTS_PER_SOL,
+                true,
+            )],
+            &[&owner],
+        )
+        .await
+        .unwrap();
+    let wallet_state: Wallet = context.get_zero_copy(wallet).await;
+    assert_eq!(wallet_state.balance, 3 * LAMPORTS_PER_SOL);
+    assert_eq!(
+        context.lamports(wallet).await,
+        wallet_lamports + 2 * LAMPORTS_PER_SOL
+    );
+
+    // withdraw
+    let owner_lamports = context.lamports(owner.pubkey()).await;
+    context
+        .process(
+            &[listings_client::edit_wallet(
+                owner.pubkey(),
+                LAMPORTS_PER_SOL,
+                false,
+            )],
+            &[&owner],
+        )
+        .await
+        .unwrap();
+    let wallet_state: Wallet = context.get_zero_copy(wallet).await;
+    assert_eq!(wallet_state.balance, 2 * LAMPORTS_PER_SOL);
+    assert_eq!(
+        context.lamports(owner.pubkey()).await,
+        owner_lamports + LAMPORTS_PER_SOL
+    );
+}
+
+#[tokio::test]
+async fn withdraw_more_than_balance_fails() {
+    let mut context = TestContext::new().await;
+    let owner = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    context.init_wallet(&owner, LAMPORTS_PER_SOL).await;
+
+    let result = context
+        .process(
+            &[listings_client::edit_wallet(
+                owner.pubkey(),
+                LAMPORTS_PER_SOL + 1,
+                false,
+            )],
+            &[&owner],
+        )
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn close_wallet_requires_no_open_orders() {
+    let mut context = TestContext::new().await;
+    let (pool_mint, market) = context.init_market().await;
+    let owner = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    let wallet = context.init_wallet(&owner, LAMPORTS_PER_SOL).await;
+
+    let nonce = Keypair::new().pubkey();
+    context
+        .process(
+            &[listings_client::init_buy_order(
+                owner.pubkey(),
+                owner.pubkey(),
+                pool_mint,
+                Pubkey::default(),
+                InitOrderData {
+                    nonce,
+                    price: LAMPORTS_PER_SOL,
+                    size: 1,
+                },
+                &RemainingAccounts::default(),
+            )],
+            &[&owner],
+        )
+        .await
+        .unwrap();
+    let wallet_state: Wallet = context.get_zero_copy(wallet).await;
+    assert_eq!(wallet_state.open_buy_orders, 1);
+
+    // the bid is still funded by the wallet
+    let result = context
+        .process(&[listings_client::close_wallet(owner.pubkey())], &[&owner])
+        .await;
+    assert!(result.is_err());
+
+    let address = order_address(nonce, market, owner.pubkey());
+    let order: Order = context.get_zero_copy(address).await;
+    context
+        .process(
+            &[listings_client::close_buy_order(
+                owner.pubkey(),
+                address,
+                &order,
+            )],
+            &[&owner],
+        )
+        .await
+        .unwrap();
+    let wallet_state: Wallet = context.get_zero_copy(wallet).await;
+    assert_eq!(wallet_state.open_buy_orders, 0);
+
+    let wallet_lamports = context.lamports(wallet).await;
+    let owner_lamports = context.lamports(owner.pubkey()).await;
+    context
+        .process(&[listings_client::close_wallet(owner.pubkey())], &[&owner])
+        .await
+        .unwrap();
+    assert!(context.get_account(wallet).await.is_none());
+    assert_eq!(
+        context.lamports(owner.pubkey()).await,
+        owner_lamports + wallet_lamports
+    );
+}
+
+#[tokio::test]
+async fn sync_wallet_credits_direct_transfers() {
+    let mut context = TestContext::new().await;
+    let owner = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    let wallet = context.init_wallet(&owner, LAMPORTS_PER_SOL).await;
+
+    context
+        .process(
+            &[system_instruction::transfer(
+                &owner.pubkey(),
+                &wallet,
+                LAMPORTS_PER_SOL,
+            )],
+            &[&owner],
+        )
+        .await
+        .unwrap();
+    let wallet_state: Wallet = context.get_zero_copy(wallet).await;
+    assert_eq!(wallet_state.balance, LAMPORTS_PER_SOL);
+
+    context
+        .process(&[listings_client::sync_wallet(owner.pubkey())], &[&owner])
+        .await
+        .unwrap();
+    let wallet_state: Wallet = context.get_zero_copy(wallet).await;
+    assert_eq!(wallet_state.balance, 2 * LAMPORTS_PER_SOL);
+
+    // the synced balance can be withdrawn in full
+    context
+        .process(
+            &[listings_client::edit_wallet(
+                owner.pubkey(),
+                2 * LAMPORTS_PER_SOL,
+                false,
+            )],
+            &[&owner],
+        )
+        .await
+        .unwrap();
+    let wallet_state: Wallet = context.get_zero_copy(wallet).await;
+    assert_eq!(wallet_state.balance, 0);
+}
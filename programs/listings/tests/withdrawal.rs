@@ -0,0 +1,250 @@
+mod common;
+
+use common::*;
+use listings::state::{Market, Order, Wallet, MAX_WITHDRAWAL_DELAY};
+use listings_client::{find_market, InitOrderData, RemainingAccounts};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+const DELAY: i64 = 3600;
+
+async fn set_delay(context: &mut TestContext, owner: &Keypair, delay: i64) {
+    context
+        .process(
+            &[listings_client::set_withdrawal_delay(owner.pubkey(), delay)],
+            &[owner],
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn withdrawal_is_time_locked() {
+    let mut context = TestContext::new().await;
+    let owner = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    let wallet = context.init_wallet(&owner, 2 * LAMPORTS_PER_SOL).await;
+    set_delay(&mut context, &owner, DELAY).await;
+
+    // instant withdrawals are disabled
+    let result = context
+        .process(
+            &[listings_client::edit_wallet(
+                owner.pubkey(),
+                LAMPORTS_PER_SOL,
+                false,
+            )],
+            &[&owner],
+        )
+        .await;
+    assert!(result.is_err());
+
+    context
+        .process(
+            &[listings_client::request_withdrawal(
+                owner.pubkey(),
+                LAMPORTS_PER_SOL,
+            )],
+            &[&owner],
+        )
+        .await
+        .unwrap();
+    let wallet_state: Wallet = context.get_zero_copy(wallet).await;
+    assert_eq!(wallet_state.pending_withdrawal, LAMPORTS_PER_SOL);
+    assert_eq!(wallet_state.balance, 2 * LAMPORTS_PER_SOL);
+
+    let result = context
+        .process(
+            &[listings_client::complete_withdrawal(owner.pubkey())],
+            &[&owner],
+        )
+        .await;
+    assert!(result.is_err());
+
+    context.warp(DELAY).await;
+    let owner_lamports = context.lamports(owner.pubkey()).await;
+    context
+        .process(
+            &[listings_client::complete_withdrawal(owner.pubkey())],
+            &[&owner],
+        )
+        .await
+        .unwrap();
+    let wallet_state: Wallet = context.get_zero_copy(wallet).await;
+    assert_eq!(wallet_state.pending_withdrawal, 0);
+    assert_eq!(wallet_state.balance, LAMPORTS_PER_SOL);
+    assert_eq!(
+        context.lamports(owner.pubkey()).await,
+        owner_lamports + LAMPORTS_PER_SOL
+    );
+}
+
+#[tokio::test]
+async fn bids_stay_fillable_during_the_delay() {
+    let mut context = TestContext::new().await;
+    let (pool_mint, market) = context.init_market().await;
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    let wallet = context.init_wallet(&buyer, LAMPORTS_PER_SOL).await;
+    set_delay(&mut context, &buyer, DELAY).await;
+
+    let nonce = Keypair::new().pubkey();
+    context
+        .process(
+            &[
+                listings_client::init_buy_order(
+                    buyer.pubkey(),
+                    buyer.pubkey(),
+                    pool_mint,
+                    Pubkey::default(),
+                    InitOrderData {
+                        nonce,
+                        price: LAMPORTS_PER_SOL,
+                        size: 1,
+                    },
+                    &RemainingAccounts::default(),
+                ),
+                listings_client::request_withdrawal(buyer.pubkey(), LAMPORTS_PER_SOL),
+            ],
+            &[&buyer],
+        )
+        .await
+        .unwrap();
+
+    let seller = context.create_user(LAMPORTS_PER_SOL).await;
+    let nft = context.mint_nft(&seller).await;
+    let address = order_address(nonce, market, buyer.pubkey());
+    let order: Order = context.get_zero_copy(address).await;
+    context
+        .process(
+            &[listings_client::fill_buy_order(
+                seller.pubkey(),
+                address,
+                &order,
+                nft,
+                &RemainingAccounts::default(),
+            )],
+            &[&seller],
+        )
+        .await
+        .unwrap();
+
+    // the fill used the requested amount, nothing is left to withdraw
+    context.warp(DELAY).await;
+    let owner_lamports = context.lamports(buyer.pubkey()).await;
+    context
+        .process(
+            &[listings_client::complete_withdrawal(buyer.pubkey())],
+            &[&buyer],
+        )
+        .await
+        .unwrap();
+    let wallet_state: Wallet = context.get_zero_copy(wallet).await;
+    assert_eq!(wallet_state.balance, 0);
+    assert_eq!(wallet_state.pending_withdrawal, 0);
+    assert_eq!(context.lamports(buyer.pubkey()).await, owner_lamports);
+}
+
+#[tokio::test]
+async fn lowering_the_delay_waits_for_the_current_delay() {
+    let mut context = TestContext::new().await;
+    let owner = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    let wallet = context.init_wallet(&owner, LAMPORTS_PER_SOL).await;
+    set_delay(&mut context, &owner, DELAY).await;
+
+    set_delay(&mut context, &owner, 0).await;
+    let wallet_state: Wallet = context.get_zero_copy(wallet).await;
+    assert_eq!(wallet_state.withdrawal_delay, DELAY);
+    assert_ne!(wallet_state.withdrawal_delay_unlock_time, 0);
+
+    context.warp(DELAY).await;
+    set_delay(&mut context, &owner, 0).await;
+    let wallet_state: Wallet = context.get_zero_copy(wallet).await;
+    assert_eq!(wallet_state.withdrawal_delay, 0);
+}
+
+#[tokio::test]
+async fn withdrawal_delay_is_capped() {
+    let mut context = TestContext::new().await;
+    let owner = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    let wallet = context.init_wallet(&owner, LAMPORTS_PER_SOL).await;
+
+    for delay in [MAX_WITHDRAWAL_DELAY + 1, i64::MAX, -1] {
+        let result = context
+            .process(
+                &[listings_client::set_withdrawal_delay(owner.pubkey(), delay)],
+                &[&owner],
+            )
+            .await;
+        assert!(result.is_err());
+    }
+
+    set_delay(&mut context, &owner, MAX_WITHDRAWAL_DELAY).await;
+    let wallet_state: Wallet = context.get_zero_copy(wallet).await;
+    assert_eq!(wallet_state.withdrawal_delay, MAX_WITHDRAWAL_DELAY);
+}
+
+#[tokio::test]
+async fn market_minimum_delay_applies_to_new_bids() {
+    let mut context = TestContext::new().await;
+    let pool_mint = Keypair::new().pubkey();
+    let initializer = context.create_user(LAMPORTS_PER_SOL).await;
+    context
+        .process(
+            &[
+                listings_client::init_market(initializer.pubkey(), pool_mint),
+                listings_client::set_min_withdrawal_delay(initializer.pubkey(), pool_mint, DELAY),
+            ],
+            &[&initializer],
+        )
+        .await
+        .unwrap();
+    let market: Market = context.get_anchor(find_market(&pool_mint).0).await;
+    assert_eq!(market.min_withdrawal_delay, DELAY);
+
+    // only the market initializer sets the minimum, up to the maximum delay
+    let other = context.create_user(LAMPORTS_PER_SOL).await;
+    let result = context
+        .process(
+            &[listings_client::set_min_withdrawal_delay(
+                other.pubkey(),
+                pool_mint,
+                0,
+            )],
+            &[&other],
+        )
+        .await;
+    assert!(result.is_err());
+    let result = context
+        .process(
+            &[listings_client::set_min_withdrawal_delay(
+                initializer.pubkey(),
+                pool_mint,
+                MAX_WITHDRAWAL_DELAY + 1,
+            )],
+            &[&initializer],
+        )
+        .await;
+    assert!(result.is_err());
+
+    let buyer = context.create_user(10 * LAMPORTS_PER_SOL).await;
+    context.init_wallet(&buyer, LAMPORTS_PER_SOL).await;
+    let init_buy_order = listings_client::init_buy_order(
+        buyer.pubkey(),
+        buyer.pubkey(),
+        pool_mint,
+        Pubkey::default(),
+        InitOrderData {
+            nonce: Keypair::new().pubkey(),
+            price: LAMPORTS_PER_SOL,
+            size: 1,
+        },
+        &RemainingAccounts::default(),
+    );
+
+    // instant withdrawals cannot back bids of the market
+    let result = context
+        .process(std::slice::from_ref(&init_buy_order), &[&buyer])
+        .await;
+    assert!(result.is_err());
+
+    set_delay(&mut context, &buyer, DELAY).await;
+    context.process(&[init_buy_order], &[&buyer]).await.unwrap();
+}
@@ -0,0 +1,233 @@
+//! stand-in for the spl stake pool program
+//! implements `DepositSol` and `WithdrawSol` with the same instruction tags, account order
+//! and stake pool offsets for `total_lamports` and `pool_token_supply`,
+//! the reserve is a plain account owned by this program instead of a stake account
+//! the sol withdrawal fee is burnt with the withdrawn pool tokens instead of paid to the manager
+
+use std::convert::{TryFrom, TryInto};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_instruction,
+};
+
+solana_program::declare_id!("mgm4BCzt3dTX9KBWYTAtFvJC4LcUt9gZBex52wXHJyV");
+
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
+
+pub const DEPOSIT_SOL_TAG: u8 = 14;
+pub const WITHDRAW_SOL_TAG: u8 = 16;
+pub const TOTAL_LAMPORTS_OFFSET: usize = 258;
+pub const POOL_TOKEN_SUPPLY_OFFSET: usize = 266;
+/// offset of `sol_withdrawal_fee` when all optional fields before it are empty
+pub const SOL_WITHDRAWAL_FEE_OFFSET: usize = 402;
+/// stake pool layout up to `last_epoch_total_lamports` with all optional fields empty
+pub const STAKE_POOL_LEN: usize = 435;
+pub const WITHDRAW_AUTHORITY_SEED: &[u8] = b"withdraw";
+
+pub fn find_withdraw_authority(stake_pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[stake_pool.as_ref(), WITHDRAW_AUTHORITY_SEED], &ID)
+}
+
+/// stake pool account data with the given totals
+pub fn stake_pool_data(total_lamports: u64, pool_token_supply: u64) -> Vec<u8> {
+    let mut data = vec![0; STAKE_POOL_LEN];
+    write_u64(&mut data, TOTAL_LAMPORTS_OFFSET, total_lamports);
+    write_u64(&mut data, POOL_TOKEN_SUPPLY_OFFSET, pool_token_supply);
+    data
+}
+
+/// set the sol withdrawal fee of stake pool account data, numerator / denominator
+pub fn set_sol_withdrawal_fee(data: &mut [u8], numerator: u64, denominator: u64) {
+    write_u64(data, SOL_WITHDRAWAL_FEE_OFFSET, denominator);
+    write_u64(data, SOL_WITHDRAWAL_FEE_OFFSET + 8, numerator);
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn write_u64(data: &mut [u8], offset: usize, value: u64) {
+    data[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+}
+
+fn mul_div(amount: u64, numerator: u64, denominator: u64) -> Result<u64, ProgramError> {
+    u64::try_from(amount as u128 * numerator as u128 / denominator as u128)
+        .map_err(|_| ProgramError::ArithmeticOverflow)
+}
+
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let (tag, rest) = data
+        .split_first()
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    let amount = rest
+        .get(..8)
+        .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    match *tag {
+        DEPOSIT_SOL_TAG => deposit_sol(program_id, accounts, amount),
+        WITHDRAW_SOL_TAG => withdraw_sol(program_id, accounts, amount),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+fn check_pool<'a>(
+    program_id: &Pubkey,
+    stake_pool: &AccountInfo<'a>,
+    withdraw_authority: &AccountInfo<'a>,
+    reserve: &AccountInfo<'a>,
+) -> Result<u8, ProgramError> {
+    if stake_pool.owner != program_id || reserve.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let (authority, bump) = find_withdraw_authority(stake_pool.key);
+    if authority != *withdraw_authority.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    Ok(bump)
+}
+
+/// accounts: stake pool, withdraw authority, reserve, lamports from, pool tokens to,
+/// manager fee account, referrer fee account, pool mint, system program, token program
+fn deposit_sol(program_id: &Pubkey, accounts: &[AccountInfo], lamports: u64) -> ProgramResult {
+    let accounts = &mut accounts.iter();
+    let stake_pool = next_account_info(accounts)?;
+    let withdraw_authority = next_account_info(accounts)?;
+    let reserve = next_account_info(accounts)?;
+    let lamports_from = next_account_info(accounts)?;
+    let pool_tokens_to = next_account_info(accounts)?;
+    let _manager_fee_account = next_account_info(accounts)?;
+    let _referrer_fee_account = next_account_info(accounts)?;
+    let pool_mint = next_account_info(accounts)?;
+    let system_program = next_account_info(accounts)?;
+    let token_program = next_account_info(accounts)?;
+
+    let bump = check_pool(program_id, stake_pool, withdraw_authority, reserve)?;
+
+    let mut data = stake_pool.try_borrow_mut_data()?;
+    let total_lamports = read_u64(&data, TOTAL_LAMPORTS_OFFSET);
+    let pool_token_supply = read_u64(&data, POOL_TOKEN_SUPPLY_OFFSET);
+    let pool_tokens = if total_lamports == 0 || pool_token_supply == 0 {
+        lamports
+    } else {
+        mul_div(lamports, pool_token_supply, total_lamports)?
+    };
+
+    invoke(
+        &system_instruction::transfer(lamports_from.key, reserve.key, lamports),
+        &[
+            lamports_from.clone(),
+            reserve.clone(),
+            system_program.clone(),
+        ],
+    )?;
+    invoke_signed(
+        &spl_token::instruction::mint_to(
+            token_program.key,
+            pool_mint.key,
+            pool_tokens_to.key,
+            withdraw_authority.key,
+            &[],
+            pool_tokens,
+        )?,
+        &[
+            pool_mint.clone(),
+            pool_tokens_to.clone(),
+            withdraw_authority.clone(),
+            token_program.clone(),
+        ],
+        &[&[stake_pool.key.as_ref(), WITHDRAW_AUTHORITY_SEED, &[bump]]],
+    )?;
+
+    write_u64(&mut data, TOTAL_LAMPORTS_OFFSET, total_lamports + lamports);
+    write_u64(
+        &mut data,
+        POOL_TOKEN_SUPPLY_OFFSET,
+        pool_token_supply + pool_tokens,
+    );
+    Ok(())
+}
+
+/// accounts: stake pool, withdraw authority, user transfer authority, pool tokens from, reserve,
+/// lamports to, manager fee account, pool mint, clock, stake history, stake program, token program
+fn withdraw_sol(program_id: &Pubkey, accounts: &[AccountInfo], pool_tokens: u64) -> ProgramResult {
+    let accounts = &mut accounts.iter();
+    let stake_pool = next_account_info(accounts)?;
+    let withdraw_authority = next_account_info(accounts)?;
+    let user_transfer_authority = next_account_info(accounts)?;
+    let pool_tokens_from = next_account_info(accounts)?;
+    let reserve = next_account_info(accounts)?;
+    let lamports_to = next_account_info(accounts)?;
+    let _manager_fee_account = next_account_info(accounts)?;
+    let pool_mint = next_account_info(accounts)?;
+    let _clock = next_account_info(accounts)?;
+    let _stake_history = next_account_info(accounts)?;
+    let _stake_program = next_account_info(accounts)?;
+    let token_program = next_account_info(accounts)?;
+
+    check_pool(program_id, stake_pool, withdraw_authority, reserve)?;
+
+    let mut data = stake_pool.try_borrow_mut_data()?;
+    let total_lamports = read_u64(&data, TOTAL_LAMPORTS_OFFSET);
+    let pool_token_supply = read_u64(&data, POOL_TOKEN_SUPPLY_OFFSET);
+    if pool_token_supply == 0 {
+        return Err(ProgramError::InsufficientFunds);
+    }
+    // the fee is rounded up like the spl stake pool
+    let fee_denominator = read_u64(&data, SOL_WITHDRAWAL_FEE_OFFSET);
+    let fee_numerator = read_u64(&data, SOL_WITHDRAWAL_FEE_OFFSET + 8);
+    let fee = if fee_denominator == 0 {
+        0
+    } else {
+        let numerator = pool_tokens as u128 * fee_numerator as u128;
+        u64::try_from((numerator + fee_denominator as u128 - 1) / fee_denominator as u128)
+            .map_err(|_| ProgramError::ArithmeticOverflow)?
+    };
+    let lamports = mul_div(
+        pool_tokens
+            .checked_sub(fee)
+            .ok_or(ProgramError::InsufficientFunds)?,
+        total_lamports,
+        pool_token_supply,
+    )?;
+
+    invoke(
+        &spl_token::instruction::burn(
+            token_program.key,
+            pool_tokens_from.key,
+            pool_mint.key,
+            user_transfer_authority.key,
+            &[],
+            pool_tokens,
+        )?,
+        &[
+            pool_tokens_from.clone(),
+            pool_mint.clone(),
+            user_transfer_authority.clone(),
+            token_program.clone(),
+        ],
+    )?;
+
+    **reserve.try_borrow_mut_lamports()? = reserve
+        .lamports()
+        .checked_sub(lamports)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    **lamports_to.try_borrow_mut_lamports()? += lamports;
+
+    write_u64(&mut data, TOTAL_LAMPORTS_OFFSET, total_lamports - lamports);
+    write_u64(
+        &mut data,
+        POOL_TOKEN_SUPPLY_OFFSET,
+        pool_token_supply - pool_tokens,
+    );
+    Ok(())
+}